@@ -0,0 +1,183 @@
+use colored::Colorize;
+use semdoc::{AttachedFile, AttachmentError, SemDoc};
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+
+pub fn list_attachments(file: &str) {
+    let bytes = std::fs::read(file).expect("File not found.");
+    let doc = SemDoc::from_bytes(&bytes).expect("Couldn't parse the SemDoc file.");
+
+    let attachments = doc.block.attachments();
+    if attachments.is_empty() {
+        println!("The document has no attachments.");
+    }
+    for attachment in attachments {
+        println!(
+            "{} {} ({} bytes, SHA-256 {})",
+            attachment.name.bold(),
+            attachment.mime_type.green(),
+            attachment.size,
+            attachment
+                .checksum
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>(),
+        );
+    }
+}
+
+/// Saves the attachments with the given name, or all attachments if no name is given, into the
+/// output directory.
+pub fn extract_attachments(file: &str, name: Option<&str>, output: &str) {
+    let bytes = std::fs::read(file).expect("File not found.");
+    let doc = SemDoc::from_bytes(&bytes).expect("Couldn't parse the SemDoc file.");
+
+    let files = match files_to_extract(doc.block.attachments(), name) {
+        Ok(files) => files,
+        Err(ExtractionError::NoMatchingAttachments) => {
+            println!("{}", "No matching attachments found.".red());
+            std::process::exit(1);
+        }
+        Err(ExtractionError::Damaged(AttachmentError::SizeMismatch { name })) => {
+            println!("{} {}", "Wrong size of attachment".red(), name.bold());
+            std::process::exit(1);
+        }
+        Err(ExtractionError::Damaged(AttachmentError::ChecksumMismatch { name })) => {
+            println!("{} {}", "Wrong checksum of attachment".red(), name.bold());
+            std::process::exit(1);
+        }
+        Err(ExtractionError::InvalidName(name)) => {
+            println!("{} {}", "Invalid attachment name".red(), name.bold());
+            std::process::exit(1);
+        }
+    };
+    for (file_name, data) in files {
+        let path = Path::new(output).join(file_name);
+        std::fs::write(&path, data).expect("Couldn't write the attachment.");
+        println!("{} {}", "Extracted".green(), path.display());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExtractionError {
+    NoMatchingAttachments,
+    Damaged(AttachmentError),
+    InvalidName(String),
+}
+
+/// Returns the file names to save the matching attachments as, along with their data. All
+/// attachments are checked first, so that nothing is saved if one of them is damaged.
+fn files_to_extract<'a>(
+    attachments: Vec<&'a AttachedFile>,
+    name: Option<&str>,
+) -> Result<Vec<(OsString, &'a [u8])>, ExtractionError> {
+    let attachments = attachments
+        .into_iter()
+        .filter(|attachment| name.map_or(true, |name| attachment.name == name))
+        .collect::<Vec<_>>();
+    if attachments.is_empty() {
+        return Err(ExtractionError::NoMatchingAttachments);
+    }
+    let mut used_file_names = HashSet::new();
+    let mut files = vec![];
+    for attachment in attachments {
+        let data = attachment.extract().map_err(ExtractionError::Damaged)?;
+        // Only use the last part of the name so that attachments can't write outside of the
+        // output directory.
+        let file_name = Path::new(&attachment.name)
+            .file_name()
+            .ok_or_else(|| ExtractionError::InvalidName(attachment.name.clone()))?;
+        files.push((unique_file_name(file_name, &mut used_file_names), data));
+    }
+    Ok(files)
+}
+
+/// Attachments in different directories can have the same file name. Later ones get a numbered
+/// suffix like "report-2.pdf" so that they don't overwrite earlier ones.
+fn unique_file_name(file_name: &OsStr, used: &mut HashSet<OsString>) -> OsString {
+    let path = Path::new(file_name);
+    let mut unique = file_name.to_owned();
+    let mut number = 1;
+    while used.contains(&unique) {
+        number += 1;
+        unique = path.file_stem().unwrap_or(file_name).to_owned();
+        unique.push(format!("-{}", number));
+        if let Some(extension) = path.extension() {
+            unique.push(".");
+            unique.push(extension);
+        }
+    }
+    used.insert(unique.clone());
+    unique
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn attachment(name: &str, data: &[u8]) -> AttachedFile {
+        AttachedFile::new(name, "text/plain", data.to_vec())
+    }
+
+    #[test]
+    fn file_names_get_numbered_suffixes() {
+        let mut used = HashSet::new();
+        let names = ["report.pdf", "report.pdf", "notes", "notes", "report.pdf"]
+            .iter()
+            .map(|name| unique_file_name(OsStr::new(name), &mut used))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "report.pdf",
+                "report-2.pdf",
+                "notes",
+                "notes-2",
+                "report-3.pdf"
+            ]
+        );
+    }
+
+    #[test]
+    fn directories_are_stripped_from_names() {
+        let first = attachment("../../etc/passwd", b"root");
+        let second = attachment("/home/alice/passwd", b"alice");
+        let files = files_to_extract(vec![&first, &second], None).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                (OsString::from("passwd"), &b"root"[..]),
+                (OsString::from("passwd-2"), &b"alice"[..]),
+            ]
+        );
+        let parent = attachment("..", b"");
+        assert_eq!(
+            files_to_extract(vec![&parent], None),
+            Err(ExtractionError::InvalidName("..".to_owned()))
+        );
+    }
+
+    #[test]
+    fn damaged_attachments_are_not_extracted() {
+        let fine = attachment("fine.txt", b"fine");
+        let mut damaged = attachment("damaged.txt", b"data");
+        damaged.data[0] ^= 0x01;
+        assert_eq!(
+            files_to_extract(vec![&fine, &damaged], None),
+            Err(ExtractionError::Damaged(
+                AttachmentError::ChecksumMismatch {
+                    name: "damaged.txt".to_owned()
+                }
+            ))
+        );
+        assert_eq!(
+            files_to_extract(vec![&fine, &damaged], Some("fine.txt")).unwrap(),
+            vec![(OsString::from("fine.txt"), &b"fine"[..])]
+        );
+        assert_eq!(
+            files_to_extract(vec![&fine], Some("other.txt")),
+            Err(ExtractionError::NoMatchingAttachments)
+        );
+    }
+}
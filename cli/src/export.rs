@@ -0,0 +1,84 @@
+use colored::Colorize;
+use semdoc::{Pure, SemDoc};
+use std::path::{Path, PathBuf};
+
+/// Saves the document as a standalone HTML page. If no output is given, the page is saved next
+/// to the file.
+pub fn export_html(file: &str, output: Option<&str>) {
+    let doc = read_doc(file);
+    let output = output_path(file, output, "html");
+    std::fs::write(&output, semdoc_to_html::semdoc_to_html(&doc)).expect("Couldn't save the page.");
+    print_exported(&output);
+}
+
+/// Saves the document as an EPUB book. If no output is given, the book is saved next to the file.
+pub fn export_epub(file: &str, output: Option<&str>) {
+    let doc = read_doc(file);
+    let output = output_path(file, output, "epub");
+    std::fs::write(&output, semdoc_to_epub::semdoc_to_epub(&doc)).expect("Couldn't save the book.");
+    print_exported(&output);
+}
+
+/// Saves the document in Pandoc's JSON format. If no output is given, the JSON is saved next to
+/// the file.
+pub fn export_pandoc(file: &str, output: Option<&str>) {
+    let doc = read_doc(file);
+    let output = output_path(file, output, "json");
+    std::fs::write(&output, semdoc_to_pandoc::semdoc_to_pandoc(&doc))
+        .expect("Couldn't save the JSON.");
+    print_exported(&output);
+}
+
+/// Saves the document as plain text with lines of at most the given width. If no output is given,
+/// the text is saved next to the file.
+pub fn export_text(file: &str, output: Option<&str>, width: usize) {
+    let doc = read_doc(file);
+    let output = output_path(file, output, "txt");
+    let options = semdoc_to_text::TextOptions {
+        width,
+        ..Default::default()
+    };
+    std::fs::write(&output, semdoc_to_text::semdoc_to_text(&doc, &options))
+        .expect("Couldn't save the text.");
+    print_exported(&output);
+}
+
+fn read_doc(file: &str) -> SemDoc<Pure> {
+    let bytes = std::fs::read(file).expect("File not found.");
+    SemDoc::from_bytes(&bytes)
+        .expect("Couldn't parse the SemDoc file.")
+        .into_pure()
+        .expect("Couldn't read the SemDoc file.")
+}
+
+fn output_path(file: &str, output: Option<&str>, extension: &str) -> PathBuf {
+    match output {
+        Some(output) => Path::new(output).to_owned(),
+        None => Path::new(file).with_extension(extension),
+    }
+}
+
+fn print_exported(output: &Path) {
+    println!(
+        "{} {}",
+        "Exported to".green(),
+        output.display().to_string().bold()
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn output_is_saved_next_to_the_file_by_default() {
+        assert_eq!(
+            output_path("docs/contract.semdoc", None, "html"),
+            Path::new("docs/contract.html")
+        );
+        assert_eq!(
+            output_path("docs/contract.semdoc", Some("out/page.htm"), "html"),
+            Path::new("out/page.htm")
+        );
+    }
+}
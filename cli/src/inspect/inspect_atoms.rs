@@ -53,22 +53,16 @@ fn format_atom(atom: &Atom, width: usize) -> String {
             )
         }
         Atom::Bytes(bytes) => format!(
-            "{}\n{}",
-            format!(
-                "{}{}",
-                format_atom_type("Bytes"),
-                format_n_bytes_long(bytes.len(), false),
-            ),
+            "{}{}\n{}",
+            format_atom_type("Bytes"),
+            format_n_bytes_long(bytes.len(), false),
             format_bytes(bytes, width),
         ),
         Atom::FewBytes(bytes) => format!(
-            "{}\n{}",
-            format!(
-                "{}{}",
-                format_atom_type("FewBytes"),
-                format_n_bytes_long(bytes.len(), bytes.len() > 0),
-            ),
-            format_bytes(&bytes, width),
+            "{}{}\n{}",
+            format_atom_type("FewBytes"),
+            format_n_bytes_long(bytes.len(), !bytes.is_empty()),
+            format_bytes(bytes, width),
         ),
         Atom::Reference(offset) => {
             format!("{}{}", format_atom_type("Reference"), offset)
@@ -1,5 +1,8 @@
 use colored::Colorize;
-use semdoc::{Block, SemDoc, Source};
+use semdoc::{
+    BibliographyEntry, Block, Comment, Definition, HighlightReason, ListNumbering, Media,
+    MediaSource, Metadata, SemDoc, Source, TemporalValue,
+};
 
 use super::utils::*;
 
@@ -7,6 +10,9 @@ pub fn inspect_blocks(file: &str) {
     let bytes = std::fs::read(file).expect("File not found.");
     let doc = SemDoc::from_bytes(&bytes).unwrap();
 
+    if !doc.metadata.is_empty() {
+        println!("{}", format_metadata(&doc.metadata));
+    }
     print!("{}", format_block(&doc.block, terminal_width_or_80(), 0));
 }
 
@@ -16,6 +22,7 @@ fn format_block<S: Source>(block: &Block<S>, width: usize, offset: usize) -> Str
     match block {
         Error(error) => format!("{}: {:?}", format_block_kind("Error"), error),
         Empty => format_block_kind("Empty"),
+        TopicChange => format_block_kind("TopicChange"),
         Text(text) => format!(
             "{}{}",
             format_block_kind("Text: "),
@@ -46,14 +53,318 @@ fn format_block<S: Source>(block: &Block<S>, width: usize, offset: usize) -> Str
             format_block_kind("BulletList"),
             format_children_without_roles(&items[..], width),
         ),
-        OrderedList(items) => format!(
-            "{}\n{}",
+        OrderedList {
+            start,
+            numbering,
+            items,
+        } => format!(
+            "{} {}{}\n{}",
             format_block_kind("OrderedList"),
+            format!("starting at {}", start).green(),
+            match numbering {
+                Some(ListNumbering::Steps) => " (steps)",
+                Some(ListNumbering::Clauses) => " (clauses)",
+                Some(ListNumbering::Ranking) => " (ranking)",
+                None => "",
+            }
+            .green(),
             format_children_without_roles(&items[..], width),
         ),
+        Checklist(items) => format!(
+            "{}\n{}",
+            format_block_kind("Checklist"),
+            format_children_strings(
+                &items
+                    .iter()
+                    .map(|item| {
+                        format!(
+                            "{}{}",
+                            if item.checked { "[x] " } else { "[ ] " }.green(),
+                            format_block(&item.content, width - 2, 4)
+                        )
+                    })
+                    .collect::<Vec<_>>()[..],
+            ),
+        ),
+        DefinitionList(definitions) => format!(
+            "{}\n{}",
+            format_block_kind("DefinitionList"),
+            format_definitions(definitions, width),
+        ),
+        Glossary(definitions) => format!(
+            "{}\n{}",
+            format_block_kind("Glossary"),
+            format_definitions(definitions, width),
+        ),
+        TermUsage { term, content } => format!(
+            "{} {}\n{}",
+            format_block_kind("TermUsage"),
+            format!("of \"{}\"", term).green(),
+            format_children_with_roles(vec![("content", content)], width),
+        ),
+        Anchor { id, content } => format!(
+            "{} {}\n{}",
+            format_block_kind("Anchor"),
+            format!("#{}", id).green(),
+            format_children_with_roles(vec![("content", content)], width),
+        ),
+        CrossReference { target, content } => format!(
+            "{} {}\n{}",
+            format_block_kind("CrossReference"),
+            format!("to #{}", target).green(),
+            format_children_with_roles(vec![("content", content)], width),
+        ),
+        Highlighted { content, reason } => format!(
+            "{}{}\n{}",
+            format_block_kind("Highlighted"),
+            match reason {
+                Some(HighlightReason::SearchHit) => " (search hit)",
+                Some(HighlightReason::KeyPoint) => " (key point)",
+                Some(HighlightReason::Change) => " (change)",
+                None => "",
+            }
+            .green(),
+            format_children_with_roles(vec![("content", content)], width),
+        ),
+        Bibliography(entries) => format!(
+            "{}\n{}",
+            format_block_kind("Bibliography"),
+            format_children_strings(
+                &entries
+                    .iter()
+                    .map(format_bibliography_entry)
+                    .collect::<Vec<_>>()[..],
+            ),
+        ),
+        Citation(keys) => format!(
+            "{} {}",
+            format_block_kind("Citation"),
+            format!("of {}", keys.join(", ")).green(),
+        ),
+        Temporal { value, text } => format!(
+            "{} {} \"{}\"",
+            format_block_kind("Temporal"),
+            format_temporal_value(*value).green(),
+            text,
+        ),
+        Attachment(attachment) => format!(
+            "{} {}",
+            format_block_kind("Attachment"),
+            format!(
+                "{}, {}, {} {}",
+                attachment.name,
+                attachment.mime_type,
+                attachment.size,
+                singular_or_plural(attachment.size as usize, "byte", "bytes")
+            )
+            .green(),
+        ),
+        Table { header, rows } => format!(
+            "{}\n{}",
+            format_block_kind("Table"),
+            format_children_strings(
+                &std::iter::once(("header", header))
+                    .chain(rows.iter().map(|row| ("row", row)))
+                    .map(|(role, cells)| format!(
+                        "{}\n{}",
+                        role.green(),
+                        format_children_without_roles(&cells[..], width - 2)
+                    ))
+                    .collect::<Vec<_>>()[..],
+            ),
+        ),
+        Code { language, code } => format!(
+            "{}{}\n{}",
+            format_block_kind("Code"),
+            match language {
+                Some(language) => format!(" in {}", language),
+                None => "".to_owned(),
+            }
+            .green(),
+            code.cyan(),
+        ),
+        Quote(content) => format!(
+            "{}\n{}",
+            format_block_kind("Quote"),
+            format_children_with_roles(vec![("content", content)], width),
+        ),
+        Link { target, content } => format!(
+            "{} {}\n{}",
+            format_block_kind("Link"),
+            format!("to {}", target).green(),
+            format_children_with_roles(vec![("content", content)], width),
+        ),
+        Audio(media) => format_media("Audio", media, width),
+        Video(media) => format_media("Video", media, width),
+        Secondary(content) => format!(
+            "{}\n{}",
+            format_block_kind("Secondary"),
+            format_children_with_roles(vec![("content", content)], width),
+        ),
+        MoreInfo { summary, body } => format!(
+            "{}\n{}",
+            format_block_kind("MoreInfo"),
+            format_children_with_roles(vec![("summary", summary), ("body", body)], width),
+        ),
+        Signed {
+            content,
+            signatures,
+        } => format!(
+            "{} {}\n{}",
+            format_block_kind("Signed"),
+            format!(
+                "by {}",
+                signatures
+                    .iter()
+                    .map(|signature| signature.signer.clone())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .green(),
+            format_children_with_roles(vec![("content", content)], width),
+        ),
+        Commented { content, comments } => format!(
+            "{}\n{}",
+            format_block_kind("Commented"),
+            format_children_strings(
+                &std::iter::once(format!(
+                    "{}{}",
+                    "content: ".green(),
+                    format_block(content, width - 2, 9)
+                ))
+                .chain(
+                    comments
+                        .iter()
+                        .map(|comment| format_comment(comment, width - 2))
+                )
+                .collect::<Vec<_>>()[..],
+            ),
+        ),
     }
 }
 
+fn format_definitions<S: Source>(definitions: &[Definition<S>], width: usize) -> String {
+    format_children_strings(
+        &definitions
+            .iter()
+            .map(|definition| {
+                format!(
+                    "{}\n{}",
+                    format_block_kind("Definition"),
+                    format_children_with_roles(
+                        std::iter::once(("term", &definition.term))
+                            .chain(definition.definitions.iter().map(|it| ("definition", it)))
+                            .collect(),
+                        width - 2,
+                    ),
+                )
+            })
+            .collect::<Vec<_>>()[..],
+    )
+}
+
+fn format_media<S: Source>(kind: &str, media: &Media<S>, width: usize) -> String {
+    let mut info = vec![media.mime_type.clone()];
+    if let Some(duration) = media.duration {
+        info.push(format!("{:.1} s", duration.as_secs_f64()));
+    }
+    info.push(match &media.source {
+        MediaSource::Embedded(payload) => format!(
+            "{} {} embedded",
+            payload.len(),
+            singular_or_plural(payload.len(), "byte", "bytes")
+        ),
+        MediaSource::External(uri) => format!("from {}", uri),
+    });
+    format!(
+        "{} {}\n{}",
+        format_block_kind(kind),
+        info.join(", ").green(),
+        format_children_with_roles(vec![("transcript", &media.transcript)], width),
+    )
+}
+
+fn format_metadata(metadata: &Metadata) -> String {
+    let mut lines = vec![format_block_kind("Metadata")];
+    if let Some(title) = &metadata.title {
+        lines.push(format!("{}{}", "title: ".green(), title));
+    }
+    for author in &metadata.authors {
+        lines.push(format!("{}{}", "author: ".green(), author));
+    }
+    if let Some(date) = metadata.date {
+        lines.push(format!(
+            "{}{}",
+            "date: ".green(),
+            format_temporal_value(date)
+        ));
+    }
+    if !metadata.tags.is_empty() {
+        lines.push(format!("{}{}", "tags: ".green(), metadata.tags.join(", ")));
+    }
+    for (key, value) in &metadata.custom {
+        lines.push(format!("{}{}", format!("{}: ", key).green(), value));
+    }
+    lines.join("\n")
+}
+
+fn format_temporal_value(value: TemporalValue) -> String {
+    match value {
+        TemporalValue::Date { year, month, day } => {
+            format!("date {:04}-{:02}-{:02}", year, month, day)
+        }
+        TemporalValue::Instant(seconds) if seconds >= 0 => {
+            format!("instant {}", format_timestamp(seconds as u64))
+        }
+        TemporalValue::Instant(seconds) => format!("instant {} s", seconds),
+        TemporalValue::Duration(seconds) => format!("duration {} s", seconds),
+    }
+}
+
+fn format_bibliography_entry(entry: &BibliographyEntry) -> String {
+    let mut lines = vec![format!(
+        "{} {}",
+        format_block_kind("BibliographyEntry"),
+        entry.key.green()
+    )];
+    lines.push(format!("{}{}", "title: ".green(), entry.title));
+    for author in &entry.authors {
+        lines.push(format!("{}{}", "author: ".green(), author));
+    }
+    if let Some(year) = entry.year {
+        lines.push(format!("{}{}", "year: ".green(), year));
+    }
+    for (scheme, value) in &entry.identifiers {
+        lines.push(format!("{}{}", format!("{}: ", scheme).green(), value));
+    }
+    lines.join("\n")
+}
+
+fn format_comment(comment: &Comment, width: usize) -> String {
+    let header = format!(
+        "{} {} ({})",
+        "Comment by".magenta(),
+        comment.author.magenta().bold(),
+        format_timestamp(comment.timestamp),
+    );
+    let text = textwrap::wrap(&comment.text, width)
+        .iter()
+        .map(|line| format!("{}\n", line.magenta().italic()))
+        .collect::<String>();
+    format!(
+        "{}\n{}{}",
+        header,
+        text,
+        format_children_strings(
+            &comment
+                .replies
+                .iter()
+                .map(|reply| format_comment(reply, width - 2))
+                .collect::<Vec<_>>()[..],
+        ),
+    )
+}
+
 fn format_block_kind(kind: &str) -> String {
     kind.yellow().bold().to_string()
 }
@@ -80,11 +391,11 @@ fn format_children_without_roles<S: Source>(children: &[Block<S>], width: usize)
     format_children_strings(
         &children
             .iter()
-            .map(|block| format_block(&block, width - 2, 0))
+            .map(|block| format_block(block, width - 2, 0))
             .collect::<Vec<_>>()[..],
     )
 }
 
 fn indent(amount: usize) -> String {
-    std::iter::repeat(" ").take(amount).collect::<String>()
+    " ".repeat(amount)
 }
@@ -17,6 +17,7 @@ mod colors {
     pub const LENGTH: Color = Color::BrightRed;
     pub const PAYLOAD: Color = Color::BrightCyan;
     pub const PADDING: Color = Color::Blue;
+    pub const OFFSET: Color = Color::BrightGreen;
 }
 
 enum WordInfo {
@@ -29,6 +30,7 @@ enum WordInfo {
     Bytes { length: u64 },
     FewBytes { length: u8 },
     BytesContinuation { num_relevant: u8 },
+    Reference { target_word: usize },
 }
 
 pub fn inspect_bytes(file: &str) {
@@ -90,6 +92,7 @@ fn info_for_bytes(bytes: &[u8]) -> Vec<WordInfo> {
                 break;
             }
         };
+        let start = cursor;
         cursor += atom.length_in_bytes();
         match atom {
             Atom::Block { kind, num_children } => {
@@ -99,7 +102,9 @@ fn info_for_bytes(bytes: &[u8]) -> Vec<WordInfo> {
             Atom::SmallBlock { kind, num_children } => {
                 info.push(WordInfo::SmallBlock { kind, num_children })
             }
-            Atom::Reference(_offset) => todo!("Handle references."),
+            Atom::Reference(offset) => info.push(WordInfo::Reference {
+                target_word: (start + offset as usize) / 8,
+            }),
             Atom::Bytes(bytes) => {
                 info.push(WordInfo::Bytes {
                     length: bytes.len() as u64,
@@ -132,13 +137,16 @@ impl WordInfo {
             WordInfo::SmallBlock { .. } => {
                 [ATOM_KIND, NUM_CHILDREN, KIND, KIND, KIND, KIND, KIND, KIND]
             }
+            WordInfo::Reference { .. } => [
+                ATOM_KIND, OFFSET, OFFSET, OFFSET, OFFSET, OFFSET, OFFSET, OFFSET,
+            ],
             WordInfo::Bytes { .. } => [
                 ATOM_KIND, LENGTH, LENGTH, LENGTH, LENGTH, LENGTH, LENGTH, LENGTH,
             ],
             WordInfo::FewBytes { length: len, .. } => {
                 let mut colors = [PADDING; 8];
-                for i in 0..min(2 + *len as usize, 8) {
-                    colors[i] = PAYLOAD;
+                for color in colors.iter_mut().take(min(2 + *len as usize, 8)) {
+                    *color = PAYLOAD;
                 }
                 colors[0] = ATOM_KIND;
                 colors[1] = LENGTH;
@@ -146,8 +154,8 @@ impl WordInfo {
             }
             WordInfo::BytesContinuation { num_relevant, .. } => {
                 let mut colors = [PADDING; 8];
-                for i in 0..(*num_relevant as usize) {
-                    colors[i] = PAYLOAD;
+                for color in colors.iter_mut().take(*num_relevant as usize) {
+                    *color = PAYLOAD;
                 }
                 colors
             }
@@ -182,7 +190,7 @@ fn format_bytes_ascii(word: &[u8], info: &WordInfo) -> String {
 fn format_info(info: &WordInfo) -> String {
     match info {
         WordInfo::Error { error } => format!("Error: {:?}", error).red().to_string(),
-        WordInfo::ErrorContinuation => format!(""),
+        WordInfo::ErrorContinuation => String::new(),
         WordInfo::Header { version } => {
             format!(
                 "Header with {}{}",
@@ -214,6 +222,11 @@ fn format_info(info: &WordInfo) -> String {
             )
             .color(colors::NUM_CHILDREN),
         ),
+        WordInfo::Reference { target_word } => format!(
+            "{}{}",
+            format_atom_kind("Reference"),
+            format!("to word {}", target_word).color(colors::OFFSET),
+        ),
         WordInfo::Bytes { length } => format!(
             "{}{}",
             format_atom_kind("Bytes"),
@@ -29,16 +29,21 @@ fn format_molecule<S: Source>(molecule: &Molecule<S>, width: usize) -> String {
             format_children_strings(
                 &children
                     .iter()
-                    .map(|block| format_molecule(&block, width - 2))
+                    .map(|block| format_molecule(block, width - 2))
                     .collect::<Vec<_>>()[..],
             ),
         ),
         Molecule::Bytes(bytes) => format!(
             "{}:\n{}",
             format_molecule_kind("Bytes"),
-            format_payload_bytes(&bytes, width)
+            format_payload_bytes(bytes, width)
         ),
         Molecule::Error(error) => format!("{}: {:?}", format_molecule_kind("Error"), error),
+        Molecule::OutOfLine(molecule) => format!(
+            "{}\n{}",
+            format_molecule_kind("OutOfLine"),
+            format_children_strings(&[format_molecule(molecule, width - 2)]),
+        ),
     }
 }
 
@@ -1,4 +1,5 @@
 use colored::Colorize;
+use semdoc::days_to_civil;
 
 pub fn format_children_strings(children: &[String]) -> String {
     children
@@ -9,7 +10,7 @@ pub fn format_children_strings(children: &[String]) -> String {
                 false => ("├─", "│ "),
                 true => ("└─", "  "),
             };
-            let content = textwrap::indent(&child, rest_prefix);
+            let content = textwrap::indent(child, rest_prefix);
             format!("{}{}", first_line_prefix, &content[rest_prefix.len()..])
         })
         .collect::<Vec<_>>()
@@ -25,6 +26,34 @@ pub fn kind_to_name(kind: u64) -> String {
         4 => "Paragraphs",
         5 => "BulletList",
         6 => "OrderedList",
+        7 => "Signed",
+        8 => "Commented",
+        9 => "Comment",
+        10 => "Secondary",
+        11 => "MoreInfo",
+        12 => "Highlighted",
+        13 => "Checklist",
+        14 => "DefinitionList",
+        15 => "Definition",
+        16 => "Glossary",
+        17 => "TermUsage",
+        18 => "Anchor",
+        19 => "CrossReference",
+        20 => "Bibliography",
+        21 => "BibliographyEntry",
+        22 => "Citation",
+        23 => "Temporal",
+        24 => "TopicChange",
+        25 => "Audio",
+        26 => "Video",
+        27 => "Attachment",
+        28 => "Table",
+        29 => "TableRow",
+        30 => "Code",
+        31 => "Quote",
+        32 => "Link",
+        33 => "Document",
+        34 => "Metadata",
         _ => "unknown",
     }
     .to_owned()
@@ -93,3 +122,20 @@ pub fn format_payload_bytes(bytes: &[u8], width: usize) -> String {
         .collect::<Vec<_>>()
         .join("\n")
 }
+
+/// Formats seconds since the Unix epoch as a UTC date and time.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    let (year, month, day) = days_to_civil(days);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
@@ -1,9 +1,14 @@
 use clap::{App, AppSettings, Arg, SubCommand};
+use colored::Colorize;
+use semdoc::{SemDoc, SignatureError};
 use std::fs::File;
-use semdoc::SemDoc;
 use std::io::prelude::*;
 
+mod attachments;
+mod export;
 mod inspect;
+use attachments::*;
+use export::*;
 use inspect::*;
 
 fn main() {
@@ -31,41 +36,217 @@ fn main() {
                         .about("Inspects the molecules of the SemDoc file."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("attachments")
+                .about("Lists or extracts the files attached to the SemDoc file.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(SubCommand::with_name("list").about("Lists all attachments."))
+                .subcommand(
+                    SubCommand::with_name("extract")
+                        .about("Extracts attachments after verifying their checksums.")
+                        .arg(
+                            Arg::with_name("name")
+                                .index(1)
+                                .help("The attachment to extract. Extracts all if omitted."),
+                        )
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .short("o")
+                                .takes_value(true)
+                                .default_value(".")
+                                .help("The directory to save the attachments in."),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Exports the SemDoc file into other formats.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("html")
+                        .about("Exports a standalone HTML page.")
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .short("o")
+                                .takes_value(true)
+                                .help("Where to save the page. Defaults to <file>.html."),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("epub")
+                        .about("Exports an EPUB book for e-readers.")
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .short("o")
+                                .takes_value(true)
+                                .help("Where to save the book. Defaults to <file>.epub."),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("pandoc")
+                        .about("Exports Pandoc's JSON format, which Pandoc converts further.")
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .short("o")
+                                .takes_value(true)
+                                .help("Where to save the JSON. Defaults to <file>.json."),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("text")
+                        .about("Exports plain text, like for the body of an email.")
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .short("o")
+                                .takes_value(true)
+                                .help("Where to save the text. Defaults to <file>.txt."),
+                        )
+                        .arg(
+                            Arg::with_name("width")
+                                .long("width")
+                                .short("w")
+                                .takes_value(true)
+                                .default_value("80")
+                                .help("The maximum number of characters per line."),
+                        ),
+                ),
+        )
         .subcommand(SubCommand::with_name("eat"))
+        .subcommand(
+            SubCommand::with_name("verify").about("Verifies the signatures of the SemDoc file."),
+        )
         .get_matches();
 
     let file = matches.value_of("file").unwrap();
     println!("The file passed is: {}", file);
 
-    if let Some(ref matches) = matches.subcommand_matches("inspect") {
+    if let Some(matches) = matches.subcommand_matches("inspect") {
         if matches.subcommand_matches("bytes").is_some() {
-            inspect_bytes(&file);
+            inspect_bytes(file);
         }
         if matches.subcommand_matches("atoms").is_some() {
-            inspect_atoms(&file);
+            inspect_atoms(file);
         }
         if matches.subcommand_matches("blocks").is_some() {
-            inspect_blocks(&file);
+            inspect_blocks(file);
         }
         if matches.subcommand_matches("molecules").is_some() {
-            inspect_molecules(&file);
+            inspect_molecules(file);
+        }
+    }
+    if let Some(matches) = matches.subcommand_matches("attachments") {
+        if matches.subcommand_matches("list").is_some() {
+            list_attachments(file);
+        }
+        if let Some(matches) = matches.subcommand_matches("extract") {
+            extract_attachments(
+                file,
+                matches.value_of("name"),
+                matches.value_of("output").unwrap(),
+            );
+        }
+    }
+    if let Some(matches) = matches.subcommand_matches("export") {
+        if let Some(matches) = matches.subcommand_matches("html") {
+            export_html(file, matches.value_of("output"));
+        }
+        if let Some(matches) = matches.subcommand_matches("epub") {
+            export_epub(file, matches.value_of("output"));
+        }
+        if let Some(matches) = matches.subcommand_matches("pandoc") {
+            export_pandoc(file, matches.value_of("output"));
+        }
+        if let Some(matches) = matches.subcommand_matches("text") {
+            let width = matches.value_of("width").unwrap();
+            let width = width.parse().expect("The width has to be a number.");
+            export_text(file, matches.value_of("output"), width);
         }
     }
     if matches.subcommand_matches("eat").is_some() {
         eat(file)
     }
+    if matches.subcommand_matches("verify").is_some() {
+        verify(file)
+    }
 }
 
 fn eat(file: &str) {
-    let content = std::fs::read_to_string(file).expect("File not found.");
-    let doc = markdown_to_semdoc::markdown_to_semdoc(&content);
+    let bytes = std::fs::read(file).expect("File not found.");
+    let content = String::from_utf8_lossy(&bytes);
+    let (doc, warnings) = if file.ends_with(".epub") {
+        epub_to_semdoc::epub_to_semdoc_with_warnings(&bytes).expect("Couldn't read the book.")
+    } else if file.ends_with(".html") || file.ends_with(".htm") {
+        html_to_semdoc::html_to_semdoc_with_warnings(&content)
+    } else if file.ends_with(".ipynb") {
+        jupyter_to_semdoc::jupyter_to_semdoc_with_warnings(&content)
+            .expect("Couldn't read the notebook.")
+    } else if file.ends_with(".json") {
+        pandoc_to_semdoc::pandoc_to_semdoc_with_warnings(&content)
+            .expect("Couldn't read the Pandoc document.")
+    } else if file.ends_with(".rst") {
+        rst_to_semdoc::rst_to_semdoc_with_warnings(&content)
+    } else if file.ends_with(".adoc") || file.ends_with(".asciidoc") {
+        asciidoc_to_semdoc::asciidoc_to_semdoc_with_warnings(&content)
+    } else {
+        markdown_to_semdoc::markdown_to_semdoc_with_warnings(&content)
+    };
+    for warning in warnings {
+        println!("{}", warning.to_string().yellow());
+    }
 
     let mut file = File::create("converted.sd").unwrap();
     file.write_all(&doc.to_bytes()).unwrap();
     inspect_blocks("converted.sd");
 
-    let doc = SemDoc::new(doc.block.simplify());
+    let doc = SemDoc::new(doc.block.simplify()).with_metadata(doc.metadata);
     let mut file = File::create("converted.sd").unwrap();
     file.write_all(&doc.to_bytes()).unwrap();
     inspect_blocks("converted.sd");
 }
+
+fn verify(file: &str) {
+    let bytes = std::fs::read(file).expect("File not found.");
+    let doc = SemDoc::from_bytes(&bytes).expect("Couldn't parse the SemDoc file.");
+
+    match doc.verify() {
+        Ok(signatures) => {
+            for signature in signatures {
+                println!(
+                    "{} {} ({})",
+                    "Valid signature by".green(),
+                    signature.signer.bold(),
+                    signature
+                        .public_key
+                        .iter()
+                        .map(|byte| format!("{:02x}", byte))
+                        .collect::<String>(),
+                );
+            }
+        }
+        Err(SignatureError::Unsigned) => {
+            println!("{}", "The document isn't signed.".red());
+            std::process::exit(1);
+        }
+        Err(SignatureError::DamagedContent) => {
+            println!("{}", "The signed content is damaged.".red());
+            std::process::exit(1);
+        }
+        Err(SignatureError::InvalidSignature { signer }) => {
+            println!("{} {}", "Invalid signature by".red(), signer.bold());
+            std::process::exit(1);
+        }
+        Err(SignatureError::UnsignedMetadata { signer }) => {
+            println!(
+                "{} {}",
+                "The metadata isn't covered by the signature of".red(),
+                signer.bold()
+            );
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,481 @@
+//! Inline markup, like `*strong*`, `` `monospace` ``, `<<setup,cross references>>` and macros
+//! like `footnote:[A footnote.]`.
+
+use crate::{trim_inlines, Attributes, ConversionWarningKind, Formatting, Parser};
+use semdoc::{Block, Pure};
+
+/// Attributes that every document has, like `{nbsp}` for a non-breaking space.
+const BUILT_IN_ATTRIBUTES: &[(&str, &str)] = &[
+    ("nbsp", "\u{a0}"),
+    ("sp", " "),
+    ("empty", ""),
+    ("zwsp", "\u{200b}"),
+    ("amp", "&"),
+    ("lt", "<"),
+    ("gt", ">"),
+    ("startsb", "["),
+    ("endsb", "]"),
+    ("vbar", "|"),
+    ("caret", "^"),
+    ("asterisk", "*"),
+    ("tilde", "~"),
+    ("backslash", "\\"),
+    ("backtick", "`"),
+    ("plus", "+"),
+    ("apos", "'"),
+    ("quot", "\""),
+    ("deg", "°"),
+    ("two-colons", "::"),
+    ("two-semicolons", ";;"),
+    ("cpp", "C++"),
+];
+
+/// URL schemes that are recognized without a `link:` prefix.
+const SCHEMES: &[&str] = &["https", "http", "ftp", "irc", "mailto"];
+
+impl Parser {
+    /// Replaces references to attributes, like `{project-name}`, with their values. References to
+    /// unknown attributes are kept.
+    pub(crate) fn substitute_attributes(&self, text: &str) -> String {
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let name = after.find('}').map(|end| &after[..end]);
+            let value = name.and_then(|name| {
+                let built_in = BUILT_IN_ATTRIBUTES.iter().find(|(it, _)| *it == name);
+                self.attribute(name)
+                    .or_else(|| built_in.map(|(_, value)| *value))
+            });
+            match (name, value) {
+                (Some(name), Some(value)) if !rest[..start].ends_with('\\') => {
+                    result.push_str(value);
+                    rest = &after[name.len() + 1..];
+                }
+                _ => {
+                    result.push('{');
+                    rest = after;
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    /// Parses inline markup. The text starts at the given line of the source.
+    pub(crate) fn inline(&mut self, text: &str, line: u32) -> Block<Pure> {
+        let text = self.substitute_attributes(text);
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut inline = Inline {
+            chars: &chars,
+            line,
+            position: 0,
+            text: String::new(),
+            blocks: vec![],
+        };
+        while inline.position < chars.len() {
+            let c = chars[inline.position];
+            if c == '\\'
+                && chars
+                    .get(inline.position + 1)
+                    .is_some_and(|c| !c.is_alphanumeric())
+            {
+                inline.text.push(chars[inline.position + 1]);
+                inline.position += 2;
+                continue;
+            }
+            let start = inline.position;
+            match self.markup(&mut inline) {
+                Some(block) => {
+                    inline.flush();
+                    inline.blocks.push(block);
+                }
+                None => {
+                    inline.position = start;
+                    inline.text.push(c);
+                    inline.position += 1;
+                }
+            }
+        }
+        inline.flush();
+        trim_inlines(Block::Flow(inline.blocks))
+    }
+
+    /// Parses the markup starting at the current position.
+    fn markup(&mut self, inline: &mut Inline) -> Option<Block<Pure>> {
+        let line = inline.current_line();
+        let rest = inline.rest();
+        let c = rest.chars().next()?;
+        match c {
+            '`' => {
+                let code = inline
+                    .unconstrained("``")
+                    .or_else(|| inline.constrained('`'))?;
+                let code = match code.strip_prefix('+').and_then(|it| it.strip_suffix('+')) {
+                    Some(literal) => literal.to_owned(),
+                    None => code,
+                };
+                Some(Block::Code {
+                    language: None,
+                    code,
+                })
+            }
+            '+' => {
+                let text = inline
+                    .unconstrained("+++")
+                    .or_else(|| inline.unconstrained("++"))
+                    .or_else(|| inline.constrained('+'))?;
+                Some(Block::Text(text))
+            }
+            '*' | '_' => {
+                let double = format!("{}{}", c, c);
+                let content = inline
+                    .unconstrained(&double)
+                    .or_else(|| inline.constrained(c))?;
+                Some(self.inline(&content, line))
+            }
+            '#' => self.highlighted(inline, &[], line),
+            '^' | '~' => {
+                let content = inline.unconstrained(&c.to_string())?;
+                if content.contains(char::is_whitespace) {
+                    return None;
+                }
+                let formatting = if c == '^' {
+                    Formatting::Superscript
+                } else {
+                    Formatting::Subscript
+                };
+                self.warn(line, ConversionWarningKind::DroppedFormatting(formatting));
+                Some(self.inline(&content, line))
+            }
+            '<' if rest.starts_with("<<") => {
+                let end = rest.find(">>")?;
+                let reference = &rest[2..end];
+                if reference.is_empty() || reference.starts_with(char::is_whitespace) {
+                    return None;
+                }
+                inline.position += rest[..end + 2].chars().count();
+                let (target, text) = match reference.split_once(',') {
+                    Some((target, text)) => (target.trim(), Some(text.trim())),
+                    None => (reference.trim(), None),
+                };
+                Some(self.cross_reference(target, text, line))
+            }
+            '[' => {
+                if let Some(anchor) = rest.strip_prefix("[[") {
+                    let anchor = anchor.trim_start_matches('[');
+                    let end = anchor.find("]]")?;
+                    let id = anchor[..end]
+                        .split(',')
+                        .next()
+                        .unwrap_or_default()
+                        .to_owned();
+                    let skipped = rest.len() - anchor.len() + end;
+                    let length = rest[..skipped].chars().count();
+                    inline.position +=
+                        length + rest[skipped..].chars().take_while(|c| *c == ']').count();
+                    return Some(Block::Anchor {
+                        id,
+                        content: Box::new(Block::Empty),
+                    });
+                }
+                // Formatted text with roles, like `[.underline]#text#`.
+                let end = rest.find(']')?;
+                if !rest[end + 1..].starts_with('#') {
+                    return None;
+                }
+                let mut attributes = Attributes::default();
+                attributes.parse(&rest[1..end]);
+                inline.position += rest[..=end].chars().count();
+                self.highlighted(inline, &attributes.roles, line)
+            }
+            c if c.is_alphabetic() && inline.is_word_start() => self.inline_macro(inline, line),
+            _ => None,
+        }
+    }
+
+    /// Parses text between `#`, which is highlighted unless it has a role like `line-through`.
+    fn highlighted(
+        &mut self,
+        inline: &mut Inline,
+        roles: &[String],
+        line: u32,
+    ) -> Option<Block<Pure>> {
+        let content = inline
+            .unconstrained("##")
+            .or_else(|| inline.constrained('#'))?;
+        let content = self.inline(&content, line);
+        let formatting = roles.iter().find_map(|role| match role.as_str() {
+            "line-through" | "strike" => Some(Formatting::Strikethrough),
+            "underline" => Some(Formatting::Underline),
+            _ => None,
+        });
+        if let Some(formatting) = formatting {
+            self.warn(line, ConversionWarningKind::DroppedFormatting(formatting));
+            return Some(content);
+        }
+        if roles.is_empty() || roles.iter().any(|role| role == "mark") {
+            return Some(Block::Highlighted {
+                content: Box::new(content),
+                reason: None,
+            });
+        }
+        let role = roles.join(" ");
+        self.warn(line, ConversionWarningKind::UnsupportedRole(role));
+        Some(content)
+    }
+
+    fn cross_reference(&mut self, target: &str, text: Option<&str>, line: u32) -> Block<Pure> {
+        let content = match text {
+            Some(text) if !text.is_empty() => self.inline(text, line),
+            _ => Block::Empty,
+        };
+        // References to other documents, like `<<setup.adoc#install>>`, are links.
+        if target.contains(".adoc") {
+            return Block::Link {
+                target: target.to_owned(),
+                content: Box::new(match content {
+                    Block::Empty => Block::Text(target.to_owned()),
+                    content => content,
+                }),
+            };
+        }
+        Block::CrossReference {
+            target: target.trim_start_matches('#').to_owned(),
+            content: Box::new(content),
+        }
+    }
+
+    /// Parses inline macros, like `link:index.html[Home]`, and URLs.
+    fn inline_macro(&mut self, inline: &mut Inline, line: u32) -> Option<Block<Pure>> {
+        let rest = inline.rest();
+        let name_end = rest.find(|c: char| !c.is_alphanumeric() && c != '-')?;
+        let name = &rest[..name_end];
+        let after_name = rest[name_end..].strip_prefix(':')?;
+        let is_scheme =
+            SCHEMES.contains(&name) && (name == "mailto" || after_name.starts_with("//"));
+        let target_end = after_name
+            .find(|c: char| c == '[' || c.is_whitespace() || (is_scheme && "<>\"".contains(c)))
+            .unwrap_or(after_name.len());
+        let target = &after_name[..target_end];
+        let attributes = after_name[target_end..]
+            .strip_prefix('[')
+            .and_then(|list| list.get(..closing_bracket(list)?));
+        let length = match attributes {
+            Some(attributes) => name_end + 1 + target_end + attributes.len() + 2,
+            None => name_end + 1 + target_end,
+        };
+
+        let block = match (name, attributes) {
+            (name, _) if is_scheme => {
+                let (url, length) = match attributes {
+                    Some(_) => (format!("{}:{}", name, target), length),
+                    // URLs without text don't include trailing punctuation.
+                    None => {
+                        let target = target.trim_end_matches(|c: char| ".,;:!?)'".contains(c));
+                        (format!("{}:{}", name, target), name_end + 1 + target.len())
+                    }
+                };
+                inline.position += rest[..length].chars().count();
+                return Some(self.link(url, attributes, line));
+            }
+            ("link", Some(_)) => self.link(target.to_owned(), attributes, line),
+            ("xref", Some(text)) => self.cross_reference(target, Some(text), line),
+            ("footnote", Some(text)) => self.footnote(target, text, line),
+            ("image", Some(text)) => {
+                let url = target.to_owned();
+                self.warn(
+                    line,
+                    ConversionWarningKind::ImageAsLink { url: url.clone() },
+                );
+                let mut attributes = Attributes::default();
+                attributes.parse(text);
+                let description = attributes.positional(0).unwrap_or(target).to_owned();
+                Block::Link {
+                    target: url,
+                    content: Box::new(Block::Text(description)),
+                }
+            }
+            ("stem" | "latexmath" | "asciimath", Some(math)) if target.is_empty() => Block::Code {
+                language: Some(crate::math_language(name, self).to_owned()),
+                code: math.to_owned(),
+            },
+            ("pass", Some(text)) if target.is_empty() => Block::Text(text.to_owned()),
+            ("anchor", Some(text)) => Block::Anchor {
+                id: target.to_owned(),
+                content: Box::new(if text.is_empty() {
+                    Block::Empty
+                } else {
+                    self.inline(text, line)
+                }),
+            },
+            ("kbd" | "btn", Some(text)) if target.is_empty() => {
+                self.warn(
+                    line,
+                    ConversionWarningKind::UnsupportedRole(name.to_owned()),
+                );
+                Block::Text(text.to_owned())
+            }
+            ("menu", Some(items)) => {
+                self.warn(
+                    line,
+                    ConversionWarningKind::UnsupportedRole(name.to_owned()),
+                );
+                let mut path = vec![target.trim()];
+                path.extend(items.split('>').map(str::trim).filter(|it| !it.is_empty()));
+                Block::Text(path.join(" > "))
+            }
+            _ => return None,
+        };
+        inline.position += rest[..length].chars().count();
+        Some(block)
+    }
+
+    /// A link whose text is the first attribute, like `https://rust-lang.org[Rust^]`.
+    fn link(&mut self, url: String, attributes: Option<&str>, line: u32) -> Block<Pure> {
+        let mut parsed = Attributes::default();
+        if let Some(attributes) = attributes.filter(|it| it.contains('=')) {
+            parsed.parse(attributes);
+        }
+        let text = match attributes {
+            Some(attributes) if attributes.contains('=') => parsed.positional(0).unwrap_or(""),
+            Some(text) => text,
+            None => "",
+        };
+        // A trailing `^` opens the link in a new window.
+        let text = text.trim_end_matches('^').trim();
+        let content = if text.is_empty() {
+            Block::Text(url.strip_prefix("mailto:").unwrap_or(&url).to_owned())
+        } else {
+            self.inline(text, line)
+        };
+        Block::Link {
+            target: url,
+            content: Box::new(content),
+        }
+    }
+
+    /// Footnotes are collected at the end of the document and referred to by their number.
+    /// Footnotes with an ID, like `footnote:disclaimer[Text]`, can be referred to again using
+    /// `footnote:disclaimer[]`.
+    fn footnote(&mut self, id: &str, text: &str, line: u32) -> Block<Pure> {
+        let label = match self.footnote_labels.get(id) {
+            Some(label) if !id.is_empty() => label.clone(),
+            _ => {
+                let label = (self.footnotes.len() + 1).to_string();
+                if !id.is_empty() {
+                    self.footnote_labels.insert(id.to_owned(), label.clone());
+                }
+                let content = self.inline(text, line);
+                self.footnotes.push(Block::Anchor {
+                    id: footnote_id(&label),
+                    content: Box::new(Block::Secondary(Box::new(content))),
+                });
+                label
+            }
+        };
+        Block::CrossReference {
+            target: footnote_id(&label),
+            content: Box::new(Block::Text(label)),
+        }
+    }
+}
+
+fn footnote_id(label: &str) -> String {
+    format!("footnote-{}", label)
+}
+
+/// The index of the bracket that closes the attribute list at the start of the text.
+fn closing_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut previous = ' ';
+    for (index, c) in text.char_indices() {
+        match c {
+            '[' if previous != '\\' => depth += 1,
+            ']' if previous != '\\' && depth == 0 => return Some(index),
+            ']' if previous != '\\' => depth -= 1,
+            _ => {}
+        }
+        previous = c;
+    }
+    None
+}
+
+/// The state of parsing inline markup.
+struct Inline<'a> {
+    chars: &'a [char],
+    /// The line of the source where the text starts.
+    line: u32,
+    position: usize,
+    /// Text that isn't part of any markup, which is added to the blocks before the next markup.
+    text: String,
+    blocks: Vec<Block<Pure>>,
+}
+
+impl<'a> Inline<'a> {
+    fn rest(&self) -> String {
+        self.chars[self.position..].iter().collect()
+    }
+
+    fn current_line(&self) -> u32 {
+        let lines_before = self.chars[..self.position]
+            .iter()
+            .filter(|c| **c == '\n')
+            .count();
+        self.line + lines_before as u32
+    }
+
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            self.blocks
+                .push(Block::Text(std::mem::take(&mut self.text)));
+        }
+    }
+
+    fn is_word_start(&self) -> bool {
+        self.position == 0 || !self.chars[self.position - 1].is_alphanumeric()
+    }
+
+    /// Parses markup that may appear anywhere, like `**strong**`.
+    fn unconstrained(&mut self, delimiter: &str) -> Option<String> {
+        let delimiter = delimiter.chars().collect::<Vec<_>>();
+        let start = self.position + delimiter.len();
+        if self.chars.get(self.position..start)? != &delimiter[..] {
+            return None;
+        }
+        let end = (start + 1..=self.chars.len().saturating_sub(delimiter.len()))
+            .find(|index| self.chars[*index..*index + delimiter.len()] == delimiter[..])?;
+        let content = self.chars[start..end].iter().collect::<String>();
+        if content.starts_with(char::is_whitespace) || content.ends_with(char::is_whitespace) {
+            return None;
+        }
+        self.position = end + delimiter.len();
+        Some(content)
+    }
+
+    /// Parses markup that has to surround words, like `*strong*`.
+    fn constrained(&mut self, delimiter: char) -> Option<String> {
+        if self.chars.get(self.position) != Some(&delimiter) {
+            return None;
+        }
+        let is_start = self.position == 0 || {
+            let previous = self.chars[self.position - 1];
+            !previous.is_alphanumeric() && previous != delimiter
+        };
+        let start = self.position + 1;
+        if !is_start || self.chars.get(start).map_or(true, |c| c.is_whitespace()) {
+            return None;
+        }
+        let end = (start + 1..self.chars.len()).find(|index| {
+            self.chars[*index] == delimiter
+                && !self.chars[index - 1].is_whitespace()
+                && self
+                    .chars
+                    .get(index + 1)
+                    .map_or(true, |c| !c.is_alphanumeric() && *c != '_')
+        })?;
+        self.position = end + 1;
+        Some(self.chars[start..end].iter().collect())
+    }
+}
@@ -0,0 +1,1494 @@
+use semdoc::{
+    nest_sections, Block, ChecklistItem, Definition, Heading, Media, MediaSource, Metadata, Pure,
+    SectionItem, SemDoc, TemporalValue,
+};
+use std::collections::HashMap;
+
+mod inline;
+mod tables;
+pub use conversion_warnings::{ConversionWarning, ConversionWarningKind, Formatting, Location};
+
+pub fn asciidoc_to_semdoc(asciidoc: &str) -> SemDoc<Pure> {
+    asciidoc_to_semdoc_with_warnings(asciidoc).0
+}
+
+/// Converts AsciiDoc into a SemDoc and reports content that couldn't be converted faithfully.
+///
+/// The document header, which consists of the title, the author and revision lines, and
+/// attribute entries like `:keywords: rust, docs`, becomes the metadata of the document.
+pub fn asciidoc_to_semdoc_with_warnings(asciidoc: &str) -> (SemDoc<Pure>, Vec<ConversionWarning>) {
+    let lines = asciidoc
+        .lines()
+        .enumerate()
+        .map(|(index, text)| Line {
+            number: index as u32 + 1,
+            text: text.trim_end().to_owned(),
+        })
+        .collect::<Vec<_>>();
+    let mut parser = Parser::default();
+    let body_start = parser.header(&lines);
+    let mut blocks = parser.blocks(&lines[body_start..]);
+    // Footnotes are defined inline, so they are collected at the end of the document.
+    blocks.extend(std::mem::take(&mut parser.footnotes));
+    let metadata = parser.metadata();
+    let block = Block::Paragraphs(blocks);
+    let doc = SemDoc::new(block.link_glossary_terms()).with_metadata(metadata);
+    (doc, parser.warnings)
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    /// The line in the source, starting at 1.
+    number: u32,
+    text: String,
+}
+impl Line {
+    fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+}
+
+/// The attributes of a block, which are given in the lines before it, like this:
+///
+/// ```asciidoc
+/// [[hello]]
+/// .A program
+/// [source,rust]
+/// ----
+/// fn main() {}
+/// ----
+/// ```
+#[derive(Default, Debug, Clone)]
+struct Attributes {
+    /// Positional attributes. The first one is the style, like "source" above.
+    positional: Vec<String>,
+    named: HashMap<String, String>,
+    id: Option<String>,
+    options: Vec<String>,
+    roles: Vec<String>,
+    title: Option<String>,
+    /// The line of the first attribute.
+    line: Option<u32>,
+}
+
+impl Attributes {
+    fn style(&self) -> Option<&str> {
+        self.positional
+            .first()
+            .map(|style| style.as_str())
+            .filter(|style| !style.is_empty())
+    }
+
+    fn positional(&self, index: usize) -> Option<&str> {
+        self.positional
+            .get(index)
+            .map(|value| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn has_option(&self, option: &str) -> bool {
+        self.options.iter().any(|it| it == option)
+    }
+
+    /// Parses an attribute list like `[source#hello%linenums,rust,indent=0]`.
+    fn parse(&mut self, list: &str) {
+        for (index, entry) in split_attribute_list(list).into_iter().enumerate() {
+            match entry.split_once('=') {
+                Some((name, value)) if is_attribute_name(name.trim()) => {
+                    let name = name.trim().to_owned();
+                    let value = unquote(value.trim()).to_owned();
+                    match name.as_str() {
+                        "id" => self.id = Some(value),
+                        "role" => self
+                            .roles
+                            .extend(value.split_whitespace().map(str::to_owned)),
+                        "options" | "opts" => self
+                            .options
+                            .extend(value.split(',').map(|it| it.trim().to_owned())),
+                        "title" => self.title = Some(value),
+                        _ => {
+                            self.named.insert(name, value);
+                        }
+                    }
+                }
+                _ if index == 0 => self.parse_shorthands(unquote(entry.trim())),
+                _ => self.positional.push(unquote(entry.trim()).to_owned()),
+            }
+        }
+    }
+
+    /// Parses the first positional attribute, which may contain an ID, roles and options after
+    /// the style, like `source#hello.large%linenums`.
+    fn parse_shorthands(&mut self, entry: &str) {
+        let style_end = entry.find(['#', '.', '%']).unwrap_or(entry.len());
+        self.positional.push(entry[..style_end].to_owned());
+        let mut rest = &entry[style_end..];
+        while let Some(kind) = rest.chars().next() {
+            let end = rest[1..]
+                .find(['#', '.', '%'])
+                .map_or(rest.len(), |end| end + 1);
+            let value = rest[1..end].to_owned();
+            match kind {
+                '#' => self.id = Some(value),
+                '.' => self.roles.push(value),
+                _ => self.options.push(value),
+            }
+            rest = &rest[end..];
+        }
+    }
+}
+
+#[derive(Default)]
+struct Parser {
+    /// Document attributes, like `:source-language: rust`, by their name.
+    attributes: HashMap<String, String>,
+    title: Option<String>,
+    authors: Vec<String>,
+    footnotes: Vec<Block<Pure>>,
+    /// The labels of footnotes with an ID, like `footnote:disclaimer[]`, by their ID.
+    footnote_labels: HashMap<String, String>,
+    warnings: Vec<ConversionWarning>,
+}
+
+impl Parser {
+    fn warn(&mut self, line: u32, kind: ConversionWarningKind) {
+        self.warnings.push(ConversionWarning {
+            location: Location::Line(line),
+            kind,
+        });
+    }
+
+    /// Parses the document header and returns the index of the first line of the body.
+    fn header(&mut self, lines: &[Line]) -> usize {
+        let mut index = 0;
+        while index < lines.len() && (lines[index].is_blank() || is_comment(&lines[index].text)) {
+            index += 1;
+        }
+        // Attribute entries may come before the title.
+        while let Some((name, value)) = lines.get(index).and_then(|l| attribute_entry(&l.text)) {
+            self.set_attribute(name, value);
+            index += 1;
+        }
+        let title = match lines
+            .get(index)
+            .and_then(|line| line.text.strip_prefix("= "))
+        {
+            Some(title) => title,
+            None => return index,
+        };
+        self.title = Some(self.inline(title.trim(), lines[index].number).plain_text());
+        index += 1;
+
+        let mut header_lines = 0;
+        while let Some(line) = lines.get(index) {
+            if line.is_blank() {
+                break;
+            }
+            index += 1;
+            if is_comment(&line.text) {
+                continue;
+            }
+            if let Some((name, value)) = attribute_entry(&line.text) {
+                self.set_attribute(name, value);
+                continue;
+            }
+            header_lines += 1;
+            if header_lines == 1 {
+                self.authors = line
+                    .text
+                    .split(';')
+                    .map(|author| {
+                        author
+                            .split('<')
+                            .next()
+                            .unwrap_or_default()
+                            .trim()
+                            .to_owned()
+                    })
+                    .filter(|author| !author.is_empty())
+                    .collect();
+            } else if header_lines == 2 {
+                // The revision line looks like `v1.0, 2021-03-14: Remark`.
+                let revision = line.text.split(':').next().unwrap_or_default();
+                let (number, date) = match revision.split_once(',') {
+                    Some((number, date)) => (Some(number), Some(date)),
+                    None if parse_date(revision).is_some() => (None, Some(revision)),
+                    None => (Some(revision), None),
+                };
+                if let Some(number) = number {
+                    let number = number.trim().trim_start_matches('v').to_owned();
+                    self.attributes.insert("revnumber".to_owned(), number);
+                }
+                if let Some(date) = date {
+                    self.attributes
+                        .insert("revdate".to_owned(), date.trim().to_owned());
+                }
+            }
+        }
+        index
+    }
+
+    fn metadata(&self) -> Metadata {
+        let mut metadata = Metadata {
+            title: self.title.clone(),
+            authors: self.authors.clone(),
+            ..Metadata::default()
+        };
+        if metadata.authors.is_empty() {
+            if let Some(authors) = self
+                .attributes
+                .get("authors")
+                .or_else(|| self.attributes.get("author"))
+            {
+                metadata.authors = authors
+                    .split([';', ','])
+                    .map(|author| author.trim().to_owned())
+                    .filter(|author| !author.is_empty())
+                    .collect();
+            }
+        }
+        if let Some(date) = self.attributes.get("revdate") {
+            match parse_date(date) {
+                Some(date) => metadata.date = Some(date),
+                None => metadata.custom.push(("date".to_owned(), date.clone())),
+            }
+        }
+        if let Some(keywords) = self.attributes.get("keywords") {
+            metadata.tags = keywords
+                .split(',')
+                .map(|tag| tag.trim().to_owned())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+        }
+        for (attribute, key) in &[
+            ("description", "description"),
+            ("revnumber", "version"),
+            ("lang", "lang"),
+        ] {
+            if let Some(value) = self.attributes.get(*attribute) {
+                metadata.custom.push((key.to_string(), value.clone()));
+            }
+        }
+        metadata
+    }
+
+    fn set_attribute(&mut self, name: &str, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                let value = self.substitute_attributes(value);
+                self.attributes.insert(name.to_owned(), value);
+            }
+            None => {
+                self.attributes.remove(name);
+            }
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(|value| value.as_str())
+    }
+
+    /// Converts lines into blocks, nesting the content of sections.
+    fn blocks(&mut self, lines: &[Line]) -> Vec<Block<Pure>> {
+        let mut items = vec![];
+        let mut attributes = Attributes::default();
+        let mut index = 0;
+        while index < lines.len() {
+            let (new_items, next) = self.item(lines, index, &mut attributes);
+            items.extend(new_items);
+            index = next;
+        }
+        nest_sections(items)
+    }
+
+    /// Parses the line at the given index, which may be an attribute of the following block or
+    /// start a block. Returns the items and the index of the next line.
+    fn item(
+        &mut self,
+        lines: &[Line],
+        index: usize,
+        attributes: &mut Attributes,
+    ) -> (Vec<SectionItem<Pure>>, usize) {
+        let line = &lines[index];
+        let text = line.text.as_str();
+        if line.is_blank() || is_comment(text) {
+            return (vec![], index + 1);
+        }
+        if text.starts_with("////") && delimiter(text) == Some(Delimiter::Comment) {
+            let (_, end) = delimited(lines, index);
+            return (vec![], end);
+        }
+        if attributes.line.is_none() {
+            attributes.line = Some(line.number);
+        }
+        if let Some(anchor) = text
+            .strip_prefix("[[")
+            .and_then(|rest| rest.strip_suffix("]]"))
+        {
+            attributes.id = Some(anchor.split(',').next().unwrap_or_default().to_owned());
+            return (vec![], index + 1);
+        }
+        if let Some(list) = text
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            attributes.parse(list);
+            return (vec![], index + 1);
+        }
+        if let Some(title) = block_title(text) {
+            attributes.title = Some(title.to_owned());
+            return (vec![], index + 1);
+        }
+        if let Some((name, value)) = attribute_entry(text) {
+            self.set_attribute(name, value);
+            return (vec![], index + 1);
+        }
+        let attributes = std::mem::take(attributes);
+        if let Some((level, title)) = heading(text) {
+            let title = self.inline(title, line.number);
+            let id = attributes
+                .id
+                .clone()
+                .unwrap_or_else(|| generate_id(&title.plain_text()));
+            let mut heading = Heading::new(level, title).with_ids(Some(id));
+            if attributes.style() == Some("glossary") {
+                heading = heading.glossary();
+            }
+            return (vec![SectionItem::Heading(heading)], index + 1);
+        }
+        let (blocks, end) = self.block(lines, index, &attributes);
+        let blocks = match (&attributes.id, blocks.len()) {
+            (Some(id), 1) => vec![Block::Anchor {
+                id: id.clone(),
+                content: Box::new(blocks.into_iter().next().unwrap()),
+            }],
+            _ => blocks,
+        };
+        (blocks.into_iter().map(SectionItem::Block).collect(), end)
+    }
+
+    /// Parses a single block with its attributes, like an item of a list after a `+` line.
+    fn attached_block(&mut self, lines: &[Line], mut index: usize) -> (Vec<Block<Pure>>, usize) {
+        let mut attributes = Attributes::default();
+        while index < lines.len() && !lines[index].is_blank() {
+            let (items, next) = self.item(lines, index, &mut attributes);
+            index = next;
+            if !items.is_empty() {
+                let blocks = items
+                    .into_iter()
+                    .map(|item| match item {
+                        SectionItem::Block(block) => block,
+                        SectionItem::Heading(heading) => heading.title,
+                    })
+                    .collect();
+                return (blocks, index);
+            }
+        }
+        (vec![], index)
+    }
+
+    /// Parses the block starting at the given line, which isn't an attribute or a title.
+    fn block(
+        &mut self,
+        lines: &[Line],
+        index: usize,
+        attributes: &Attributes,
+    ) -> (Vec<Block<Pure>>, usize) {
+        let line = &lines[index];
+        let text = line.text.as_str();
+        if let Some(delimiter) = delimiter(text) {
+            let (content, end) = delimited(lines, index);
+            let blocks = self.delimited_block(delimiter, text, content, attributes, line.number);
+            return (blocks, end);
+        }
+        if text.chars().all(|c| c == '\'') && text.len() >= 3 {
+            return (vec![Block::TopicChange], index + 1);
+        }
+        if text == "<<<" {
+            return (vec![], index + 1);
+        }
+        if let Some(block_macro) = BlockMacro::parse(text) {
+            let blocks = self.block_macro(block_macro, attributes, line.number);
+            return (blocks, index + 1);
+        }
+        if list_marker(text).is_some() {
+            let (list, end) = self.list(lines, index, &[], attributes);
+            return (vec![titled(self.title(attributes), list)], end);
+        }
+        self.paragraph(lines, index, attributes)
+    }
+
+    fn title(&mut self, attributes: &Attributes) -> Option<Block<Pure>> {
+        let line = attributes.line.unwrap_or_default();
+        attributes
+            .title
+            .as_ref()
+            .map(|title| self.inline(title, line))
+    }
+
+    fn delimited_block(
+        &mut self,
+        delimiter: Delimiter,
+        opening: &str,
+        content: &[Line],
+        attributes: &Attributes,
+        line: u32,
+    ) -> Vec<Block<Pure>> {
+        let style = attributes.style().map(|style| style.to_lowercase());
+        let style = style.as_deref();
+        let title = self.title(attributes);
+        if let Some(kind) = style.filter(|style| ADMONITIONS.contains(style)) {
+            if matches!(delimiter, Delimiter::Example | Delimiter::Open) {
+                let body = self.blocks(content);
+                return vec![admonition(kind, title, body)];
+            }
+        }
+        let block = match delimiter {
+            Delimiter::Listing | Delimiter::Literal => {
+                let language = match delimiter {
+                    Delimiter::Listing => self.language(attributes),
+                    _ => None,
+                };
+                Block::Code {
+                    language,
+                    code: join_lines(content),
+                }
+            }
+            Delimiter::Fenced => {
+                let language = opening.trim_start_matches('`').trim();
+                Block::Code {
+                    language: Some(language.to_owned()).filter(|it| !it.is_empty()),
+                    code: join_lines(content),
+                }
+            }
+            Delimiter::Open if matches!(style, Some("source" | "listing")) => Block::Code {
+                language: self.language(attributes),
+                code: join_lines(content),
+            },
+            Delimiter::Example => {
+                // Like example callouts in Markdown, examples are asides.
+                let title = title.unwrap_or_else(|| Block::Text("Example".to_owned()));
+                let section = Block::Section {
+                    title: Box::new(title),
+                    body: Box::new(Block::Paragraphs(self.blocks(content))),
+                };
+                return vec![Block::Secondary(Box::new(section))];
+            }
+            Delimiter::Sidebar => {
+                let body = Block::Paragraphs(self.blocks(content));
+                return vec![Block::Secondary(Box::new(titled(title, body)))];
+            }
+            Delimiter::Quote | Delimiter::Open if matches!(style, Some("quote" | "verse")) => {
+                self.quote(content, attributes)
+            }
+            Delimiter::Quote => self.quote(content, attributes),
+            Delimiter::Open => Block::Paragraphs(self.blocks(content)),
+            Delimiter::Passthrough => match style {
+                Some("stem" | "latexmath" | "asciimath") => Block::Code {
+                    language: Some(math_language(style.unwrap(), self).to_owned()),
+                    code: join_lines(content),
+                },
+                _ => {
+                    let html = join_lines(content);
+                    self.warn(line, ConversionWarningKind::DroppedHtml(html));
+                    return vec![];
+                }
+            },
+            Delimiter::Table(separator) => self.table(content, separator, attributes),
+            Delimiter::Comment => return vec![],
+        };
+        vec![titled(title, block)]
+    }
+
+    /// The language of source code. Without an explicit one, the `source-language` attribute of
+    /// the document is used.
+    fn language(&self, attributes: &Attributes) -> Option<String> {
+        match attributes.style() {
+            None | Some("source") => attributes
+                .positional(1)
+                .or_else(|| self.attribute("source-language"))
+                .map(|language| language.to_owned()),
+            Some(_) => None,
+        }
+    }
+
+    /// Converts a quote or a verse. The attribution, like `[quote, Ada Lovelace, Notes]`, is
+    /// added to the quoted content.
+    fn quote(&mut self, content: &[Line], attributes: &Attributes) -> Block<Pure> {
+        let mut blocks = if attributes.style() == Some("verse") {
+            vec![self.verse(content)]
+        } else {
+            self.blocks(content)
+        };
+        let attribution = attributes.positional[1.min(attributes.positional.len())..]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .map(|part| part.as_str())
+            .collect::<Vec<_>>();
+        if !attribution.is_empty() {
+            let line = attributes.line.unwrap_or_default();
+            let attribution = self.inline(&format!("— {}", attribution.join(", ")), line);
+            blocks.push(attribution);
+        }
+        Block::Quote(Box::new(Block::Paragraphs(blocks)))
+    }
+
+    /// Verses keep their line breaks.
+    fn verse(&mut self, lines: &[Line]) -> Block<Pure> {
+        let mut parts = vec![];
+        for (index, line) in lines.iter().enumerate() {
+            if index > 0 {
+                parts.push(Block::Text("\n".to_owned()));
+            }
+            parts.push(self.inline(line.text.trim(), line.number));
+        }
+        Block::Flow(parts)
+    }
+
+    fn block_macro(
+        &mut self,
+        block_macro: BlockMacro,
+        attributes: &Attributes,
+        line: u32,
+    ) -> Vec<Block<Pure>> {
+        let mut macro_attributes = Attributes::default();
+        macro_attributes.parse(&block_macro.attributes);
+        let title = self.title(attributes);
+        let target = self.substitute_attributes(&block_macro.target);
+        let block = match block_macro.name.as_str() {
+            "image" => {
+                self.warn(
+                    line,
+                    ConversionWarningKind::ImageAsLink {
+                        url: target.clone(),
+                    },
+                );
+                let description = macro_attributes.positional(0).unwrap_or(&target).to_owned();
+                Block::Link {
+                    target,
+                    content: Box::new(Block::Text(description)),
+                }
+            }
+            kind @ ("video" | "audio") => {
+                let source = match macro_attributes.positional(0) {
+                    Some("youtube") => format!("https://www.youtube.com/watch?v={}", target),
+                    Some("vimeo") => format!("https://vimeo.com/{}", target),
+                    _ => target,
+                };
+                let media = Media {
+                    mime_type: guess_mime_type(kind, &source),
+                    source: MediaSource::External(source),
+                    duration: None,
+                    transcript: Box::new(Block::Empty),
+                };
+                if kind == "audio" {
+                    Block::Audio(media)
+                } else {
+                    Block::Video(media)
+                }
+            }
+            // The table of contents is generated by readers.
+            "toc" => return vec![],
+            name => {
+                let name = name.to_owned();
+                self.warn(line, ConversionWarningKind::UnsupportedDirective(name));
+                return vec![];
+            }
+        };
+        vec![titled(title, block)]
+    }
+
+    fn paragraph(
+        &mut self,
+        lines: &[Line],
+        index: usize,
+        attributes: &Attributes,
+    ) -> (Vec<Block<Pure>>, usize) {
+        let end = (index..lines.len())
+            .find(|index| lines[*index].is_blank())
+            .unwrap_or(lines.len());
+        let content = lines[index..end]
+            .iter()
+            .filter(|line| !is_comment(&line.text))
+            .cloned()
+            .collect::<Vec<_>>();
+        let number = lines[index].number;
+        let style = attributes.style().map(|style| style.to_lowercase());
+        let title = self.title(attributes);
+        let block = match style.as_deref() {
+            Some(kind) if ADMONITIONS.contains(&kind) => {
+                let body = self.paragraph_text(&content, attributes);
+                return (vec![admonition(kind, title, vec![body])], end);
+            }
+            Some("source" | "listing") => Block::Code {
+                language: self.language(attributes),
+                code: join_lines(&content),
+            },
+            Some("literal") => Block::Code {
+                language: None,
+                code: join_lines(&content),
+            },
+            Some("quote" | "verse") => self.quote(&content, attributes),
+            Some(style @ ("stem" | "latexmath" | "asciimath")) => Block::Code {
+                language: Some(math_language(style, self).to_owned()),
+                code: join_lines(&content),
+            },
+            Some("pass") => {
+                let html = join_lines(&content);
+                self.warn(number, ConversionWarningKind::DroppedHtml(html));
+                return (vec![], end);
+            }
+            // Indented paragraphs are literal.
+            _ if lines[index].text.starts_with([' ', '\t']) => Block::Code {
+                language: None,
+                code: join_lines(&dedent(&content)),
+            },
+            _ => {
+                let first = lines[index].text.as_str();
+                // Admonition paragraphs start with a label, like `NOTE: `.
+                if let Some((label, rest)) = first.split_once(": ") {
+                    let kind = label.to_lowercase();
+                    if ADMONITIONS.contains(&kind.as_str()) && label == label.to_uppercase() {
+                        let mut content = content;
+                        content[0].text = rest.to_owned();
+                        let body = self.paragraph_text(&content, attributes);
+                        return (vec![admonition(&kind, title, vec![body])], end);
+                    }
+                }
+                self.paragraph_text(&content, attributes)
+            }
+        };
+        (vec![titled(title, block)], end)
+    }
+
+    /// Converts the text of a paragraph. Lines ending with ` +` end with a line break.
+    fn paragraph_text(&mut self, lines: &[Line], attributes: &Attributes) -> Block<Pure> {
+        let hard_breaks =
+            attributes.has_option("hardbreaks") || self.attribute("hardbreaks-option").is_some();
+        let mut text = String::new();
+        for (index, line) in lines.iter().enumerate() {
+            let line_text = line.text.trim();
+            match line_text.strip_suffix(" +") {
+                Some(line_text) => {
+                    text.push_str(line_text);
+                    text.push('\n');
+                }
+                None => {
+                    text.push_str(line_text);
+                    if index + 1 < lines.len() {
+                        text.push(if hard_breaks { '\n' } else { ' ' });
+                    }
+                }
+            }
+        }
+        let number = lines.first().map_or(0, |line| line.number);
+        self.inline(&text, number)
+    }
+
+    /// Parses a list. Lists with a different marker nested in an item, unless the marker belongs
+    /// to one of the parent lists.
+    fn list(
+        &mut self,
+        lines: &[Line],
+        mut index: usize,
+        parents: &[String],
+        attributes: &Attributes,
+    ) -> (Block<Pure>, usize) {
+        let first = list_marker(&lines[index].text).unwrap();
+        let mut markers = parents.to_vec();
+        markers.push(first.marker.clone());
+        let mut items = vec![];
+        let end = loop {
+            let mut marker = list_marker(&lines[index].text).unwrap();
+            marker.line = lines[index].number;
+            let (body, end) = self.list_item(lines, index, &marker, &markers);
+            items.push((marker, body));
+            let next = skip_blank(lines, end);
+            match lines.get(next).and_then(|line| list_marker(&line.text)) {
+                Some(marker) if marker.marker == first.marker => index = next,
+                _ => break end,
+            }
+        };
+        let block = match first.kind {
+            ListKind::Bullet if items.iter().all(|(marker, _)| marker.checked.is_some()) => {
+                Block::Checklist(
+                    items
+                        .into_iter()
+                        .map(|(marker, body)| ChecklistItem {
+                            checked: marker.checked.unwrap(),
+                            content: Block::Paragraphs(body),
+                        })
+                        .collect(),
+                )
+            }
+            ListKind::Bullet => Block::BulletList(
+                items
+                    .into_iter()
+                    .map(|(_, body)| Block::Paragraphs(body))
+                    .collect(),
+            ),
+            ListKind::Ordered => Block::OrderedList {
+                start: attributes
+                    .named
+                    .get("start")
+                    .and_then(|start| start.parse().ok())
+                    .or(first.number)
+                    .unwrap_or(1),
+                numbering: None,
+                items: items
+                    .into_iter()
+                    .map(|(_, body)| Block::Paragraphs(body))
+                    .collect(),
+            },
+            ListKind::Description => {
+                let definitions = items
+                    .into_iter()
+                    .map(|(marker, body)| Definition {
+                        term: self.inline(&marker.term, marker.line),
+                        definitions: vec![Block::Paragraphs(body)],
+                    })
+                    .collect();
+                if attributes.style() == Some("glossary") {
+                    Block::Glossary(definitions)
+                } else {
+                    Block::DefinitionList(definitions)
+                }
+            }
+        };
+        (block, end)
+    }
+
+    fn list_item(
+        &mut self,
+        lines: &[Line],
+        index: usize,
+        marker: &ListMarker,
+        markers: &[String],
+    ) -> (Vec<Block<Pure>>, usize) {
+        let mut text_lines = vec![];
+        if !marker.rest.is_empty() {
+            text_lines.push(Line {
+                number: lines[index].number,
+                text: marker.rest.clone(),
+            });
+        }
+        let mut end = index + 1;
+        // The text of description list items may start on the next line.
+        if text_lines.is_empty() && marker.kind == ListKind::Description {
+            end = skip_blank(lines, end);
+        }
+        while let Some(line) = lines.get(end) {
+            let text = line.text.trim();
+            if line.is_blank()
+                || list_marker(&line.text).is_some()
+                || text == "+"
+                || delimiter(text).is_some()
+                || (text.starts_with('[') && text.ends_with(']'))
+            {
+                break;
+            }
+            if !is_comment(text) {
+                text_lines.push(Line {
+                    number: line.number,
+                    text: text.to_owned(),
+                });
+            }
+            end += 1;
+        }
+        let mut body = vec![];
+        if !text_lines.is_empty() {
+            body.push(self.paragraph_text(&text_lines, &Attributes::default()));
+        }
+        loop {
+            // Lines with a single `+` attach the next block to the item.
+            if lines.get(end).map(|line| line.text.trim()) == Some("+") {
+                let (blocks, next) = self.attached_block(lines, end + 1);
+                body.extend(blocks);
+                end = next;
+                continue;
+            }
+            let next = skip_blank(lines, end);
+            match lines.get(next).and_then(|line| list_marker(&line.text)) {
+                Some(nested) if !markers.contains(&nested.marker) => {
+                    let (list, list_end) = self.list(lines, next, markers, &Attributes::default());
+                    body.push(list);
+                    end = list_end;
+                }
+                _ => break,
+            }
+        }
+        (body, end)
+    }
+}
+
+/// Admonitions whose content is an aside rather than something readers should pay extra
+/// attention to.
+const ASIDE_ADMONITIONS: &[&str] = &["note", "tip"];
+
+const ADMONITIONS: &[&str] = &["note", "tip", "important", "caution", "warning"];
+
+fn admonition(kind: &str, title: Option<Block<Pure>>, body: Vec<Block<Pure>>) -> Block<Pure> {
+    let title = title.unwrap_or_else(|| Block::Text(capitalize(kind)));
+    let section = Block::Section {
+        title: Box::new(title),
+        body: Box::new(Block::Paragraphs(body)),
+    };
+    if ASIDE_ADMONITIONS.contains(&kind) {
+        Block::Secondary(Box::new(section))
+    } else {
+        section
+    }
+}
+
+/// Puts a block with a title, like `.A program`, into a section.
+fn titled(title: Option<Block<Pure>>, block: Block<Pure>) -> Block<Pure> {
+    match title {
+        Some(title) => Block::Section {
+            title: Box::new(title),
+            body: Box::new(block),
+        },
+        None => block,
+    }
+}
+
+/// The language of math, which the `stem` attribute of the document sets for `stem` blocks.
+fn math_language(style: &str, parser: &Parser) -> &'static str {
+    match style {
+        "stem" => match parser.attribute("stem") {
+            Some("latexmath") => "latex",
+            _ => "asciimath",
+        },
+        "latexmath" => "latex",
+        _ => "asciimath",
+    }
+}
+
+/// Parses section titles like `== Setup`, returning the level and the title.
+fn heading(text: &str) -> Option<(usize, &str)> {
+    let level = text.chars().take_while(|c| *c == '=').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let title = text[level..].strip_prefix(' ')?.trim();
+    if title.is_empty() {
+        return None;
+    }
+    Some((level - 1, title))
+}
+
+/// Generates the ID of a section like Asciidoctor, like "_getting_started" for "Getting
+/// Started".
+fn generate_id(title: &str) -> String {
+    let words = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    format!("_{}", words.join("_"))
+}
+
+/// Parses dates like "2021-03-14".
+fn parse_date(text: &str) -> Option<TemporalValue> {
+    let mut parts = text.trim().splitn(3, '-');
+    let date = TemporalValue::Date {
+        year: parts.next()?.parse().ok()?,
+        month: parts.next()?.parse().ok()?,
+        day: parts.next()?.parse().ok()?,
+    };
+    if date.is_valid() {
+        Some(date)
+    } else {
+        None
+    }
+}
+
+fn is_comment(text: &str) -> bool {
+    text.starts_with("//") && !text.starts_with("///")
+}
+
+/// Parses block titles like `.A program`.
+fn block_title(text: &str) -> Option<&str> {
+    let title = text.strip_prefix('.')?;
+    if title.starts_with(['.', ' ']) || title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+/// Parses attribute entries like `:source-language: rust`. Entries like `:name!:` unset the
+/// attribute.
+fn attribute_entry(text: &str) -> Option<(&str, Option<&str>)> {
+    let rest = text.strip_prefix(':')?;
+    let (name, value) = rest.split_once(':')?;
+    if !value.is_empty() && !value.starts_with(' ') {
+        return None;
+    }
+    if let Some(name) = name.strip_suffix('!').or_else(|| name.strip_prefix('!')) {
+        return Some((name, None)).filter(|_| is_attribute_name(name));
+    }
+    if !is_attribute_name(name) {
+        return None;
+    }
+    Some((name, Some(value.trim())))
+}
+
+fn is_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+fn split_attribute_list(list: &str) -> Vec<String> {
+    let mut entries = vec![];
+    let mut entry = String::new();
+    let mut quote = None;
+    for c in list.chars() {
+        match (c, quote) {
+            ('"' | '\'', None) if entry.trim().is_empty() || entry.ends_with('=') => {
+                quote = Some(c);
+                entry.push(c);
+            }
+            (c, Some(open)) if c == open => {
+                quote = None;
+                entry.push(c);
+            }
+            (',', None) => entries.push(std::mem::take(&mut entry)),
+            (c, _) => entry.push(c),
+        }
+    }
+    entries.push(entry);
+    entries
+}
+
+fn unquote(text: &str) -> &str {
+    for quote in &['"', '\''] {
+        if let Some(inner) = text
+            .strip_prefix(*quote)
+            .and_then(|rest| rest.strip_suffix(*quote))
+        {
+            return inner;
+        }
+    }
+    text
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delimiter {
+    Listing,
+    Literal,
+    Example,
+    Sidebar,
+    Quote,
+    Passthrough,
+    Comment,
+    Open,
+    /// A table with the cell separator, like `|` for `|===` or `,` for `,===`.
+    Table(char),
+    /// A Markdown-style code block, like ```` ```rust ````.
+    Fenced,
+}
+
+fn delimiter(text: &str) -> Option<Delimiter> {
+    if text == "--" {
+        return Some(Delimiter::Open);
+    }
+    if text.starts_with("```") {
+        return Some(Delimiter::Fenced);
+    }
+    let mut chars = text.chars();
+    let first = chars.next()?;
+    if matches!(first, '|' | ',' | ':' | '!') && text[1..].len() >= 3 && chars.all(|c| c == '=') {
+        return Some(Delimiter::Table(first));
+    }
+    if text.len() < 4 || !text.chars().all(|c| c == first) {
+        return None;
+    }
+    Some(match first {
+        '-' => Delimiter::Listing,
+        '.' => Delimiter::Literal,
+        '=' => Delimiter::Example,
+        '*' => Delimiter::Sidebar,
+        '_' => Delimiter::Quote,
+        '+' => Delimiter::Passthrough,
+        '/' => Delimiter::Comment,
+        _ => return None,
+    })
+}
+
+/// The content of the delimited block starting at the given line and the index after it.
+fn delimited(lines: &[Line], start: usize) -> (&[Line], usize) {
+    let opening = lines[start].text.as_str();
+    let closing = if opening.starts_with("```") {
+        "```"
+    } else {
+        opening
+    };
+    match (start + 1..lines.len()).find(|index| lines[*index].text == closing) {
+        Some(end) => (&lines[start + 1..end], end + 1),
+        None => (&lines[start + 1..], lines.len()),
+    }
+}
+
+/// A block macro, like `image::cat.png[A cat]`.
+struct BlockMacro {
+    name: String,
+    target: String,
+    attributes: String,
+}
+
+impl BlockMacro {
+    fn parse(text: &str) -> Option<BlockMacro> {
+        let (name, rest) = text.split_once("::")?;
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-') {
+            return None;
+        }
+        let (target, attributes) = rest.strip_suffix(']')?.split_once('[')?;
+        if target.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(BlockMacro {
+            name: name.to_owned(),
+            target: target.to_owned(),
+            attributes: attributes.to_owned(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListKind {
+    Bullet,
+    Ordered,
+    Description,
+}
+
+struct ListMarker {
+    kind: ListKind,
+    /// The marker that items of the same list share, like "**" or "::".
+    marker: String,
+    /// The explicit number of ordered list items, like 3 for `3.`.
+    number: Option<u64>,
+    /// Whether a checklist item is checked, like `* [x] Done`.
+    checked: Option<bool>,
+    /// The term of description list items.
+    term: String,
+    rest: String,
+    line: u32,
+}
+
+fn list_marker(text: &str) -> Option<ListMarker> {
+    let text = text.trim_start();
+    let marker = |kind, marker: &str, rest: &str| ListMarker {
+        kind,
+        marker: marker.to_owned(),
+        number: None,
+        checked: None,
+        term: String::new(),
+        rest: rest.trim().to_owned(),
+        line: 0,
+    };
+    let (symbols, rest) = text.split_at(text.find(' ').unwrap_or(text.len()));
+    if !rest.is_empty() || symbols.is_empty() {
+        let first = symbols.chars().next()?;
+        if symbols == "-" || (first == '*' && symbols.chars().all(|c| c == '*')) {
+            let mut list_marker = marker(ListKind::Bullet, symbols, rest);
+            for (checkbox, checked) in &[("[ ] ", false), ("[x] ", true), ("[*] ", true)] {
+                if let Some(rest) = list_marker.rest.strip_prefix(checkbox) {
+                    list_marker.checked = Some(*checked);
+                    list_marker.rest = rest.trim().to_owned();
+                }
+            }
+            return Some(list_marker);
+        }
+        if first == '.' && symbols.chars().all(|c| c == '.') && symbols.len() <= 5 {
+            return Some(marker(ListKind::Ordered, symbols, rest));
+        }
+        if let Some(number) = symbols.strip_suffix('.') {
+            if let Ok(number) = number.parse() {
+                let mut list_marker = marker(ListKind::Ordered, "1.", rest);
+                list_marker.number = Some(number);
+                return Some(list_marker);
+            }
+            if number.len() == 1 && number.chars().all(|c| c.is_ascii_lowercase()) {
+                return Some(marker(ListKind::Ordered, "a.", rest));
+            }
+        }
+    }
+    for delimiter in &["::::", ":::", "::", ";;"] {
+        if let Some((term, rest)) = text.split_once(delimiter) {
+            let is_marker = !term.trim().is_empty()
+                && !term.ends_with(':')
+                && (rest.is_empty() || rest.starts_with(' '));
+            if is_marker {
+                let mut list_marker = marker(ListKind::Description, delimiter, rest);
+                list_marker.term = term.trim().to_owned();
+                return Some(list_marker);
+            }
+        }
+    }
+    None
+}
+
+fn skip_blank(lines: &[Line], start: usize) -> usize {
+    (start..lines.len())
+        .find(|index| !lines[*index].is_blank())
+        .unwrap_or(lines.len())
+}
+
+fn join_lines(lines: &[Line]) -> String {
+    lines
+        .iter()
+        .map(|line| line.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Removes the common indentation of the lines.
+fn dedent(lines: &[Line]) -> Vec<Line> {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_blank())
+        .map(|line| line.text.len() - line.text.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    lines
+        .iter()
+        .map(|line| Line {
+            number: line.number,
+            text: line.text.get(indent..).unwrap_or_default().to_owned(),
+        })
+        .collect()
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn guess_mime_type(kind: &str, uri: &str) -> String {
+    let extension = uri
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match (kind, extension.as_str()) {
+        (_, "mp3") => "audio/mpeg".to_owned(),
+        (_, "wav") => "audio/wav".to_owned(),
+        (_, "flac") => "audio/flac".to_owned(),
+        ("audio", "ogg" | "oga" | "opus") => "audio/ogg".to_owned(),
+        ("audio", "m4a" | "mp4") => "audio/mp4".to_owned(),
+        (_, "ogg" | "ogv") => "video/ogg".to_owned(),
+        (_, "webm") => "video/webm".to_owned(),
+        (_, "mp4" | "m4v") => "video/mp4".to_owned(),
+        (kind, _) => format!("{}/*", kind),
+    }
+}
+
+/// Removes whitespace around inline content.
+fn trim_inlines(block: Block<Pure>) -> Block<Pure> {
+    match block.simplify() {
+        Block::Text(text) => Block::Text(text.trim().to_owned()),
+        Block::Flow(mut children) => {
+            if let Some(Block::Text(text)) = children.first_mut() {
+                *text = text.trim_start().to_owned();
+            }
+            if let Some(Block::Text(text)) = children.last_mut() {
+                *text = text.trim_end().to_owned();
+            }
+            Block::Flow(children).simplify()
+        }
+        block => block,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use semdoc::Block::*;
+
+    fn convert(asciidoc: &str) -> Block<Pure> {
+        asciidoc_to_semdoc(asciidoc).block.simplify()
+    }
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    #[test]
+    fn header_becomes_metadata() {
+        let doc = asciidoc_to_semdoc(
+            "= The Manual\nAda Lovelace <ada@example.com>; Alan Turing\nv1.2, 2021-03-14: Draft\n\
+             :keywords: rust, docs\n:description: How to use it.\n\nHello.\n",
+        );
+        assert_eq!(doc.metadata.title, Some("The Manual".to_owned()));
+        assert_eq!(
+            doc.metadata.authors,
+            vec!["Ada Lovelace".to_owned(), "Alan Turing".to_owned()]
+        );
+        assert_eq!(
+            doc.metadata.date,
+            Some(TemporalValue::Date {
+                year: 2021,
+                month: 3,
+                day: 14
+            })
+        );
+        assert_eq!(
+            doc.metadata.tags,
+            vec!["rust".to_owned(), "docs".to_owned()]
+        );
+        assert_eq!(doc.metadata.custom("description"), Some("How to use it."));
+        assert_eq!(doc.metadata.custom("version"), Some("1.2"));
+        assert_eq!(doc.block.simplify(), text("Hello."));
+    }
+
+    #[test]
+    fn sections_are_nested() {
+        assert_eq!(
+            convert("== Setup\n\nInstall it.\n\n[[details]]\n=== Details\n\nMore.\n\n== Usage\n"),
+            Paragraphs(vec![
+                Anchor {
+                    id: "_setup".to_owned(),
+                    content: Box::new(Section {
+                        title: Box::new(text("Setup")),
+                        body: Box::new(Paragraphs(vec![
+                            text("Install it."),
+                            Anchor {
+                                id: "details".to_owned(),
+                                content: Box::new(Section {
+                                    title: Box::new(text("Details")),
+                                    body: Box::new(text("More.")),
+                                }),
+                            },
+                        ])),
+                    }),
+                },
+                Anchor {
+                    id: "_usage".to_owned(),
+                    content: Box::new(Section {
+                        title: Box::new(text("Usage")),
+                        body: Box::new(Empty),
+                    }),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(
+            convert(
+                "* One\n** Nested\n* Two\n+\nContinued.\n\n//-\n\n[start=3]\n. Three\n. Four\n\n\
+                 //-\n\n- [x] Done\n- [ ] Todo\n"
+            ),
+            Paragraphs(vec![
+                BulletList(vec![
+                    Paragraphs(vec![text("One"), BulletList(vec![text("Nested")])]),
+                    Paragraphs(vec![text("Two"), text("Continued.")]),
+                ]),
+                OrderedList {
+                    start: 3,
+                    numbering: None,
+                    items: vec![text("Three"), text("Four")],
+                },
+                Checklist(vec![
+                    ChecklistItem {
+                        checked: true,
+                        content: text("Done"),
+                    },
+                    ChecklistItem {
+                        checked: false,
+                        content: text("Todo"),
+                    },
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn admonitions_and_listings() {
+        assert_eq!(
+            convert(
+                ":source-language: rust\n\nTIP: Take a break.\n\n\
+                 [WARNING]\n====\nIt's hot.\n====\n\n\
+                 .A program\n[source]\n----\nfn main() {}\n----\n"
+            ),
+            Paragraphs(vec![
+                Secondary(Box::new(Section {
+                    title: Box::new(text("Tip")),
+                    body: Box::new(text("Take a break.")),
+                })),
+                Section {
+                    title: Box::new(text("Warning")),
+                    body: Box::new(text("It's hot.")),
+                },
+                Section {
+                    title: Box::new(text("A program")),
+                    body: Box::new(Code {
+                        language: Some("rust".to_owned()),
+                        code: "fn main() {}".to_owned(),
+                    }),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn inline_markup() {
+        let (doc, warnings) = asciidoc_to_semdoc_with_warnings(
+            ":project: SemDoc\n\n*{project}* uses `code`, #marks# and H~2~O. See <<setup>>,\n\
+             <<setup,the setup>> and https://rust-lang.org[Rust^].\n",
+        );
+        assert_eq!(
+            doc.block.simplify(),
+            Flow(vec![
+                text("SemDoc uses "),
+                Code {
+                    language: None,
+                    code: "code".to_owned(),
+                },
+                text(", "),
+                Highlighted {
+                    content: Box::new(text("marks")),
+                    reason: None,
+                },
+                text(" and H2O. See "),
+                CrossReference {
+                    target: "setup".to_owned(),
+                    content: Box::new(Empty),
+                },
+                text(", "),
+                CrossReference {
+                    target: "setup".to_owned(),
+                    content: Box::new(text("the setup")),
+                },
+                text(" and "),
+                Link {
+                    target: "https://rust-lang.org".to_owned(),
+                    content: Box::new(text("Rust")),
+                },
+                text("."),
+            ])
+        );
+        assert_eq!(
+            warnings,
+            vec![ConversionWarning {
+                location: Location::Line(3),
+                kind: ConversionWarningKind::DroppedFormatting(Formatting::Subscript),
+            }]
+        );
+    }
+
+    #[test]
+    fn footnotes_are_collected_at_the_end() {
+        assert_eq!(
+            convert("Rust.footnote:fast[It's fast.] Really.footnote:fast[]\n\nMore.\n"),
+            Paragraphs(vec![
+                Flow(vec![
+                    text("Rust."),
+                    CrossReference {
+                        target: "footnote-1".to_owned(),
+                        content: Box::new(text("1")),
+                    },
+                    text(" Really."),
+                    CrossReference {
+                        target: "footnote-1".to_owned(),
+                        content: Box::new(text("1")),
+                    },
+                ]),
+                text("More."),
+                Anchor {
+                    id: "footnote-1".to_owned(),
+                    content: Box::new(Secondary(Box::new(text("It's fast.")))),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn tables() {
+        let table = Table {
+            header: vec![text("Name"), text("Age")],
+            rows: vec![
+                vec![text("Ada"), text("36")],
+                vec![text("Alan"), text("41")],
+            ],
+        };
+        assert_eq!(
+            convert("|===\n|Name |Age\n\n|Ada |36\n|Alan\n|41\n|==="),
+            table
+        );
+        assert_eq!(
+            convert("[%header]\n,===\nName,Age\nAda,36\nAlan,41\n,==="),
+            table
+        );
+        assert_eq!(
+            convert("[cols=\"1,1\"]\n|===\n2+|Merged\n|a |b\n|==="),
+            Table {
+                header: vec![],
+                rows: vec![vec![text("Merged"), Empty], vec![text("a"), text("b")]],
+            }
+        );
+    }
+
+    #[test]
+    fn glossaries_link_terms() {
+        assert_eq!(
+            convert("Each crate compiles.\n\n[glossary]\nCrate:: A package.\n"),
+            Paragraphs(vec![
+                Flow(vec![
+                    text("Each "),
+                    TermUsage {
+                        term: "crate".to_owned(),
+                        content: Box::new(text("crate")),
+                    },
+                    text(" compiles."),
+                ]),
+                Glossary(vec![Definition {
+                    term: text("Crate"),
+                    definitions: vec![text("A package.")],
+                }]),
+            ])
+        );
+    }
+
+    #[test]
+    fn unsupported_macros_are_dropped_with_a_warning() {
+        let (doc, warnings) = asciidoc_to_semdoc_with_warnings(
+            "Text.\n\ninclude::chapter.adoc[]\n\nimage::cat.png[A cat]\n",
+        );
+        assert_eq!(
+            doc.block.simplify(),
+            Paragraphs(vec![
+                text("Text."),
+                Link {
+                    target: "cat.png".to_owned(),
+                    content: Box::new(text("A cat")),
+                },
+            ])
+        );
+        assert_eq!(
+            warnings,
+            vec![
+                ConversionWarning {
+                    location: Location::Line(3),
+                    kind: ConversionWarningKind::UnsupportedDirective("include".to_owned()),
+                },
+                ConversionWarning {
+                    location: Location::Line(5),
+                    kind: ConversionWarningKind::ImageAsLink {
+                        url: "cat.png".to_owned()
+                    },
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Tables, like `|===` tables with cells separated by `|` and `,===` tables with comma-separated
+//! values. Cells spanning multiple columns are followed by empty cells and cells spanning
+//! multiple rows aren't supported.
+
+use crate::{dedent, Attributes, Line, Parser};
+use semdoc::{Block, Pure};
+
+/// A cell and its specifier, like `2+` for a cell spanning two columns or `a` for a cell
+/// containing AsciiDoc blocks.
+struct Cell {
+    spec: String,
+    lines: Vec<Line>,
+}
+
+impl Parser {
+    pub(crate) fn table(
+        &mut self,
+        content: &[Line],
+        separator: char,
+        attributes: &Attributes,
+    ) -> Block<Pure> {
+        // Like Asciidoctor, a first line with all cells of the first row that is followed by a
+        // blank line is the header.
+        let first = content.iter().position(|line| !line.is_blank());
+        let has_implicit_header =
+            first.is_some_and(|first| content.get(first + 1).is_some_and(Line::is_blank));
+        let has_header = attributes.has_option("header")
+            || (has_implicit_header && !attributes.has_option("noheader"));
+
+        let mut rows = if separator == '|' {
+            self.cell_rows(content, attributes)
+        } else {
+            content
+                .iter()
+                .filter(|line| !line.is_blank())
+                .map(|line| {
+                    split_values(&line.text, separator)
+                        .iter()
+                        .map(|value| self.inline(value, line.number))
+                        .collect()
+                })
+                .collect()
+        };
+        let header = if has_header && !rows.is_empty() {
+            rows.remove(0)
+        } else {
+            vec![]
+        };
+        Block::Table { header, rows }
+    }
+
+    fn cell_rows(&mut self, content: &[Line], attributes: &Attributes) -> Vec<Vec<Block<Pure>>> {
+        let mut cells: Vec<Cell> = vec![];
+        let mut first_row_length = None;
+        for line in content {
+            if line.is_blank() {
+                if let Some(cell) = cells.last_mut() {
+                    cell.lines.push(line.clone());
+                }
+                if first_row_length.is_none() && !cells.is_empty() {
+                    first_row_length = Some(cells.len());
+                }
+                continue;
+            }
+            let pieces = split_cells(&line.text);
+            let mut pieces = pieces.into_iter();
+            let before = pieces.next().unwrap_or_default();
+            let mut next_spec = String::new();
+            let is_spec = pieces.len() > 0 && is_cell_spec(before.trim());
+            if is_spec {
+                next_spec = before.trim().to_owned();
+            } else if let Some(cell) = cells.last_mut() {
+                // Text before the first separator continues the previous cell.
+                cell.lines.push(Line {
+                    number: line.number,
+                    text: before,
+                });
+            }
+            let is_first_line_of_row = first_row_length.is_none() && cells.is_empty();
+            let pieces = pieces.collect::<Vec<_>>();
+            for (index, piece) in pieces.iter().enumerate() {
+                let mut text = piece.as_str();
+                let spec = std::mem::take(&mut next_spec);
+                // The specifier of the next cell is at the end of this one, like `a 2+|b`.
+                if index + 1 < pieces.len() {
+                    let (rest, last_word) = match text.rfind(char::is_whitespace) {
+                        Some(space) => (&text[..space], &text[space + 1..]),
+                        None => ("", text),
+                    };
+                    if is_cell_spec(last_word) {
+                        next_spec = last_word.to_owned();
+                        text = rest;
+                    }
+                }
+                cells.push(Cell {
+                    spec,
+                    lines: vec![Line {
+                        number: line.number,
+                        text: text.trim().to_owned(),
+                    }],
+                });
+            }
+            if is_first_line_of_row && cells.len() > 1 {
+                first_row_length = Some(cells.len());
+            }
+        }
+
+        let columns = attributes
+            .named
+            .get("cols")
+            .map(|cols| count_columns(cols))
+            .or(first_row_length)
+            .unwrap_or(cells.len())
+            .max(1);
+        let mut blocks = vec![];
+        for cell in cells {
+            let (span, repeat) = parse_spec(&cell.spec);
+            let block = self.cell(&cell);
+            for _ in 1..repeat {
+                blocks.push(block.clone());
+            }
+            blocks.push(block);
+            for _ in 1..span {
+                blocks.push(Block::Empty);
+            }
+        }
+        blocks.chunks(columns).map(|row| row.to_vec()).collect()
+    }
+
+    fn cell(&mut self, cell: &Cell) -> Block<Pure> {
+        let mut lines = cell.lines.clone();
+        while lines.last().is_some_and(Line::is_blank) {
+            lines.pop();
+        }
+        // Cells with the `a` style contain AsciiDoc blocks, other cells only paragraphs.
+        let blocks = if cell.spec.ends_with('a') {
+            self.blocks(&dedent(&lines))
+        } else {
+            lines
+                .split(Line::is_blank)
+                .filter(|paragraph| !paragraph.is_empty())
+                .map(|paragraph| {
+                    let text = paragraph
+                        .iter()
+                        .map(|line| line.text.trim())
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    self.inline(&text, paragraph[0].number)
+                })
+                .collect()
+        };
+        match blocks.len() {
+            0 => Block::Empty,
+            1 => blocks.into_iter().next().unwrap(),
+            _ => Block::Paragraphs(blocks),
+        }
+    }
+}
+
+/// Splits a line at the `|` separators that aren't escaped.
+fn split_cells(text: &str) -> Vec<String> {
+    let mut pieces = vec![String::new()];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                pieces.last_mut().unwrap().push('|');
+                chars.next();
+            }
+            '|' => pieces.push(String::new()),
+            c => pieces.last_mut().unwrap().push(c),
+        }
+    }
+    pieces
+}
+
+/// Whether the text is a cell specifier, like `2+`, `3*`, `^.^` or `a`.
+fn is_cell_spec(text: &str) -> bool {
+    !text.is_empty()
+        && text.chars().all(|c| "0123456789.+*<^>adehlmsv".contains(c))
+        && text.chars().filter(|c| c.is_alphabetic()).count() <= 1
+        && text.chars().rev().skip(1).all(|c| !c.is_alphabetic())
+}
+
+/// Parses the number of columns a cell spans and how often it's repeated from its specifier.
+fn parse_spec(spec: &str) -> (usize, usize) {
+    let number = |suffix: char| {
+        let end = spec.find(suffix)?;
+        let start = spec[..end]
+            .rfind(|c: char| !c.is_ascii_digit())
+            .map_or(0, |start| start + 1);
+        spec[start..end].parse().ok()
+    };
+    (number('+').unwrap_or(1), number('*').unwrap_or(1))
+}
+
+/// Counts the columns of a `cols` attribute, like `1,2,1` or `3*`.
+fn count_columns(cols: &str) -> usize {
+    cols.split([',', ';'])
+        .map(|column| match column.split_once('*') {
+            Some((count, _)) => count.trim().parse().unwrap_or(1),
+            None => 1,
+        })
+        .sum()
+}
+
+/// Splits a line of values, which may be quoted like `"Hello, world"`.
+fn split_values(text: &str, separator: char) -> Vec<String> {
+    let mut values = vec![];
+    let mut value = String::new();
+    let mut is_quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if is_quoted && chars.peek() == Some(&'"') => {
+                value.push('"');
+                chars.next();
+            }
+            '"' => is_quoted = !is_quoted,
+            c if c == separator && !is_quoted => {
+                values.push(value.trim().to_owned());
+                value.clear();
+            }
+            c => value.push(c),
+        }
+    }
+    values.push(value.trim().to_owned());
+    values
+}
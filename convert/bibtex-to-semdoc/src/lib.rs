@@ -0,0 +1,93 @@
+use biblatex::{Bibliography, ChunksExt, DateValue, Entry, PermissiveType};
+use semdoc::{BibliographyEntry, Block, Pure, SemDoc};
+
+pub use biblatex::ParseError;
+
+/// Fields of BibTeX entries that identify the work.
+const IDENTIFIER_FIELDS: [&str; 5] = ["doi", "isbn", "issn", "url", "eprint"];
+
+pub fn bibtex_to_semdoc(bibtex: &str) -> Result<SemDoc<Pure>, ParseError> {
+    Ok(SemDoc::new(Block::Bibliography(bibtex_to_entries(bibtex)?)))
+}
+
+pub fn bibtex_to_entries(bibtex: &str) -> Result<Vec<BibliographyEntry>, ParseError> {
+    Ok(Bibliography::parse(bibtex)?.iter().map(to_entry).collect())
+}
+
+fn to_entry(entry: &Entry) -> BibliographyEntry {
+    BibliographyEntry {
+        key: entry.key.clone(),
+        authors: entry
+            .author()
+            .unwrap_or_default()
+            .iter()
+            .map(|person| person.to_string())
+            .collect(),
+        title: entry
+            .title()
+            .map(|title| title.format_verbatim())
+            .unwrap_or_default(),
+        year: match entry.date() {
+            Ok(PermissiveType::Typed(date)) => Some(match date.value {
+                DateValue::At(date)
+                | DateValue::After(date)
+                | DateValue::Before(date)
+                | DateValue::Between(date, _) => date.year,
+            }),
+            _ => None,
+        },
+        identifiers: IDENTIFIER_FIELDS
+            .iter()
+            .filter_map(|field| {
+                entry
+                    .get(field)
+                    .map(|value| (field.to_string(), value.format_verbatim()))
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn entries_are_converted() {
+        let bibtex = r#"
+            @article{knuth1984,
+                author = {Knuth, Donald E. and Levy, Silvio},
+                title = {Literate {P}rogramming},
+                journal = {The Computer Journal},
+                year = {1984},
+                doi = {10.1093/comjnl/27.2.97},
+            }
+            @misc{unknown,
+                title = {Anonymous Notes},
+            }
+        "#;
+        assert_eq!(
+            bibtex_to_entries(bibtex).unwrap(),
+            vec![
+                BibliographyEntry {
+                    key: "knuth1984".to_owned(),
+                    authors: vec!["Donald E. Knuth".to_owned(), "Silvio Levy".to_owned()],
+                    title: "Literate Programming".to_owned(),
+                    year: Some(1984),
+                    identifiers: vec![("doi".to_owned(), "10.1093/comjnl/27.2.97".to_owned())],
+                },
+                BibliographyEntry {
+                    key: "unknown".to_owned(),
+                    authors: vec![],
+                    title: "Anonymous Notes".to_owned(),
+                    year: None,
+                    identifiers: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_bibtex_is_rejected() {
+        assert!(bibtex_to_semdoc("@article{broken, title = {Unclosed").is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! Warnings about content of sources, like Markdown, HTML or Jupyter notebooks, that couldn't be
+//! converted faithfully.
+
+use std::fmt;
+
+/// Something in the source that couldn't be converted faithfully.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionWarning {
+    pub location: Location,
+    pub kind: ConversionWarningKind,
+}
+
+/// Where in the source the problematic content is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    /// Somewhere in a source without meaningful lines, like an HTML tree or a Pandoc document.
+    Unknown,
+
+    /// The line where the problematic content starts, starting at 1.
+    Line(u32),
+
+    /// A location in one of the files of a source that consists of several files, like a book.
+    File {
+        name: String,
+        location: Box<Location>,
+    },
+
+    /// A location in a cell of a notebook, starting at 1. Lines are counted from the start of
+    /// the cell or its output.
+    Cell {
+        number: usize,
+        location: Box<Location>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionWarningKind {
+    /// Raw HTML was dropped, because it's syntax rather than semantics.
+    DroppedHtml(String),
+
+    /// Raw content for a specific output format, like HTML or LaTeX, was dropped.
+    DroppedRaw { format: String },
+
+    /// An element that only makes sense in a browser, like a script or a form, was dropped along
+    /// with its content.
+    DroppedElement(String),
+
+    /// Formatting without a semantic counterpart was dropped, but its content was kept.
+    DroppedFormatting(Formatting),
+
+    /// SemDoc has no images yet, so an image became a link to it.
+    ImageAsLink { url: String },
+
+    /// The front matter couldn't be parsed, so the document has no metadata.
+    InvalidFrontMatter(String),
+
+    /// A directive or block macro without a SemDoc counterpart, like an `include`, was dropped
+    /// along with its content.
+    UnsupportedDirective(String),
+
+    /// An inline role or macro without a SemDoc counterpart, like a keyboard shortcut, was
+    /// dropped, but its text was kept.
+    UnsupportedRole(String),
+
+    /// An element the converter doesn't know, probably from a newer version of the format, was
+    /// dropped.
+    UnknownElement(String),
+
+    /// A file in the reading order is missing from the book.
+    MissingFile(String),
+
+    /// A file in the reading order isn't an HTML document, so it was skipped.
+    UnsupportedFile { file: String, media_type: String },
+
+    /// An output only available in formats without a counterpart, like an interactive widget,
+    /// was dropped.
+    UnsupportedOutput { mime_types: Vec<String> },
+
+    /// Raw cells are meant for tools like nbconvert, so they were dropped.
+    DroppedRawCell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Formatting {
+    Strikethrough,
+    Superscript,
+    Subscript,
+    Underline,
+}
+
+impl ConversionWarning {
+    /// Moves the warning into a file of the source, like a chapter of a book.
+    pub fn in_file(self, name: &str) -> Self {
+        ConversionWarning {
+            location: Location::File {
+                name: name.to_owned(),
+                location: Box::new(self.location),
+            },
+            kind: self.kind,
+        }
+    }
+
+    /// Moves the warning into a cell of a notebook.
+    pub fn in_cell(self, number: usize) -> Self {
+        ConversionWarning {
+            location: Location::Cell {
+                number,
+                location: Box::new(self.location),
+            },
+            kind: self.kind,
+        }
+    }
+}
+
+impl fmt::Display for ConversionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.location)?;
+        match &self.kind {
+            ConversionWarningKind::DroppedHtml(html) => {
+                write!(f, "Dropped HTML {:?}.", html.trim())
+            }
+            ConversionWarningKind::DroppedRaw { format } => {
+                write!(f, "Dropped raw content for the {} format.", format)
+            }
+            ConversionWarningKind::DroppedElement(name) => {
+                write!(f, "Dropped a <{}> element.", name)
+            }
+            ConversionWarningKind::DroppedFormatting(formatting) => {
+                write!(f, "Dropped {:?} formatting.", formatting)
+            }
+            ConversionWarningKind::ImageAsLink { url } => {
+                write!(f, "Converted the image {} into a link.", url)
+            }
+            ConversionWarningKind::InvalidFrontMatter(error) => {
+                write!(f, "Ignored the invalid front matter: {}", error)
+            }
+            ConversionWarningKind::UnsupportedDirective(name) => {
+                write!(f, "Dropped the unsupported directive {:?}.", name)
+            }
+            ConversionWarningKind::UnsupportedRole(name) => {
+                write!(f, "Kept only the text of the unsupported role {:?}.", name)
+            }
+            ConversionWarningKind::UnknownElement(name) => {
+                write!(f, "Dropped the unknown element {}.", name)
+            }
+            ConversionWarningKind::MissingFile(file) => {
+                write!(f, "Skipped the missing file {}.", file)
+            }
+            ConversionWarningKind::UnsupportedFile { file, media_type } => {
+                write!(
+                    f,
+                    "Skipped {}, because {} isn't supported.",
+                    file, media_type
+                )
+            }
+            ConversionWarningKind::UnsupportedOutput { mime_types } => write!(
+                f,
+                "Dropped an output, because {} isn't supported.",
+                mime_types.join(", ")
+            ),
+            ConversionWarningKind::DroppedRawCell => write!(f, "Dropped a raw cell."),
+        }
+    }
+}
+
+/// Formats the location as a prefix of the warning, like "chapter.xhtml: Line 3: ".
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Unknown => Ok(()),
+            Location::Line(line) => write!(f, "Line {}: ", line),
+            Location::File { name, location } => write!(f, "{}: {}", name, location),
+            Location::Cell { number, location } => write!(f, "Cell {}: {}", number, location),
+        }
+    }
+}
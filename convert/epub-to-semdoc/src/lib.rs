@@ -0,0 +1,541 @@
+use html_to_semdoc::{linked_html_to_block, LinkedFiles};
+pub use html_to_semdoc::{ConversionWarning, ConversionWarningKind, Location};
+use scraper::Html;
+use semdoc::{normalize_term, AttachedFile, Block, Pure, SemDoc};
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Cursor, Read},
+};
+use zip::ZipArchive;
+
+mod package;
+use package::*;
+
+#[derive(Debug)]
+pub enum EpubError {
+    /// The file isn't a ZIP archive.
+    InvalidArchive(String),
+    /// The book doesn't contain a file that every EPUB needs, like the package document.
+    MissingFile(String),
+    /// A file that describes the book isn't well-formed XML.
+    InvalidXml { file: String, error: String },
+}
+
+pub fn epub_to_semdoc(epub: &[u8]) -> Result<SemDoc<Pure>, EpubError> {
+    Ok(epub_to_semdoc_with_warnings(epub)?.0)
+}
+
+/// Converts an EPUB book into a single SemDoc and reports content that couldn't be converted
+/// faithfully.
+///
+/// The HTML files in the reading order are converted like HTML pages. The table of contents
+/// determines the sections of the document: Each entry becomes a section containing the files
+/// from the one it links to up to the one the next entry links to.
+pub fn epub_to_semdoc_with_warnings(
+    epub: &[u8],
+) -> Result<(SemDoc<Pure>, Vec<ConversionWarning>), EpubError> {
+    let files = unzip(epub)?;
+    let read = |path: &str| -> Result<String, EpubError> {
+        let data = files
+            .get(path)
+            .ok_or_else(|| EpubError::MissingFile(path.to_owned()))?;
+        Ok(String::from_utf8_lossy(data).into_owned())
+    };
+    let package_path = package_path(&read("META-INF/container.xml")?)?;
+    let package = parse_package(&package_path, &read(&package_path)?)?;
+    let navigation = match &package.navigation {
+        Some(path) if path.ends_with(".ncx") => parse_ncx(path, &read(path)?)?,
+        Some(path) => parse_navigation_document(path, &read(path)?),
+        None => vec![],
+    };
+
+    let mut warnings = vec![];
+    let mut documents = vec![];
+    for path in &package.spine {
+        let media_type = package
+            .manifest
+            .get(path)
+            .map(|item| item.media_type.as_str())
+            .unwrap_or_default();
+        match files.get(path) {
+            None => warnings.push(ConversionWarning {
+                location: Location::Unknown,
+                kind: ConversionWarningKind::MissingFile(path.clone()),
+            }),
+            Some(_) if !media_type.contains("html") => warnings.push(ConversionWarning {
+                location: Location::Unknown,
+                kind: ConversionWarningKind::UnsupportedFile {
+                    file: path.clone(),
+                    media_type: media_type.to_owned(),
+                },
+            }),
+            Some(data) => documents.push((path.clone(), String::from_utf8_lossy(data))),
+        }
+    }
+
+    // Links to other files become cross-references, so the linked content needs anchors.
+    let document_paths = documents
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<HashSet<_>>();
+    let mut referenced_ids: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut referenced_files = HashSet::new();
+    for (path, html) in &documents {
+        for href in links(html) {
+            let target = resolve(path, &href);
+            if target == *path || !document_paths.contains(&target) {
+                continue;
+            }
+            match fragment(&href) {
+                Some(id) => {
+                    referenced_ids.entry(target).or_default().insert(id);
+                }
+                None => {
+                    referenced_files.insert(target);
+                }
+            }
+        }
+    }
+
+    let mut blocks = HashMap::new();
+    for (path, html) in &documents {
+        let resolve_link = |href: &str| {
+            let target = resolve(path, href);
+            if href.starts_with('#') || !document_paths.contains(&target) {
+                return None;
+            }
+            Some(fragment(href).unwrap_or_else(|| file_id(&target)))
+        };
+        let load = |href: &str| {
+            let target = resolve(path, href);
+            let data = files.get(&target)?;
+            let mime_type = match package.manifest.get(&target) {
+                Some(item) => item.media_type.as_str(),
+                None => "application/octet-stream",
+            };
+            let name = target.rsplit('/').next().unwrap_or_default();
+            Some(AttachedFile::new(name, mime_type, data.clone()))
+        };
+        let linked_files = LinkedFiles {
+            referenced_ids: referenced_ids.remove(path).unwrap_or_default(),
+            resolve_link: &resolve_link,
+            load: &load,
+        };
+        let (block, file_warnings) = linked_html_to_block(html, &linked_files);
+        warnings.extend(
+            file_warnings
+                .into_iter()
+                .map(|warning| warning.in_file(path)),
+        );
+        blocks.insert(path.clone(), block);
+    }
+
+    let spine = documents
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+    let block = Book {
+        spine,
+        blocks,
+        referenced_files,
+    }
+    .assemble(&navigation);
+    let doc = SemDoc::new(block.link_glossary_terms()).with_metadata(package.metadata);
+    Ok((doc, warnings))
+}
+
+fn unzip(epub: &[u8]) -> Result<HashMap<String, Vec<u8>>, EpubError> {
+    let invalid = |error: zip::result::ZipError| EpubError::InvalidArchive(error.to_string());
+    let mut archive = ZipArchive::new(Cursor::new(epub)).map_err(invalid)?;
+    let mut files = HashMap::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(invalid)?;
+        let mut data = vec![];
+        file.read_to_end(&mut data)
+            .map_err(|error| EpubError::InvalidArchive(error.to_string()))?;
+        files.insert(file.name().to_owned(), data);
+    }
+    Ok(files)
+}
+
+/// The targets of all links in the HTML.
+fn links(html: &str) -> Vec<String> {
+    Html::parse_document(html)
+        .root_element()
+        .descendants()
+        .filter_map(|node| node.value().as_element()?.attr("href"))
+        .map(|href| href.to_owned())
+        .collect()
+}
+
+/// The ID of the anchor for links to a whole file, like "chapter-2" for "EPUB/chapter-2.xhtml".
+fn file_id(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let stem = match name.rsplit_once('.') {
+        Some((stem, _)) => stem,
+        None => name,
+    };
+    stem.to_owned()
+}
+
+/// The converted HTML files of a book.
+struct Book {
+    /// The paths of the files in reading order.
+    spine: Vec<String>,
+    blocks: HashMap<String, Block<Pure>>,
+    /// Files that other files link to as a whole.
+    referenced_files: HashSet<String>,
+}
+
+impl Book {
+    /// Puts the files into sections according to the table of contents.
+    fn assemble(mut self, navigation: &[NavigationPoint]) -> Block<Pure> {
+        // Each file belongs to the first entry that links to it or, if there is none, to the
+        // entry of the file before it. Entries are identified by their index in pre-order.
+        let mut owners = HashMap::new();
+        for (index, point) in pre_order(navigation).into_iter().enumerate() {
+            if let Some(file) = &point.file {
+                owners.entry(file.clone()).or_insert(index);
+            }
+        }
+
+        let mut front = vec![];
+        let mut contents: HashMap<usize, Vec<(String, Block<Pure>)>> = HashMap::new();
+        let mut owner = None;
+        for path in std::mem::take(&mut self.spine) {
+            let block = self.blocks.remove(&path).unwrap_or(Block::Empty);
+            if let Some(new_owner) = owners.get(&path) {
+                owner = Some(*new_owner);
+            }
+            match owner {
+                Some(owner) => contents.entry(owner).or_default().push((path, block)),
+                None => front.push(self.anchor_file(&path, block)),
+            }
+        }
+
+        let mut index = 0;
+        let sections = navigation
+            .iter()
+            .filter_map(|point| self.section(point, &mut index, &mut contents))
+            .collect::<Vec<_>>();
+        front.extend(sections);
+        Block::Paragraphs(front).simplify()
+    }
+
+    fn section(
+        &self,
+        point: &NavigationPoint,
+        index: &mut usize,
+        contents: &mut HashMap<usize, Vec<(String, Block<Pure>)>>,
+    ) -> Option<Block<Pure>> {
+        let files = contents.remove(index).unwrap_or_default();
+        *index += 1;
+        let children = point
+            .children
+            .iter()
+            .filter_map(|child| self.section(child, index, contents))
+            .collect::<Vec<_>>();
+        if files.is_empty() && children.is_empty() {
+            return None;
+        }
+
+        let mut ids = vec![];
+        let mut body = vec![];
+        for (file_index, (path, block)) in files.into_iter().enumerate() {
+            if file_index == 0 {
+                if self.referenced_files.contains(&path) {
+                    ids.push(file_id(&path));
+                }
+                // Files usually start with a heading that repeats the entry's title.
+                body.extend(without_title(block, &point.title, &mut ids));
+            } else {
+                body.push(self.anchor_file(&path, block));
+            }
+        }
+        body.extend(children);
+
+        let mut section = Block::Section {
+            title: Box::new(Block::Text(point.title.clone())),
+            body: Box::new(Block::Paragraphs(body)),
+        };
+        for id in ids.into_iter().rev() {
+            section = Block::Anchor {
+                id,
+                content: Box::new(section),
+            };
+        }
+        Some(section)
+    }
+
+    fn anchor_file(&self, path: &str, block: Block<Pure>) -> Block<Pure> {
+        if self.referenced_files.contains(path) {
+            Block::Anchor {
+                id: file_id(path),
+                content: Box::new(block),
+            }
+        } else {
+            block
+        }
+    }
+}
+
+fn pre_order(points: &[NavigationPoint]) -> Vec<&NavigationPoint> {
+    points
+        .iter()
+        .flat_map(|point| std::iter::once(point).chain(pre_order(&point.children)))
+        .collect()
+}
+
+/// If the content starts with a section with the given title, returns its body and the content
+/// after it. The IDs of anchors around the section are added to the IDs.
+fn without_title(block: Block<Pure>, title: &str, ids: &mut Vec<String>) -> Vec<Block<Pure>> {
+    let mut blocks = match block {
+        Block::Paragraphs(children) => children,
+        block => vec![block],
+    };
+    if blocks.is_empty() {
+        return blocks;
+    }
+    let mut first = blocks.remove(0);
+    let mut first_ids = vec![];
+    while let Block::Anchor { id, content } = first {
+        first_ids.push(id);
+        first = *content;
+    }
+    match first {
+        Block::Section {
+            title: section_title,
+            body,
+        } if titles_match(&section_title.plain_text(), title) => {
+            ids.extend(first_ids);
+            let mut body = match *body {
+                Block::Paragraphs(children) => children,
+                body => vec![body],
+            };
+            body.extend(blocks);
+            body
+        }
+        mut first => {
+            for id in first_ids.into_iter().rev() {
+                first = Block::Anchor {
+                    id,
+                    content: Box::new(first),
+                };
+            }
+            blocks.insert(0, first);
+            blocks
+        }
+    }
+}
+
+/// Whether a heading repeats the title of an entry in the table of contents. Entries often have
+/// a number in front, like "1. Introduction".
+fn titles_match(heading: &str, entry: &str) -> bool {
+    let heading = normalize_term(heading);
+    let entry = normalize_term(entry);
+    !heading.is_empty() && (entry.contains(&heading) || heading.contains(&entry))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use semdoc::{Block::*, Metadata};
+    use semdoc_to_epub::semdoc_to_epub;
+    use std::io::Write;
+    use zip::{write::FileOptions, ZipWriter};
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    fn section(title: &str, body: Block<Pure>) -> Block<Pure> {
+        Section {
+            title: Box::new(text(title)),
+            body: Box::new(body),
+        }
+    }
+
+    fn anchor(id: &str, content: Block<Pure>) -> Block<Pure> {
+        Anchor {
+            id: id.to_owned(),
+            content: Box::new(content),
+        }
+    }
+
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        for (name, content) in files {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn round_trip() {
+        let doc = SemDoc::new(Paragraphs(vec![
+            anchor(
+                "setup",
+                section(
+                    "Setup",
+                    Paragraphs(vec![
+                        text("Install it."),
+                        anchor("linux", section("On Linux", text("Use a package manager."))),
+                    ]),
+                ),
+            ),
+            anchor(
+                "usage",
+                section(
+                    "Usage",
+                    Flow(vec![
+                        text("Do the "),
+                        CrossReference {
+                            target: "linux".to_owned(),
+                            content: Box::new(text("setup")),
+                        },
+                        text(" first."),
+                    ]),
+                ),
+            ),
+        ]))
+        .with_metadata(Metadata {
+            title: Some("Manual".to_owned()),
+            authors: vec!["Ada".to_owned()],
+            custom: vec![("lang".to_owned(), "en".to_owned())],
+            ..Metadata::default()
+        });
+        let (imported, warnings) = epub_to_semdoc_with_warnings(&semdoc_to_epub(&doc)).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(imported.block, doc.block);
+        assert_eq!(imported.metadata.title.as_deref(), Some("Manual"));
+        assert_eq!(imported.metadata.authors, vec!["Ada".to_owned()]);
+        assert_eq!(imported.metadata.custom("lang"), Some("en"));
+    }
+
+    const CONTAINER: &str = "<?xml version=\"1.0\"?>
+<container xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\" version=\"1.0\">
+<rootfiles><rootfile full-path=\"OEBPS/content.opf\" /></rootfiles>
+</container>";
+
+    const PACKAGE: &str = "<?xml version=\"1.0\"?>
+<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"2.0\" unique-identifier=\"id\">
+<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">
+<dc:title>Old Book</dc:title>
+<dc:identifier id=\"id\">isbn-123</dc:identifier>
+<dc:date>1999-12-31</dc:date>
+<meta name=\"cover\" content=\"cover-image\" />
+</metadata>
+<manifest>
+<item id=\"ncx\" href=\"toc.ncx\" media-type=\"application/x-dtbncx+xml\" />
+<item id=\"one\" href=\"text/one.html\" media-type=\"application/xhtml+xml\" />
+<item id=\"two\" href=\"text/two.html\" media-type=\"application/xhtml+xml\" />
+<item id=\"three\" href=\"text/three.html\" media-type=\"application/xhtml+xml\" />
+</manifest>
+<spine toc=\"ncx\">
+<itemref idref=\"one\" /><itemref idref=\"two\" /><itemref idref=\"three\" />
+</spine>
+</package>";
+
+    const NCX: &str = "<?xml version=\"1.0\"?>
+<!DOCTYPE ncx PUBLIC \"-//NISO//DTD ncx 2005-1//EN\"
+ \"http://www.daisy.org/z3986/2005/ncx-2005-1.dtd\">
+<ncx xmlns=\"http://www.daisy.org/z3986/2005/ncx/\" version=\"2005-1\">
+<navMap>
+<navPoint id=\"p1\"><navLabel><text>1. Beginning</text></navLabel>
+<content src=\"text/one.html\" />
+<navPoint id=\"p2\"><navLabel><text>Details</text></navLabel>
+<content src=\"text/two.html\" /></navPoint>
+</navPoint>
+</navMap>
+</ncx>";
+
+    #[test]
+    fn epub_2_with_ncx() {
+        let epub = zip(&[
+            ("mimetype", "application/epub+zip"),
+            ("META-INF/container.xml", CONTAINER),
+            ("OEBPS/content.opf", PACKAGE),
+            ("OEBPS/toc.ncx", NCX),
+            (
+                "OEBPS/text/one.html",
+                "<html><body><h1>Beginning</h1><p>It starts.</p></body></html>",
+            ),
+            (
+                "OEBPS/text/two.html",
+                "<html><body><p>See <a href=\"one.html\">the start</a>.</p></body></html>",
+            ),
+            (
+                "OEBPS/text/three.html",
+                "<html><body><p>The end.</p></body></html>",
+            ),
+        ]);
+        let doc = epub_to_semdoc(&epub).unwrap();
+        assert_eq!(
+            doc.block,
+            anchor(
+                "one",
+                section(
+                    "1. Beginning",
+                    Paragraphs(vec![
+                        text("It starts."),
+                        section(
+                            "Details",
+                            Paragraphs(vec![
+                                Flow(vec![
+                                    text("See "),
+                                    CrossReference {
+                                        target: "one".to_owned(),
+                                        content: Box::new(text("the start")),
+                                    },
+                                    text("."),
+                                ]),
+                                text("The end."),
+                            ]),
+                        ),
+                    ]),
+                ),
+            )
+        );
+        assert_eq!(doc.metadata.title.as_deref(), Some("Old Book"));
+        assert_eq!(
+            doc.metadata.date,
+            Some(semdoc::TemporalValue::Date {
+                year: 1999,
+                month: 12,
+                day: 31
+            })
+        );
+        assert_eq!(doc.metadata.custom("identifier"), Some("isbn-123"));
+    }
+
+    #[test]
+    fn missing_container() {
+        let epub = zip(&[("mimetype", "application/epub+zip")]);
+        assert!(matches!(
+            epub_to_semdoc(&epub),
+            Err(EpubError::MissingFile(file)) if file == "META-INF/container.xml"
+        ));
+        assert!(matches!(
+            epub_to_semdoc(b"not a zip"),
+            Err(EpubError::InvalidArchive(_))
+        ));
+    }
+
+    #[test]
+    fn resolves_links() {
+        let chapter = "OEBPS/text/chapter-1.xhtml";
+        assert_eq!(
+            resolve(chapter, "chapter-2.xhtml#intro"),
+            "OEBPS/text/chapter-2.xhtml"
+        );
+        assert_eq!(
+            resolve(chapter, "../images/my%20cat.png"),
+            "OEBPS/images/my cat.png"
+        );
+        assert_eq!(resolve(chapter, "./notes.xhtml"), "OEBPS/text/notes.xhtml");
+        assert_eq!(resolve("", "EPUB/package.opf"), "EPUB/package.opf");
+        assert_eq!(fragment("chapter-2.xhtml#intro"), Some("intro".to_owned()));
+        assert_eq!(fragment("chapter-2.xhtml"), None);
+    }
+}
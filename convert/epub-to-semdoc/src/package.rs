@@ -0,0 +1,300 @@
+//! Reads the files that describe the book: the container, the package document with the metadata
+//! and the reading order, and the table of contents.
+
+use crate::EpubError;
+use scraper::{ElementRef, Html};
+use semdoc::{Metadata, TemporalValue};
+use std::collections::HashMap;
+
+pub(crate) struct Package {
+    pub metadata: Metadata,
+    /// The files of the book by their path in the archive.
+    pub manifest: HashMap<String, ManifestItem>,
+    /// The paths of the files in reading order.
+    pub spine: Vec<String>,
+    /// The path of the table of contents, which is either an EPUB 3 navigation document or an
+    /// EPUB 2 NCX file.
+    pub navigation: Option<String>,
+}
+
+pub(crate) struct ManifestItem {
+    pub media_type: String,
+    pub properties: Vec<String>,
+}
+
+/// An entry in the table of contents.
+pub(crate) struct NavigationPoint {
+    pub title: String,
+    /// The path of the file the entry links to.
+    pub file: Option<String>,
+    pub children: Vec<NavigationPoint>,
+}
+
+/// Finds the path of the package document in the `META-INF/container.xml`.
+pub(crate) fn package_path(container: &str) -> Result<String, EpubError> {
+    let container = parse_xml("META-INF/container.xml", container)?;
+    container
+        .descendants()
+        .find(|node| node.has_tag_name("rootfile"))
+        .and_then(|rootfile| rootfile.attribute("full-path"))
+        .map(|path| resolve("", path))
+        .ok_or_else(|| EpubError::MissingFile("package document".to_owned()))
+}
+
+pub(crate) fn parse_package(path: &str, package: &str) -> Result<Package, EpubError> {
+    let package = parse_xml(path, package)?;
+    let root = package.root_element();
+    let unique_identifier = root.attribute("unique-identifier");
+
+    let mut metadata = Metadata::default();
+    let elements = root
+        .descendants()
+        .filter(|node| node.is_element())
+        .collect::<Vec<_>>();
+    if let Some(metadata_element) = elements.iter().find(|node| node.has_tag_name("metadata")) {
+        for node in metadata_element.children().filter(|node| node.is_element()) {
+            let text = collapse_whitespace(node.text().unwrap_or_default());
+            match node.tag_name().name() {
+                "title" if metadata.title.is_none() => metadata.title = Some(text),
+                "creator" => metadata.authors.push(text),
+                "subject" => metadata.tags.push(text),
+                "language" => metadata.custom.push(("lang".to_owned(), text)),
+                "date" => match parse_date(&text) {
+                    Some(date) if metadata.date.is_none() => metadata.date = Some(date),
+                    _ => metadata.custom.push(("date".to_owned(), text)),
+                },
+                "identifier" if node.attribute("id") == unique_identifier => {
+                    metadata.custom.push(("identifier".to_owned(), text))
+                }
+                "description" | "publisher" | "rights" | "source" | "contributor" => {
+                    let key = node.tag_name().name().to_owned();
+                    metadata.custom.push((key, text));
+                }
+                // EPUB 2 metadata. EPUB 3 metadata with a `property` only refines the other
+                // entries or describes the file, like when it was modified.
+                "meta" => match (node.attribute("name"), node.attribute("content")) {
+                    (Some("cover"), _) | (None, _) | (_, None) => {}
+                    (Some(name), Some(content)) => {
+                        metadata.custom.push((name.to_owned(), content.to_owned()))
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+
+    let mut manifest = HashMap::new();
+    let mut paths_by_id = HashMap::new();
+    for item in elements.iter().filter(|node| node.has_tag_name("item")) {
+        let (id, href) = match (item.attribute("id"), item.attribute("href")) {
+            (Some(id), Some(href)) => (id, href),
+            _ => continue,
+        };
+        let item_path = resolve(path, href);
+        paths_by_id.insert(id, item_path.clone());
+        manifest.insert(
+            item_path,
+            ManifestItem {
+                media_type: item.attribute("media-type").unwrap_or_default().to_owned(),
+                properties: item
+                    .attribute("properties")
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .map(|property| property.to_owned())
+                    .collect(),
+            },
+        );
+    }
+
+    let spine_element = elements.iter().find(|node| node.has_tag_name("spine"));
+    let spine = elements
+        .iter()
+        .filter(|node| node.has_tag_name("itemref"))
+        .filter_map(|itemref| paths_by_id.get(itemref.attribute("idref")?).cloned())
+        .collect();
+    let navigation = manifest
+        .iter()
+        .find(|(_, item)| item.properties.iter().any(|property| property == "nav"))
+        .map(|(path, _)| path.clone())
+        .or_else(|| {
+            let toc = spine_element?.attribute("toc")?;
+            paths_by_id.get(toc).cloned()
+        });
+
+    Ok(Package {
+        metadata,
+        manifest,
+        spine,
+        navigation,
+    })
+}
+
+/// Reads the table of contents of an EPUB 3 navigation document.
+pub(crate) fn parse_navigation_document(path: &str, html: &str) -> Vec<NavigationPoint> {
+    let document = Html::parse_document(html);
+    let navs = document
+        .root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|element| element.value().name() == "nav")
+        .collect::<Vec<_>>();
+    let toc = navs.iter().find(|nav| {
+        nav.value()
+            .attr("epub:type")
+            .is_some_and(|types| types.split_whitespace().any(|it| it == "toc"))
+    });
+    match toc
+        .or_else(|| navs.first())
+        .and_then(|nav| child(*nav, "ol"))
+    {
+        Some(list) => navigation_list(path, list),
+        None => vec![],
+    }
+}
+
+fn navigation_list(path: &str, list: ElementRef) -> Vec<NavigationPoint> {
+    list.children()
+        .filter_map(ElementRef::wrap)
+        .filter(|item| item.value().name() == "li")
+        .map(|item| {
+            let label = child(item, "a").or_else(|| child(item, "span"));
+            NavigationPoint {
+                title: label
+                    .map(|label| collapse_whitespace(&label.text().collect::<String>()))
+                    .unwrap_or_default(),
+                file: label
+                    .and_then(|label| label.value().attr("href"))
+                    .map(|href| resolve(path, href)),
+                children: child(item, "ol")
+                    .map(|list| navigation_list(path, list))
+                    .unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+fn child<'a>(element: ElementRef<'a>, name: &str) -> Option<ElementRef<'a>> {
+    element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|child| child.value().name() == name)
+}
+
+/// Reads the table of contents of an EPUB 2 NCX file.
+pub(crate) fn parse_ncx(path: &str, ncx: &str) -> Result<Vec<NavigationPoint>, EpubError> {
+    let ncx = parse_xml(path, ncx)?;
+    Ok(
+        match ncx.descendants().find(|node| node.has_tag_name("navMap")) {
+            Some(map) => ncx_points(path, map),
+            None => vec![],
+        },
+    )
+}
+
+fn ncx_points(path: &str, parent: roxmltree::Node) -> Vec<NavigationPoint> {
+    parent
+        .children()
+        .filter(|node| node.has_tag_name("navPoint"))
+        .map(|point| NavigationPoint {
+            title: point
+                .children()
+                .find(|node| node.has_tag_name("navLabel"))
+                .and_then(|label| label.descendants().find(|node| node.has_tag_name("text")))
+                .and_then(|text| text.text())
+                .map(collapse_whitespace)
+                .unwrap_or_default(),
+            file: point
+                .children()
+                .find(|node| node.has_tag_name("content"))
+                .and_then(|content| content.attribute("src"))
+                .map(|src| resolve(path, src)),
+            children: ncx_points(path, point),
+        })
+        .collect()
+}
+
+fn parse_xml<'a>(path: &str, xml: &'a str) -> Result<roxmltree::Document<'a>, EpubError> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..roxmltree::ParsingOptions::default()
+    };
+    roxmltree::Document::parse_with_options(xml, options).map_err(|error| EpubError::InvalidXml {
+        file: path.to_owned(),
+        error: error.to_string(),
+    })
+}
+
+/// Parses dates like "2021-03-14". Dates with only a year or a month can't be represented.
+fn parse_date(text: &str) -> Option<TemporalValue> {
+    let date = text.get(..10)?;
+    let mut parts = date.split('-');
+    let date = TemporalValue::Date {
+        year: parts.next()?.parse().ok()?,
+        month: parts.next()?.parse().ok()?,
+        day: parts.next()?.parse().ok()?,
+    };
+    if date.is_valid() {
+        Some(date)
+    } else {
+        None
+    }
+}
+
+/// Resolves a link in the file at the given path to the path of the linked file in the archive,
+/// like "OEBPS/images/cat.png" for "../images/cat.png" in "OEBPS/text/chapter-1.xhtml".
+pub(crate) fn resolve(path: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let href = percent_decode(href);
+    let mut segments = if href.starts_with('/') {
+        vec![]
+    } else {
+        path.split('/').collect::<Vec<_>>()
+    };
+    // The last segment is the name of the file the link is in.
+    segments.pop();
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// The ID of the content a link points to in the linked file, like "setup" for
+/// "chapter-2.xhtml#setup".
+pub(crate) fn fragment(href: &str) -> Option<String> {
+    match href.split_once('#') {
+        Some((_, fragment)) if !fragment.is_empty() => Some(percent_decode(fragment)),
+        _ => None,
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = text
+            .get(index + 1..index + 3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,36 @@
+/// Decodes a URI like "data:image/png;base64,iVBO..." into the MIME type and the data.
+///
+/// Only base64-encoded data is supported.
+pub(crate) fn decode_data_uri(uri: &str) -> Option<(String, Vec<u8>)> {
+    let (header, payload) = uri.strip_prefix("data:")?.split_once(',')?;
+    let mime_type = header.strip_suffix(";base64")?;
+    let mime_type = match mime_type.split(';').next().unwrap_or_default() {
+        "" => "text/plain",
+        mime_type => mime_type,
+    };
+    Some((mime_type.to_owned(), decode_base64(payload)?))
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut data = vec![];
+    let mut bits = 0u32;
+    let mut num_bits = 0;
+    for c in encoded.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return None,
+        };
+        bits = bits << 6 | value;
+        num_bits += 6;
+        if num_bits >= 8 {
+            num_bits -= 8;
+            data.push((bits >> num_bits) as u8);
+            bits &= (1 << num_bits) - 1;
+        }
+    }
+    Some(data)
+}
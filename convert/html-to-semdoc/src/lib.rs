@@ -0,0 +1,1119 @@
+use ego_tree::NodeRef;
+use scraper::{node::Element, ElementRef, Html, Node};
+use semdoc::{
+    nest_sections, normalize_term, AttachedFile, Block, ChecklistItem, Definition, Heading,
+    HighlightReason, ListNumbering, Media, MediaSource, Metadata, Pure, SectionItem, SemDoc,
+};
+use std::collections::HashSet;
+
+mod data_uri;
+mod temporal;
+pub use conversion_warnings::{ConversionWarning, ConversionWarningKind, Formatting, Location};
+use data_uri::*;
+use temporal::*;
+
+pub fn html_to_semdoc(html: &str) -> SemDoc<Pure> {
+    html_to_semdoc_with_warnings(html).0
+}
+
+/// Converts HTML into a SemDoc and reports content that couldn't be converted faithfully.
+///
+/// Only the `<main>` element is converted if there is one, so that site navigation and footers
+/// don't end up in the document.
+pub fn html_to_semdoc_with_warnings(html: &str) -> (SemDoc<Pure>, Vec<ConversionWarning>) {
+    let document = Html::parse_document(html);
+    let root = document.root_element();
+    let mut converter = Converter {
+        referenced_ids: referenced_ids(root),
+        files: None,
+        warnings: vec![],
+    };
+    let content = root
+        .descendants()
+        .find(|node| matches!(node.value(), Node::Element(element) if element.name() == "main"))
+        .unwrap_or(*root);
+    let block = converter.blocks(content.children()).simplify();
+    let doc = SemDoc::new(block.link_glossary_terms()).with_metadata(metadata(root));
+    (doc, converter.warnings)
+}
+
+/// The other files of a document that consists of multiple HTML files, like an e-book.
+pub struct LinkedFiles<'a> {
+    /// IDs in this file that other files link to.
+    pub referenced_ids: HashSet<String>,
+    /// Turns the target of a link into the ID of the linked content if it's in one of the files,
+    /// like "chapter-2.xhtml#setup" into "setup".
+    pub resolve_link: &'a dyn Fn(&str) -> Option<String>,
+    /// Loads a file that an image or media refers to.
+    pub load: &'a dyn Fn(&str) -> Option<AttachedFile>,
+}
+
+/// Converts one HTML file of a document that consists of multiple files, like a chapter of an
+/// e-book.
+///
+/// Links to the other files become cross-references and images and media in the files are
+/// embedded. Glossary terms aren't linked, because the glossary may be in another file.
+pub fn linked_html_to_block(
+    html: &str,
+    files: &LinkedFiles,
+) -> (Block<Pure>, Vec<ConversionWarning>) {
+    let document = Html::parse_document(html);
+    let root = document.root_element();
+    let mut referenced_ids = referenced_ids(root);
+    referenced_ids.extend(files.referenced_ids.iter().cloned());
+    let mut converter = Converter {
+        referenced_ids,
+        files: Some(files),
+        warnings: vec![],
+    };
+    let content = root
+        .descendants()
+        .find(|node| matches!(node.value(), Node::Element(element) if element.name() == "main"))
+        .unwrap_or(*root);
+    let block = converter.blocks(content.children()).simplify();
+    (block, converter.warnings)
+}
+
+/// Elements that aren't content, like the head of the document, which are ignored silently.
+const IGNORED: &[&str] = &[
+    "head", "title", "meta", "link", "style", "base", "template", "track", "param", "wbr",
+];
+
+/// Elements that only make sense in a browser. They are dropped along with their content.
+const DROPPED: &[&str] = &[
+    "script", "noscript", "iframe", "object", "embed", "canvas", "svg", "math", "form", "input",
+    "button", "select", "textarea", "nav", "dialog", "map", "frameset", "frame", "applet",
+    "portal",
+];
+
+/// Elements that only group their content. Their content becomes part of the surrounding content
+/// on its own line, so that headings inside them still nest with the headings outside them.
+const CONTAINERS: &[&str] = &[
+    "html",
+    "body",
+    "main",
+    "div",
+    "p",
+    "header",
+    "footer",
+    "center",
+    "hgroup",
+    "address",
+    "li",
+    "search",
+    "legend",
+    "fieldset",
+    "dt",
+    "dd",
+    "caption",
+    "figcaption",
+    "summary",
+];
+
+/// Inline formatting that has no semantic counterpart, but whose removal changes the meaning.
+fn lossy_formatting(name: &str) -> Option<Formatting> {
+    match name {
+        "s" | "strike" | "del" => Some(Formatting::Strikethrough),
+        "sub" => Some(Formatting::Subscript),
+        "sup" => Some(Formatting::Superscript),
+        _ => None,
+    }
+}
+
+struct Converter<'f> {
+    /// IDs that links in the document point to. Elements with these IDs become anchors.
+    referenced_ids: HashSet<String>,
+    files: Option<&'f LinkedFiles<'f>>,
+    warnings: Vec<ConversionWarning>,
+}
+
+/// A piece of content. Consecutive inline items form a paragraph.
+enum Item {
+    Heading {
+        level: u32,
+        id: Option<String>,
+        title: Block<Pure>,
+    },
+    Block(Block<Pure>),
+    Inline(Block<Pure>),
+    /// The end of a paragraph.
+    Break,
+}
+
+impl<'f> Converter<'f> {
+    /// Converts nodes into blocks, putting the content following a heading into its section.
+    fn blocks<'a>(&mut self, nodes: impl Iterator<Item = NodeRef<'a, Node>>) -> Block<Pure> {
+        let mut items = vec![];
+        for node in nodes {
+            self.node(node, &mut items);
+        }
+        Block::Paragraphs(nest_sections(sections(items))).simplify()
+    }
+
+    /// Converts nodes into a single line of content, like the text of a link.
+    fn inline<'a>(&mut self, nodes: impl Iterator<Item = NodeRef<'a, Node>>) -> Block<Pure> {
+        let mut items = vec![];
+        for node in nodes {
+            self.node(node, &mut items);
+        }
+        let inlines = items
+            .into_iter()
+            .filter_map(|item| match item {
+                Item::Heading { title, .. } => Some(title),
+                Item::Block(block) | Item::Inline(block) => Some(block),
+                Item::Break => None,
+            })
+            .collect();
+        paragraph(inlines)
+    }
+
+    fn node(&mut self, node: NodeRef<Node>, items: &mut Vec<Item>) {
+        match node.value() {
+            Node::Text(text) => items.push(Item::Inline(Block::Text(collapse_whitespace(text)))),
+            Node::Element(_) => self.element(ElementRef::wrap(node).unwrap(), items),
+            _ => {}
+        }
+    }
+
+    fn element(&mut self, element: ElementRef, items: &mut Vec<Item>) {
+        let name = element.value().name();
+        if IGNORED.contains(&name) {
+            return;
+        }
+        if DROPPED.contains(&name) {
+            self.warn(ConversionWarningKind::DroppedElement(name.to_owned()));
+            return;
+        }
+        if let Some(level) = heading_level(element.value()) {
+            let title = trim_inlines(self.inline(element.children()));
+            items.push(Item::Heading {
+                level,
+                id: element.value().id().map(|id| id.to_owned()),
+                title,
+            });
+            return;
+        }
+
+        let id = element
+            .value()
+            .id()
+            .filter(|id| self.referenced_ids.contains(*id))
+            .map(|id| id.to_owned());
+        if CONTAINERS.contains(&name) {
+            match id {
+                Some(id) => items.push(Item::Block(Block::Anchor {
+                    id,
+                    content: Box::new(self.blocks(element.children())),
+                })),
+                None => {
+                    items.push(Item::Break);
+                    for child in element.children() {
+                        self.node(child, items);
+                    }
+                    items.push(Item::Break);
+                }
+            }
+            return;
+        }
+        let item = match self.block(element) {
+            Some(block) => Item::Block(block),
+            None => Item::Inline(self.inline_element(element)),
+        };
+        items.push(match (id, item) {
+            (None, item) => item,
+            (Some(id), Item::Block(content)) => Item::Block(Block::Anchor {
+                id,
+                content: Box::new(content),
+            }),
+            (Some(id), Item::Inline(content)) => Item::Inline(Block::Anchor {
+                id,
+                content: Box::new(content),
+            }),
+            (Some(_), item) => item,
+        });
+    }
+
+    /// Converts elements that start on their own line. Returns `None` for inline elements.
+    fn block(&mut self, element: ElementRef) -> Option<Block<Pure>> {
+        Some(match element.value().name() {
+            "section" | "article" => self.blocks(element.children()),
+            "ul" | "ol" | "menu" => self.list(element),
+            "dl" => {
+                let definitions = self.definitions(element);
+                if has_class(element.value(), "glossary") {
+                    Block::Glossary(definitions)
+                } else {
+                    Block::DefinitionList(definitions)
+                }
+            }
+            "table" => self.table(element),
+            "pre" => {
+                let code = element.text().collect::<String>();
+                Block::Code {
+                    language: element
+                        .descendants()
+                        .filter_map(|node| node.value().as_element())
+                        .find_map(language),
+                    code: code.strip_suffix('\n').unwrap_or(&code).to_owned(),
+                }
+            }
+            "blockquote" => Block::Quote(Box::new(self.blocks(element.children()))),
+            "hr" => Block::TopicChange,
+            "aside" => Block::Secondary(Box::new(self.blocks(element.children()))),
+            "details" => {
+                let (summary, body): (Vec<_>, Vec<_>) = element
+                    .children()
+                    .partition(|child| is_element(*child, "summary"));
+                Block::MoreInfo {
+                    summary: Box::new(trim_inlines(
+                        self.inline(summary.into_iter().flat_map(|summary| summary.children())),
+                    )),
+                    body: Box::new(self.blocks(body.into_iter())),
+                }
+            }
+            "audio" | "video" => self.media(element),
+            "figure" => {
+                let (caption, content): (Vec<_>, Vec<_>) = element
+                    .children()
+                    .partition(|child| is_element(*child, "figcaption"));
+                let caption = caption.into_iter().flat_map(|caption| caption.children());
+                match self.blocks(content.into_iter()) {
+                    Block::Audio(media) => Block::Audio(self.with_transcript(media, caption)),
+                    Block::Video(media) => Block::Video(self.with_transcript(media, caption)),
+                    content => Block::Section {
+                        title: Box::new(trim_inlines(self.inline(caption))),
+                        body: Box::new(content),
+                    },
+                }
+            }
+            _ => return None,
+        })
+    }
+
+    fn list(&mut self, list: ElementRef) -> Block<Pure> {
+        let items = list
+            .children()
+            .filter(|child| is_element(*child, "li"))
+            .collect::<Vec<_>>();
+        let checkboxes = items
+            .iter()
+            .map(|item| {
+                item.children()
+                    .find(|child| !is_whitespace(*child))
+                    .filter(|child| is_checkbox(*child))
+            })
+            .collect::<Vec<_>>();
+        if !items.is_empty() && checkboxes.iter().all(|checkbox| checkbox.is_some()) {
+            return Block::Checklist(
+                items
+                    .iter()
+                    .zip(checkboxes)
+                    .map(|(item, checkbox)| {
+                        let checkbox = checkbox.unwrap();
+                        ChecklistItem {
+                            checked: checkbox
+                                .value()
+                                .as_element()
+                                .unwrap()
+                                .attr("checked")
+                                .is_some(),
+                            content: self
+                                .blocks(item.children().filter(|child| *child != checkbox)),
+                        }
+                    })
+                    .collect(),
+            );
+        }
+
+        let items = items
+            .into_iter()
+            .map(|item| self.blocks(item.children()))
+            .collect();
+        if list.value().name() != "ol" {
+            return Block::BulletList(items);
+        }
+        let numbering = list.value().classes().find_map(|class| match class {
+            "steps" => Some(ListNumbering::Steps),
+            "clauses" => Some(ListNumbering::Clauses),
+            "ranking" => Some(ListNumbering::Ranking),
+            _ => None,
+        });
+        Block::OrderedList {
+            start: list
+                .value()
+                .attr("start")
+                .and_then(|start| start.trim().parse().ok())
+                .unwrap_or(1),
+            numbering,
+            items,
+        }
+    }
+
+    /// Converts the terms and descriptions of a `<dl>`. Descriptions belong to the terms before
+    /// them.
+    fn definitions(&mut self, list: ElementRef) -> Vec<Definition<Pure>> {
+        let mut definitions: Vec<Definition<Pure>> = vec![];
+        // HTML allows wrapping groups of terms and descriptions in a `<div>`.
+        let children = list.children().flat_map(|child| {
+            if is_element(child, "div") {
+                child.children().collect()
+            } else {
+                vec![child]
+            }
+        });
+        for child in children {
+            match child.value().as_element().map(|element| element.name()) {
+                Some("dt") => definitions.push(Definition {
+                    term: trim_inlines(self.inline(child.children())),
+                    definitions: vec![],
+                }),
+                Some("dd") => {
+                    let details = self.blocks(child.children());
+                    match definitions.last_mut() {
+                        Some(definition) => definition.definitions.push(details),
+                        None => definitions.push(Definition {
+                            term: Block::Empty,
+                            definitions: vec![details],
+                        }),
+                    }
+                }
+                _ => {}
+            }
+        }
+        definitions
+    }
+
+    fn table(&mut self, table: ElementRef) -> Block<Pure> {
+        let mut header = None;
+        let mut rows = vec![];
+        for child in table.children() {
+            let element = match child.value().as_element() {
+                Some(element) => element,
+                None => continue,
+            };
+            let row_nodes = match element.name() {
+                "tr" => vec![child],
+                "thead" | "tbody" | "tfoot" => child
+                    .children()
+                    .filter(|row| is_element(*row, "tr"))
+                    .collect(),
+                _ => continue,
+            };
+            for row in row_nodes {
+                let cells = row
+                    .children()
+                    .filter(|cell| is_element(*cell, "td") || is_element(*cell, "th"))
+                    .collect::<Vec<_>>();
+                let is_header = element.name() == "thead"
+                    || (header.is_none()
+                        && rows.is_empty()
+                        && !cells.is_empty()
+                        && cells.iter().all(|cell| is_element(*cell, "th")));
+                let cells = cells
+                    .into_iter()
+                    .map(|cell| self.blocks(cell.children()))
+                    .collect::<Vec<_>>();
+                if is_header && header.is_none() {
+                    header = Some(cells);
+                } else {
+                    rows.push(cells);
+                }
+            }
+        }
+        let table_block = Block::Table {
+            header: header.unwrap_or_default(),
+            rows,
+        };
+        match table.children().find(|child| is_element(*child, "caption")) {
+            Some(caption) => Block::Section {
+                title: Box::new(trim_inlines(self.inline(caption.children()))),
+                body: Box::new(table_block),
+            },
+            None => table_block,
+        }
+    }
+
+    fn media(&mut self, element: ElementRef) -> Block<Pure> {
+        let sources = std::iter::once(element.value()).chain(
+            element
+                .children()
+                .filter_map(|child| child.value().as_element())
+                .filter(|child| child.name() == "source"),
+        );
+        let (uri, mime_type) = sources
+            .filter_map(|source| Some((source.attr("src")?, source.attr("type"))))
+            .next()
+            .unwrap_or_default();
+        let kind = element.value().name();
+        let (source, mime_type) = match self.load(uri) {
+            Some((data_mime_type, data)) => (MediaSource::Embedded(data), data_mime_type),
+            None => (
+                MediaSource::External(uri.to_owned()),
+                mime_type
+                    .map(|mime_type| mime_type.to_owned())
+                    .unwrap_or_else(|| guess_mime_type(kind, uri)),
+            ),
+        };
+        let media = Media {
+            source,
+            mime_type,
+            duration: None,
+            // The content of media elements is only shown by browsers that can't play them.
+            transcript: Box::new(Block::Empty),
+        };
+        if kind == "audio" {
+            Block::Audio(media)
+        } else {
+            Block::Video(media)
+        }
+    }
+
+    /// Uses the caption of a figure as the transcript of the media in it. A transcript that is
+    /// collapsed in `<details>` is unwrapped.
+    fn with_transcript<'a>(
+        &mut self,
+        media: Media<Pure>,
+        caption: impl Iterator<Item = NodeRef<'a, Node>>,
+    ) -> Media<Pure> {
+        let caption = caption
+            .filter(|child| !is_whitespace(*child))
+            .collect::<Vec<_>>();
+        let transcript = match caption.as_slice() {
+            [details] if is_element(*details, "details") => self.blocks(
+                details
+                    .children()
+                    .filter(|child| !is_element(*child, "summary")),
+            ),
+            caption => self.blocks(caption.iter().copied()),
+        };
+        Media {
+            transcript: Box::new(transcript),
+            ..media
+        }
+    }
+
+    /// Converts elements inside a line of text. Unknown elements and those that only change the
+    /// appearance become their content.
+    fn inline_element(&mut self, element: ElementRef) -> Block<Pure> {
+        let value = element.value();
+        let name = value.name();
+        let content = |converter: &mut Converter| Box::new(converter.inline(element.children()));
+        match name {
+            "br" => Block::Text("\n".to_owned()),
+            "a" => {
+                let target = value.attr("href").unwrap_or_default().trim();
+                if let Some(name) = value.attr("download") {
+                    if let Some((mime_type, data)) = self.load(target) {
+                        let name = match name.trim() {
+                            "" => file_name(target).unwrap_or("file"),
+                            name => name,
+                        };
+                        return Block::Attachment(AttachedFile::new(name, &mime_type, data));
+                    }
+                }
+                if let Some(term) = target.strip_prefix("#term-") {
+                    if has_class(value, "term") {
+                        return Block::TermUsage {
+                            term: normalize_term(&term.replace('-', " ")),
+                            content: content(self),
+                        };
+                    }
+                }
+                let linked_id = self.files.and_then(|files| (files.resolve_link)(target));
+                if let Some(id) = linked_id {
+                    return Block::CrossReference {
+                        target: id,
+                        content: content(self),
+                    };
+                }
+                match target.strip_prefix('#') {
+                    _ if target.is_empty() => *content(self),
+                    Some(id) => Block::CrossReference {
+                        target: id.to_owned(),
+                        content: content(self),
+                    },
+                    None => Block::Link {
+                        target: target.to_owned(),
+                        content: content(self),
+                    },
+                }
+            }
+            "img" => {
+                let source = value.attr("src").unwrap_or_default();
+                let alt = value.attr("alt").unwrap_or_default().trim();
+                if let Some((mime_type, data)) = self.load(source) {
+                    let name = match alt {
+                        "" => file_name(source).unwrap_or("image"),
+                        alt => alt,
+                    };
+                    return Block::Attachment(AttachedFile::new(name, &mime_type, data));
+                }
+                self.warn(ConversionWarningKind::ImageAsLink {
+                    url: source.to_owned(),
+                });
+                Block::Link {
+                    target: source.to_owned(),
+                    content: Box::new(Block::Text(alt.to_owned())),
+                }
+            }
+            "code" | "kbd" | "samp" | "tt" => Block::Code {
+                language: language(value),
+                code: element.text().collect(),
+            },
+            "mark" => Block::Highlighted {
+                content: content(self),
+                reason: value.classes().find_map(|class| match class {
+                    "search-hit" => Some(HighlightReason::SearchHit),
+                    "key-point" => Some(HighlightReason::KeyPoint),
+                    "change" => Some(HighlightReason::Change),
+                    _ => None,
+                }),
+            },
+            "ins" => Block::Highlighted {
+                content: content(self),
+                reason: Some(HighlightReason::Change),
+            },
+            "q" => Block::Quote(content(self)),
+            "small" => Block::Secondary(content(self)),
+            "time" => {
+                let text = element.text().collect::<String>();
+                let text = collapse_whitespace(text.trim());
+                match parse_temporal_value(value.attr("datetime").unwrap_or(&text)) {
+                    Some(temporal_value) => Block::Temporal {
+                        value: temporal_value,
+                        text,
+                    },
+                    None => *content(self),
+                }
+            }
+            "span" if has_class(value, "citation") => {
+                let keys = element
+                    .descendants()
+                    .filter_map(|node| node.value().as_element()?.attr("href"))
+                    .filter_map(|href| href.strip_prefix("#cite-"))
+                    .map(|key| key.to_owned())
+                    .collect::<Vec<_>>();
+                if keys.is_empty() {
+                    *content(self)
+                } else {
+                    Block::Citation(keys)
+                }
+            }
+            _ => {
+                if let Some(formatting) = lossy_formatting(name) {
+                    self.warn(ConversionWarningKind::DroppedFormatting(formatting));
+                }
+                *content(self)
+            }
+        }
+    }
+
+    fn warn(&mut self, kind: ConversionWarningKind) {
+        self.warnings.push(ConversionWarning {
+            location: Location::Unknown,
+            kind,
+        });
+    }
+
+    /// Loads data embedded in data URIs or files linked from the document.
+    fn load(&self, uri: &str) -> Option<(String, Vec<u8>)> {
+        decode_data_uri(uri).or_else(|| {
+            let file = (self.files?.load)(uri)?;
+            Some((file.mime_type, file.data))
+        })
+    }
+}
+
+/// Collects the IDs that links like `<a href="#intro">` refer to.
+fn referenced_ids(root: ElementRef) -> HashSet<String> {
+    root.descendants()
+        .filter_map(|node| node.value().as_element()?.attr("href"))
+        .filter_map(|href| href.strip_prefix('#'))
+        .map(|id| id.to_owned())
+        .collect()
+}
+
+/// Reads the metadata from the `<title>` and `<meta>` elements and the language of the document.
+fn metadata(root: ElementRef) -> Metadata {
+    let mut metadata = Metadata::default();
+    if let Some(lang) = root.value().attr("lang") {
+        metadata.custom.push(("lang".to_owned(), lang.to_owned()));
+    }
+    for element in root.descendants().filter_map(ElementRef::wrap) {
+        let value = element.value();
+        if value.name() == "title" {
+            let title = collapse_whitespace(element.text().collect::<String>().trim());
+            if !title.is_empty() {
+                metadata.title = Some(title);
+            }
+            continue;
+        }
+        if value.name() != "meta" {
+            continue;
+        }
+        let (name, content) = match (value.attr("name"), value.attr("content")) {
+            (Some(name), Some(content)) => (name, content.trim()),
+            _ => continue,
+        };
+        match name.to_lowercase().as_str() {
+            "author" | "dcterms.creator" => metadata.authors.push(content.to_owned()),
+            "keywords" => metadata.tags.extend(
+                content
+                    .split(',')
+                    .map(|tag| tag.trim().to_owned())
+                    .filter(|tag| !tag.is_empty()),
+            ),
+            "date" | "dcterms.date" if parse_temporal_value(content).is_some() => {
+                metadata.date = parse_temporal_value(content)
+            }
+            // These only affect how browsers display the page.
+            "viewport" | "color-scheme" | "theme-color" | "generator" => {}
+            _ => metadata.custom.push((name.to_owned(), content.to_owned())),
+        }
+    }
+    metadata
+}
+
+/// Groups consecutive inline items into paragraphs, so the content following headings can be
+/// nested into sections.
+fn sections(items: Vec<Item>) -> Vec<SectionItem<Pure>> {
+    let mut sections = vec![];
+    let mut inlines = vec![];
+    for item in items {
+        let section = match item {
+            Item::Inline(inline) => {
+                inlines.push(inline);
+                continue;
+            }
+            Item::Break => None,
+            Item::Block(block) => Some(SectionItem::Block(block)),
+            Item::Heading { level, id, title } => Some(SectionItem::Heading(
+                Heading::new(level as usize, title).with_ids(id),
+            )),
+        };
+        sections.push(SectionItem::Block(paragraph(std::mem::take(&mut inlines))));
+        sections.extend(section);
+    }
+    sections.push(SectionItem::Block(paragraph(inlines)));
+    sections
+}
+
+fn heading_level(element: &Element) -> Option<u32> {
+    match element.name() {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ if element.attr("role") == Some("heading") => {
+            Some(element.attr("aria-level")?.trim().parse().ok()?)
+        }
+        _ => None,
+    }
+}
+
+/// Turns consecutive inline content into a single block, with the whitespace collapsed like
+/// browsers do.
+fn paragraph(inlines: Vec<Block<Pure>>) -> Block<Pure> {
+    match trim_inlines(Block::Flow(inlines)) {
+        Block::Text(text) => Block::Text(collapse_spaces(&text)),
+        Block::Flow(children) => Block::Flow(
+            children
+                .into_iter()
+                .map(|child| match child {
+                    Block::Text(text) => Block::Text(collapse_spaces(&text)),
+                    child => child,
+                })
+                .collect(),
+        ),
+        block => block,
+    }
+}
+
+/// Removes whitespace around inline content.
+fn trim_inlines(block: Block<Pure>) -> Block<Pure> {
+    match block.simplify() {
+        Block::Text(text) => Block::Text(text.trim().to_owned()).simplify(),
+        Block::Flow(mut children) => {
+            if let Some(Block::Text(text)) = children.first_mut() {
+                *text = text.trim_start().to_owned();
+            }
+            if let Some(Block::Text(text)) = children.last_mut() {
+                *text = text.trim_end().to_owned();
+            }
+            Block::Flow(children).simplify()
+        }
+        block => block,
+    }
+}
+
+/// Replaces runs of whitespace with a single space.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut was_whitespace = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !was_whitespace {
+                collapsed.push(' ');
+            }
+            was_whitespace = true;
+        } else {
+            collapsed.push(c);
+            was_whitespace = false;
+        }
+    }
+    collapsed
+}
+
+/// Removes the spaces that remain where collapsed texts of different elements meet, like in
+/// "<em>Hello </em> world". Line breaks swallow the spaces around them.
+fn collapse_spaces(text: &str) -> String {
+    let mut collapsed = text.to_owned();
+    while collapsed.contains("  ") {
+        collapsed = collapsed.replace("  ", " ");
+    }
+    collapsed.replace(" \n", "\n").replace("\n ", "\n")
+}
+
+fn is_element(node: NodeRef<Node>, name: &str) -> bool {
+    matches!(node.value(), Node::Element(element) if element.name() == name)
+}
+
+fn has_class(element: &Element, name: &str) -> bool {
+    element.classes().any(|class| class == name)
+}
+
+fn is_whitespace(node: NodeRef<Node>) -> bool {
+    matches!(node.value(), Node::Text(text) if text.trim().is_empty())
+        || matches!(node.value(), Node::Comment(_))
+}
+
+fn is_checkbox(node: NodeRef<Node>) -> bool {
+    match node.value() {
+        Node::Element(element) => {
+            element.name() == "input" && element.attr("type") == Some("checkbox")
+        }
+        _ => false,
+    }
+}
+
+/// Finds the language of code in classes like "language-rust", which many syntax highlighters use.
+fn language(element: &Element) -> Option<String> {
+    element.classes().find_map(|class| {
+        class
+            .strip_prefix("language-")
+            .or_else(|| class.strip_prefix("lang-"))
+            .map(|language| language.to_owned())
+    })
+}
+
+/// The name of the file a URI refers to, like "cat.png" for "images/cat.png".
+fn file_name(uri: &str) -> Option<&str> {
+    if uri.starts_with("data:") {
+        return None;
+    }
+    let path = uri.split(['?', '#']).next().unwrap_or_default();
+    path.rsplit('/').next().filter(|name| !name.is_empty())
+}
+
+fn guess_mime_type(kind: &str, uri: &str) -> String {
+    let extension = uri
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match (kind, extension.as_str()) {
+        (_, "mp3") => "audio/mpeg".to_owned(),
+        (_, "wav") => "audio/wav".to_owned(),
+        (_, "flac") => "audio/flac".to_owned(),
+        ("audio", "ogg" | "oga" | "opus") => "audio/ogg".to_owned(),
+        ("audio", "m4a" | "mp4") => "audio/mp4".to_owned(),
+        ("audio", "webm") => "audio/webm".to_owned(),
+        (_, "ogg" | "ogv") => "video/ogg".to_owned(),
+        (_, "webm") => "video/webm".to_owned(),
+        (_, "mp4" | "m4v") => "video/mp4".to_owned(),
+        (kind, _) => format!("{}/*", kind),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use semdoc::{Block::*, TemporalValue};
+
+    fn convert(html: &str) -> Block<Pure> {
+        html_to_semdoc(html).block.simplify()
+    }
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    fn section(title: &str, body: Block<Pure>) -> Block<Pure> {
+        Section {
+            title: Box::new(text(title)),
+            body: Box::new(body),
+        }
+    }
+
+    #[test]
+    fn whitespace_is_collapsed() {
+        assert_eq!(
+            convert("<p>\n  Hello <em> dear </em>\n  world!<br>\n  Bye\n</p>"),
+            text("Hello dear world!\nBye")
+        );
+    }
+
+    #[test]
+    fn headings_nest_across_containers() {
+        assert_eq!(
+            convert(
+                "<h1>Fruits</h1><div><p>Tasty.</p><h2>Apples</h2><p>Red.</p></div>\
+                 <h1>Vegetables</h1>"
+            ),
+            Paragraphs(vec![
+                section(
+                    "Fruits",
+                    Paragraphs(vec![text("Tasty."), section("Apples", text("Red."))])
+                ),
+                section("Vegetables", Empty),
+            ])
+        );
+    }
+
+    #[test]
+    fn sections_nest_on_their_own() {
+        assert_eq!(
+            convert("<h1>Outer</h1><section><h3>Inner</h3><p>Inside.</p></section><p>Outside.</p>"),
+            section(
+                "Outer",
+                Paragraphs(vec![section("Inner", text("Inside.")), text("Outside.")])
+            )
+        );
+    }
+
+    #[test]
+    fn definition_lists_in_glossary_sections_become_glossaries() {
+        assert_eq!(
+            convert("<h2>Glossary</h2><dl><dt>SemDoc</dt><dd>A semantic document.</dd></dl>"),
+            section(
+                "Glossary",
+                Glossary(vec![Definition {
+                    term: text("SemDoc"),
+                    definitions: vec![text("A semantic document.")],
+                }])
+            )
+        );
+    }
+
+    #[test]
+    fn only_main_content_is_converted() {
+        let doc = html_to_semdoc(
+            "<html lang=\"en\"><head><title>Recipes</title>\
+             <meta name=\"author\" content=\"Ada\"><meta name=\"keywords\" content=\"food, cake\">\
+             <meta name=\"viewport\" content=\"width=device-width\"></head>\
+             <body><header>Site</header><main><p>Bake it.</p></main></body></html>",
+        );
+        assert_eq!(doc.block.simplify(), text("Bake it."));
+        assert_eq!(
+            doc.metadata,
+            Metadata {
+                title: Some("Recipes".to_owned()),
+                authors: vec!["Ada".to_owned()],
+                date: None,
+                tags: vec!["food".to_owned(), "cake".to_owned()],
+                custom: vec![("lang".to_owned(), "en".to_owned())],
+            }
+        );
+    }
+
+    #[test]
+    fn scripts_and_forms_are_dropped_with_warnings() {
+        let (doc, warnings) = html_to_semdoc_with_warnings(
+            "<p>Hi<script>alert(1)</script></p><form><input name=\"q\"></form><style>p {}</style>",
+        );
+        assert_eq!(doc.block.simplify(), text("Hi"));
+        assert_eq!(
+            warnings,
+            vec![
+                ConversionWarning {
+                    location: Location::Unknown,
+                    kind: ConversionWarningKind::DroppedElement("script".to_owned()),
+                },
+                ConversionWarning {
+                    location: Location::Unknown,
+                    kind: ConversionWarningKind::DroppedElement("form".to_owned()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn checkbox_lists_become_checklists() {
+        assert_eq!(
+            convert(
+                "<ul><li><input type=\"checkbox\" checked> Tag the release</li>\
+                 <li><input type=\"checkbox\">Update the changelog</li></ul>"
+            ),
+            Checklist(vec![
+                ChecklistItem {
+                    checked: true,
+                    content: text("Tag the release"),
+                },
+                ChecklistItem {
+                    checked: false,
+                    content: text("Update the changelog"),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn tables_with_captions_become_sections() {
+        assert_eq!(
+            convert(
+                "<table><caption>Prices</caption><tr><th>Fruit</th><th>Price</th></tr>\
+                 <tr><td>Apple</td><td>1 €</td></tr></table>"
+            ),
+            section(
+                "Prices",
+                Table {
+                    header: vec![text("Fruit"), text("Price")],
+                    rows: vec![vec![text("Apple"), text("1 €")]],
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn preformatted_code_keeps_its_whitespace() {
+        assert_eq!(
+            convert("<pre><code class=\"language-rust\">fn main() {\n    run();\n}\n</code></pre>"),
+            Code {
+                language: Some("rust".to_owned()),
+                code: "fn main() {\n    run();\n}".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn links_to_ids_become_cross_references() {
+        assert_eq!(
+            convert("<p>See <a href=\"#setup\">the setup</a>.</p><p id=\"setup\">Install it.</p>"),
+            Paragraphs(vec![
+                Flow(vec![
+                    text("See "),
+                    CrossReference {
+                        target: "setup".to_owned(),
+                        content: Box::new(text("the setup")),
+                    },
+                    text("."),
+                ]),
+                Anchor {
+                    id: "setup".to_owned(),
+                    content: Box::new(text("Install it.")),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn semantic_inline_elements_are_kept() {
+        assert_eq!(
+            convert(
+                "<p><mark class=\"key-point\">Due</mark> <time datetime=\"2021-03-14\">Pi day\
+                 </time>, <small>probably</small>.</p>"
+            ),
+            Flow(vec![
+                Highlighted {
+                    content: Box::new(text("Due")),
+                    reason: Some(HighlightReason::KeyPoint),
+                },
+                text(" "),
+                Temporal {
+                    value: TemporalValue::Date {
+                        year: 2021,
+                        month: 3,
+                        day: 14,
+                    },
+                    text: "Pi day".to_owned(),
+                },
+                text(", "),
+                Secondary(Box::new(text("probably"))),
+                text("."),
+            ])
+        );
+    }
+
+    #[test]
+    fn details_become_more_info() {
+        assert_eq!(
+            convert("<details><summary>Why?</summary><p>Because.</p></details>"),
+            MoreInfo {
+                summary: Box::new(text("Why?")),
+                body: Box::new(text("Because.")),
+            }
+        );
+    }
+
+    #[test]
+    fn linked_files_are_resolved() {
+        let files = LinkedFiles {
+            referenced_ids: vec!["intro".to_owned()].into_iter().collect(),
+            resolve_link: &|target| target.strip_prefix("setup.xhtml#").map(|id| id.to_owned()),
+            load: &|path| match path {
+                "images/cat.png" => Some(AttachedFile::new("cat.png", "image/png", vec![1, 2])),
+                _ => None,
+            },
+        };
+        let (block, warnings) = linked_html_to_block(
+            "<p id=\"intro\">See <a href=\"setup.xhtml#linux\">Linux</a>.</p>\
+             <p><img src=\"images/cat.png\" alt=\"\"></p>",
+            &files,
+        );
+        assert_eq!(
+            block,
+            Paragraphs(vec![
+                Anchor {
+                    id: "intro".to_owned(),
+                    content: Box::new(Flow(vec![
+                        text("See "),
+                        CrossReference {
+                            target: "linux".to_owned(),
+                            content: Box::new(text("Linux")),
+                        },
+                        text("."),
+                    ])),
+                },
+                Attachment(AttachedFile::new("cat.png", "image/png", vec![1, 2])),
+            ])
+        );
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn images_become_links() {
+        let (doc, warnings) =
+            html_to_semdoc_with_warnings("<p><img src=\"cat.png\" alt=\"A cat\"></p>");
+        assert_eq!(
+            doc.block.simplify(),
+            Link {
+                target: "cat.png".to_owned(),
+                content: Box::new(text("A cat")),
+            }
+        );
+        assert_eq!(
+            warnings,
+            vec![ConversionWarning {
+                location: Location::Unknown,
+                kind: ConversionWarningKind::ImageAsLink {
+                    url: "cat.png".to_owned()
+                },
+            }]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use semdoc::{civil_to_days, TemporalValue};
+
+/// Parses machine-readable values of `<time>` elements, like "2021-03-14",
+/// "2021-03-14T15:09:26Z", "2021-03-14 15:09+01:00" or durations like "PT1H30M".
+pub(crate) fn parse_temporal_value(text: &str) -> Option<TemporalValue> {
+    let text = text.trim();
+    if text.starts_with('P') {
+        return parse_duration(text);
+    }
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = text.get(range)?;
+        if digits.chars().all(|c| c.is_ascii_digit()) {
+            digits.parse().ok()
+        } else {
+            None
+        }
+    };
+    if text.get(4..5)? != "-" || text.get(7..8)? != "-" {
+        return None;
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let date = TemporalValue::Date {
+        year: year as i32,
+        month: month as u8,
+        day: day as u8,
+    };
+    if !date.is_valid() {
+        return None;
+    }
+    if text.len() == 10 {
+        return Some(date);
+    }
+
+    if !matches!(text.get(10..11)?, "T" | " ") || text.get(13..14)? != ":" {
+        return None;
+    }
+    let (hour, minute) = (number(11..13)?, number(14..16)?);
+    let mut rest = &text[16..];
+    let mut second = 0;
+    if let Some(seconds) = rest.strip_prefix(':') {
+        second = seconds.get(0..2)?.parse().ok()?;
+        rest = &seconds[2..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            rest = fraction.trim_start_matches(|c: char| c.is_ascii_digit());
+        }
+    }
+    // Times without an offset are local times, which are treated like UTC.
+    let offset_in_minutes = match rest {
+        "" | "Z" => 0,
+        offset => {
+            let sign = match offset.get(0..1)? {
+                "+" => 1,
+                "-" => -1,
+                _ => return None,
+            };
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            sign * (hours.parse::<i64>().ok()? * 60 + minutes.parse::<i64>().ok()?)
+        }
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let seconds = civil_to_days(year, month, day) * 86400 + hour * 3600 + minute * 60 + second
+        - offset_in_minutes * 60;
+    Some(TemporalValue::Instant(seconds))
+}
+
+/// Parses durations like "P2D", "PT1H30M" or "PT90S". Years and months don't have a fixed length,
+/// so they aren't supported.
+fn parse_duration(text: &str) -> Option<TemporalValue> {
+    let mut seconds = 0;
+    let mut number = String::new();
+    let mut is_time = false;
+    for c in text[1..].chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => is_time = true,
+            _ => {
+                let value: u64 = number.parse().ok()?;
+                number.clear();
+                seconds += value
+                    * match (c, is_time) {
+                        ('W', false) => 7 * 86400,
+                        ('D', false) => 86400,
+                        ('H', true) => 3600,
+                        ('M', true) => 60,
+                        ('S', true) => 1,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    if number.is_empty() && text.len() > 1 {
+        Some(TemporalValue::Duration(seconds))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,359 @@
+use markdown_to_semdoc::markdown_to_semdoc_with_warnings;
+pub use markdown_to_semdoc::{ConversionWarning, ConversionWarningKind, Location};
+use semdoc::{nest_sections, AttachedFile, Block, Heading, Metadata, Pure, SectionItem, SemDoc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+mod outputs;
+use outputs::*;
+
+#[derive(Debug)]
+pub enum NotebookError {
+    /// The input isn't valid JSON.
+    InvalidJson(String),
+    /// The JSON isn't a notebook, which is an object with the format version and the cells.
+    NotANotebook,
+    /// The notebook uses a format version before 4, which had the cells in worksheets.
+    UnsupportedVersion(u64),
+}
+
+pub fn jupyter_to_semdoc(json: &str) -> Result<SemDoc<Pure>, NotebookError> {
+    Ok(jupyter_to_semdoc_with_warnings(json)?.0)
+}
+
+/// Converts a Jupyter notebook (an `.ipynb` file) and reports content that couldn't be converted
+/// faithfully.
+///
+/// Markdown cells are converted like Markdown documents. Their headings start sections that go
+/// until the next heading of the same or a higher level, so sections contain the cells following
+/// a heading. Code cells become code in the language of the notebook's kernel, followed by their
+/// outputs. Cells that ran are put into a section titled with their execution count, like
+/// "In [3]", so readers can see in which order the cells ran.
+pub fn jupyter_to_semdoc_with_warnings(
+    json: &str,
+) -> Result<(SemDoc<Pure>, Vec<ConversionWarning>), NotebookError> {
+    let notebook: Value = serde_json::from_str(json)
+        .map_err(|error| NotebookError::InvalidJson(error.to_string()))?;
+    match notebook["nbformat"].as_u64() {
+        Some(version) if version < 4 => return Err(NotebookError::UnsupportedVersion(version)),
+        Some(_) => {}
+        None => return Err(NotebookError::NotANotebook),
+    }
+    let cells = notebook["cells"]
+        .as_array()
+        .ok_or(NotebookError::NotANotebook)?;
+    let metadata = &notebook["metadata"];
+    let language = metadata["kernelspec"]["language"]
+        .as_str()
+        .or_else(|| metadata["language_info"]["name"].as_str());
+
+    let mut converter = Converter {
+        language: language.map(|it| it.to_owned()),
+        ..Default::default()
+    };
+    let mut items = vec![];
+    for (index, cell) in cells.iter().enumerate() {
+        let number = index + 1;
+        let source = text(&cell["source"]);
+        match cell["cell_type"].as_str().unwrap_or_default() {
+            "markdown" => items.extend(converter.markdown_cell(number, &source, cell)),
+            "code" => items.push(SectionItem::Block(
+                converter.code_cell(number, &source, cell),
+            )),
+            _ => converter.warnings.push(
+                ConversionWarning {
+                    location: Location::Unknown,
+                    kind: ConversionWarningKind::DroppedRawCell,
+                }
+                .in_cell(number),
+            ),
+        }
+    }
+    let block = Block::Paragraphs(nest_sections(items)).link_glossary_terms();
+    let doc = SemDoc::new(block).with_metadata(notebook_metadata(metadata));
+    Ok((doc, converter.warnings))
+}
+
+#[derive(Default)]
+struct Converter {
+    /// The language of the code cells.
+    language: Option<String>,
+    warnings: Vec<ConversionWarning>,
+    /// How often each heading ID was used, so headings in different cells get unique IDs.
+    heading_ids: HashMap<String, usize>,
+    /// The number of images in outputs so far, which are used for the names of the attachments.
+    num_images: usize,
+}
+
+impl Converter {
+    /// Splits a Markdown cell at its headings, so the sections they start can contain the
+    /// following cells.
+    fn markdown_cell(
+        &mut self,
+        number: usize,
+        source: &str,
+        cell: &Value,
+    ) -> Vec<SectionItem<Pure>> {
+        let attachments = attachments(&cell["attachments"]);
+        let lines = source.lines().collect::<Vec<_>>();
+        let mut items = vec![];
+        let mut start = 0;
+        let mut fence: Option<&str> = None;
+        for (index, line) in lines.iter().enumerate() {
+            let trimmed = line.trim_start();
+            let level = match fence {
+                Some(marker) => {
+                    if trimmed.starts_with(marker) {
+                        fence = None;
+                    }
+                    continue;
+                }
+                None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                    fence = Some(&trimmed[..3]);
+                    continue;
+                }
+                None => match heading_level(line) {
+                    Some(level) => level,
+                    None => continue,
+                },
+            };
+            let before = self.markdown(number, &lines[start..index], start, &attachments);
+            items.extend(before.map(SectionItem::Block));
+            if let Some(heading) = self.markdown(number, &lines[index..=index], index, &attachments)
+            {
+                items.push(self.heading(level, heading));
+            }
+            start = index + 1;
+        }
+        let rest = self.markdown(number, &lines[start..], start, &attachments);
+        items.extend(rest.map(SectionItem::Block));
+        items
+    }
+
+    /// Converts lines of a Markdown cell, starting at the line with the given index.
+    fn markdown(
+        &mut self,
+        number: usize,
+        lines: &[&str],
+        start: usize,
+        attachments: &HashMap<String, AttachedFile>,
+    ) -> Option<Block<Pure>> {
+        if lines.iter().all(|line| line.trim().is_empty()) {
+            return None;
+        }
+        let (doc, warnings) = markdown_to_semdoc_with_warnings(&lines.join("\n"));
+        for mut warning in warnings {
+            if let ConversionWarningKind::ImageAsLink { url } = &warning.kind {
+                if attachment_name(url).is_some_and(|name| attachments.contains_key(name)) {
+                    continue;
+                }
+            }
+            if let Location::Line(line) = &mut warning.location {
+                *line += start as u32;
+            }
+            self.warnings.push(warning.in_cell(number));
+        }
+        Some(embed_attachments(doc.block, attachments))
+    }
+
+    /// Turns a converted heading, which is an anchor around an empty section, into the start of a
+    /// section.
+    fn heading(&mut self, level: usize, heading: Block<Pure>) -> SectionItem<Pure> {
+        let (id, title) = match heading {
+            Block::Paragraphs(mut blocks) if blocks.len() == 1 => match blocks.remove(0) {
+                Block::Anchor { id, content } => match *content {
+                    Block::Section { title, .. } => (id, *title),
+                    content => return SectionItem::Block(content),
+                },
+                block => return SectionItem::Block(block),
+            },
+            block => return SectionItem::Block(block),
+        };
+        let count = self.heading_ids.entry(id.clone()).or_insert(0);
+        let id = if *count == 0 {
+            id
+        } else {
+            format!("{}-{}", id, count)
+        };
+        *count += 1;
+        SectionItem::Heading(Heading::new(level, title).with_ids(Some(id)))
+    }
+
+    fn code_cell(&mut self, number: usize, source: &str, cell: &Value) -> Block<Pure> {
+        let mut blocks = vec![];
+        if !source.trim().is_empty() {
+            blocks.push(Block::Code {
+                language: self.language.clone(),
+                code: source.to_owned(),
+            });
+        }
+        blocks.extend(self.outputs(number, list(&cell["outputs"])));
+        let body = Block::Paragraphs(blocks);
+        match cell["execution_count"].as_u64() {
+            Some(count) => Block::Section {
+                title: Box::new(Block::Text(format!("In [{}]", count))),
+                body: Box::new(body),
+            },
+            None => body,
+        }
+    }
+}
+
+fn notebook_metadata(metadata: &Value) -> Metadata {
+    Metadata {
+        title: metadata["title"].as_str().map(|it| it.to_owned()),
+        authors: list(&metadata["authors"])
+            .iter()
+            .filter_map(|author| author["name"].as_str())
+            .map(|it| it.to_owned())
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Files attached to a Markdown cell, which images can refer to like `attachment:plot.png`.
+fn attachments(value: &Value) -> HashMap<String, AttachedFile> {
+    let files = match value.as_object() {
+        Some(files) => files,
+        None => return HashMap::new(),
+    };
+    files
+        .iter()
+        .filter_map(|(name, data)| {
+            let (mime_type, data) = data.as_object()?.iter().next()?;
+            let data = decode_data(mime_type, &text(data))?;
+            Some((name.clone(), AttachedFile::new(name, mime_type, data)))
+        })
+        .collect()
+}
+
+fn attachment_name(url: &str) -> Option<&str> {
+    url.strip_prefix("attachment:")
+}
+
+/// Replaces links to attachments, which the Markdown converter creates for images, with the
+/// attached files.
+fn embed_attachments(
+    block: Block<Pure>,
+    attachments: &HashMap<String, AttachedFile>,
+) -> Block<Pure> {
+    match block {
+        Block::Link { target, content } => {
+            match attachment_name(&target).and_then(|name| attachments.get(name)) {
+                Some(file) => Block::Attachment(file.clone()),
+                None => Block::Link {
+                    target,
+                    content: Box::new(embed_attachments(*content, attachments)),
+                },
+            }
+        }
+        block => block.map_children(|child| embed_attachments(child, attachments)),
+    }
+}
+
+/// Parses ATX headings like `## Results`, returning the level.
+fn heading_level(line: &str) -> Option<usize> {
+    let indentation = line.len() - line.trim_start_matches(' ').len();
+    let line = &line[indentation..];
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    let is_heading = indentation <= 3
+        && (1..=6).contains(&level)
+        && (rest.is_empty() || rest.starts_with([' ', '\t']));
+    is_heading.then_some(level)
+}
+
+/// Texts in notebooks are either a string or a list of lines that include their line breaks.
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn list(value: &Value) -> &[Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn notebook(cells: &str) -> String {
+        format!(
+            "{{\"cells\": [{}], \"metadata\": {{}}, \"nbformat\": 4, \"nbformat_minor\": 5}}",
+            cells
+        )
+    }
+
+    fn markdown_cell(source: &str) -> String {
+        format!(
+            "{{\"cell_type\": \"markdown\", \"metadata\": {{}}, \"source\": {:?}}}",
+            source
+        )
+    }
+
+    fn convert(cells: &[String]) -> Block<Pure> {
+        jupyter_to_semdoc(&notebook(&cells.join(", ")))
+            .unwrap()
+            .block
+            .simplify()
+    }
+
+    #[test]
+    fn rejects_invalid_notebooks() {
+        assert!(matches!(
+            jupyter_to_semdoc("{\"cells\": ["),
+            Err(NotebookError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            jupyter_to_semdoc("{\"cells\": []}"),
+            Err(NotebookError::NotANotebook)
+        ));
+        assert!(matches!(
+            jupyter_to_semdoc("{\"worksheets\": [], \"nbformat\": 3}"),
+            Err(NotebookError::UnsupportedVersion(3))
+        ));
+    }
+
+    #[test]
+    fn headings_in_different_cells_get_unique_ids() {
+        let block = convert(&[
+            markdown_cell("## Setup"),
+            markdown_cell("## Setup\n\n```python\n# Not a heading\n```"),
+        ]);
+        let setup = |id: &str, body: Block<Pure>| Block::Anchor {
+            id: id.to_owned(),
+            content: Box::new(Block::Section {
+                title: Box::new(Block::Text("Setup".to_owned())),
+                body: Box::new(body),
+            }),
+        };
+        let code = Block::Code {
+            language: Some("python".to_owned()),
+            code: "# Not a heading".to_owned(),
+        };
+        assert_eq!(
+            block,
+            Block::Paragraphs(vec![setup("setup", Block::Empty), setup("setup-1", code)])
+        );
+    }
+
+    #[test]
+    fn images_can_be_attached_to_markdown_cells() {
+        let cell = "{\"cell_type\": \"markdown\", \"metadata\": {}, \
+            \"attachments\": {\"logo.svg\": {\"image/svg+xml\": \"<svg/>\"}}, \
+            \"source\": \"![Logo](attachment:logo.svg)\"}";
+        let (doc, warnings) = jupyter_to_semdoc_with_warnings(&notebook(cell)).unwrap();
+        assert_eq!(
+            doc.block.simplify(),
+            Block::Attachment(AttachedFile::new(
+                "logo.svg",
+                "image/svg+xml",
+                b"<svg/>".to_vec()
+            ))
+        );
+        assert!(warnings.is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+//! The outputs of code cells, like printed text, plots or tables.
+
+use crate::{list, text, ConversionWarning, ConversionWarningKind, Converter, Location};
+use semdoc::{AttachedFile, Block, Pure};
+use serde_json::Value;
+
+/// The image formats outputs can have, along with the extension of the attachments.
+const IMAGE_TYPES: [(&str, &str); 4] = [
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/svg+xml", "svg"),
+];
+
+impl Converter {
+    pub(crate) fn outputs(&mut self, cell: usize, outputs: &[Value]) -> Vec<Block<Pure>> {
+        let mut blocks = vec![];
+        let mut previous_stream = None;
+        for output in outputs {
+            let stream = output["name"]
+                .as_str()
+                .filter(|_| output["output_type"] == "stream");
+            match output["output_type"].as_str().unwrap_or_default() {
+                "stream" => {
+                    let output = strip_escape_codes(&text(&output["text"]));
+                    // Kernels split the output of a stream at arbitrary points.
+                    match blocks.last_mut() {
+                        Some(Block::Code { code, .. }) if stream == previous_stream => {
+                            code.push_str(&output)
+                        }
+                        _ => blocks.push(Block::Code {
+                            language: None,
+                            code: output,
+                        }),
+                    }
+                }
+                "execute_result" | "display_data" => {
+                    blocks.extend(self.rich_output(cell, &output["data"]))
+                }
+                "error" => {
+                    let traceback = list(&output["traceback"])
+                        .iter()
+                        .map(|line| strip_escape_codes(line.as_str().unwrap_or_default()))
+                        .collect::<Vec<_>>();
+                    let code = if traceback.is_empty() {
+                        format!("{}: {}", text(&output["ename"]), text(&output["evalue"]))
+                    } else {
+                        traceback.join("\n")
+                    };
+                    blocks.push(Block::Code {
+                        language: None,
+                        code,
+                    });
+                }
+                _ => {}
+            }
+            previous_stream = stream;
+        }
+        for block in &mut blocks {
+            if let Block::Code { code, .. } = block {
+                code.truncate(code.trim_end_matches('\n').len());
+            }
+        }
+        blocks
+    }
+
+    /// Converts an output that is available in multiple formats, choosing the richest one that
+    /// SemDoc supports: Images become attachments, HTML tables (like the ones of pandas data
+    /// frames) become tables, Markdown is converted and LaTeX and plain text become code.
+    fn rich_output(&mut self, cell: usize, data: &Value) -> Option<Block<Pure>> {
+        let format = |mime_type: &str| data.get(mime_type).map(text);
+        for (mime_type, extension) in &IMAGE_TYPES {
+            if let Some(image) = format(mime_type).and_then(|it| decode_data(mime_type, &it)) {
+                self.num_images += 1;
+                let name = format!("output-{}.{}", self.num_images, extension);
+                return Some(Block::Attachment(AttachedFile::new(
+                    &name, mime_type, image,
+                )));
+            }
+        }
+        if let Some(html) = format("text/html").filter(|html| html.contains("<table")) {
+            let (doc, warnings) = html_to_semdoc::html_to_semdoc_with_warnings(&html);
+            self.warnings
+                .extend(warnings.into_iter().map(|warning| warning.in_cell(cell)));
+            return Some(doc.block);
+        }
+        if let Some(markdown) = format("text/markdown") {
+            let (doc, warnings) = markdown_to_semdoc::markdown_to_semdoc_with_warnings(&markdown);
+            self.warnings
+                .extend(warnings.into_iter().map(|warning| warning.in_cell(cell)));
+            return Some(doc.block);
+        }
+        if let Some(latex) = format("text/latex") {
+            return Some(Block::Code {
+                language: Some("latex".to_owned()),
+                code: latex,
+            });
+        }
+        if let Some(plain) = format("text/plain") {
+            return Some(Block::Code {
+                language: None,
+                code: plain,
+            });
+        }
+        let mime_types = data
+            .as_object()
+            .map(|formats| formats.keys().cloned().collect())
+            .unwrap_or_default();
+        self.warnings.push(
+            ConversionWarning {
+                location: Location::Unknown,
+                kind: ConversionWarningKind::UnsupportedOutput { mime_types },
+            }
+            .in_cell(cell),
+        );
+        None
+    }
+}
+
+/// Decodes the data of an image. SVGs are text, other images are encoded using base64.
+pub(crate) fn decode_data(mime_type: &str, data: &str) -> Option<Vec<u8>> {
+    if mime_type == "image/svg+xml" {
+        Some(data.as_bytes().to_vec())
+    } else {
+        decode_base64(data)
+    }
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut data = vec![];
+    let mut bits = 0u32;
+    let mut num_bits = 0;
+    for c in encoded.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' => 62,
+            '/' => 63,
+            _ => return None,
+        };
+        bits = bits << 6 | value;
+        num_bits += 6;
+        if num_bits >= 8 {
+            num_bits -= 8;
+            data.push((bits >> num_bits) as u8);
+            bits &= (1 << num_bits) - 1;
+        }
+    }
+    Some(data)
+}
+
+/// Removes the ANSI escape codes that color tracebacks and other output in terminals, like
+/// `\x1b[0;31m`.
+fn strip_escape_codes(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            // The parameters are followed by a final character like `m`.
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
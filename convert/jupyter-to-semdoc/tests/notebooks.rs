@@ -0,0 +1,112 @@
+//! Notebooks along with the SemDocs they should turn into.
+
+use jupyter_to_semdoc::{
+    jupyter_to_semdoc_with_warnings, ConversionWarning, ConversionWarningKind, Location,
+};
+use semdoc::{AttachedFile, Block, Block::*, Definition, Pure};
+
+fn text(text: &str) -> Block<Pure> {
+    Text(text.to_owned())
+}
+
+fn section(title: Block<Pure>, body: Block<Pure>) -> Block<Pure> {
+    Section {
+        title: Box::new(title),
+        body: Box::new(body),
+    }
+}
+
+fn anchor(id: &str, content: Block<Pure>) -> Block<Pure> {
+    Anchor {
+        id: id.to_owned(),
+        content: Box::new(content),
+    }
+}
+
+fn code(language: Option<&str>, code: &str) -> Block<Pure> {
+    Code {
+        language: language.map(|it| it.to_owned()),
+        code: code.to_owned(),
+    }
+}
+
+#[test]
+fn cities() {
+    let (doc, warnings) =
+        jupyter_to_semdoc_with_warnings(include_str!("fixtures/cities.ipynb")).unwrap();
+    assert_eq!(doc.metadata.title.as_deref(), Some("Cities"));
+    assert_eq!(doc.metadata.authors, vec!["Ada Lovelace".to_owned()]);
+
+    let table = Table {
+        header: vec![Empty, text("city"), text("population")],
+        rows: vec![vec![text("0"), text("Tokyo"), text("37400068")]],
+    };
+    let first_cell = section(
+        text("In [1]"),
+        Paragraphs(vec![
+            code(
+                Some("python"),
+                "import pandas as pd\ncities = pd.read_csv(\"cities.csv\")\ncities.head(1)",
+            ),
+            table,
+        ]),
+    );
+    let plot = AttachedFile::new("output-1.png", "image/png", b"\x89PNG\r\n\x1a\n".to_vec());
+    let growth = Paragraphs(vec![
+        text("Loading the history takes a while:"),
+        section(
+            text("In [3]"),
+            Paragraphs(vec![
+                code(Some("python"), "history = load_history()\nhistory.plot()"),
+                code(None, "Loading 1950 to 2020\ndone"),
+                Attachment(plot),
+            ]),
+        ),
+        section(
+            text("In [2]"),
+            Paragraphs(vec![
+                code(Some("python"), "cities.growth"),
+                code(
+                    None,
+                    "AttributeError: 'DataFrame' object has no attribute 'growth'",
+                ),
+            ]),
+        ),
+        code(Some("python"), "# TODO: Compare continents"),
+    ]);
+    let growth_title = TermUsage {
+        term: "growth".to_owned(),
+        content: Box::new(text("Growth")),
+    };
+    let glossary = Glossary(vec![Definition {
+        term: text("Growth"),
+        definitions: vec![text("The change of the population per year.")],
+    }]);
+    assert_eq!(
+        doc.block.simplify(),
+        Paragraphs(vec![
+            anchor(
+                "cities",
+                section(
+                    text("Cities"),
+                    Paragraphs(vec![
+                        text("We look at the largest cities."),
+                        first_cell,
+                        anchor("growth", section(growth_title, growth)),
+                    ])
+                )
+            ),
+            anchor("glossary", section(text("Glossary"), glossary)),
+        ])
+    );
+    assert_eq!(
+        warnings,
+        vec![ConversionWarning {
+            location: Location::Cell {
+                number: 6,
+                location: Box::new(Location::Unknown),
+            },
+            kind: ConversionWarningKind::DroppedRawCell,
+        }]
+    );
+}
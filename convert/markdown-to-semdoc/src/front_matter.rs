@@ -0,0 +1,221 @@
+use semdoc::{civil_to_days, Metadata, TemporalValue};
+
+/// A value in the front matter, independent of whether it was written in YAML or TOML.
+enum Value {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+impl Value {
+    fn into_list(self) -> Vec<String> {
+        match self {
+            Value::Scalar(text) => text
+                .split(',')
+                .map(|it| it.trim().to_owned())
+                .filter(|it| !it.is_empty())
+                .collect(),
+            Value::List(items) => items,
+        }
+    }
+
+    fn into_text(self) -> String {
+        match self {
+            Value::Scalar(text) => text,
+            Value::List(items) => items.join(", "),
+        }
+    }
+}
+
+/// Turns YAML front matter (between `---` lines) or TOML front matter (between `+++` lines) into
+/// the metadata of a document.
+///
+/// The title, authors, date and tags go into their own fields. All other entries become custom
+/// entries. Nested keys are joined with dots, like "social.mastodon".
+pub(crate) fn front_matter_to_metadata(front_matter: &str) -> Result<Metadata, String> {
+    let mut lines = front_matter.lines();
+    let delimiter = lines.next().unwrap_or_default().trim();
+    let content = lines
+        .take_while(|line| line.trim() != delimiter)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let entries = if delimiter == "+++" {
+        toml_entries(&content)?
+    } else {
+        yaml_entries(&content)?
+    };
+
+    let mut metadata = Metadata::default();
+    for (key, value) in entries {
+        match key.to_lowercase().as_str() {
+            "title" => metadata.title = Some(value.into_text()),
+            "author" | "authors" => metadata.authors.extend(match value {
+                Value::Scalar(author) => vec![author],
+                Value::List(authors) => authors,
+            }),
+            "tags" | "keywords" => metadata.tags.extend(value.into_list()),
+            "date" => {
+                let text = value.into_text();
+                match parse_date(&text) {
+                    Some(date) => metadata.date = Some(date),
+                    None => metadata.custom.push((key, text)),
+                }
+            }
+            _ => metadata.custom.push((key, value.into_text())),
+        }
+    }
+    Ok(metadata)
+}
+
+fn yaml_entries(content: &str) -> Result<Vec<(String, Value)>, String> {
+    if content.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    match serde_yaml::from_str(content).map_err(|error| error.to_string())? {
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut entries = vec![];
+            add_yaml_entries("", mapping, &mut entries);
+            Ok(entries)
+        }
+        _ => Err("The front matter isn't a map of keys to values.".to_owned()),
+    }
+}
+
+fn add_yaml_entries(
+    prefix: &str,
+    mapping: serde_yaml::Mapping,
+    entries: &mut Vec<(String, Value)>,
+) {
+    for (key, value) in mapping {
+        let key = format!("{}{}", prefix, yaml_scalar(key).unwrap_or_default());
+        match value {
+            serde_yaml::Value::Mapping(mapping) => {
+                add_yaml_entries(&format!("{}.", key), mapping, entries)
+            }
+            serde_yaml::Value::Sequence(items) => entries.push((
+                key,
+                Value::List(items.into_iter().filter_map(yaml_scalar).collect()),
+            )),
+            value => {
+                if let Some(value) = yaml_scalar(value) {
+                    entries.push((key, Value::Scalar(value)));
+                }
+            }
+        }
+    }
+}
+
+fn yaml_scalar(value: serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(text) => Some(text),
+        serde_yaml::Value::Number(number) => Some(number.to_string()),
+        serde_yaml::Value::Bool(bool) => Some(bool.to_string()),
+        _ => None,
+    }
+}
+
+fn toml_entries(content: &str) -> Result<Vec<(String, Value)>, String> {
+    match content
+        .parse()
+        .map_err(|error: toml::de::Error| error.to_string())?
+    {
+        toml::Value::Table(table) => {
+            let mut entries = vec![];
+            add_toml_entries("", table, &mut entries);
+            Ok(entries)
+        }
+        _ => Err("The front matter isn't a map of keys to values.".to_owned()),
+    }
+}
+
+fn add_toml_entries(prefix: &str, table: toml::value::Table, entries: &mut Vec<(String, Value)>) {
+    for (key, value) in table {
+        let key = format!("{}{}", prefix, key);
+        match value {
+            toml::Value::Table(table) => add_toml_entries(&format!("{}.", key), table, entries),
+            toml::Value::Array(items) => entries.push((
+                key,
+                Value::List(items.into_iter().filter_map(toml_scalar).collect()),
+            )),
+            value => {
+                if let Some(value) = toml_scalar(value) {
+                    entries.push((key, Value::Scalar(value)));
+                }
+            }
+        }
+    }
+}
+
+fn toml_scalar(value: toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(text) => Some(text),
+        toml::Value::Integer(number) => Some(number.to_string()),
+        toml::Value::Float(number) => Some(number.to_string()),
+        toml::Value::Boolean(bool) => Some(bool.to_string()),
+        toml::Value::Datetime(datetime) => Some(datetime.to_string()),
+        _ => None,
+    }
+}
+
+/// Parses dates like "2021-03-14" and instants like "2021-03-14T15:09:26Z" or
+/// "2021-03-14 15:09:26+01:00". Instants without an offset are in UTC, like in YAML.
+fn parse_date(text: &str) -> Option<TemporalValue> {
+    let text = text.trim();
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = text.get(range)?;
+        if digits.chars().all(|c| c.is_ascii_digit()) {
+            digits.parse().ok()
+        } else {
+            None
+        }
+    };
+    if text.get(4..5)? != "-" || text.get(7..8)? != "-" {
+        return None;
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let date = TemporalValue::Date {
+        year: year as i32,
+        month: month as u8,
+        day: day as u8,
+    };
+    if !date.is_valid() {
+        return None;
+    }
+    if text.len() == 10 {
+        return Some(date);
+    }
+
+    if !matches!(text.get(10..11)?, "T" | "t" | " ") || text.get(13..14)? != ":" {
+        return None;
+    }
+    let (hour, minute) = (number(11..13)?, number(14..16)?);
+    let mut rest = &text[16..];
+    let mut second = 0;
+    if let Some(seconds) = rest.strip_prefix(':') {
+        second = seconds.get(0..2)?.parse().ok()?;
+        rest = &seconds[2..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            rest = fraction.trim_start_matches(|c: char| c.is_ascii_digit());
+        }
+    }
+    let offset_in_minutes = match rest.trim_start() {
+        "" | "Z" | "z" => 0,
+        offset => {
+            let sign = match offset.get(0..1)? {
+                "+" => 1,
+                "-" => -1,
+                _ => return None,
+            };
+            let (hours, minutes) = match offset[1..].split_once(':') {
+                Some((hours, minutes)) => (hours, minutes),
+                None => (offset.get(1..3)?, offset.get(3..)?),
+            };
+            sign * (hours.parse::<i64>().ok()? * 60 + minutes.parse::<i64>().ok()?)
+        }
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let seconds = civil_to_days(year, month, day) * 86400 + hour * 3600 + minute * 60 + second
+        - offset_in_minutes * 60;
+    Some(TemporalValue::Instant(seconds))
+}
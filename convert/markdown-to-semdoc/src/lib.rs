@@ -3,14 +3,112 @@ use comrak::{
     nodes::{Ast, AstNode, ListType, NodeValue},
     parse_document, Arena, ComrakOptions,
 };
-use semdoc::{Block, Pure, SemDoc};
-use std::cell::RefCell;
+use semdoc::{
+    nest_sections, Block, ChecklistItem, Definition, Heading, Metadata, Pure, SectionItem, SemDoc,
+};
+use std::{cell::RefCell, collections::HashMap};
+
+mod front_matter;
+pub use conversion_warnings::{ConversionWarning, ConversionWarningKind, Formatting, Location};
+use front_matter::*;
 
 pub fn markdown_to_semdoc(markdown: &str) -> SemDoc<Pure> {
+    markdown_to_semdoc_with_warnings(markdown).0
+}
+
+/// Converts Markdown into a SemDoc and reports content that couldn't be converted faithfully.
+pub fn markdown_to_semdoc_with_warnings(markdown: &str) -> (SemDoc<Pure>, Vec<ConversionWarning>) {
     let arena = Arena::new();
-    let root = parse_document(&arena, markdown, &ComrakOptions::default());
+    let mut options = ComrakOptions::default();
+    options.extension.strikethrough = true;
+    options.extension.table = true;
+    options.extension.autolink = true;
+    options.extension.tasklist = true;
+    options.extension.footnotes = true;
+    options.extension.description_lists = true;
+    options.extension.front_matter_delimiter = Some(
+        if markdown.starts_with("+++") {
+            "+++"
+        } else {
+            "---"
+        }
+        .to_owned(),
+    );
+    let root = parse_document(&arena, &hide_escaped_equals(markdown), &options);
+    for node in root.descendants() {
+        reveal_escaped_equals_in_node(node);
+    }
+
+    let mut metadata = Metadata::default();
+    let mut warnings = vec![];
+    for node in root.descendants() {
+        if let NodeValue::FrontMatter(front_matter) = &node.data.borrow().value {
+            match front_matter_to_metadata(&String::from_utf8_lossy(front_matter)) {
+                Ok(front_matter) => metadata = front_matter,
+                Err(error) => warnings.push(ConversionWarning {
+                    location: Location::Line(1),
+                    kind: ConversionWarningKind::InvalidFrontMatter(error),
+                }),
+            }
+        }
+        warnings.extend(node.warning());
+    }
+    let block = reveal_escaped_equals_in_texts(root.to_block());
+    let block = dedupe_anchors(block, &mut HashMap::new());
+    let doc = SemDoc::new(block.link_glossary_terms()).with_metadata(metadata);
+    (doc, warnings)
+}
+
+/// Stands in for escaped equal signs, which comrak would otherwise turn into plain ones before
+/// `==` marks are found. It's a noncharacter, so it never occurs in actual text.
+const ESCAPED_EQUALS: char = '\u{fdd0}';
+
+fn hide_escaped_equals(markdown: &str) -> String {
+    let mut hidden = String::new();
+    let mut num_backslashes = 0;
+    for c in markdown.chars() {
+        if c == '=' && num_backslashes % 2 == 1 {
+            hidden.pop();
+            hidden.push(ESCAPED_EQUALS);
+        } else {
+            hidden.push(c);
+        }
+        num_backslashes = if c == '\\' { num_backslashes + 1 } else { 0 };
+    }
+    hidden
+}
+
+/// Puts escaped equal signs back into everything except texts, which still need to be searched
+/// for `==` marks. Backslashes don't escape anything in code and HTML, so they are restored too.
+fn reveal_escaped_equals_in_node<'a>(node: &'a AstNode<'a>) {
+    let reveal = |bytes: &mut Vec<u8>, replacement: &str| {
+        let text = String::from_utf8_lossy(bytes);
+        if text.contains(ESCAPED_EQUALS) {
+            *bytes = text.replace(ESCAPED_EQUALS, replacement).into_bytes();
+        }
+    };
+    match &mut node.data.borrow_mut().value {
+        NodeValue::Code(literal)
+        | NodeValue::HtmlInline(literal)
+        | NodeValue::FrontMatter(literal) => reveal(literal, "\\="),
+        NodeValue::CodeBlock(code_block) => {
+            reveal(&mut code_block.info, "=");
+            reveal(&mut code_block.literal, "\\=");
+        }
+        NodeValue::HtmlBlock(html) => reveal(&mut html.literal, "\\="),
+        NodeValue::Link(link) | NodeValue::Image(link) => {
+            reveal(&mut link.url, "=");
+            reveal(&mut link.title, "=");
+        }
+        _ => {}
+    }
+}
 
-    SemDoc::new(root.to_block())
+fn reveal_escaped_equals_in_texts(block: Block<Pure>) -> Block<Pure> {
+    match block {
+        Block::Text(text) => Block::Text(text.replace(ESCAPED_EQUALS, "=")),
+        block => block.map_children(reveal_escaped_equals_in_texts),
+    }
 }
 
 trait ToBlock<'a> {
@@ -20,45 +118,303 @@ impl<'a> ToBlock<'a> for AstNode<'a> {
     fn to_block(&'a self) -> Block<Pure> {
         use NodeValue::*;
         match self.data.borrow().value.clone() {
-            Document => Block::Paragraphs(self.children().to_blocks()),
-            Heading(_) => Block::Section {
-                title: Box::new(Block::Paragraphs(self.children().to_blocks())),
-                body: Box::new(Block::Empty),
-            },
-            Paragraph => Block::Flow(self.children().to_blocks()),
-            Text(text) => Block::Text(String::from_utf8(text).unwrap()),
+            Document => Block::Paragraphs(nest_sections(sections(self.children().collect()))),
+            // Front matter becomes the metadata of the document.
+            FrontMatter(_) => Block::Empty,
+            Heading(_) => nest_sections(vec![SectionItem::Heading(heading(self))]).remove(0),
+            Paragraph => trim_inlines(Block::Flow(highlight_marks(self.children().to_blocks()))),
+            Text(text) => citations(String::from_utf8(text).unwrap()),
             SoftBreak => Block::Text(" ".to_owned()),
-            // TODO(marcelgarus): Handle emphasis.
-            Emph => Block::Flow(self.children().to_blocks()),
-            // TODO(marcelgarus): Handle strong text.
-            Strong => Block::Flow(self.children().to_blocks()),
+            LineBreak => Block::Text("\n".to_owned()),
+            // SemDoc leaves it to readers how to stress content. Emphasis and strong text are
+            // in almost every document, so they're flattened without a warning on purpose.
+            Emph | Strong => Block::Flow(highlight_marks(self.children().to_blocks())),
+            Strikethrough | Superscript => Block::Flow(self.children().to_blocks()),
+            // Code spans that only contain line breaks are empty.
+            Code(code) if code.is_empty() => Block::Empty,
+            Code(code) => Block::Code {
+                language: None,
+                code: String::from_utf8(code).unwrap(),
+            },
+            CodeBlock(code_block) => {
+                let info = String::from_utf8(code_block.info).unwrap();
+                let code = String::from_utf8(code_block.literal).unwrap();
+                Block::Code {
+                    language: info.split_whitespace().next().map(|it| it.to_owned()),
+                    code: code.strip_suffix('\n').unwrap_or(&code).to_owned(),
+                }
+            }
             List(list) => {
+                let task_states = self
+                    .children()
+                    .map(|item| item.task_state())
+                    .collect::<Option<Vec<_>>>();
+                if let Some(task_states) = task_states {
+                    return Block::Checklist(
+                        task_states
+                            .into_iter()
+                            .zip(self.children().to_blocks())
+                            .map(|(checked, content)| ChecklistItem { checked, content })
+                            .collect(),
+                    );
+                }
                 let items = self.children().to_blocks();
-                println!("List type is {:?}", list.list_type);
                 match list.list_type {
                     ListType::Bullet => Block::BulletList(items),
-                    ListType::Ordered => Block::OrderedList(items),
+                    ListType::Ordered => Block::OrderedList {
+                        start: list.start as u64,
+                        numbering: None,
+                        items,
+                    },
                 }
             }
             Item(_) => Block::Paragraphs(self.children().to_blocks()),
-            // TODO(marcelgarus): Handle HTML better.
-            HtmlBlock(_) => Block::Empty,
-            ThematicBreak => Block::Empty,
-            // TODO(marcelgarus): Handle links better.
-            Link(link) => Block::Text(String::from_utf8(link.title).unwrap()),
-            _ => {
-                println!("Not handling node {:?} yet.", self);
-                Block::Empty
+            // The state is handled by the list containing the task item.
+            TaskItem(_) => Block::Empty,
+            DescriptionList => Block::DefinitionList(self.to_definitions()),
+            DescriptionItem(_) | DescriptionTerm | DescriptionDetails => {
+                Block::Paragraphs(self.children().to_blocks())
             }
+            Table(_) => {
+                let mut rows = self.children().map(|row| row.children().to_blocks());
+                let header = match self
+                    .first_child()
+                    .map(|row| row.data.borrow().value.clone())
+                {
+                    Some(TableRow(true)) => rows.next().unwrap(),
+                    _ => vec![],
+                };
+                Block::Table {
+                    header,
+                    rows: rows.collect(),
+                }
+            }
+            // Rows and cells are handled by the table containing them.
+            TableRow(_) => Block::Flow(self.children().to_blocks()),
+            TableCell => Block::Flow(highlight_marks(self.children().to_blocks())),
+            // HTML is syntax, so it's dropped unless it's a `<details>` element, which is handled
+            // when converting a sequence of nodes.
+            HtmlBlock(_) | HtmlInline(_) => Block::Empty,
+            BlockQuote => match self.to_callout() {
+                Some(callout) => callout,
+                None => Block::Quote(Box::new(Block::Paragraphs(self.children().to_blocks()))),
+            },
+            ThematicBreak => Block::TopicChange,
+            Link(link) if link.url.starts_with(b"#") => Block::CrossReference {
+                target: String::from_utf8(link.url[1..].to_vec()).unwrap(),
+                content: Box::new(Block::Flow(self.children().to_blocks())),
+            },
+            Link(link) => Block::Link {
+                target: String::from_utf8(link.url).unwrap(),
+                content: Box::new(Block::Flow(self.children().to_blocks())),
+            },
+            // TODO(marcelgarus): Embed images once SemDoc supports them.
+            Image(link) => Block::Link {
+                target: String::from_utf8(link.url).unwrap(),
+                content: Box::new(Block::Flow(self.children().to_blocks())),
+            },
+            FootnoteReference(name) => {
+                let name = String::from_utf8(name).unwrap();
+                Block::CrossReference {
+                    target: footnote_id(&name),
+                    content: Box::new(Block::Text(name)),
+                }
+            }
+            FootnoteDefinition(name) => Block::Anchor {
+                id: footnote_id(&String::from_utf8(name).unwrap()),
+                content: Box::new(Block::Secondary(Box::new(Block::Paragraphs(
+                    self.children().to_blocks(),
+                )))),
+            },
         }
     }
 }
+
+fn footnote_id(name: &str) -> String {
+    format!("footnote-{}", name)
+}
+
+trait Warning<'a> {
+    fn warning(&'a self) -> Option<ConversionWarning>;
+}
+impl<'a> Warning<'a> for AstNode<'a> {
+    /// Reports if this node itself can't be converted faithfully.
+    fn warning(&'a self) -> Option<ConversionWarning> {
+        let kind = match &self.data.borrow().value {
+            NodeValue::HtmlBlock(html) => {
+                let html = String::from_utf8(html.literal.clone()).unwrap();
+                if is_details_html(&html) {
+                    return None;
+                }
+                ConversionWarningKind::DroppedHtml(html)
+            }
+            NodeValue::HtmlInline(html) => {
+                ConversionWarningKind::DroppedHtml(String::from_utf8(html.clone()).unwrap())
+            }
+            NodeValue::Strikethrough => {
+                ConversionWarningKind::DroppedFormatting(Formatting::Strikethrough)
+            }
+            NodeValue::Superscript => {
+                ConversionWarningKind::DroppedFormatting(Formatting::Superscript)
+            }
+            NodeValue::Image(link) => ConversionWarningKind::ImageAsLink {
+                url: String::from_utf8(link.url.clone()).unwrap(),
+            },
+            _ => return None,
+        };
+        // Comrak only knows the lines of block nodes, so inline nodes use the line of the block
+        // containing them.
+        let line = self
+            .ancestors()
+            .map(|node| node.data.borrow().start_line)
+            .find(|line| *line > 0)
+            .unwrap_or(1);
+        Some(ConversionWarning {
+            location: Location::Line(line),
+            kind,
+        })
+    }
+}
+
+/// Turns Pandoc-style citations like `[@knuth1984; @levy]` in the text into `Block::Citation`s.
+fn citations(text: String) -> Block<Pure> {
+    let mut parts = vec![];
+    let mut rest = &text[..];
+    while let Some(start) = rest.find("[@") {
+        let keys = rest[start..].find(']').and_then(|end| {
+            let keys = rest[start + 1..start + end]
+                .split(';')
+                .map(|key| key.trim().strip_prefix('@'))
+                .collect::<Option<Vec<_>>>()?;
+            if keys
+                .iter()
+                .any(|key| key.is_empty() || key.contains(char::is_whitespace))
+            {
+                return None;
+            }
+            Some((end, keys))
+        });
+        let (end, keys) = match keys {
+            Some(citation) => citation,
+            None => {
+                parts.push(Block::Text(rest[..start + 2].to_owned()));
+                rest = &rest[start + 2..];
+                continue;
+            }
+        };
+        parts.push(Block::Text(rest[..start].to_owned()));
+        parts.push(Block::Citation(
+            keys.into_iter().map(|key| key.to_owned()).collect(),
+        ));
+        rest = &rest[start + end + 1..];
+    }
+    if parts.is_empty() {
+        return Block::Text(text);
+    }
+    parts.push(Block::Text(rest.to_owned()));
+    Block::Flow(parts)
+}
+
+/// Turns a heading into the ID GitHub uses for it, so that `[text](#id)` links keep working.
+fn heading_slug(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+/// Makes anchor IDs unique by appending `-1`, `-2`, etc. to repeated ones, like GitHub does.
+fn dedupe_anchors(block: Block<Pure>, seen: &mut HashMap<String, usize>) -> Block<Pure> {
+    match block {
+        Block::Anchor { id, content } => {
+            let count = seen.entry(id.clone()).or_insert(0);
+            let id = if *count == 0 {
+                id
+            } else {
+                format!("{}-{}", id, count)
+            };
+            *count += 1;
+            Block::Anchor {
+                id,
+                content: Box::new(dedupe_anchors(*content, seen)),
+            }
+        }
+        other => other.map_children(|child| dedupe_anchors(child, seen)),
+    }
+}
+
+/// Converts the top-level nodes, keeping the headings apart so the content following them can be
+/// nested into sections.
+fn sections<'a>(nodes: Vec<&'a AstNode<'a>>) -> Vec<SectionItem<Pure>> {
+    let mut items = vec![];
+    let mut content = vec![];
+    for node in nodes {
+        if heading_level(node).is_none() {
+            content.push(node);
+            continue;
+        }
+        items.extend(
+            std::mem::take(&mut content)
+                .to_blocks()
+                .into_iter()
+                .map(SectionItem::Block),
+        );
+        items.push(SectionItem::Heading(heading(node)));
+    }
+    items.extend(content.to_blocks().into_iter().map(SectionItem::Block));
+    items
+}
+
+fn heading_level<'a>(node: &'a AstNode<'a>) -> Option<u32> {
+    match &node.data.borrow().value {
+        NodeValue::Heading(heading) => Some(heading.level),
+        _ => None,
+    }
+}
+
+fn heading<'a>(node: &'a AstNode<'a>) -> Heading<Pure> {
+    // Titles are a single line, so line breaks in setext headings become spaces.
+    let title = node
+        .children()
+        .to_blocks()
+        .into_iter()
+        .map(|block| match block {
+            Block::Text(text) if text == "\n" => Block::Text(" ".to_owned()),
+            block => block,
+        })
+        .collect();
+    let title = trim_inlines(Block::Flow(highlight_marks(title)));
+    let id = heading_slug(&title.plain_text());
+    Heading::new(heading_level(node).unwrap() as usize, title).with_ids(Some(id))
+}
+
 trait ToBlocks {
     fn to_blocks(self) -> Vec<Block<Pure>>;
 }
 impl<'a> ToBlocks for Vec<&'a AstNode<'a>> {
     fn to_blocks(self) -> Vec<Block<Pure>> {
-        self.iter().map(|node| node.to_block()).collect()
+        let mut blocks = vec![];
+        let mut nodes = self.into_iter().peekable();
+        while let Some(node) = nodes.next() {
+            match node.html() {
+                Some(html) if is_opening_details_html(&html) => {
+                    blocks.push(details_to_block(&html, &mut nodes))
+                }
+                _ => match (blocks.last_mut(), node.to_block()) {
+                    // Comrak moves footnote definitions to the end, so definition lists that were
+                    // only separated by one are a single list.
+                    (
+                        Some(Block::DefinitionList(definitions)),
+                        Block::DefinitionList(more_definitions),
+                    ) => definitions.extend(more_definitions),
+                    (_, block) => blocks.push(block),
+                },
+            }
+        }
+        blocks
     }
 }
 impl<'a> ToBlocks for Children<'a, RefCell<Ast>> {
@@ -66,3 +422,746 @@ impl<'a> ToBlocks for Children<'a, RefCell<Ast>> {
         self.collect::<Vec<_>>().clone().to_blocks()
     }
 }
+
+/// Wraps content between `==` marks into `Block::Highlighted`s.
+///
+/// Comrak doesn't support highlighting, so the marks are still part of the texts. Marks without a
+/// closing counterpart are kept as they are. Nested flows are flattened first, so that marks in
+/// texts next to citations are found too.
+fn highlight_marks(inlines: Vec<Block<Pure>>) -> Vec<Block<Pure>> {
+    let inlines = inlines.into_iter().flat_map(|inline| match inline {
+        Block::Flow(children) => children,
+        other => vec![other],
+    });
+    let mut blocks = vec![];
+    let mut highlighted: Option<Vec<Block<Pure>>> = None;
+    for inline in inlines {
+        let text = match inline {
+            Block::Text(text) => text,
+            other => {
+                highlighted.as_mut().unwrap_or(&mut blocks).push(other);
+                continue;
+            }
+        };
+        for (index, part) in text.split("==").enumerate() {
+            if index > 0 {
+                highlighted = match highlighted.take() {
+                    Some(content) => {
+                        blocks.push(Block::Highlighted {
+                            content: Box::new(Block::Flow(content)),
+                            reason: None,
+                        });
+                        None
+                    }
+                    None => Some(vec![]),
+                };
+            }
+            highlighted
+                .as_mut()
+                .unwrap_or(&mut blocks)
+                .push(Block::Text(part.to_owned()));
+        }
+    }
+    if let Some(unclosed) = highlighted {
+        blocks.push(Block::Text("==".to_owned()));
+        blocks.extend(unclosed);
+    }
+    blocks
+}
+
+/// Removes whitespace around inline content, like the spaces before a hard line break or the
+/// ones next to empty highlights.
+fn trim_inlines(block: Block<Pure>) -> Block<Pure> {
+    match block.simplify() {
+        Block::Text(text) => Block::Text(text.trim().to_owned()),
+        Block::Flow(mut children) => {
+            if let Some(Block::Text(text)) = children.first_mut() {
+                *text = text.trim_start().to_owned();
+            }
+            if let Some(Block::Text(text)) = children.last_mut() {
+                *text = text.trim_end().to_owned();
+            }
+            Block::Flow(children).simplify()
+        }
+        block => block,
+    }
+}
+
+trait ToDefinitions<'a> {
+    fn to_definitions(&'a self) -> Vec<Definition<Pure>>;
+}
+impl<'a> ToDefinitions<'a> for AstNode<'a> {
+    /// Converts the items of a description list into definitions.
+    fn to_definitions(&'a self) -> Vec<Definition<Pure>> {
+        self.children()
+            .map(|item| {
+                let mut term = Block::Empty;
+                let mut definitions = vec![];
+                for child in item.children() {
+                    match child.data.borrow().value {
+                        NodeValue::DescriptionTerm => term = child.to_block(),
+                        _ => definitions.push(child.to_block()),
+                    }
+                }
+                Definition { term, definitions }
+            })
+            .collect()
+    }
+}
+
+trait TaskState {
+    fn task_state(&self) -> Option<bool>;
+}
+impl<'a> TaskState for AstNode<'a> {
+    /// If this is a task list item, returns whether it's checked.
+    fn task_state(&self) -> Option<bool> {
+        let paragraph = self.first_child()?;
+        let task_item = paragraph.first_child()?;
+        let value = &task_item.data.borrow().value;
+        match value {
+            NodeValue::TaskItem(checked) => Some(*checked),
+            _ => None,
+        }
+    }
+}
+
+trait Html {
+    fn html(&self) -> Option<String>;
+}
+impl<'a> Html for AstNode<'a> {
+    fn html(&self) -> Option<String> {
+        match &self.data.borrow().value {
+            NodeValue::HtmlBlock(html) => Some(String::from_utf8(html.literal.clone()).unwrap()),
+            _ => None,
+        }
+    }
+}
+
+/// Turns a `<details>` element into a `Block::MoreInfo`.
+///
+/// Unless the whole element is contained in the `opening_html`, the Markdown content between the
+/// opening HTML block and the matching `</details>` becomes the body, so the following `nodes` are
+/// consumed up to there.
+fn details_to_block<'a>(
+    opening_html: &str,
+    nodes: &mut impl Iterator<Item = &'a AstNode<'a>>,
+) -> Block<Pure> {
+    let summary = match (
+        opening_html.find("<summary>"),
+        opening_html.find("</summary>"),
+    ) {
+        (Some(start), Some(end)) if start < end => {
+            strip_html_tags(&opening_html[start..end]).trim().to_owned()
+        }
+        _ => "Details".to_owned(),
+    };
+    let body = if let Some(end) = opening_html.find("</details>") {
+        let start = opening_html
+            .find("</summary>")
+            .map(|index| index + 10)
+            .unwrap_or(0);
+        Block::Text(
+            strip_html_tags(&opening_html[start.min(end)..end])
+                .trim()
+                .to_owned(),
+        )
+    } else {
+        let mut children = vec![];
+        let mut depth = 0;
+        for node in nodes {
+            if let Some(html) = node.html() {
+                if is_opening_details_html(&html) && !html.contains("</details>") {
+                    depth += 1;
+                } else if html.contains("</details>") {
+                    if depth == 0 {
+                        break;
+                    }
+                    depth -= 1;
+                }
+            }
+            children.push(node);
+        }
+        Block::Paragraphs(children.to_blocks())
+    };
+    Block::MoreInfo {
+        summary: Box::new(Block::Text(summary)),
+        body: Box::new(body),
+    }
+}
+
+fn is_opening_details_html(html: &str) -> bool {
+    html.trim_start().starts_with("<details")
+}
+
+/// Whether the HTML is part of a `<details>` element that becomes a `Block::MoreInfo`.
+fn is_details_html(html: &str) -> bool {
+    is_opening_details_html(html) || html.contains("</details>")
+}
+
+fn strip_html_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Callout types whose content is an aside rather than something readers should pay extra
+/// attention to.
+const ASIDE_CALLOUTS: &[&str] = &["note", "tip", "hint", "info", "aside", "example"];
+
+trait ToCallout<'a> {
+    fn to_callout(&'a self) -> Option<Block<Pure>>;
+}
+impl<'a> ToCallout<'a> for AstNode<'a> {
+    /// Converts block quotes using the callout syntax into blocks.
+    ///
+    /// Callouts start with a line like `[!note] Title`. Adding a `-` or `+` after the type (like
+    /// `[!tip]- Troubleshooting`) makes the callout collapsible, so it becomes a
+    /// `Block::MoreInfo`. Other callouts of aside types become `Block::Secondary`.
+    fn to_callout(&'a self) -> Option<Block<Pure>> {
+        let first_paragraph = self.first_child()?;
+        if !matches!(first_paragraph.data.borrow().value, NodeValue::Paragraph) {
+            return None;
+        }
+        let first_text = first_paragraph.first_child()?;
+        let marker_line = match &first_text.data.borrow().value {
+            NodeValue::Text(text) => String::from_utf8(text.clone()).unwrap(),
+            _ => return None,
+        };
+        if !marker_line.starts_with("[!") {
+            return None;
+        }
+        let marker_end = marker_line.find(']')?;
+        let kind = marker_line[2..marker_end].to_lowercase();
+        if kind.is_empty() || !kind.chars().all(|c| c.is_alphanumeric() || c == '-') {
+            return None;
+        }
+        let rest = &marker_line[(marker_end + 1)..];
+        let is_collapsible = rest.starts_with('-') || rest.starts_with('+');
+        let rest = rest.trim_start_matches(['-', '+']).trim_start();
+
+        // The title is the rest of the first line, the body is everything after it.
+        let mut title = vec![citations(rest.to_owned())];
+        let mut first_paragraph_rest = vec![];
+        let mut is_in_title = true;
+        for node in first_paragraph.children().skip(1) {
+            match node.data.borrow().value {
+                NodeValue::SoftBreak | NodeValue::LineBreak if is_in_title => {
+                    is_in_title = false;
+                    continue;
+                }
+                _ => {}
+            }
+            if is_in_title {
+                title.push(node.to_block());
+            } else {
+                first_paragraph_rest.push(node);
+            }
+        }
+        let title = match trim_inlines(Block::Flow(highlight_marks(title))) {
+            Block::Empty => {
+                let mut chars = kind.chars();
+                Block::Text(match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => "Note".to_owned(),
+                })
+            }
+            title => title,
+        };
+        let mut body = vec![Block::Flow(highlight_marks(
+            first_paragraph_rest.to_blocks(),
+        ))];
+        body.extend(self.children().skip(1).collect::<Vec<_>>().to_blocks());
+        let body = Block::Paragraphs(body);
+
+        Some(if is_collapsible {
+            Block::MoreInfo {
+                summary: Box::new(title),
+                body: Box::new(body),
+            }
+        } else {
+            let section = Block::Section {
+                title: Box::new(title),
+                body: Box::new(body),
+            };
+            if ASIDE_CALLOUTS.contains(&kind.as_str()) {
+                Block::Secondary(Box::new(section))
+            } else {
+                section
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use semdoc::{Block::*, TemporalValue};
+
+    fn convert(markdown: &str) -> Block<Pure> {
+        markdown_to_semdoc(markdown).block.simplify()
+    }
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    #[test]
+    fn task_lists_become_checklists() {
+        assert_eq!(
+            convert("- [ ] Tag the release\n- [x] Update the changelog\n"),
+            Checklist(vec![
+                ChecklistItem {
+                    checked: false,
+                    content: text("Tag the release"),
+                },
+                ChecklistItem {
+                    checked: true,
+                    content: text("Update the changelog"),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn partial_task_lists_stay_bullet_lists() {
+        assert_eq!(
+            convert("- [x] Done\n- Not a task\n"),
+            BulletList(vec![text("Done"), text("Not a task")])
+        );
+    }
+
+    #[test]
+    fn description_lists_become_definition_lists() {
+        assert_eq!(
+            convert("Apple\n\n: A fruit.\n"),
+            DefinitionList(vec![Definition {
+                term: text("Apple"),
+                definitions: vec![text("A fruit.")],
+            }])
+        );
+    }
+
+    #[test]
+    fn glossaries_are_linked() {
+        assert_eq!(
+            convert("Every reader is welcome.\n\n# Glossary\n\nReader\n\n: Someone reading.\n"),
+            Paragraphs(vec![
+                Flow(vec![
+                    text("Every "),
+                    TermUsage {
+                        term: "reader".to_owned(),
+                        content: Box::new(text("reader")),
+                    },
+                    text(" is welcome."),
+                ]),
+                Anchor {
+                    id: "glossary".to_owned(),
+                    content: Box::new(Section {
+                        title: Box::new(text("Glossary")),
+                        body: Box::new(Glossary(vec![Definition {
+                            term: text("Reader"),
+                            definitions: vec![text("Someone reading.")],
+                        }])),
+                    }),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn ordered_lists_keep_their_start() {
+        assert_eq!(
+            convert("5. Fifth\n6. Sixth\n"),
+            OrderedList {
+                start: 5,
+                numbering: None,
+                items: vec![text("Fifth"), text("Sixth")],
+            }
+        );
+    }
+
+    #[test]
+    fn marks_become_highlights() {
+        assert_eq!(
+            convert("This is ==really *very*== important."),
+            Flow(vec![
+                text("This is "),
+                Highlighted {
+                    content: Box::new(text("really very")),
+                    reason: None,
+                },
+                text(" important."),
+            ])
+        );
+    }
+
+    #[test]
+    fn unclosed_marks_stay_text() {
+        assert_eq!(convert("Is a == b?"), text("Is a == b?"));
+    }
+
+    #[test]
+    fn details_become_more_info() {
+        assert_eq!(
+            convert("<details>\n<summary>Troubleshooting</summary>\n\nRestart it.\n\n</details>\n"),
+            MoreInfo {
+                summary: Box::new(text("Troubleshooting")),
+                body: Box::new(text("Restart it.")),
+            }
+        );
+    }
+
+    #[test]
+    fn nested_details_are_grouped() {
+        assert_eq!(
+            convert(
+                "<details>\n<summary>Outer</summary>\n\n<details>\n<summary>Inner</summary>\n\n\
+                 Deep.\n\n</details>\n\nShallow.\n\n</details>\n\nAfter.\n"
+            ),
+            Paragraphs(vec![
+                MoreInfo {
+                    summary: Box::new(text("Outer")),
+                    body: Box::new(Paragraphs(vec![
+                        MoreInfo {
+                            summary: Box::new(text("Inner")),
+                            body: Box::new(text("Deep.")),
+                        },
+                        text("Shallow."),
+                    ])),
+                },
+                text("After."),
+            ])
+        );
+    }
+
+    #[test]
+    fn inline_details_become_more_info() {
+        assert_eq!(
+            convert("<details><summary>Why?</summary>Because.</details>\n"),
+            MoreInfo {
+                summary: Box::new(text("Why?")),
+                body: Box::new(text("Because.")),
+            }
+        );
+    }
+
+    #[test]
+    fn collapsible_callouts_become_more_info() {
+        assert_eq!(
+            convert("> [!tip]- Trouble *shooting*\n> Restart it.\n"),
+            MoreInfo {
+                summary: Box::new(text("Trouble shooting")),
+                body: Box::new(text("Restart it.")),
+            }
+        );
+    }
+
+    #[test]
+    fn aside_callouts_become_secondary() {
+        assert_eq!(
+            convert("> [!note]\n> By the way.\n"),
+            Secondary(Box::new(Section {
+                title: Box::new(text("Note")),
+                body: Box::new(text("By the way.")),
+            }))
+        );
+    }
+
+    fn warnings(markdown: &str) -> Vec<ConversionWarning> {
+        markdown_to_semdoc_with_warnings(markdown).1
+    }
+
+    #[test]
+    fn dropped_content_is_reported() {
+        assert_eq!(
+            warnings("Fine.\n\n<div>Boxed</div>\n\nSome ~~old~~ <b>bold</b> ![logo](logo.png)\n"),
+            vec![
+                ConversionWarning {
+                    location: Location::Line(3),
+                    kind: ConversionWarningKind::DroppedHtml("<div>Boxed</div>\n".to_owned()),
+                },
+                ConversionWarning {
+                    location: Location::Line(5),
+                    kind: ConversionWarningKind::DroppedFormatting(Formatting::Strikethrough),
+                },
+                ConversionWarning {
+                    location: Location::Line(5),
+                    kind: ConversionWarningKind::DroppedHtml("<b>".to_owned()),
+                },
+                ConversionWarning {
+                    location: Location::Line(5),
+                    kind: ConversionWarningKind::DroppedHtml("</b>".to_owned()),
+                },
+                ConversionWarning {
+                    location: Location::Line(5),
+                    kind: ConversionWarningKind::ImageAsLink {
+                        url: "logo.png".to_owned()
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn emphasis_is_not_reported() {
+        assert_eq!(warnings("Some *stressed* and **strong** words.\n"), vec![]);
+    }
+
+    #[test]
+    fn details_are_not_reported() {
+        assert_eq!(
+            warnings("<details>\n<summary>Why?</summary>\n\nBecause.\n\n</details>\n"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn yaml_front_matter_becomes_metadata() {
+        let doc = markdown_to_semdoc(
+            "---\ntitle: Release Notes\nauthors:\n  - Ada\n  - Grace\ndate: 2021-03-14\n\
+             tags: [release, changelog]\nversion: 1.2\nsocial:\n  mastodon: \"@semdoc\"\n---\n\n\
+             Hello!\n",
+        );
+        assert_eq!(
+            doc.metadata,
+            Metadata {
+                title: Some("Release Notes".to_owned()),
+                authors: vec!["Ada".to_owned(), "Grace".to_owned()],
+                date: Some(TemporalValue::Date {
+                    year: 2021,
+                    month: 3,
+                    day: 14,
+                }),
+                tags: vec!["release".to_owned(), "changelog".to_owned()],
+                custom: vec![
+                    ("version".to_owned(), "1.2".to_owned()),
+                    ("social.mastodon".to_owned(), "@semdoc".to_owned()),
+                ],
+            }
+        );
+        assert_eq!(doc.block.simplify(), text("Hello!"));
+    }
+
+    #[test]
+    fn toml_front_matter_becomes_metadata() {
+        let doc = markdown_to_semdoc(
+            "+++\ntitle = \"Launch\"\nauthor = \"Ada\"\ndate = 2021-03-14T15:09:26+01:00\n\
+             keywords = \"rockets, space\"\ndraft = true\n+++\n\nHello!\n",
+        );
+        assert_eq!(
+            doc.metadata,
+            Metadata {
+                title: Some("Launch".to_owned()),
+                authors: vec!["Ada".to_owned()],
+                date: Some(TemporalValue::Instant(1615730966)),
+                tags: vec!["rockets".to_owned(), "space".to_owned()],
+                custom: vec![("draft".to_owned(), "true".to_owned())],
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_dates_stay_custom_entries() {
+        let doc = markdown_to_semdoc("---\ndate: last summer\n---\n");
+        assert_eq!(doc.metadata.date, None);
+        assert_eq!(doc.metadata.custom("date"), Some("last summer"));
+    }
+
+    #[test]
+    fn invalid_front_matter_is_reported() {
+        let (doc, warnings) = markdown_to_semdoc_with_warnings("---\n- just\n- a list\n---\n");
+        assert!(doc.metadata.is_empty());
+        assert_eq!(
+            warnings,
+            vec![ConversionWarning {
+                location: Location::Line(1),
+                kind: ConversionWarningKind::InvalidFrontMatter(
+                    "The front matter isn't a map of keys to values.".to_owned()
+                ),
+            }]
+        );
+    }
+
+    fn section(id: &str, title: &str, body: Block<Pure>) -> Block<Pure> {
+        Block::Anchor {
+            id: id.to_owned(),
+            content: Box::new(Block::Section {
+                title: Box::new(text(title)),
+                body: Box::new(body),
+            }),
+        }
+    }
+
+    #[test]
+    fn headings_get_anchors() {
+        assert_eq!(
+            convert("# Getting Started!\n\n## Setup\n\n## Setup"),
+            section(
+                "getting-started",
+                "Getting Started!",
+                Block::Paragraphs(vec![
+                    section("setup", "Setup", Block::Empty),
+                    section("setup-1", "Setup", Block::Empty),
+                ])
+            )
+        );
+    }
+
+    #[test]
+    fn fragment_links_become_cross_references() {
+        let doc = markdown_to_semdoc("# Setup\n\nSee [the setup](#setup).");
+        assert_eq!(doc.validate_references(), Ok(()));
+        assert_eq!(
+            doc.block.simplify(),
+            section(
+                "setup",
+                "Setup",
+                Block::Flow(vec![
+                    text("See "),
+                    Block::CrossReference {
+                        target: "setup".to_owned(),
+                        content: Box::new(text("the setup")),
+                    },
+                    text("."),
+                ])
+            )
+        );
+    }
+
+    #[test]
+    fn bracketed_keys_become_citations() {
+        assert_eq!(
+            convert("As shown [@knuth1984; @levy], programs are [@ literature] [@]."),
+            Flow(vec![
+                text("As shown "),
+                Citation(vec!["knuth1984".to_owned(), "levy".to_owned()]),
+                text(", programs are [@ literature] [@]."),
+            ])
+        );
+    }
+
+    #[test]
+    fn thematic_breaks_become_topic_changes() {
+        assert_eq!(
+            convert("The night fell.\n\n---\n\nThe sun rose."),
+            Paragraphs(vec![
+                text("The night fell."),
+                TopicChange,
+                text("The sun rose."),
+            ])
+        );
+    }
+
+    #[test]
+    fn content_is_nested_under_headings() {
+        assert_eq!(
+            convert("Intro\n\n# One\n\nA\n\n### Deep\n\nB\n\n## Two\n\nC\n\n# Three"),
+            Paragraphs(vec![
+                text("Intro"),
+                section(
+                    "one",
+                    "One",
+                    Paragraphs(vec![
+                        text("A"),
+                        section("deep", "Deep", text("B")),
+                        section("two", "Two", text("C")),
+                    ])
+                ),
+                section("three", "Three", Empty),
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_code_spans_are_dropped() {
+        assert_eq!(convert("a `\n` b"), text("a  b"));
+    }
+
+    #[test]
+    fn whitespace_around_inlines_is_trimmed() {
+        assert_eq!(convert("Almost done ===="), text("Almost done"));
+        assert_eq!(
+            convert("Release  \nnotes\n==="),
+            section("release-notes", "Release notes", Empty)
+        );
+    }
+
+    #[test]
+    fn definition_lists_around_footnotes_are_merged() {
+        let markdown = "Apple\n\n: A fruit.[^1]\n\n[^1]: Mostly.\n\nPear\n\n: Another fruit.\n";
+        match convert(markdown) {
+            Paragraphs(children) => assert!(matches!(
+                &children[0],
+                DefinitionList(definitions) if definitions.len() == 2
+            )),
+            other => panic!("Expected paragraphs, got {:?}.", other),
+        }
+    }
+
+    #[test]
+    fn marks_next_to_citations_become_highlights() {
+        assert_eq!(
+            convert("==[@knuth1984] agrees=="),
+            Highlighted {
+                content: Box::new(Flow(vec![
+                    Citation(vec!["knuth1984".to_owned()]),
+                    text(" agrees"),
+                ])),
+                reason: None,
+            }
+        );
+    }
+
+    #[test]
+    fn callout_titles_are_converted_like_paragraphs() {
+        assert_eq!(
+            convert("> [!warning] ==Hot== surface [@iso13732]\n> Don't touch it.\n"),
+            Section {
+                title: Box::new(Flow(vec![
+                    Highlighted {
+                        content: Box::new(text("Hot")),
+                        reason: None,
+                    },
+                    text(" surface "),
+                    Citation(vec!["iso13732".to_owned()]),
+                ])),
+                body: Box::new(text("Don't touch it.")),
+            }
+        );
+    }
+
+    #[test]
+    fn markers_with_spaces_are_no_callouts() {
+        assert_eq!(
+            convert("> [!not a kind] Hi\n"),
+            Quote(Box::new(text("[!not a kind] Hi")))
+        );
+    }
+
+    #[test]
+    fn escaped_marks_stay_text() {
+        assert_eq!(convert("a\\=\\=b\\=\\=c"), text("a==b==c"));
+        assert_eq!(
+            convert("`a\\=\\=b` \\\\==c=="),
+            Flow(vec![
+                Code {
+                    language: None,
+                    code: "a\\=\\=b".to_owned(),
+                },
+                text(" \\"),
+                Highlighted {
+                    content: Box::new(text("c")),
+                    reason: None,
+                },
+            ])
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! Examples from the CommonMark spec (version 0.29) and the GitHub Flavored Markdown spec, grouped
+//! by the spec section they come from, along with the blocks they should turn into.
+
+use markdown_to_semdoc::markdown_to_semdoc;
+use semdoc::{Block, Block::*, Pure};
+
+fn convert(markdown: &str) -> Block<Pure> {
+    markdown_to_semdoc(markdown).block.simplify()
+}
+
+fn text(text: &str) -> Block<Pure> {
+    Text(text.to_owned())
+}
+
+fn section(id: &str, title: &str, body: Block<Pure>) -> Block<Pure> {
+    Anchor {
+        id: id.to_owned(),
+        content: Box::new(Section {
+            title: Box::new(text(title)),
+            body: Box::new(body),
+        }),
+    }
+}
+
+fn link(target: &str, content: Block<Pure>) -> Block<Pure> {
+    Link {
+        target: target.to_owned(),
+        content: Box::new(content),
+    }
+}
+
+fn code(language: Option<&str>, code: &str) -> Block<Pure> {
+    Code {
+        language: language.map(|it| it.to_owned()),
+        code: code.to_owned(),
+    }
+}
+
+#[test]
+fn thematic_breaks() {
+    assert_eq!(
+        convert("Foo\n***\nbar\n"),
+        Paragraphs(vec![text("Foo"), TopicChange, text("bar")])
+    );
+}
+
+#[test]
+fn atx_headings() {
+    assert_eq!(
+        convert("# foo\n## foo\n### foo\n"),
+        section(
+            "foo",
+            "foo",
+            section("foo-1", "foo", section("foo-2", "foo", Empty))
+        )
+    );
+}
+
+#[test]
+fn setext_headings() {
+    assert_eq!(
+        convert("Foo *bar*\n=========\n"),
+        section("foo-bar", "Foo bar", Empty)
+    );
+}
+
+#[test]
+fn indented_code_blocks() {
+    assert_eq!(
+        convert("    a simple\n      indented code block\n"),
+        code(None, "a simple\n  indented code block")
+    );
+}
+
+#[test]
+fn fenced_code_blocks() {
+    assert_eq!(
+        convert("```ruby\ndef foo(x)\n  return 3\nend\n```\n"),
+        code(Some("ruby"), "def foo(x)\n  return 3\nend")
+    );
+}
+
+#[test]
+fn fenced_code_blocks_use_the_first_word_of_the_info_string() {
+    assert_eq!(
+        convert("~~~~    ruby startline=3 $%@#$\ndef foo(x)\nend\n~~~~~~~\n"),
+        code(Some("ruby"), "def foo(x)\nend")
+    );
+}
+
+#[test]
+fn html_blocks() {
+    assert_eq!(convert("<!-- foo -->\n\nbar\n"), text("bar"));
+}
+
+#[test]
+fn paragraphs() {
+    assert_eq!(
+        convert("aaa\n\nbbb\n"),
+        Paragraphs(vec![text("aaa"), text("bbb")])
+    );
+}
+
+#[test]
+fn block_quotes() {
+    assert_eq!(
+        convert("> # Foo\n> bar\n> baz\n"),
+        Quote(Box::new(Paragraphs(vec![
+            section("foo", "Foo", Empty),
+            text("bar baz"),
+        ])))
+    );
+}
+
+#[test]
+fn list_items() {
+    assert_eq!(
+        convert("123456789. ok\n"),
+        OrderedList {
+            start: 123456789,
+            numbering: None,
+            items: vec![text("ok")],
+        }
+    );
+}
+
+#[test]
+fn lists() {
+    assert_eq!(
+        convert("- foo\n- bar\n+ baz\n"),
+        Paragraphs(vec![
+            BulletList(vec![text("foo"), text("bar")]),
+            BulletList(vec![text("baz")]),
+        ])
+    );
+}
+
+#[test]
+fn backslash_escapes() {
+    assert_eq!(convert("\\*not emphasized*\n"), text("*not emphasized*"));
+}
+
+#[test]
+fn entity_and_numeric_character_references() {
+    assert_eq!(convert("&copy; &#35; &amp;\n"), text("© # &"));
+}
+
+#[test]
+fn code_spans() {
+    assert_eq!(
+        convert("Use `foo` here.\n"),
+        Flow(vec![text("Use "), code(None, "foo"), text(" here.")])
+    );
+}
+
+#[test]
+fn emphasis_and_strong_emphasis() {
+    assert_eq!(convert("*foo **bar** baz*\n"), text("foo bar baz"));
+}
+
+#[test]
+fn links() {
+    assert_eq!(
+        convert("[link](/uri \"title\")\n"),
+        link("/uri", text("link"))
+    );
+}
+
+#[test]
+fn reference_links() {
+    assert_eq!(
+        convert("[foo][bar]\n\n[bar]: /url \"title\"\n"),
+        link("/url", text("foo"))
+    );
+}
+
+#[test]
+fn images() {
+    assert_eq!(
+        convert("![foo](/url \"title\")\n"),
+        link("/url", text("foo"))
+    );
+}
+
+#[test]
+fn autolinks() {
+    assert_eq!(
+        convert("<http://foo.bar.baz>\n"),
+        link("http://foo.bar.baz", text("http://foo.bar.baz"))
+    );
+}
+
+#[test]
+fn raw_html() {
+    assert_eq!(convert("<a><bab><c2c>\n"), Empty);
+}
+
+#[test]
+fn hard_line_breaks() {
+    assert_eq!(convert("foo  \nbaz\n"), text("foo\nbaz"));
+    assert_eq!(convert("foo\\\nbaz\n"), text("foo\nbaz"));
+}
+
+#[test]
+fn soft_line_breaks() {
+    assert_eq!(convert("foo\nbaz\n"), text("foo baz"));
+}
+
+#[test]
+fn gfm_tables() {
+    assert_eq!(
+        convert("| foo | bar |\n| --- | --- |\n| baz | bim |\n"),
+        Table {
+            header: vec![text("foo"), text("bar")],
+            rows: vec![vec![text("baz"), text("bim")]],
+        }
+    );
+}
+
+#[test]
+fn gfm_tables_keep_empty_cells() {
+    assert_eq!(
+        convert("| abc | def |\n| --- | --- |\n| bar |\n"),
+        Table {
+            header: vec![text("abc"), text("def")],
+            rows: vec![vec![text("bar"), Empty]],
+        }
+    );
+}
+
+#[test]
+fn gfm_task_list_items() {
+    assert_eq!(
+        convert("- [ ] foo\n- [x] bar\n"),
+        Checklist(vec![
+            semdoc::ChecklistItem {
+                checked: false,
+                content: text("foo"),
+            },
+            semdoc::ChecklistItem {
+                checked: true,
+                content: text("bar"),
+            },
+        ])
+    );
+}
+
+#[test]
+fn gfm_strikethrough() {
+    assert_eq!(convert("~~Hi~~ Hello, world!\n"), text("Hi Hello, world!"));
+}
+
+#[test]
+fn gfm_autolinks() {
+    assert_eq!(
+        convert("Visit www.commonmark.org/help for more information.\n"),
+        Flow(vec![
+            text("Visit "),
+            link(
+                "http://www.commonmark.org/help",
+                text("www.commonmark.org/help")
+            ),
+            text(" for more information."),
+        ])
+    );
+}
+
+#[test]
+fn gfm_footnotes() {
+    assert_eq!(
+        convert("Here is a footnote reference.[^note]\n\n[^note]: Here is the footnote.\n"),
+        Paragraphs(vec![
+            Flow(vec![
+                text("Here is a footnote reference."),
+                CrossReference {
+                    target: "footnote-1".to_owned(),
+                    content: Box::new(text("1")),
+                },
+            ]),
+            Anchor {
+                id: "footnote-1".to_owned(),
+                content: Box::new(Secondary(Box::new(text("Here is the footnote.")))),
+            },
+        ])
+    );
+}
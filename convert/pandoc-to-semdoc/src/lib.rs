@@ -0,0 +1,578 @@
+use semdoc::{
+    nest_sections, normalize_term, BibliographyEntry, Block, ChecklistItem, Definition, Heading,
+    Pure, SectionItem, SemDoc,
+};
+use serde_json::Value;
+
+mod metadata;
+pub use conversion_warnings::{ConversionWarning, ConversionWarningKind, Formatting, Location};
+use metadata::*;
+
+#[derive(Debug)]
+pub enum PandocError {
+    /// The input isn't valid JSON.
+    InvalidJson(String),
+    /// The JSON isn't a Pandoc document, which is an object with the API version, the metadata
+    /// and the blocks.
+    NotADocument,
+    /// The document uses a version of Pandoc's AST with a different structure, like the one
+    /// before version 1.21 that had simpler tables.
+    UnsupportedVersion(String),
+}
+
+pub fn pandoc_to_semdoc(json: &str) -> Result<SemDoc<Pure>, PandocError> {
+    Ok(pandoc_to_semdoc_with_warnings(json)?.0)
+}
+
+/// Converts a document in Pandoc's JSON format, like the output of `pandoc -t json`, and reports
+/// content that couldn't be converted faithfully.
+///
+/// Headers start sections that go until the next header of the same or a higher level. The
+/// metadata fields `title`, `author`, `date` and `keywords` become the metadata of the document,
+/// other textual fields become custom metadata. The works in the `references` field become a
+/// bibliography, which is placed in the div with the ID `refs` like Pandoc's citeproc does, or at
+/// the end of the document.
+pub fn pandoc_to_semdoc_with_warnings(
+    json: &str,
+) -> Result<(SemDoc<Pure>, Vec<ConversionWarning>), PandocError> {
+    let document: Value =
+        serde_json::from_str(json).map_err(|error| PandocError::InvalidJson(error.to_string()))?;
+    let version = list(&document["pandoc-api-version"])
+        .iter()
+        .map(|part| part.as_u64().unwrap_or_default())
+        .collect::<Vec<_>>();
+    let blocks = document["blocks"]
+        .as_array()
+        .filter(|_| !version.is_empty())
+        .ok_or(PandocError::NotADocument)?;
+    if version.len() < 2 || version[0] != 1 || version[1] < 21 {
+        let version = version.iter().map(u64::to_string).collect::<Vec<_>>();
+        return Err(PandocError::UnsupportedVersion(version.join(".")));
+    }
+
+    let meta = &document["meta"];
+    let mut converter = Converter {
+        bibliography: references(&meta["references"]),
+        ..Default::default()
+    };
+    let mut body = converter.blocks(blocks);
+    if !converter.bibliography.is_empty() {
+        body.push(Block::Bibliography(converter.bibliography));
+    }
+    body.extend(converter.footnotes);
+    let block = Block::Paragraphs(body).link_glossary_terms();
+    let doc = SemDoc::new(block).with_metadata(metadata(meta));
+    Ok((doc, converter.warnings))
+}
+
+#[derive(Default)]
+struct Converter {
+    warnings: Vec<ConversionWarning>,
+    /// The works from the metadata, until they are placed in the document.
+    bibliography: Vec<BibliographyEntry>,
+    footnotes: Vec<Block<Pure>>,
+}
+
+/// Admonitions whose content is an aside rather than something readers should pay extra
+/// attention to.
+const ASIDE_ADMONITIONS: &[&str] = &["note", "tip"];
+
+/// The kinds of alerts of Pandoc's `alerts` extension, which are the same as GitHub's.
+const ADMONITIONS: &[&str] = &["note", "tip", "important", "warning", "caution"];
+
+impl Converter {
+    fn blocks(&mut self, values: &[Value]) -> Vec<Block<Pure>> {
+        let items = values
+            .iter()
+            .filter_map(|value| self.item(value))
+            .collect::<Vec<_>>();
+        nest_sections(items)
+    }
+
+    /// Converts blocks into a single one, like the content of a list item or a table cell.
+    fn item_block(&mut self, values: &[Value]) -> Block<Pure> {
+        let mut blocks = self.blocks(values);
+        match blocks.len() {
+            0 => Block::Empty,
+            1 => blocks.remove(0),
+            _ => Block::Paragraphs(blocks),
+        }
+    }
+
+    /// Converts a block. Headers are kept apart, so the following blocks can be nested into their
+    /// sections.
+    fn item(&mut self, value: &Value) -> Option<SectionItem<Pure>> {
+        let (kind, content) = element(value);
+        Some(SectionItem::Block(match kind {
+            "Plain" | "Para" => self.inlines(list(content)),
+            "LineBlock" => {
+                let mut parts = vec![];
+                for (index, line) in list(content).iter().enumerate() {
+                    if index > 0 {
+                        push_text(&mut parts, "\n");
+                    }
+                    self.inlines_into(list(line), &mut parts);
+                }
+                flow(parts)
+            }
+            "CodeBlock" => {
+                let attr = Attr::from(&content[0]);
+                let code = Block::Code {
+                    language: attr.language(),
+                    code: string(&content[1]).to_owned(),
+                };
+                anchored(&attr.id, code)
+            }
+            "RawBlock" => {
+                self.drop_raw(&content[0]);
+                return None;
+            }
+            "BlockQuote" => Block::Quote(Box::new(self.item_block(list(content)))),
+            "OrderedList" => Block::OrderedList {
+                start: content[0][0].as_u64().unwrap_or(1),
+                numbering: None,
+                items: list(&content[1])
+                    .iter()
+                    .map(|item| self.item_block(list(item)))
+                    .collect(),
+            },
+            "BulletList" => self.bullet_list(list(content)),
+            "DefinitionList" => Block::DefinitionList(self.definitions(list(content))),
+            "Header" => {
+                let level = content[0].as_u64().unwrap_or(1) as usize;
+                let id = Some(Attr::from(&content[1]).id).filter(|id| !id.is_empty());
+                let title = self.inlines(list(&content[2]));
+                return Some(SectionItem::Heading(
+                    Heading::new(level, title).with_ids(id),
+                ));
+            }
+            "HorizontalRule" => Block::TopicChange,
+            "Table" => self.table(content),
+            "Figure" => {
+                let attr = Attr::from(&content[0]);
+                let figure = self.item_block(list(&content[2]));
+                anchored(&attr.id, self.captioned(&content[1], figure))
+            }
+            "Div" => self.div(content),
+            kind => {
+                self.warn(ConversionWarningKind::UnknownElement(kind.to_owned()));
+                return None;
+            }
+        }))
+    }
+
+    fn bullet_list(&mut self, items: &[Value]) -> Block<Pure> {
+        let checks = items
+            .iter()
+            .map(|item| check_box(list(item)))
+            .collect::<Option<Vec<_>>>();
+        match checks {
+            Some(checks) if !items.is_empty() => Block::Checklist(
+                items
+                    .iter()
+                    .zip(checks)
+                    .map(|(item, checked)| ChecklistItem {
+                        checked,
+                        content: self.item_block(&without_check_box(list(item))),
+                    })
+                    .collect(),
+            ),
+            _ => Block::BulletList(
+                items
+                    .iter()
+                    .map(|item| self.item_block(list(item)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn definitions(&mut self, values: &[Value]) -> Vec<Definition<Pure>> {
+        values
+            .iter()
+            .map(|definition| Definition {
+                term: self.inlines(list(&definition[0])),
+                definitions: list(&definition[1])
+                    .iter()
+                    .map(|details| self.item_block(list(details)))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Converts a table, which consists of its attributes, caption, column specifications, head,
+    /// bodies and foot. Cells spanning multiple columns are followed by empty cells and cells
+    /// spanning multiple rows aren't supported.
+    fn table(&mut self, content: &Value) -> Block<Pure> {
+        let attr = Attr::from(&content[0]);
+        let mut head = list(&content[3][1])
+            .iter()
+            .map(|row| self.row(row))
+            .collect::<Vec<_>>()
+            .into_iter();
+        let header = head.next().unwrap_or_default();
+        let mut rows = head.collect::<Vec<_>>();
+        for body in list(&content[4]) {
+            // Bodies consist of their attributes, the number of row head columns, intermediate
+            // head rows and the actual rows.
+            for row in list(&body[2]).iter().chain(list(&body[3])) {
+                rows.push(self.row(row));
+            }
+        }
+        for row in list(&content[5][1]) {
+            rows.push(self.row(row));
+        }
+        let table = Block::Table { header, rows };
+        anchored(&attr.id, self.captioned(&content[1], table))
+    }
+
+    fn row(&mut self, row: &Value) -> Vec<Block<Pure>> {
+        let mut cells = vec![];
+        for cell in list(&row[1]) {
+            cells.push(self.item_block(list(&cell[4])));
+            let span = cell[3].as_u64().unwrap_or(1);
+            cells.extend((1..span).map(|_| Block::Empty));
+        }
+        cells
+    }
+
+    /// Puts a table or figure into a section titled with its caption, which consists of an
+    /// optional short caption and the actual caption.
+    fn captioned(&mut self, caption: &Value, block: Block<Pure>) -> Block<Pure> {
+        match self.item_block(list(&caption[1])) {
+            Block::Empty => block,
+            title => Block::Section {
+                title: Box::new(title),
+                body: Box::new(block),
+            },
+        }
+    }
+
+    fn div(&mut self, content: &Value) -> Block<Pure> {
+        let attr = Attr::from(&content[0]);
+        let children = list(&content[1]);
+        if attr.id == "refs" && !self.bibliography.is_empty() {
+            return Block::Bibliography(std::mem::take(&mut self.bibliography));
+        }
+        // Alerts and divs like `<details>` have their title in a nested div with the `title`
+        // class.
+        let (title, body) = match children.split_first() {
+            Some((first, rest)) if Attr::from(&first["c"][0]).has_class("title") => {
+                (Some(self.item_block(list(&first["c"][1]))), rest)
+            }
+            _ => (None, children),
+        };
+        let kind = attr
+            .classes
+            .iter()
+            .find(|class| ADMONITIONS.contains(&class.as_str()));
+        let block = if let Some(kind) = kind {
+            let section = Block::Section {
+                title: Box::new(title.unwrap_or_else(|| Block::Text(capitalize(kind)))),
+                body: Box::new(Block::Paragraphs(self.blocks(body))),
+            };
+            if ASIDE_ADMONITIONS.contains(&kind.as_str()) {
+                Block::Secondary(Box::new(section))
+            } else {
+                section
+            }
+        } else if attr.has_class("details") {
+            Block::MoreInfo {
+                summary: Box::new(title.unwrap_or(Block::Empty)),
+                body: Box::new(self.item_block(body)),
+            }
+        } else if attr.has_class("aside") {
+            Block::Secondary(Box::new(self.item_block(children)))
+        } else if attr.has_class("glossary") {
+            let blocks = self
+                .blocks(children)
+                .into_iter()
+                .map(|block| match block {
+                    Block::DefinitionList(definitions) => Block::Glossary(definitions),
+                    block => block,
+                })
+                .collect();
+            Block::Paragraphs(blocks)
+        } else {
+            self.item_block(children)
+        };
+        anchored(&attr.id, block)
+    }
+
+    fn inlines(&mut self, values: &[Value]) -> Block<Pure> {
+        let mut parts = vec![];
+        self.inlines_into(values, &mut parts);
+        flow(parts)
+    }
+
+    /// Converts inlines, merging neighboring texts like the words and spaces of a sentence.
+    fn inlines_into(&mut self, values: &[Value], parts: &mut Vec<Block<Pure>>) {
+        for value in values {
+            let (kind, content) = element(value);
+            match kind {
+                "Str" => push_text(parts, string(content)),
+                "Space" | "SoftBreak" => push_text(parts, " "),
+                "LineBreak" => push_text(parts, "\n"),
+                // Like in Markdown, stressed text is flattened without a warning on purpose,
+                // because SemDoc leaves it to readers how to stress content.
+                "Emph" | "Strong" | "SmallCaps" => self.inlines_into(list(content), parts),
+                "Underline" | "Strikeout" | "Superscript" | "Subscript" => {
+                    let formatting = match kind {
+                        "Underline" => Formatting::Underline,
+                        "Strikeout" => Formatting::Strikethrough,
+                        "Superscript" => Formatting::Superscript,
+                        _ => Formatting::Subscript,
+                    };
+                    self.warn(ConversionWarningKind::DroppedFormatting(formatting));
+                    self.inlines_into(list(content), parts);
+                }
+                "Quoted" => {
+                    let (open, close) = match element(&content[0]).0 {
+                        "SingleQuote" => ("‘", "’"),
+                        _ => ("“", "”"),
+                    };
+                    push_text(parts, open);
+                    self.inlines_into(list(&content[1]), parts);
+                    push_text(parts, close);
+                }
+                "Cite" => parts.push(Block::Citation(
+                    list(&content[0])
+                        .iter()
+                        .map(|citation| string(&citation["citationId"]).to_owned())
+                        .collect(),
+                )),
+                "Code" => parts.push(Block::Code {
+                    language: Attr::from(&content[0]).language(),
+                    code: string(&content[1]).to_owned(),
+                }),
+                "Math" => parts.push(Block::Code {
+                    language: Some("latex".to_owned()),
+                    code: string(&content[1]).to_owned(),
+                }),
+                "RawInline" => self.drop_raw(&content[0]),
+                "Link" => {
+                    let target = string(&content[2][0]).to_owned();
+                    let content = Box::new(self.inlines(list(&content[1])));
+                    parts.push(match target.strip_prefix('#') {
+                        Some(id) => Block::CrossReference {
+                            target: id.to_owned(),
+                            content,
+                        },
+                        None => Block::Link { target, content },
+                    });
+                }
+                "Image" => {
+                    let url = string(&content[2][0]).to_owned();
+                    self.warn(ConversionWarningKind::ImageAsLink { url: url.clone() });
+                    parts.push(Block::Link {
+                        target: url,
+                        content: Box::new(self.inlines(list(&content[1]))),
+                    });
+                }
+                "Note" => {
+                    // Footnotes inside this one get their numbers first.
+                    let body = self.item_block(list(content));
+                    let number = self.footnotes.len() + 1;
+                    let id = format!("footnote-{}", number);
+                    self.footnotes.push(Block::Anchor {
+                        id: id.clone(),
+                        content: Box::new(Block::Secondary(Box::new(body))),
+                    });
+                    parts.push(Block::CrossReference {
+                        target: id,
+                        content: Box::new(Block::Text(number.to_string())),
+                    });
+                }
+                "Span" => {
+                    let attr = Attr::from(&content[0]);
+                    let inlines = list(&content[1]);
+                    let block = if attr.has_class("term") {
+                        let content = self.inlines(inlines);
+                        Block::TermUsage {
+                            term: match attr.attribute("term") {
+                                Some(term) => term.to_owned(),
+                                None => normalize_term(&content.plain_text()),
+                            },
+                            content: Box::new(content),
+                        }
+                    } else if attr.has_class("mark") {
+                        Block::Highlighted {
+                            content: Box::new(self.inlines(inlines)),
+                            reason: None,
+                        }
+                    } else if attr.id.is_empty() {
+                        self.inlines_into(inlines, parts);
+                        continue;
+                    } else {
+                        self.inlines(inlines)
+                    };
+                    parts.push(anchored(&attr.id, block));
+                }
+                kind => self.warn(ConversionWarningKind::UnknownElement(kind.to_owned())),
+            }
+        }
+    }
+
+    fn drop_raw(&mut self, format: &Value) {
+        self.warn(ConversionWarningKind::DroppedRaw {
+            format: string(format).to_owned(),
+        });
+    }
+
+    /// Pandoc documents don't know the lines of their sources, so warnings have no location.
+    fn warn(&mut self, kind: ConversionWarningKind) {
+        self.warnings.push(ConversionWarning {
+            location: Location::Unknown,
+            kind,
+        });
+    }
+}
+
+/// The identifier, classes and key-value pairs of an element.
+struct Attr {
+    id: String,
+    classes: Vec<String>,
+    attributes: Vec<(String, String)>,
+}
+impl Attr {
+    fn from(value: &Value) -> Attr {
+        Attr {
+            id: string(&value[0]).to_owned(),
+            classes: list(&value[1])
+                .iter()
+                .map(|class| string(class).to_owned())
+                .collect(),
+            attributes: list(&value[2])
+                .iter()
+                .map(|pair| (string(&pair[0]).to_owned(), string(&pair[1]).to_owned()))
+                .collect(),
+        }
+    }
+
+    fn has_class(&self, class: &str) -> bool {
+        self.classes.iter().any(|it| it == class)
+    }
+
+    fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(it, _)| it == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Code has its language as the first class, like `python`. Other classes are about how to
+    /// display the code.
+    fn language(&self) -> Option<String> {
+        self.classes
+            .iter()
+            .find(|class| !matches!(class.as_str(), "sourceCode" | "numberLines"))
+            .cloned()
+    }
+}
+
+/// Splits an element like `{"t": "Para", "c": [...]}` into its type and content. Elements
+/// without content, like `{"t": "Space"}`, have `null` as their content.
+fn element(value: &Value) -> (&str, &Value) {
+    (string(&value["t"]), &value["c"])
+}
+
+fn list(value: &Value) -> &[Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+fn string(value: &Value) -> &str {
+    value.as_str().unwrap_or_default()
+}
+
+/// Whether a list item of Pandoc's `task_lists` extension is checked, which start with a ballot
+/// box like `☐ Buy milk`. Returns `None` for other items.
+fn check_box(item: &[Value]) -> Option<bool> {
+    let (kind, inlines) = element(item.first()?);
+    if kind != "Plain" && kind != "Para" {
+        return None;
+    }
+    match element(&inlines[0]) {
+        ("Str", text) if text == "☐" => Some(false),
+        ("Str", text) if text == "☒" => Some(true),
+        _ => None,
+    }
+}
+
+fn without_check_box(item: &[Value]) -> Vec<Value> {
+    let mut item = item.to_vec();
+    if let Some(inlines) = item[0].get_mut("c").and_then(Value::as_array_mut) {
+        inlines.remove(0);
+        if inlines.first().is_some_and(|inline| inline["t"] == "Space") {
+            inlines.remove(0);
+        }
+    }
+    item
+}
+
+fn push_text(parts: &mut Vec<Block<Pure>>, text: &str) {
+    match parts.last_mut() {
+        Some(Block::Text(last)) => last.push_str(text),
+        _ => parts.push(Block::Text(text.to_owned())),
+    }
+}
+
+fn flow(mut parts: Vec<Block<Pure>>) -> Block<Pure> {
+    match parts.len() {
+        0 => Block::Empty,
+        1 => parts.remove(0),
+        _ => Block::Flow(parts),
+    }
+}
+
+/// Wraps the block in an anchor if the element has an ID.
+fn anchored(id: &str, block: Block<Pure>) -> Block<Pure> {
+    if id.is_empty() {
+        block
+    } else {
+        Block::Anchor {
+            id: id.to_owned(),
+            content: Box::new(block),
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rejects_invalid_documents() {
+        assert!(matches!(
+            pandoc_to_semdoc("{\"blocks\": ["),
+            Err(PandocError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            pandoc_to_semdoc("{\"blocks\": []}"),
+            Err(PandocError::NotADocument)
+        ));
+        // Before version 1.17, documents were an array of the metadata and the blocks.
+        assert!(matches!(
+            pandoc_to_semdoc("[{\"unMeta\": {}}, []]"),
+            Err(PandocError::NotADocument)
+        ));
+    }
+
+    #[test]
+    fn rejects_old_tables() {
+        let json = "{\"pandoc-api-version\": [1, 20], \"meta\": {}, \"blocks\": []}";
+        match pandoc_to_semdoc(json) {
+            Err(PandocError::UnsupportedVersion(version)) => assert_eq!(version, "1.20"),
+            other => panic!(
+                "Expected an unsupported version, got {:?}",
+                other.map(|_| ())
+            ),
+        }
+    }
+}
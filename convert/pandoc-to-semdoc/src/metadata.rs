@@ -0,0 +1,151 @@
+//! Pandoc's metadata, which usually comes from the YAML front matter of the source, including the
+//! works in the `references` field that Pandoc's citeproc uses as the bibliography.
+
+use crate::{element, list, string};
+use semdoc::{BibliographyEntry, Metadata, TemporalValue};
+use serde_json::Value;
+
+/// The CSL variables of works that identify them.
+const IDENTIFIERS: [&str; 6] = ["DOI", "ISBN", "ISSN", "URL", "PMID", "PMCID"];
+
+pub(crate) fn metadata(meta: &Value) -> Metadata {
+    let mut metadata = Metadata::default();
+    let fields = match meta.as_object() {
+        Some(fields) => fields,
+        None => return metadata,
+    };
+    for (key, value) in fields {
+        match key.as_str() {
+            "title" => metadata.title = Some(stringify(value)).filter(|title| !title.is_empty()),
+            "author" => {
+                metadata.authors = values(value)
+                    .into_iter()
+                    .map(author)
+                    .filter(|author| !author.is_empty())
+                    .collect();
+            }
+            "date" => {
+                let date = stringify(value);
+                match parse_date(&date) {
+                    Some(date) => metadata.date = Some(date),
+                    None => metadata.custom.push(("date".to_owned(), date)),
+                }
+            }
+            "keywords" | "tags" => {
+                metadata
+                    .tags
+                    .extend(values(value).into_iter().map(stringify));
+            }
+            "references" => {}
+            key => {
+                // Lists and maps, like the configuration of filters, aren't about the document.
+                if matches!(
+                    element(value).0,
+                    "MetaString" | "MetaInlines" | "MetaBlocks" | "MetaBool"
+                ) {
+                    metadata.custom.push((key.to_owned(), stringify(value)));
+                }
+            }
+        }
+    }
+    metadata
+}
+
+/// Turns the works in the `references` field, which are in the CSL JSON format, into bibliography
+/// entries.
+pub(crate) fn references(value: &Value) -> Vec<BibliographyEntry> {
+    values(value)
+        .into_iter()
+        .filter_map(|work| {
+            let fields = work["c"].as_object()?;
+            let field = |key: &str| fields.get(key).map(stringify);
+            Some(BibliographyEntry {
+                key: field("id")?,
+                authors: fields
+                    .get("author")
+                    .map(|authors| values(authors).into_iter().map(csl_name).collect())
+                    .unwrap_or_default(),
+                title: field("title").unwrap_or_default(),
+                year: fields.get("issued").and_then(csl_year),
+                identifiers: IDENTIFIERS
+                    .iter()
+                    .filter_map(|scheme| Some((scheme.to_lowercase(), field(scheme)?)))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// The text of inlines, blocks or metadata values, like Pandoc's `stringify`.
+pub(crate) fn stringify(value: &Value) -> String {
+    match value {
+        Value::Array(values) => values.iter().map(stringify).collect(),
+        Value::Object(_) => {
+            let (kind, content) = element(value);
+            match kind {
+                "Str" | "MetaString" => string(content).to_owned(),
+                "Space" | "SoftBreak" | "LineBreak" => " ".to_owned(),
+                "Code" | "Math" => string(&content[1]).to_owned(),
+                "MetaBool" => content.as_bool().unwrap_or_default().to_string(),
+                "RawInline" | "RawBlock" | "Note" | "MetaMap" => String::new(),
+                _ => stringify(content),
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+/// The items of a `MetaList` or the value itself if it's not a list.
+fn values(value: &Value) -> Vec<&Value> {
+    match element(value) {
+        ("MetaList", items) => list(items).iter().collect(),
+        _ if value.is_null() => vec![],
+        _ => vec![value],
+    }
+}
+
+/// Authors are either just a name or a map with a name and other information, like an email
+/// address.
+fn author(value: &Value) -> String {
+    match value["c"].get("name") {
+        Some(name) => stringify(name),
+        None => stringify(value),
+    }
+}
+
+/// CSL names are either literal, like the name of an organization, or consist of parts.
+fn csl_name(value: &Value) -> String {
+    let parts = match element(value) {
+        ("MetaMap", parts) => parts,
+        _ => return stringify(value),
+    };
+    if let Some(literal) = parts.get("literal") {
+        return stringify(literal);
+    }
+    ["given", "non-dropping-particle", "family", "suffix"]
+        .iter()
+        .filter_map(|part| parts.get(*part).map(stringify))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Dates in CSL are either text, like "1984-02-01", or a map with the `date-parts`, like
+/// `[[1984, 2, 1]]`.
+fn csl_year(issued: &Value) -> Option<i32> {
+    let year = match issued["c"].get("date-parts") {
+        Some(parts) => stringify(values(values(parts).first()?).first()?),
+        None => stringify(issued),
+    };
+    year.split('-').next()?.trim().parse().ok()
+}
+
+/// Parses dates like "2021-03-14".
+fn parse_date(text: &str) -> Option<TemporalValue> {
+    let mut parts = text.trim().splitn(3, '-');
+    let date = TemporalValue::Date {
+        year: parts.next()?.parse().ok()?,
+        month: parts.next()?.parse().ok()?,
+        day: parts.next()?.parse().ok()?,
+    };
+    Some(date).filter(TemporalValue::is_valid)
+}
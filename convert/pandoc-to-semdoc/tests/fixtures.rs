@@ -0,0 +1,208 @@
+//! Documents in Pandoc's JSON format along with the SemDocs they should turn into. The fixtures
+//! are checked in, so the tests don't need Pandoc.
+
+use pandoc_to_semdoc::{pandoc_to_semdoc_with_warnings, ConversionWarningKind, Formatting};
+use semdoc::{
+    BibliographyEntry, Block, Block::*, ChecklistItem, Definition, Metadata, Pure, SemDoc,
+    TemporalValue,
+};
+
+/// Converts the document and returns the kinds of the warnings, because Pandoc documents have no
+/// lines the warnings could point to.
+fn convert(json: &str) -> (SemDoc<Pure>, Vec<ConversionWarningKind>) {
+    let (doc, warnings) = pandoc_to_semdoc_with_warnings(json).unwrap();
+    (
+        SemDoc::new(doc.block.simplify()).with_metadata(doc.metadata),
+        warnings.into_iter().map(|warning| warning.kind).collect(),
+    )
+}
+
+fn text(text: &str) -> Block<Pure> {
+    Text(text.to_owned())
+}
+
+fn anchor(id: &str, content: Block<Pure>) -> Block<Pure> {
+    Anchor {
+        id: id.to_owned(),
+        content: Box::new(content),
+    }
+}
+
+fn section(title: &str, body: Block<Pure>) -> Block<Pure> {
+    Section {
+        title: Box::new(text(title)),
+        body: Box::new(body),
+    }
+}
+
+fn code(language: Option<&str>, code: &str) -> Block<Pure> {
+    Code {
+        language: language.map(|it| it.to_owned()),
+        code: code.to_owned(),
+    }
+}
+
+fn cross_reference(target: &str, content: Block<Pure>) -> Block<Pure> {
+    CrossReference {
+        target: target.to_owned(),
+        content: Box::new(content),
+    }
+}
+
+fn link(target: &str, content: &str) -> Block<Pure> {
+    Link {
+        target: target.to_owned(),
+        content: Box::new(text(content)),
+    }
+}
+
+#[test]
+fn release_notes() {
+    let (doc, warnings) = convert(include_str!("fixtures/release-notes.json"));
+    assert_eq!(
+        doc.metadata,
+        Metadata {
+            title: Some("Release Notes".to_owned()),
+            authors: vec!["Ada Lovelace".to_owned(), "Grace Hopper".to_owned()],
+            date: Some(TemporalValue::Date {
+                year: 2021,
+                month: 3,
+                day: 14,
+            }),
+            tags: vec!["release".to_owned(), "notes".to_owned()],
+            custom: vec![("lang".to_owned(), "en".to_owned())],
+        }
+    );
+    let introduction = Flow(vec![
+        text("This "),
+        TermUsage {
+            term: "release".to_owned(),
+            content: Box::new(text("release")),
+        },
+        text(" is faster"),
+        cross_reference("footnote-1", text("1")),
+        text(" and slower. See "),
+        cross_reference("setup", text("the setup")),
+        text(" and "),
+        Citation(vec!["knuth1984".to_owned()]),
+        text("."),
+    ]);
+    let checklist = Checklist(vec![
+        ChecklistItem {
+            checked: true,
+            content: text("Build"),
+        },
+        ChecklistItem {
+            checked: false,
+            content: text("Ship"),
+        },
+    ]);
+    let table = Table {
+        header: vec![text("Name"), text("Value")],
+        rows: vec![
+            vec![code(None, "threads"), text("4")],
+            vec![text("Defaults"), Empty],
+        ],
+    };
+    let setup = Paragraphs(vec![
+        code(Some("rust"), "fn main() {\n    println!(\"Hello\");\n}"),
+        section("Warning", text("Back up your data.")),
+        section("Settings", table),
+    ]);
+    let glossary = Glossary(vec![Definition {
+        term: text("Release"),
+        definitions: vec![text("A published version.")],
+    }]);
+    assert_eq!(
+        doc.block,
+        Paragraphs(vec![
+            anchor(
+                "introduction",
+                section(
+                    "Introduction",
+                    Paragraphs(vec![
+                        introduction,
+                        checklist,
+                        anchor("setup", section("Setup", setup)),
+                    ])
+                )
+            ),
+            anchor("glossary", section("Glossary", glossary)),
+            Bibliography(vec![BibliographyEntry {
+                key: "knuth1984".to_owned(),
+                authors: vec!["Donald E. Knuth".to_owned()],
+                title: "Literate Programming".to_owned(),
+                year: Some(1984),
+                identifiers: vec![("doi".to_owned(), "10.1093/comjnl/27.2.97".to_owned())],
+            }]),
+            anchor(
+                "footnote-1",
+                Secondary(Box::new(text("Measured on a laptop.")))
+            ),
+        ])
+    );
+    assert_eq!(
+        warnings,
+        vec![ConversionWarningKind::DroppedFormatting(
+            Formatting::Strikethrough
+        )]
+    );
+}
+
+#[test]
+fn inlines_and_dropped_content() {
+    let (doc, warnings) = convert(include_str!("fixtures/inlines.json"));
+    assert!(doc.metadata.is_empty());
+    assert_eq!(
+        doc.block,
+        Paragraphs(vec![
+            Flow(vec![
+                text("“Hi”, "),
+                Highlighted {
+                    content: Box::new(text("important")),
+                    reason: None,
+                },
+                text(" plain "),
+                anchor("here", text("anchored")),
+                text(" "),
+                code(Some("latex"), "e^{i\\pi}"),
+                text(" 2\n"),
+                link("logo.png", "Logo"),
+            ]),
+            text("Roses are red\nViolets are blue"),
+            TopicChange,
+            OrderedList {
+                start: 3,
+                numbering: None,
+                items: vec![
+                    text("Three"),
+                    Paragraphs(vec![text("Four"), Quote(Box::new(text("Quoted")))]),
+                ],
+            },
+            anchor(
+                "fig-chart",
+                section("A chart", link("chart.svg", "A chart"))
+            ),
+            Secondary(Box::new(section("Note", text("Aside.")))),
+        ])
+    );
+    assert_eq!(
+        warnings,
+        vec![
+            ConversionWarningKind::DroppedRaw {
+                format: "html".to_owned()
+            },
+            ConversionWarningKind::DroppedFormatting(Formatting::Superscript),
+            ConversionWarningKind::ImageAsLink {
+                url: "logo.png".to_owned()
+            },
+            ConversionWarningKind::DroppedRaw {
+                format: "tex".to_owned()
+            },
+            ConversionWarningKind::ImageAsLink {
+                url: "chart.svg".to_owned()
+            },
+            ConversionWarningKind::UnknownElement("Hologram".to_owned()),
+        ]
+    );
+}
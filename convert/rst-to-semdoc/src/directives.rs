@@ -0,0 +1,410 @@
+//! Directives, like `.. note::` or `.. code-block:: rust`, of docutils and Sphinx.
+
+use crate::{field_marker, join_lines, slug, ConversionWarningKind, Line, Parser};
+use semdoc::{Block, Pure};
+
+/// Admonitions whose content is an aside rather than something readers should pay extra
+/// attention to.
+const ASIDE_ADMONITIONS: &[&str] = &["note", "tip", "hint", "seealso"];
+
+const ADMONITIONS: &[&str] = &[
+    "note",
+    "tip",
+    "hint",
+    "seealso",
+    "important",
+    "attention",
+    "caution",
+    "danger",
+    "error",
+    "warning",
+];
+
+/// Directives that only affect navigation or the output of docutils, like a table of contents.
+const IGNORED_DIRECTIVES: &[&str] = &[
+    "contents",
+    "sectnum",
+    "toctree",
+    "index",
+    "meta",
+    "tabularcolumns",
+    "only",
+];
+
+/// A directive like this:
+///
+/// ```rst
+/// .. code-block:: rust
+///    :caption: A program
+///
+///    fn main() {}
+/// ```
+pub(crate) struct Directive {
+    pub name: String,
+    /// The lines of the argument, like "rust" above.
+    pub argument: Vec<Line>,
+    /// The options, like the caption above.
+    pub options: Vec<(String, String)>,
+    pub content: Vec<Line>,
+    /// The line of the source where the directive starts.
+    pub line: u32,
+}
+
+impl Directive {
+    /// Parses a directive from its name, the rest of its first line and the following lines
+    /// without their indentation.
+    pub fn parse(name: &str, argument: &str, line: u32, body: Vec<Line>) -> Directive {
+        let mut lines = body.into_iter().peekable();
+        let mut arguments = vec![];
+        if !argument.trim().is_empty() {
+            arguments.push(Line {
+                number: line,
+                text: argument.trim().to_owned(),
+            });
+        }
+        while let Some(line) = lines.peek() {
+            if line.is_blank() || field_marker(&line.text).is_some() {
+                break;
+            }
+            arguments.push(lines.next().unwrap());
+        }
+        let mut options: Vec<(String, String)> = vec![];
+        while let Some(line) = lines.peek() {
+            if line.is_blank() {
+                break;
+            }
+            match field_marker(&line.text) {
+                Some((name, value)) => options.push((name.to_owned(), value.to_owned())),
+                // Option values can continue on indented lines.
+                None => match options.last_mut() {
+                    Some((_, value)) => {
+                        value.push(' ');
+                        value.push_str(line.text.trim());
+                    }
+                    None => break,
+                },
+            }
+            lines.next();
+        }
+        let mut content = lines.skip_while(Line::is_blank).collect::<Vec<_>>();
+        while content.last().is_some_and(Line::is_blank) {
+            content.pop();
+        }
+        Directive {
+            name: name.to_lowercase(),
+            argument: arguments,
+            options,
+            content,
+            line,
+        }
+    }
+
+    fn argument_text(&self) -> String {
+        self.argument
+            .iter()
+            .map(|line| line.text.trim())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(option, _)| option == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The argument and the content, for directives without arguments whose content may start
+    /// on the first line, like `.. note:: Text`.
+    fn argument_and_content(&self) -> Vec<Line> {
+        let mut lines = self.argument.clone();
+        if !lines.is_empty() && !self.content.is_empty() {
+            lines.push(Line {
+                number: self.line,
+                text: String::new(),
+            });
+        }
+        lines.extend(self.content.iter().cloned());
+        lines
+    }
+}
+
+impl Parser {
+    pub(crate) fn directive(&mut self, directive: Directive) -> Vec<Block<Pure>> {
+        let blocks = self.directive_blocks(&directive);
+        // Directives can be referred to using their `:name:` option.
+        match directive.option("name") {
+            Some(name) if !blocks.is_empty() => {
+                let content = match blocks.len() {
+                    1 => blocks.into_iter().next().unwrap(),
+                    _ => Block::Paragraphs(blocks),
+                };
+                vec![Block::Anchor {
+                    id: slug(name),
+                    content: Box::new(content),
+                }]
+            }
+            _ => blocks,
+        }
+    }
+
+    fn directive_blocks(&mut self, directive: &Directive) -> Vec<Block<Pure>> {
+        let line = directive.line;
+        let name = directive.name.as_str();
+        match name {
+            name if ADMONITIONS.contains(&name) => {
+                let title = match name {
+                    "seealso" => "See also".to_owned(),
+                    name => capitalize(name),
+                };
+                let body = self.blocks(&directive.argument_and_content());
+                let section = titled(Block::Text(title), body);
+                if ASIDE_ADMONITIONS.contains(&name) {
+                    vec![Block::Secondary(Box::new(section))]
+                } else {
+                    vec![section]
+                }
+            }
+            "admonition" => {
+                let title = self.inline(&directive.argument_text(), line);
+                let body = self.blocks(&directive.content);
+                vec![titled(title, body)]
+            }
+            "versionadded" | "versionchanged" | "deprecated" => {
+                let argument = directive.argument_text();
+                let (version, explanation) =
+                    argument.split_once(' ').unwrap_or((argument.as_str(), ""));
+                let title = match name {
+                    "versionadded" => format!("New in version {}", version),
+                    "versionchanged" => format!("Changed in version {}", version),
+                    _ => format!("Deprecated since version {}", version),
+                };
+                let mut body = vec![];
+                if !explanation.trim().is_empty() {
+                    body.push(self.inline(explanation, line));
+                }
+                body.extend(self.blocks(&directive.content));
+                vec![titled(Block::Text(title), body)]
+            }
+            "code" | "code-block" | "sourcecode" => {
+                let language = directive.argument_text();
+                vec![Block::Code {
+                    language: match language.as_str() {
+                        "" => self.default_language.clone(),
+                        "none" | "text" => None,
+                        language => Some(language.to_owned()),
+                    },
+                    code: join_lines(&directive.content),
+                }]
+            }
+            "parsed-literal" => vec![Block::Code {
+                language: None,
+                code: join_lines(&directive.content),
+            }],
+            "highlight" => {
+                self.default_language = match directive.argument_text().as_str() {
+                    "none" | "text" | "default" => None,
+                    language => Some(language.to_owned()),
+                };
+                vec![]
+            }
+            "math" => vec![Block::Code {
+                language: Some("latex".to_owned()),
+                code: join_lines(&directive.argument_and_content()),
+            }],
+            "image" | "figure" => {
+                let url = directive.argument_text();
+                self.warn(
+                    line,
+                    ConversionWarningKind::ImageAsLink { url: url.clone() },
+                );
+                let description = directive.option("alt").unwrap_or(&url).to_owned();
+                let link = Block::Link {
+                    target: url,
+                    content: Box::new(Block::Text(description)),
+                };
+                // The content of figures is their caption and legend.
+                let mut blocks = vec![link];
+                blocks.extend(self.blocks(&directive.content));
+                blocks
+            }
+            "topic" | "sidebar" => {
+                let title = self.inline(&directive.argument_text(), line);
+                let body = self.blocks(&directive.content);
+                vec![Block::Secondary(Box::new(titled(title, body)))]
+            }
+            "rubric" | "centered" => vec![self.inline(&directive.argument_text(), line)],
+            "epigraph" | "highlights" | "pull-quote" => {
+                let content = Block::Paragraphs(self.blocks(&directive.content));
+                vec![Block::Quote(Box::new(content))]
+            }
+            "container" | "compound" | "class" | "hlist" => self.blocks(&directive.content),
+            "glossary" => self
+                .blocks(&directive.content)
+                .into_iter()
+                .map(|block| match block {
+                    Block::DefinitionList(definitions) => Block::Glossary(definitions),
+                    block => block,
+                })
+                .collect(),
+            "table" => {
+                let body = self.blocks(&directive.content);
+                self.with_title(directive, body)
+            }
+            "list-table" => {
+                let table = self.list_table(directive);
+                self.with_title(directive, vec![table])
+            }
+            "csv-table" => {
+                if directive.option("file").is_some() || directive.option("url").is_some() {
+                    let name = "csv-table".to_owned();
+                    self.warn(line, ConversionWarningKind::UnsupportedDirective(name));
+                    return vec![];
+                }
+                let table = self.csv_table(directive);
+                self.with_title(directive, vec![table])
+            }
+            name if IGNORED_DIRECTIVES.contains(&name) => vec![],
+            "raw"
+                if directive
+                    .argument_text()
+                    .split_whitespace()
+                    .any(|it| it == "html") =>
+            {
+                let html = join_lines(&directive.content);
+                self.warn(line, ConversionWarningKind::DroppedHtml(html));
+                vec![]
+            }
+            name => {
+                let name = name.to_owned();
+                self.warn(line, ConversionWarningKind::UnsupportedDirective(name));
+                vec![]
+            }
+        }
+    }
+
+    /// Puts tables with a title into a section.
+    fn with_title(&mut self, directive: &Directive, body: Vec<Block<Pure>>) -> Vec<Block<Pure>> {
+        let title = directive.argument_text();
+        if title.is_empty() {
+            body
+        } else {
+            vec![titled(self.inline(&title, directive.line), body)]
+        }
+    }
+
+    /// Converts a list table, which is a bullet list of rows that are bullet lists of cells.
+    fn list_table(&mut self, directive: &Directive) -> Block<Pure> {
+        let header_rows = directive
+            .option("header-rows")
+            .and_then(|rows| rows.trim().parse().ok())
+            .unwrap_or(0);
+        let mut rows = vec![];
+        for block in self.blocks(&directive.content) {
+            let items = match block {
+                Block::BulletList(items) => items,
+                _ => continue,
+            };
+            for item in items {
+                let cells = match item {
+                    Block::Paragraphs(mut blocks) if blocks.len() == 1 => blocks.remove(0),
+                    item => item,
+                };
+                let cells = match cells {
+                    Block::BulletList(cells) => cells.into_iter().map(unwrap_single).collect(),
+                    cell => vec![cell],
+                };
+                rows.push(cells);
+            }
+        }
+        table(rows, header_rows)
+    }
+
+    fn csv_table(&mut self, directive: &Directive) -> Block<Pure> {
+        let delimiter = match directive.option("delim") {
+            Some("tab") => '\t',
+            Some("space") => ' ',
+            Some(delimiter) => delimiter.chars().next().unwrap_or(','),
+            None => ',',
+        };
+        let mut rows = vec![];
+        let mut header_rows = directive
+            .option("header-rows")
+            .and_then(|rows| rows.trim().parse().ok())
+            .unwrap_or(0);
+        if let Some(header) = directive.option("header") {
+            rows.push(self.csv_row(header, delimiter, directive.line));
+            header_rows = 1;
+        }
+        for line in &directive.content {
+            if !line.is_blank() {
+                rows.push(self.csv_row(&line.text, delimiter, line.number));
+            }
+        }
+        table(rows, header_rows)
+    }
+
+    fn csv_row(&mut self, text: &str, delimiter: char, line: u32) -> Vec<Block<Pure>> {
+        split_csv(text, delimiter)
+            .iter()
+            .map(|cell| self.inline(cell, line))
+            .collect()
+    }
+}
+
+fn titled(title: Block<Pure>, body: Vec<Block<Pure>>) -> Block<Pure> {
+    Block::Section {
+        title: Box::new(title),
+        body: Box::new(Block::Paragraphs(body)),
+    }
+}
+
+fn table(mut rows: Vec<Vec<Block<Pure>>>, header_rows: usize) -> Block<Pure> {
+    // SemDoc tables have a single header row, so additional ones become normal rows.
+    let header = if header_rows > 0 && !rows.is_empty() {
+        rows.remove(0)
+    } else {
+        vec![]
+    };
+    Block::Table { header, rows }
+}
+
+fn unwrap_single(block: Block<Pure>) -> Block<Pure> {
+    match block {
+        Block::Paragraphs(mut blocks) if blocks.len() == 1 => blocks.remove(0),
+        Block::Paragraphs(blocks) if blocks.is_empty() => Block::Empty,
+        block => block,
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Splits a line of comma-separated values, which may be quoted like `"Hello, world"`.
+fn split_csv(text: &str, delimiter: char) -> Vec<String> {
+    let mut cells = vec![];
+    let mut cell = String::new();
+    let mut is_quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if is_quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => is_quoted = !is_quoted,
+            c if c == delimiter && !is_quoted => {
+                cells.push(cell.trim().to_owned());
+                cell.clear();
+            }
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_owned());
+    cells
+}
@@ -0,0 +1,433 @@
+//! Inline markup, like `*emphasis*`, ``` ``literals`` ```, `` `links <https://…>`_ `` and
+//! roles like `` :ref:`setup` ``.
+
+use crate::{footnote_id, slug, trim_inlines, ConversionWarningKind, Formatting, Parser};
+use semdoc::{normalize_term, Block, Pure};
+
+/// Roles whose text is code, like `` :file:`Cargo.toml` ``, including the ones of the Python
+/// domain, which Sphinx uses by default.
+const CODE_ROLES: &[&str] = &[
+    "code",
+    "literal",
+    "samp",
+    "file",
+    "command",
+    "program",
+    "envvar",
+    "option",
+    "makevar",
+    "regexp",
+    "mailheader",
+    "func",
+    "meth",
+    "class",
+    "mod",
+    "attr",
+    "data",
+    "exc",
+    "obj",
+    "const",
+    "attribute",
+    "function",
+    "method",
+    "module",
+    "exception",
+];
+
+/// Roles that only format their text, which SemDoc leaves to readers.
+const TEXT_ROLES: &[&str] = &[
+    "emphasis",
+    "strong",
+    "title-reference",
+    "title",
+    "t",
+    "abbr",
+    "dfn",
+    "guilabel",
+];
+
+impl Parser {
+    /// Parses inline markup. The text starts at the given line of the source.
+    pub(crate) fn inline(&mut self, text: &str, line: u32) -> Block<Pure> {
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut inline = Inline {
+            chars: &chars,
+            line,
+            position: 0,
+            text: String::new(),
+            blocks: vec![],
+        };
+        while inline.position < chars.len() {
+            self.inline_step(&mut inline);
+        }
+        inline.flush();
+        trim_inlines(Block::Flow(inline.blocks))
+    }
+
+    /// Parses the markup at the current position or takes a single character as text.
+    fn inline_step(&mut self, inline: &mut Inline) {
+        let c = inline.chars[inline.position];
+        if c == '\\' {
+            match inline.chars.get(inline.position + 1) {
+                // Escaped whitespace disappears, like in `Python\ 3`.
+                Some(c) if c.is_whitespace() => {}
+                Some(c) => inline.text.push(*c),
+                None => {}
+            }
+            inline.position += 2;
+            return;
+        }
+        if inline.is_start() {
+            if let Some(block) = self.markup(inline) {
+                inline.flush();
+                inline.blocks.push(block);
+                return;
+            }
+        }
+        // Line breaks in paragraphs are just spaces.
+        inline.text.push(if c == '\n' { ' ' } else { c });
+        inline.position += 1;
+    }
+
+    /// Parses the markup starting at the current position, which is at a start boundary.
+    fn markup(&mut self, inline: &mut Inline) -> Option<Block<Pure>> {
+        let line = inline.current_line();
+        let rest = inline.rest();
+        if rest.starts_with("``") {
+            let code = inline.delimited("``", "``")?;
+            return Some(Block::Code {
+                language: None,
+                code: code.replace('\n', " "),
+            });
+        }
+        if rest.starts_with("**") {
+            let content = inline.delimited("**", "**")?;
+            return Some(self.inline(&content, line));
+        }
+        if rest.starts_with('*') {
+            let content = inline.delimited("*", "*")?;
+            return Some(self.inline(&content, line));
+        }
+        if rest.starts_with('`') {
+            return self.interpreted(inline, None);
+        }
+        if rest.starts_with(':') {
+            let start = inline.position;
+            let role = inline.delimited(":", ":")?;
+            let is_role = !role.is_empty()
+                && role
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || "-_:.+".contains(c));
+            if is_role && inline.rest().starts_with('`') {
+                if let Some(block) = self.interpreted(inline, Some(role)) {
+                    return Some(block);
+                }
+            }
+            inline.position = start;
+            return None;
+        }
+        if rest.starts_with('[') {
+            let start = inline.position;
+            if let Some(label) = inline.delimited("[", "]_") {
+                if !label.is_empty() && !label.contains(char::is_whitespace) {
+                    let display = match label.as_str() {
+                        "#" | "*" => self.footnote_label(&label, false),
+                        label => label.trim_start_matches('#').to_owned(),
+                    };
+                    return Some(Block::CrossReference {
+                        target: footnote_id(&display),
+                        content: Box::new(Block::Text(display)),
+                    });
+                }
+            }
+            inline.position = start;
+            return None;
+        }
+        if rest.starts_with('|') {
+            let start = inline.position;
+            if let Some(name) = inline.delimited("|", "|") {
+                // References to substitutions, like `|name|_`, just use the substitution.
+                while inline.rest().starts_with('_') {
+                    inline.position += 1;
+                }
+                return Some(match self.substitutions.remove(&name) {
+                    Some(replacement) => {
+                        let block = self.inline(&replacement, line);
+                        self.substitutions.insert(name, replacement);
+                        block
+                    }
+                    None => Block::Text(name),
+                });
+            }
+            inline.position = start;
+            return None;
+        }
+        if rest.starts_with("http://")
+            || rest.starts_with("https://")
+            || rest.starts_with("mailto:")
+        {
+            let length = rest
+                .find(|c: char| c.is_whitespace() || "<>\"".contains(c))
+                .unwrap_or(rest.len());
+            let url = rest[..length].trim_end_matches(|c: char| ".,;:!?)'".contains(c));
+            let url = url.to_owned();
+            inline.position += url.chars().count();
+            return Some(Block::Link {
+                target: url.clone(),
+                content: Box::new(Block::Text(url)),
+            });
+        }
+        self.simple_reference(inline)
+    }
+
+    /// Parses references to known targets that consist of a single word, like `Rust_`.
+    fn simple_reference(&mut self, inline: &mut Inline) -> Option<Block<Pure>> {
+        let rest = inline.rest();
+        let word_length = rest
+            .find(|c: char| !(c.is_alphanumeric() || "-_.+".contains(c)))
+            .unwrap_or(rest.len());
+        let word = rest[..word_length].trim_end_matches('.');
+        let name = word.strip_suffix('_').filter(|name| !name.ends_with('_'))?;
+        let key = normalize_term(name);
+        if !self.link_targets.contains_key(&key) && !self.labels.contains(&key) {
+            return None;
+        }
+        inline.position += word.chars().count();
+        Some(self.reference(name, Block::Text(name.to_owned())))
+    }
+
+    /// Parses interpreted text in backquotes, which is either a reference, like `` `Rust`_ ``, or
+    /// text with a role, like `` :ref:`setup` `` or `` `setup`:ref: ``.
+    fn interpreted(&mut self, inline: &mut Inline, role: Option<String>) -> Option<Block<Pure>> {
+        let line = inline.current_line();
+        let start = inline.position;
+        let content = match inline.delimited("`", "`") {
+            Some(content) if !content.is_empty() => content,
+            _ => {
+                inline.position = start;
+                return None;
+            }
+        };
+        let rest = inline.rest();
+        if role.is_none() && rest.starts_with("__") {
+            inline.position += 2;
+            let (text, target) = split_embedded(&content);
+            let target = target.or_else(|| self.anonymous_targets.pop_front());
+            let content = self.inline(&text, line);
+            return Some(match target {
+                Some(target) => Block::Link {
+                    target,
+                    content: Box::new(content),
+                },
+                None => content,
+            });
+        }
+        if role.is_none() && rest.starts_with('_') {
+            inline.position += 1;
+            return Some(match split_embedded(&content) {
+                (text, Some(target)) => match target.strip_suffix('_') {
+                    Some(name) => {
+                        let content = self.inline(&text, line);
+                        self.reference(name, content)
+                    }
+                    None => Block::Link {
+                        target,
+                        content: Box::new(self.inline(&text, line)),
+                    },
+                },
+                (text, None) => {
+                    let content = self.inline(&text, line);
+                    self.reference(&text, content)
+                }
+            });
+        }
+        let role = match role {
+            Some(role) => Some(role),
+            None if rest.starts_with(':') => {
+                let suffix_start = inline.position;
+                match inline.delimited(":", ":") {
+                    Some(role) if !role.is_empty() => Some(role),
+                    _ => {
+                        inline.position = suffix_start;
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        Some(match role {
+            Some(role) => self.role(&role, &content, line),
+            None => Block::Text(content),
+        })
+    }
+
+    /// Resolves a reference to a named target, which is either an external hyperlink target or an
+    /// internal target like a section.
+    fn reference(&mut self, name: &str, content: Block<Pure>) -> Block<Pure> {
+        match self.link_targets.get(&normalize_term(name)) {
+            Some(url) => Block::Link {
+                target: url.clone(),
+                content: Box::new(content),
+            },
+            None => Block::CrossReference {
+                target: slug(name),
+                content: Box::new(content),
+            },
+        }
+    }
+
+    fn role(&mut self, role: &str, content: &str, line: u32) -> Block<Pure> {
+        let (text, target) = split_embedded(content);
+        let code = |code: &str| Block::Code {
+            language: None,
+            code: code.trim_start_matches(['~', '!']).to_owned(),
+        };
+        let name = role.rsplit(':').next().unwrap_or(role);
+        match name {
+            "ref" | "numref" => {
+                let (target, content) = match target {
+                    Some(target) => (target, Block::Text(text)),
+                    None => (text, Block::Empty),
+                };
+                Block::CrossReference {
+                    target: slug(&target),
+                    content: Box::new(content),
+                }
+            }
+            "doc" | "download" => Block::Link {
+                target: target.clone().unwrap_or_else(|| text.clone()),
+                content: Box::new(Block::Text(text)),
+            },
+            "term" => Block::TermUsage {
+                term: normalize_term(target.as_ref().unwrap_or(&text)),
+                content: Box::new(Block::Text(text)),
+            },
+            "math" => Block::Code {
+                language: Some("latex".to_owned()),
+                code: content.to_owned(),
+            },
+            "pep" => Block::Link {
+                target: format!("https://peps.python.org/pep-{:0>4}/", content),
+                content: Box::new(Block::Text(format!("PEP {}", content))),
+            },
+            "rfc" => Block::Link {
+                target: format!("https://datatracker.ietf.org/doc/html/rfc{}", content),
+                content: Box::new(Block::Text(format!("RFC {}", content))),
+            },
+            "sub" | "subscript" | "sup" | "superscript" => {
+                let formatting = if name.starts_with("sub") {
+                    Formatting::Subscript
+                } else {
+                    Formatting::Superscript
+                };
+                self.warn(line, ConversionWarningKind::DroppedFormatting(formatting));
+                self.inline(content, line)
+            }
+            name if TEXT_ROLES.contains(&name) => self.inline(content, line),
+            name if CODE_ROLES.contains(&name) || role.contains(':') => code(&text),
+            _ => {
+                let role = role.to_owned();
+                self.warn(line, ConversionWarningKind::UnsupportedRole(role));
+                Block::Text(text)
+            }
+        }
+    }
+}
+
+/// The state of parsing inline markup.
+struct Inline<'a> {
+    chars: &'a [char],
+    /// The line of the source where the text starts.
+    line: u32,
+    position: usize,
+    /// Text that isn't part of any markup, which is added to the blocks before the next markup.
+    text: String,
+    blocks: Vec<Block<Pure>>,
+}
+
+impl<'a> Inline<'a> {
+    fn rest(&self) -> String {
+        self.chars[self.position..].iter().collect()
+    }
+
+    fn current_line(&self) -> u32 {
+        let lines_before = self.chars[..self.position]
+            .iter()
+            .filter(|c| **c == '\n')
+            .count();
+        self.line + lines_before as u32
+    }
+
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            self.blocks
+                .push(Block::Text(std::mem::take(&mut self.text)));
+        }
+    }
+
+    /// Whether inline markup may start at the current position, which is the case after
+    /// whitespace and some punctuation.
+    fn is_start(&self) -> bool {
+        self.position == 0
+            || self.chars[self.position - 1].is_whitespace()
+            || "'\"([{<-/:".contains(self.chars[self.position - 1])
+    }
+
+    /// Whether inline markup may end before the given position.
+    fn is_end(&self, position: usize) -> bool {
+        match self.chars.get(position) {
+            None => true,
+            Some(c) => c.is_whitespace() || "'\")]}>-/:.,;!?\\_`*|".contains(*c),
+        }
+    }
+
+    /// Parses markup between the start and end strings, moving the position after it. The
+    /// content may neither start nor end with whitespace.
+    fn delimited(&mut self, start: &str, end: &str) -> Option<String> {
+        let start_length = start.chars().count();
+        let end_chars = end.chars().collect::<Vec<_>>();
+        let content_start = self.position + start_length;
+        if self
+            .chars
+            .get(content_start)
+            .map_or(true, |c| c.is_whitespace())
+        {
+            return None;
+        }
+        let mut index = content_start;
+        while index + end_chars.len() <= self.chars.len() {
+            if self.chars[index] == '\\' && start != "``" {
+                index += 2;
+                continue;
+            }
+            let is_end = self.chars[index..index + end_chars.len()] == end_chars[..]
+                && index > content_start
+                && !self.chars[index - 1].is_whitespace()
+                && self.is_end(index + end_chars.len());
+            if is_end {
+                let content = self.chars[content_start..index].iter().collect();
+                self.position = index + end_chars.len();
+                return Some(content);
+            }
+            index += 1;
+        }
+        None
+    }
+}
+
+/// Splits interpreted text like `` `Rust <https://rust-lang.org>` `` into its text and the
+/// embedded target. If there's only a target, it's also the text.
+fn split_embedded(content: &str) -> (String, Option<String>) {
+    let content = content.trim();
+    if let Some(without_end) = content.strip_suffix('>') {
+        if let Some(start) = without_end.rfind('<') {
+            let target = without_end[start + 1..]
+                .split_whitespace()
+                .collect::<String>();
+            let text = without_end[..start].trim();
+            let text = if text.is_empty() { &target } else { text };
+            return (text.replace('\n', " "), Some(target));
+        }
+    }
+    (content.replace('\n', " "), None)
+}
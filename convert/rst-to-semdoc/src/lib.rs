@@ -0,0 +1,1115 @@
+use semdoc::{
+    nest_sections, normalize_term, Block, Definition, Heading, Metadata, Pure, SectionItem, SemDoc,
+    TemporalValue,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+mod directives;
+mod inline;
+mod tables;
+pub use conversion_warnings::{ConversionWarning, ConversionWarningKind, Formatting, Location};
+
+pub fn rst_to_semdoc(rst: &str) -> SemDoc<Pure> {
+    rst_to_semdoc_with_warnings(rst).0
+}
+
+/// Converts reStructuredText into a SemDoc and reports content that couldn't be converted
+/// faithfully.
+///
+/// Like in docutils, a lone top-level section title at the start of the document becomes the
+/// title of the document and a field list right after it, like `:Author: Ada Lovelace`, becomes
+/// the rest of its metadata.
+pub fn rst_to_semdoc_with_warnings(rst: &str) -> (SemDoc<Pure>, Vec<ConversionWarning>) {
+    let lines = rst
+        .lines()
+        .enumerate()
+        .map(|(index, text)| Line {
+            number: index as u32 + 1,
+            text: expand_tabs(text.trim_end()),
+        })
+        .collect::<Vec<_>>();
+    let mut parser = Parser::new(&lines);
+    let mut items = parser.items(&lines);
+    let metadata = document_info(&mut items);
+    let block = Block::Paragraphs(nest_sections(sections(items)));
+    let doc = SemDoc::new(block.link_glossary_terms()).with_metadata(metadata);
+    (doc, parser.warnings)
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    /// The line in the source, starting at 1.
+    number: u32,
+    text: String,
+}
+impl Line {
+    fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    fn indent(&self) -> usize {
+        self.text.len() - self.text.trim_start_matches(' ').len()
+    }
+}
+
+/// A part of the document. Sections are formed after parsing, because the content of a section
+/// continues until the next title of the same or a higher level.
+enum Item {
+    Heading {
+        level: usize,
+        title: Block<Pure>,
+        /// The IDs of explicit targets right before the title, like `.. _setup:`.
+        ids: Vec<String>,
+    },
+    Fields(Vec<Field>),
+    /// An explicit target, like `.. _setup:`, that applies to the following item.
+    Target(String),
+    Block(Block<Pure>),
+}
+
+/// A field of a field list, like `:Version: 1.0`.
+struct Field {
+    name: String,
+    /// The source text of the body.
+    text: String,
+    body: Block<Pure>,
+}
+
+struct Parser {
+    /// The adornments of section titles in the order they first appear, each consisting of the
+    /// character and whether there's an overline. The index is the level of the section.
+    section_styles: Vec<(char, bool)>,
+    /// URLs of external hyperlink targets, like `.. _Rust: https://rust-lang.org`, by their
+    /// normalized name.
+    link_targets: HashMap<String, String>,
+    /// URLs of anonymous hyperlink targets, like `__ https://rust-lang.org`, in the order they
+    /// appear.
+    anonymous_targets: VecDeque<String>,
+    /// The normalized names of internal targets, like `.. _setup:`.
+    labels: HashSet<String>,
+    /// Texts of substitutions, like `.. |version| replace:: 1.0`, by their name.
+    substitutions: HashMap<String, String>,
+    /// The language of literal blocks, which the `highlight` directive sets.
+    default_language: Option<String>,
+    /// How many auto-numbered footnotes, like `[#]_`, were defined and referenced so far.
+    auto_footnote_definitions: usize,
+    auto_footnote_references: usize,
+    warnings: Vec<ConversionWarning>,
+}
+
+impl Parser {
+    /// Collects the targets and substitutions of the whole document, because they can be used
+    /// before they are defined.
+    fn new(lines: &[Line]) -> Parser {
+        let mut parser = Parser {
+            section_styles: vec![],
+            link_targets: HashMap::new(),
+            anonymous_targets: VecDeque::new(),
+            labels: HashSet::new(),
+            substitutions: HashMap::new(),
+            default_language: None,
+            auto_footnote_definitions: 0,
+            auto_footnote_references: 0,
+            warnings: vec![],
+        };
+        for line in lines {
+            let text = line.text.trim_start();
+            if let Some(url) = text.strip_prefix("__ ") {
+                parser.anonymous_targets.push_back(url.trim().to_owned());
+                continue;
+            }
+            let markup = match text.strip_prefix(".. ") {
+                Some(markup) => markup.trim_start(),
+                None => continue,
+            };
+            if let Some(target) = markup.strip_prefix('_') {
+                let (name, url) = match split_target(target) {
+                    Some(target) => target,
+                    None => continue,
+                };
+                if name == "_" {
+                    parser.anonymous_targets.push_back(url.to_owned());
+                } else if url.is_empty() {
+                    parser.labels.insert(normalize_term(name));
+                } else if !url.ends_with('_') {
+                    let url = url.split_whitespace().collect::<String>();
+                    parser.link_targets.insert(normalize_term(name), url);
+                }
+            } else if let Some(definition) = markup.strip_prefix('|') {
+                if let Some((name, text)) = definition.split_once("| replace::") {
+                    parser
+                        .substitutions
+                        .insert(name.to_owned(), text.trim().to_owned());
+                }
+            }
+        }
+        parser
+    }
+
+    fn warn(&mut self, line: u32, kind: ConversionWarningKind) {
+        self.warnings.push(ConversionWarning {
+            location: Location::Line(line),
+            kind,
+        });
+    }
+
+    /// Converts lines into blocks, nesting the content of sections.
+    fn blocks(&mut self, lines: &[Line]) -> Vec<Block<Pure>> {
+        nest_sections(sections(self.items(lines)))
+    }
+
+    fn items(&mut self, lines: &[Line]) -> Vec<Item> {
+        let mut items = vec![];
+        let mut targets = vec![];
+        let mut index = 0;
+        while index < lines.len() {
+            if lines[index].is_blank() {
+                index += 1;
+                continue;
+            }
+            let (new_items, next) = self.item(lines, index);
+            index = next;
+            for item in new_items {
+                match item {
+                    Item::Target(id) => targets.push(id),
+                    Item::Heading { level, title, .. } => items.push(Item::Heading {
+                        level,
+                        title,
+                        ids: std::mem::take(&mut targets),
+                    }),
+                    Item::Fields(fields) if targets.is_empty() => items.push(Item::Fields(fields)),
+                    Item::Fields(fields) => items.push(Item::Block(anchored(
+                        std::mem::take(&mut targets),
+                        fields_to_block(fields),
+                    ))),
+                    Item::Block(block) => {
+                        items.push(Item::Block(anchored(std::mem::take(&mut targets), block)))
+                    }
+                }
+            }
+        }
+        items
+    }
+
+    /// Parses the construct starting at the given non-blank line. Returns the items and the index
+    /// of the line after the construct.
+    fn item(&mut self, lines: &[Line], index: usize) -> (Vec<Item>, usize) {
+        let line = &lines[index];
+        let text = line.text.as_str();
+        if line.indent() > 0 {
+            let end = indented_end(lines, index);
+            let content = self.blocks(&dedent(&lines[index..end]));
+            let quote = Block::Quote(Box::new(Block::Paragraphs(content)));
+            return (vec![Item::Block(quote)], end);
+        }
+        // Anonymous targets, like `__ https://rust-lang.org`, were collected before.
+        if text.starts_with("__ ") {
+            return (vec![], block_end(lines, index));
+        }
+        if text == ".." || text.starts_with(".. ") {
+            return self.explicit_markup(lines, index);
+        }
+        if let Some(heading) = self.heading(lines, index) {
+            return heading;
+        }
+        if is_adornment(text)
+            && text.len() >= 4
+            && lines.get(index + 1).map_or(true, Line::is_blank)
+        {
+            return (vec![Item::Block(Block::TopicChange)], index + 1);
+        }
+        if tables::is_grid_table_border(text) {
+            let end = block_end(lines, index);
+            let table = self.grid_table(&lines[index..end]);
+            return (vec![Item::Block(table)], end);
+        }
+        if tables::is_simple_table_border(text) {
+            let end = tables::simple_table_end(lines, index);
+            let table = self.simple_table(&lines[index..end]);
+            return (vec![Item::Block(table)], end);
+        }
+        if bullet(text).is_some() {
+            return self.bullet_list(lines, index);
+        }
+        if enumerator(text).is_some() {
+            return self.enumerated_list(lines, index);
+        }
+        if field_marker(text).is_some() {
+            return self.field_list(lines, index);
+        }
+        if text == "|" || text.starts_with("| ") {
+            return self.line_block(lines, index);
+        }
+        if text.starts_with(">>> ") {
+            let end = block_end(lines, index);
+            let code = Block::Code {
+                language: Some("pycon".to_owned()),
+                code: join_lines(&lines[index..end]),
+            };
+            return (vec![Item::Block(code)], end);
+        }
+        if is_definition_start(lines, index) {
+            return self.definition_list(lines, index);
+        }
+        self.paragraph(lines, index)
+    }
+
+    /// Parses a section title that is underlined or both overlined and underlined.
+    fn heading(&mut self, lines: &[Line], index: usize) -> Option<(Vec<Item>, usize)> {
+        let text = &lines[index].text;
+        let next = lines.get(index + 1).map(|line| line.text.as_str());
+        let (title, style, end) = if is_adornment(text)
+            && matches!(next, Some(title) if !title.trim().is_empty())
+            && matches!(lines.get(index + 2), Some(under) if under.text.starts_with(&text[..1])
+                && is_adornment(&under.text))
+        {
+            let style = (text.chars().next().unwrap(), true);
+            (next.unwrap().trim(), style, index + 3)
+        } else {
+            let underline = next.filter(|next| is_adornment(next))?;
+            if underline.len() < 3 && underline.len() < text.chars().count() {
+                return None;
+            }
+            (
+                text.trim(),
+                (underline.chars().next().unwrap(), false),
+                index + 2,
+            )
+        };
+        let level = match self.section_styles.iter().position(|it| *it == style) {
+            Some(level) => level,
+            None => {
+                self.section_styles.push(style);
+                self.section_styles.len() - 1
+            }
+        };
+        let title = self.inline(title, lines[index].number);
+        let heading = Item::Heading {
+            level,
+            title,
+            ids: vec![],
+        };
+        Some((vec![heading], end))
+    }
+
+    fn bullet_list(&mut self, lines: &[Line], mut index: usize) -> (Vec<Item>, usize) {
+        let (marker, _, _) = bullet(&lines[index].text).unwrap();
+        let mut items = vec![];
+        loop {
+            let (_, rest, column) = bullet(&lines[index].text).unwrap();
+            let (body, end) = list_item(lines, index, rest, column);
+            items.push(Block::Paragraphs(self.blocks(&body)));
+            let next = skip_blank(lines, end);
+            match lines.get(next).and_then(|line| bullet(&line.text)) {
+                Some((next_marker, _, _)) if next_marker == marker => index = next,
+                _ => return (vec![Item::Block(Block::BulletList(items))], end),
+            }
+        }
+    }
+
+    fn enumerated_list(&mut self, lines: &[Line], mut index: usize) -> (Vec<Item>, usize) {
+        let first = enumerator(&lines[index].text).unwrap();
+        let mut items = vec![];
+        loop {
+            let enumerator = enumerator(&lines[index].text).unwrap();
+            let (body, end) = list_item(lines, index, enumerator.rest, enumerator.column);
+            items.push(Block::Paragraphs(self.blocks(&body)));
+            let next = skip_blank(lines, end);
+            match lines
+                .get(next)
+                .and_then(|line| self::enumerator(&line.text))
+            {
+                Some(enumerator) if enumerator.format == first.format => index = next,
+                _ => {
+                    let list = Block::OrderedList {
+                        start: first.number.unwrap_or(1),
+                        numbering: None,
+                        items,
+                    };
+                    return (vec![Item::Block(list)], end);
+                }
+            }
+        }
+    }
+
+    fn field_list(&mut self, lines: &[Line], mut index: usize) -> (Vec<Item>, usize) {
+        let mut fields = vec![];
+        loop {
+            let (name, rest) = field_marker(&lines[index].text).unwrap();
+            let (body, end) = list_item(lines, index, rest, 0);
+            let body = dedent_following(body);
+            fields.push(Field {
+                name: name.to_owned(),
+                text: body
+                    .iter()
+                    .map(|line| line.text.trim())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .trim()
+                    .to_owned(),
+                body: Block::Paragraphs(self.blocks(&body)),
+            });
+            let next = skip_blank(lines, end);
+            match lines.get(next) {
+                Some(line) if field_marker(&line.text).is_some() => index = next,
+                _ => return (vec![Item::Fields(fields)], end),
+            }
+        }
+    }
+
+    /// Line blocks, like addresses or poems, keep their line breaks.
+    fn line_block(&mut self, lines: &[Line], index: usize) -> (Vec<Item>, usize) {
+        let end = block_end(lines, index);
+        let mut texts: Vec<(u32, String)> = vec![];
+        for line in &lines[index..end] {
+            match line.text.strip_prefix('|') {
+                Some(text) => texts.push((line.number, text.trim().to_owned())),
+                // Indented lines continue the previous line.
+                None => match texts.last_mut() {
+                    Some((_, text)) => {
+                        text.push(' ');
+                        text.push_str(line.text.trim());
+                    }
+                    None => texts.push((line.number, line.text.trim().to_owned())),
+                },
+            }
+        }
+        let mut parts = vec![];
+        for (position, (number, text)) in texts.iter().enumerate() {
+            if position > 0 {
+                parts.push(Block::Text("\n".to_owned()));
+            }
+            parts.push(self.inline(text, *number));
+        }
+        (vec![Item::Block(Block::Flow(parts))], end)
+    }
+
+    fn definition_list(&mut self, lines: &[Line], mut index: usize) -> (Vec<Item>, usize) {
+        let mut definitions = vec![];
+        loop {
+            let term_line = &lines[index];
+            // SemDoc has no counterpart for classifiers after the term, like `name : str`.
+            let term = term_line.text.split(" : ").next().unwrap_or_default();
+            let term = self.inline(term.trim(), term_line.number);
+            let end = indented_end(lines, index + 1);
+            let body = self.blocks(&dedent(&lines[index + 1..end]));
+            definitions.push(Definition {
+                term,
+                definitions: vec![Block::Paragraphs(body)],
+            });
+            let next = skip_blank(lines, end);
+            if next < lines.len() && is_definition_start(lines, next) {
+                index = next;
+            } else {
+                return (vec![Item::Block(Block::DefinitionList(definitions))], end);
+            }
+        }
+    }
+
+    /// Parses a paragraph. If it ends with `::`, the following indented lines are a literal
+    /// block.
+    fn paragraph(&mut self, lines: &[Line], index: usize) -> (Vec<Item>, usize) {
+        let end = block_end(lines, index);
+        let text = lines[index..end]
+            .iter()
+            .map(|line| line.text.trim())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let (text, is_literal) = match text.strip_suffix("::") {
+            Some(rest) if rest.is_empty() || rest.ends_with(char::is_whitespace) => {
+                (rest.trim_end(), true)
+            }
+            Some(_) => (&text[..text.len() - 1], true),
+            None => (text.as_str(), false),
+        };
+        let mut items = vec![];
+        if !text.is_empty() {
+            items.push(Item::Block(self.inline(text, lines[index].number)));
+        }
+        let literal_start = skip_blank(lines, end);
+        if !is_literal || !matches!(lines.get(literal_start), Some(line) if line.indent() > 0) {
+            return (items, end);
+        }
+        let literal_end = indented_end(lines, literal_start);
+        items.push(Item::Block(Block::Code {
+            language: self.default_language.clone(),
+            code: join_lines(&dedent(&lines[literal_start..literal_end])),
+        }));
+        (items, literal_end)
+    }
+
+    /// Parses constructs starting with `..`: Footnotes, targets, substitution definitions,
+    /// directives and comments.
+    fn explicit_markup(&mut self, lines: &[Line], index: usize) -> (Vec<Item>, usize) {
+        let number = lines[index].number;
+        let end = indented_end(lines, index + 1).max(index + 1);
+        let first = lines[index].text[2..].trim();
+        let body = dedent(&lines[index + 1..end]);
+
+        // Footnotes and citations, like `.. [1] Text`.
+        if let Some(label) = first
+            .strip_prefix('[')
+            .and_then(|rest| rest.split(']').next())
+        {
+            if !label.is_empty() && !label.contains(char::is_whitespace) {
+                let rest = first[label.len() + 2..].trim();
+                let mut content = vec![Line {
+                    number,
+                    text: rest.to_owned(),
+                }];
+                content.extend(body);
+                let id = footnote_id(&self.footnote_label(label, true));
+                let body = Block::Paragraphs(self.blocks(&content));
+                let footnote = Block::Anchor {
+                    id,
+                    content: Box::new(Block::Secondary(Box::new(body))),
+                };
+                return (vec![Item::Block(footnote)], end);
+            }
+        }
+        // Internal targets, like `.. _setup:`. External ones were collected before.
+        if let Some(target) = first.strip_prefix('_') {
+            return match split_target(target) {
+                Some((name, "")) if name != "_" => (vec![Item::Target(slug(name))], end),
+                _ => (vec![], end),
+            };
+        }
+        if let Some(definition) = first.strip_prefix('|') {
+            if let Some((_, directive)) = definition.split_once("| ") {
+                let name = directive.split("::").next().unwrap_or_default().trim();
+                if name != "replace" {
+                    let name = format!("|{}", name);
+                    self.warn(number, ConversionWarningKind::UnsupportedDirective(name));
+                }
+            }
+            return (vec![], end);
+        }
+        if let Some((name, argument)) = first.split_once("::") {
+            if !name.is_empty() && name.chars().all(is_name_char) {
+                let directive = directives::Directive::parse(name, argument, number, body);
+                let blocks = self.directive(directive);
+                return (blocks.into_iter().map(Item::Block).collect(), end);
+            }
+        }
+        // Everything else is a comment.
+        (vec![], end)
+    }
+
+    /// The label of a footnote. Auto-numbered footnotes, like `[#]`, get the next number.
+    fn footnote_label(&mut self, label: &str, is_definition: bool) -> String {
+        match label {
+            "#" | "*" => {
+                let counter = if is_definition {
+                    &mut self.auto_footnote_definitions
+                } else {
+                    &mut self.auto_footnote_references
+                };
+                *counter += 1;
+                counter.to_string()
+            }
+            label => label.trim_start_matches('#').to_owned(),
+        }
+    }
+}
+
+/// Turns the items into the parts of sections. Sections are anchored by the slug of their title
+/// inside the anchors of their explicit targets.
+fn sections(items: Vec<Item>) -> Vec<SectionItem<Pure>> {
+    items
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::Heading { level, title, ids } => {
+                let id = slug(&title.plain_text());
+                let mut ids = ids.into_iter().filter(|it| *it != id).collect::<Vec<_>>();
+                ids.push(id);
+                Some(SectionItem::Heading(
+                    Heading::new(level, title).with_ids(ids),
+                ))
+            }
+            Item::Fields(fields) => Some(SectionItem::Block(fields_to_block(fields))),
+            Item::Target(_) => None,
+            Item::Block(block) => Some(SectionItem::Block(block)),
+        })
+        .collect()
+}
+
+fn fields_to_block(fields: Vec<Field>) -> Block<Pure> {
+    Block::DefinitionList(
+        fields
+            .into_iter()
+            .map(|field| Definition {
+                term: Block::Text(field.name),
+                definitions: vec![field.body],
+            })
+            .collect(),
+    )
+}
+
+/// Wraps the block in anchors with the given IDs.
+fn anchored(ids: Vec<String>, block: Block<Pure>) -> Block<Pure> {
+    ids.into_iter()
+        .rev()
+        .fold(block, |block, id| Block::Anchor {
+            id,
+            content: Box::new(block),
+        })
+}
+
+/// Turns a lone top-level section title at the start into the title of the document and a field
+/// list right after it into the other metadata, like docutils does.
+fn document_info(items: &mut Vec<Item>) -> Metadata {
+    let mut metadata = Metadata::default();
+    let top_level_titles = items
+        .iter()
+        .filter(|item| matches!(item, Item::Heading { level: 0, .. }))
+        .count();
+    if top_level_titles == 1 {
+        if let Some(Item::Heading {
+            level: 0, title, ..
+        }) = items.first()
+        {
+            metadata.title = Some(title.plain_text());
+            items.remove(0);
+        }
+    }
+    if !matches!(items.first(), Some(Item::Fields(_))) {
+        return metadata;
+    }
+    let fields = match items.remove(0) {
+        Item::Fields(fields) => fields,
+        _ => unreachable!(),
+    };
+    for field in fields {
+        let name = field.name.to_lowercase();
+        match name.as_str() {
+            "author" => metadata.authors.push(field.text),
+            "authors" => metadata.authors.extend(
+                field
+                    .text
+                    .split([';', ','])
+                    .map(|author| author.trim().to_owned())
+                    .filter(|author| !author.is_empty()),
+            ),
+            "date" if metadata.date.is_none() && parse_date(&field.text).is_some() => {
+                metadata.date = parse_date(&field.text)
+            }
+            "tags" | "keywords" => metadata.tags.extend(
+                field
+                    .text
+                    .split(',')
+                    .map(|tag| tag.trim().to_owned())
+                    .filter(|tag| !tag.is_empty()),
+            ),
+            _ => metadata.custom.push((name, field.text)),
+        }
+    }
+    metadata
+}
+
+/// Parses dates like "2021-03-14".
+fn parse_date(text: &str) -> Option<TemporalValue> {
+    let mut parts = text.trim().splitn(3, '-');
+    let date = TemporalValue::Date {
+        year: parts.next()?.parse().ok()?,
+        month: parts.next()?.parse().ok()?,
+        day: parts.next()?.parse().ok()?,
+    };
+    if date.is_valid() {
+        Some(date)
+    } else {
+        None
+    }
+}
+
+/// Turns a name into an ID like docutils does, like "getting-started" for "Getting Started!".
+fn slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn footnote_id(label: &str) -> String {
+    format!("footnote-{}", label)
+}
+
+/// Splits a target like `` `Rust Book`: https://doc.rust-lang.org/book `` into its name and URL.
+/// The name of anonymous targets is "_".
+fn split_target(target: &str) -> Option<(&str, &str)> {
+    let (name, url) = match target.strip_prefix('`') {
+        Some(quoted) => {
+            let (name, rest) = quoted.split_once('`')?;
+            (name, rest.strip_prefix(':')?)
+        }
+        None => target.split_once(':')?,
+    };
+    Some((name.trim(), url.trim()))
+}
+
+/// Whether the line consists of a repeated punctuation character, like the underline of a title.
+fn is_adornment(text: &str) -> bool {
+    let mut chars = text.chars();
+    let first = match chars.next() {
+        Some(first) if first.is_ascii_punctuation() => first,
+        _ => return false,
+    };
+    text.len() >= 2 && chars.all(|c| c == first)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '+')
+}
+
+/// Parses the marker of a bullet list item, returning the marker, the rest of the line and the
+/// column where the content starts.
+fn bullet(text: &str) -> Option<(char, &str, usize)> {
+    let marker = text.chars().next()?;
+    if !matches!(marker, '*' | '+' | '-' | '•' | '‣' | '⁃') {
+        return None;
+    }
+    let rest = &text[marker.len_utf8()..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    let content = rest.trim_start();
+    Some((marker, content, 1 + rest.len() - content.len()))
+}
+
+struct Enumerator<'a> {
+    /// The number of the item, or `None` for auto-numbered items like `#.`.
+    number: Option<u64>,
+    /// The punctuation around the number, like "." for `1.` or "()" for `(1)`.
+    format: &'static str,
+    rest: &'a str,
+    column: usize,
+}
+
+fn enumerator(text: &str) -> Option<Enumerator<'_>> {
+    let (format, inner) = if let Some(rest) = text.strip_prefix('(') {
+        ("()", rest)
+    } else {
+        ("", text)
+    };
+    let length = inner
+        .find(|c: char| !c.is_ascii_digit() && c != '#')
+        .unwrap_or(inner.len());
+    let label = &inner[..length];
+    let number = match label {
+        "#" => None,
+        label if !label.is_empty() && !label.contains('#') => Some(label.parse().ok()?),
+        _ => return None,
+    };
+    let closing = inner[length..].chars().next()?;
+    let format = match (format, closing) {
+        ("()", ')') => "()",
+        ("", '.') => ".",
+        ("", ')') => ")",
+        _ => return None,
+    };
+    let rest = &inner[length + 1..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    let content = rest.trim_start();
+    Some(Enumerator {
+        number,
+        format,
+        rest: content,
+        column: text.len() - content.len(),
+    })
+}
+
+/// Parses the marker of a field, like `:Version:`, returning the name and the rest of the line.
+fn field_marker(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix(':')?;
+    let end = rest.find(':')?;
+    let name = &rest[..end];
+    let after = &rest[end + 1..];
+    if name.is_empty() || name.starts_with(' ') || name.contains('`') {
+        return None;
+    }
+    if !after.is_empty() && !after.starts_with(' ') {
+        return None;
+    }
+    Some((name, after.trim()))
+}
+
+/// Whether a definition list item starts at the line: A term followed by an indented definition.
+fn is_definition_start(lines: &[Line], index: usize) -> bool {
+    let text = &lines[index].text;
+    lines[index].indent() == 0
+        && !text.starts_with("..")
+        && bullet(text).is_none()
+        && enumerator(text).is_none()
+        && field_marker(text).is_none()
+        && matches!(lines.get(index + 1), Some(next) if !next.is_blank() && next.indent() > 0)
+}
+
+/// The lines of a list item or field: the rest of its first line and the following lines that
+/// are indented.
+fn list_item(lines: &[Line], index: usize, rest: &str, column: usize) -> (Vec<Line>, usize) {
+    let end = indented_end(lines, index + 1).max(index + 1);
+    let mut body = vec![Line {
+        number: lines[index].number,
+        text: rest.to_owned(),
+    }];
+    body.extend(lines[index + 1..end].iter().map(|line| Line {
+        number: line.number,
+        text: line.text[line.indent().min(column)..].to_owned(),
+    }));
+    (body, end)
+}
+
+/// Removes the common indentation of all lines but the first one, like for fields whose body
+/// starts on the line of the name.
+fn dedent_following(mut lines: Vec<Line>) -> Vec<Line> {
+    let following = dedent(&lines[1..]);
+    lines.truncate(1);
+    lines.extend(following);
+    lines
+}
+
+/// Removes the common indentation of the lines.
+fn dedent(lines: &[Line]) -> Vec<Line> {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_blank())
+        .map(Line::indent)
+        .min()
+        .unwrap_or_default();
+    lines
+        .iter()
+        .map(|line| Line {
+            number: line.number,
+            text: line.text.get(indent..).unwrap_or_default().to_owned(),
+        })
+        .collect()
+}
+
+/// The index after the indented lines starting at the given one. Blank lines at the end don't
+/// belong to them.
+fn indented_end(lines: &[Line], start: usize) -> usize {
+    let mut end = start;
+    while end < lines.len() && (lines[end].is_blank() || lines[end].indent() > 0) {
+        end += 1;
+    }
+    while end > start && lines[end - 1].is_blank() {
+        end -= 1;
+    }
+    end
+}
+
+/// The index of the next blank line.
+fn block_end(lines: &[Line], start: usize) -> usize {
+    (start..lines.len())
+        .find(|index| lines[*index].is_blank())
+        .unwrap_or(lines.len())
+}
+
+fn skip_blank(lines: &[Line], start: usize) -> usize {
+    (start..lines.len())
+        .find(|index| !lines[*index].is_blank())
+        .unwrap_or(lines.len())
+}
+
+fn join_lines(lines: &[Line]) -> String {
+    lines
+        .iter()
+        .map(|line| line.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn expand_tabs(text: &str) -> String {
+    let mut expanded = String::new();
+    for c in text.chars() {
+        if c == '\t' {
+            expanded.push_str(&" ".repeat(8 - expanded.chars().count() % 8));
+        } else {
+            expanded.push(c);
+        }
+    }
+    expanded
+}
+
+/// Removes whitespace around inline content.
+fn trim_inlines(block: Block<Pure>) -> Block<Pure> {
+    match block.simplify() {
+        Block::Text(text) => Block::Text(text.trim().to_owned()),
+        Block::Flow(mut children) => {
+            if let Some(Block::Text(text)) = children.first_mut() {
+                *text = text.trim_start().to_owned();
+            }
+            if let Some(Block::Text(text)) = children.last_mut() {
+                *text = text.trim_end().to_owned();
+            }
+            Block::Flow(children).simplify()
+        }
+        block => block,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use semdoc::Block::*;
+
+    fn convert(rst: &str) -> Block<Pure> {
+        rst_to_semdoc(rst).block.simplify()
+    }
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    fn section(id: &str, title: &str, body: Vec<Block<Pure>>) -> Block<Pure> {
+        Anchor {
+            id: id.to_owned(),
+            content: Box::new(Section {
+                title: Box::new(text(title)),
+                body: Box::new(Paragraphs(body).simplify()),
+            }),
+        }
+    }
+
+    #[test]
+    fn document_title_and_docinfo_become_metadata() {
+        let doc = rst_to_semdoc(
+            "=======\nManual\n=======\n\n:Author: Ada Lovelace\n:Date: 2021-03-14\n\
+             :Version: 1.0\n\nIntro.\n\nSetup\n=====\n\nInstall it.\n\nDetails\n-------\n\nMore.\n",
+        );
+        assert_eq!(doc.metadata.title, Some("Manual".to_owned()));
+        assert_eq!(doc.metadata.authors, vec!["Ada Lovelace".to_owned()]);
+        assert_eq!(
+            doc.metadata.date,
+            Some(TemporalValue::Date {
+                year: 2021,
+                month: 3,
+                day: 14
+            })
+        );
+        assert_eq!(doc.metadata.custom("version"), Some("1.0"));
+        assert_eq!(
+            doc.block.simplify(),
+            Paragraphs(vec![
+                text("Intro."),
+                section(
+                    "setup",
+                    "Setup",
+                    vec![
+                        text("Install it."),
+                        section("details", "Details", vec![text("More.")])
+                    ]
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn lists_and_literal_blocks() {
+        assert_eq!(
+            convert(
+                "* First\n* Second\n\n  continued.\n\n3. Three\n4. Four\n\n\
+                 Run this::\n\n    cargo run\n"
+            ),
+            Paragraphs(vec![
+                BulletList(vec![
+                    text("First"),
+                    Paragraphs(vec![text("Second"), text("continued.")]),
+                ]),
+                OrderedList {
+                    start: 3,
+                    numbering: None,
+                    items: vec![text("Three"), text("Four")],
+                },
+                text("Run this:"),
+                Code {
+                    language: None,
+                    code: "cargo run".to_owned()
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn admonitions_and_code_directives() {
+        assert_eq!(
+            convert(
+                ".. note:: Take care.\n\n.. warning::\n\n   It's hot.\n\n\
+                 .. code-block:: rust\n   :linenos:\n\n   fn main() {}\n"
+            ),
+            Paragraphs(vec![
+                Secondary(Box::new(Section {
+                    title: Box::new(text("Note")),
+                    body: Box::new(text("Take care.")),
+                })),
+                Section {
+                    title: Box::new(text("Warning")),
+                    body: Box::new(text("It's hot.")),
+                },
+                Code {
+                    language: Some("rust".to_owned()),
+                    code: "fn main() {}".to_owned()
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn references_resolve_to_links_and_anchors() {
+        assert_eq!(
+            convert(
+                "See `Rust`_, `the book <https://doc.rust-lang.org/book>`_, :ref:`setup` and \
+                 :ref:`the setup <setup>`.\n\n.. _Rust: https://rust-lang.org\n\n\
+                 .. _setup:\n\nInstallation\n============\n\nDone.\n"
+            ),
+            Paragraphs(vec![
+                Flow(vec![
+                    text("See "),
+                    Link {
+                        target: "https://rust-lang.org".to_owned(),
+                        content: Box::new(text("Rust")),
+                    },
+                    text(", "),
+                    Link {
+                        target: "https://doc.rust-lang.org/book".to_owned(),
+                        content: Box::new(text("the book")),
+                    },
+                    text(", "),
+                    CrossReference {
+                        target: "setup".to_owned(),
+                        content: Box::new(Empty),
+                    },
+                    text(" and "),
+                    CrossReference {
+                        target: "setup".to_owned(),
+                        content: Box::new(text("the setup")),
+                    },
+                    text("."),
+                ]),
+                Anchor {
+                    id: "setup".to_owned(),
+                    content: Box::new(section("installation", "Installation", vec![text("Done.")])),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn inline_markup() {
+        assert_eq!(
+            convert(
+                "Use *the* ``cargo``\n**tool**, H\\ :sub:`2`\\ O and |version|.\n\n\
+                     .. |version| replace:: 1.0\n"
+            ),
+            Flow(vec![
+                text("Use the "),
+                Code {
+                    language: None,
+                    code: "cargo".to_owned()
+                },
+                text(" tool, H2O and 1.0."),
+            ])
+        );
+        let (_, warnings) = rst_to_semdoc_with_warnings("A :kbd:`Ctrl` key.\n");
+        assert_eq!(
+            warnings,
+            vec![ConversionWarning {
+                location: Location::Line(1),
+                kind: ConversionWarningKind::UnsupportedRole("kbd".to_owned()),
+            }]
+        );
+    }
+
+    #[test]
+    fn footnotes() {
+        assert_eq!(
+            convert("Rust [#]_ is fast.\n\n.. [#] A language.\n"),
+            Paragraphs(vec![
+                Flow(vec![
+                    text("Rust "),
+                    CrossReference {
+                        target: "footnote-1".to_owned(),
+                        content: Box::new(text("1")),
+                    },
+                    text(" is fast."),
+                ]),
+                Anchor {
+                    id: "footnote-1".to_owned(),
+                    content: Box::new(Secondary(Box::new(text("A language.")))),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn tables() {
+        let table = Table {
+            header: vec![text("Name"), text("Age")],
+            rows: vec![
+                vec![text("Ada"), text("36")],
+                vec![text("Alan"), text("41")],
+            ],
+        };
+        assert_eq!(
+            convert("=====  ===\nName   Age\n=====  ===\nAda    36\nAlan   41\n=====  ===\n"),
+            table
+        );
+        assert_eq!(
+            convert(
+                "+------+-----+\n| Name | Age |\n+======+=====+\n| Ada  | 36  |\n\
+                 +------+-----+\n| Alan | 41  |\n+------+-----+\n"
+            ),
+            table
+        );
+        assert_eq!(
+            convert(
+                ".. list-table::\n   :header-rows: 1\n\n   * - Name\n     - Age\n   * - Ada\n\
+                 \x20    - 36\n   * - Alan\n     - 41\n"
+            ),
+            table
+        );
+        assert_eq!(
+            convert(".. csv-table::\n   :header: Name, Age\n\n   Ada, 36\n   Alan, 41\n"),
+            table
+        );
+    }
+
+    #[test]
+    fn glossary_terms_are_linked() {
+        assert_eq!(
+            convert(
+                "Each :term:`crate` compiles.\n\n.. glossary::\n\n   crate\n      A package.\n"
+            ),
+            Paragraphs(vec![
+                Flow(vec![
+                    text("Each "),
+                    TermUsage {
+                        term: "crate".to_owned(),
+                        content: Box::new(text("crate")),
+                    },
+                    text(" compiles."),
+                ]),
+                Glossary(vec![Definition {
+                    term: text("crate"),
+                    definitions: vec![text("A package.")],
+                }]),
+            ])
+        );
+    }
+
+    #[test]
+    fn unsupported_directives_are_dropped_with_a_warning() {
+        let (doc, warnings) =
+            rst_to_semdoc_with_warnings("Text.\n\n.. include:: other.rst\n\n.. contents::\n");
+        assert_eq!(doc.block.simplify(), text("Text."));
+        assert_eq!(
+            warnings,
+            vec![ConversionWarning {
+                location: Location::Line(3),
+                kind: ConversionWarningKind::UnsupportedDirective("include".to_owned()),
+            }]
+        );
+    }
+}
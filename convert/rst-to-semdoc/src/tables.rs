@@ -0,0 +1,155 @@
+//! Grid tables and simple tables. Cells spanning multiple columns or rows aren't supported.
+
+use crate::{block_end, dedent, Line, Parser};
+use semdoc::{Block, Pure};
+
+/// Whether the line is the border of a grid table, like `+-----+-----+`.
+pub(crate) fn is_grid_table_border(text: &str) -> bool {
+    (text.starts_with("+-") || text.starts_with("+="))
+        && text.ends_with('+')
+        && text.chars().all(|c| matches!(c, '+' | '-' | '='))
+}
+
+/// Whether the line is the border of a simple table, like `=====  =====`.
+pub(crate) fn is_simple_table_border(text: &str) -> bool {
+    text.starts_with('=') && text.contains(' ') && text.chars().all(|c| c == '=' || c == ' ')
+}
+
+/// The index after a simple table, which ends with a border followed by a blank line.
+pub(crate) fn simple_table_end(lines: &[Line], start: usize) -> usize {
+    (start + 1..lines.len())
+        .find(|index| {
+            is_simple_table_border(&lines[*index].text)
+                && lines.get(index + 1).map_or(true, Line::is_blank)
+        })
+        .map(|index| index + 1)
+        .unwrap_or_else(|| block_end(lines, start))
+}
+
+impl Parser {
+    pub(crate) fn grid_table(&mut self, lines: &[Line]) -> Block<Pure> {
+        let boundaries = lines[0]
+            .text
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| *c == '+')
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let mut header = None;
+        let mut rows = vec![];
+        let mut row: Vec<Vec<Line>> = vec![];
+        for line in &lines[1..] {
+            if is_grid_table_border(&line.text) {
+                let cells = self.cells(std::mem::take(&mut row));
+                if line.text.contains('=') && header.is_none() && rows.is_empty() {
+                    header = Some(cells);
+                } else {
+                    rows.push(cells);
+                }
+                continue;
+            }
+            let chars = line.text.chars().collect::<Vec<_>>();
+            let texts = boundaries.windows(2).map(|window| Line {
+                number: line.number,
+                text: chars
+                    .get(window[0] + 1..window[1].min(chars.len()))
+                    .unwrap_or_default()
+                    .iter()
+                    .collect::<String>()
+                    .trim_end()
+                    .to_owned(),
+            });
+            if row.is_empty() {
+                row = texts.map(|text| vec![text]).collect();
+            } else {
+                for (cell, text) in row.iter_mut().zip(texts) {
+                    cell.push(text);
+                }
+            }
+        }
+        Block::Table {
+            header: header.unwrap_or_default(),
+            rows,
+        }
+    }
+
+    pub(crate) fn simple_table(&mut self, lines: &[Line]) -> Block<Pure> {
+        let border = lines[0].text.chars().collect::<Vec<_>>();
+        let mut starts = vec![];
+        for (index, c) in border.iter().enumerate() {
+            if *c == '=' && (index == 0 || border[index - 1] == ' ') {
+                starts.push(index);
+            }
+        }
+        let borders = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| is_simple_table_border(&line.text))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let has_header = borders.len() >= 3;
+
+        let mut header = vec![];
+        let mut rows = vec![];
+        let mut row: Vec<Vec<Line>> = vec![];
+        for (index, line) in lines.iter().enumerate().skip(1) {
+            if is_simple_table_border(&line.text) || line.is_blank() {
+                if !row.is_empty() {
+                    let cells = self.cells(std::mem::take(&mut row));
+                    if has_header && index <= borders[1] {
+                        header = cells;
+                    } else {
+                        rows.push(cells);
+                    }
+                }
+                continue;
+            }
+            let chars = line.text.chars().collect::<Vec<_>>();
+            let texts = starts
+                .iter()
+                .enumerate()
+                .map(|(column, start)| {
+                    let end = match starts.get(column + 1) {
+                        Some(next) => (*next).min(chars.len()),
+                        None => chars.len(),
+                    };
+                    Line {
+                        number: line.number,
+                        text: chars
+                            .get(*start..end)
+                            .unwrap_or_default()
+                            .iter()
+                            .collect::<String>()
+                            .trim_end()
+                            .to_owned(),
+                    }
+                })
+                .collect::<Vec<_>>();
+            // Lines with an empty first column continue the previous row.
+            if texts[0].is_blank() && !row.is_empty() {
+                for (cell, text) in row.iter_mut().zip(texts) {
+                    cell.push(text);
+                }
+            } else {
+                if !row.is_empty() {
+                    rows.push(self.cells(std::mem::take(&mut row)));
+                }
+                row = texts.into_iter().map(|text| vec![text]).collect();
+            }
+        }
+        Block::Table { header, rows }
+    }
+
+    fn cells(&mut self, row: Vec<Vec<Line>>) -> Vec<Block<Pure>> {
+        row.into_iter()
+            .map(|lines| {
+                let mut blocks = self.blocks(&dedent(&lines));
+                match blocks.len() {
+                    0 => Block::Empty,
+                    1 => blocks.remove(0),
+                    _ => Block::Paragraphs(blocks),
+                }
+            })
+            .collect()
+    }
+}
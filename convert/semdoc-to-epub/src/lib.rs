@@ -0,0 +1,424 @@
+use semdoc::{Block, Pure, SemDoc};
+use semdoc_to_html::part_to_html;
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Cursor, Write},
+};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+mod package;
+use package::*;
+
+/// Turns a document into an EPUB 3 book.
+///
+/// Every top-level section becomes a chapter. Content between top-level sections becomes a
+/// chapter without a title. The navigation document lists the sections of all chapters, nested
+/// like in the document. Embedded images and media are saved as separate files in the book.
+pub fn semdoc_to_epub(doc: &SemDoc<Pure>) -> Vec<u8> {
+    let mut ids = doc
+        .block
+        .anchors()
+        .keys()
+        .map(|id| id.to_string())
+        .collect();
+    let root = anchor_sections(doc.block.clone().simplify(), &mut ids);
+
+    let mut resources = vec![];
+    let mut chapters = split_into_chapters(&root)
+        .into_iter()
+        .enumerate()
+        .map(|(index, block)| {
+            let file_name = format!("chapter-{}.xhtml", index + 1);
+            Chapter {
+                html: part_to_html(&root, &block, &mut resources),
+                navigation: navigation(&block, &file_name),
+                file_name,
+            }
+        })
+        .collect::<Vec<_>>();
+    link_across_chapters(&mut chapters);
+
+    let book = Book {
+        metadata: &doc.metadata,
+        identifier: identifier(doc),
+        chapters,
+        resources,
+    };
+    book.to_zip()
+}
+
+/// A part of the book that is saved in its own file.
+struct Chapter {
+    file_name: String,
+    html: String,
+    /// The sections in the chapter.
+    navigation: Vec<NavigationEntry>,
+}
+
+/// A section that readers can jump to.
+struct NavigationEntry {
+    /// The file and the ID of the section, like "chapter-2.xhtml#setup".
+    target: String,
+    title: String,
+    children: Vec<NavigationEntry>,
+}
+
+impl<'a> Book<'a> {
+    fn to_zip(&self) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        // Readers detect EPUBs by the uncompressed "mimetype" file at the start of the archive.
+        let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mut add = |path: &str, data: &[u8], options: FileOptions| {
+            zip.start_file(path, options)
+                .and_then(|_| Ok(zip.write_all(data)?))
+                .expect("Writing to memory can't fail.");
+        };
+        add("mimetype", b"application/epub+zip", stored);
+        add("META-INF/container.xml", CONTAINER.as_bytes(), deflated);
+        add("EPUB/package.opf", self.package().as_bytes(), deflated);
+        add(
+            "EPUB/nav.xhtml",
+            self.navigation_document().as_bytes(),
+            deflated,
+        );
+        for chapter in &self.chapters {
+            let path = format!("EPUB/{}", chapter.file_name);
+            add(&path, self.chapter_document(chapter).as_bytes(), deflated);
+        }
+        for resource in &self.resources {
+            // Most media formats are already compressed.
+            add(&format!("EPUB/{}", resource.path), &resource.data, stored);
+        }
+        zip.finish()
+            .expect("Writing to memory can't fail.")
+            .into_inner()
+    }
+}
+
+/// Gives every section an ID, so the navigation document can link to it.
+fn anchor_sections(block: Block<Pure>, ids: &mut HashSet<String>) -> Block<Pure> {
+    match block {
+        Block::Anchor { id, content } if matches!(*content, Block::Section { .. }) => {
+            Block::Anchor {
+                id,
+                content: Box::new(content.map_children(|child| anchor_sections(child, ids))),
+            }
+        }
+        Block::Section { title, body } => Block::Anchor {
+            id: unique_id(&title.plain_text(), ids),
+            content: Box::new(Block::Section {
+                title: Box::new(anchor_sections(*title, ids)),
+                body: Box::new(anchor_sections(*body, ids)),
+            }),
+        },
+        block => block.map_children(|child| anchor_sections(child, ids)),
+    }
+}
+
+/// Turns the text into an ID that isn't used yet, like the HTML exporter does.
+fn unique_id(text: &str, ids: &mut HashSet<String>) -> String {
+    let mut slug = String::new();
+    for c in text.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = match slug.trim_end_matches('-') {
+        "" => "section".to_owned(),
+        slug => slug.to_owned(),
+    };
+    let mut id = slug.clone();
+    let mut counter = 1;
+    while ids.contains(&id) {
+        counter += 1;
+        id = format!("{}-{}", slug, counter);
+    }
+    ids.insert(id.clone());
+    id
+}
+
+/// Splits the document along its top-level sections. Content between them is kept together.
+fn split_into_chapters(root: &Block<Pure>) -> Vec<Block<Pure>> {
+    let blocks = match root {
+        Block::Paragraphs(children) => children.clone(),
+        Block::Empty => vec![],
+        block => vec![block.clone()],
+    };
+    let mut chapters = vec![];
+    let mut content = vec![];
+    for block in blocks {
+        if is_section(&block) {
+            if !content.is_empty() {
+                chapters.push(Block::Paragraphs(std::mem::take(&mut content)).simplify());
+            }
+            chapters.push(block);
+        } else {
+            content.push(block);
+        }
+    }
+    if !content.is_empty() || chapters.is_empty() {
+        chapters.push(Block::Paragraphs(content).simplify());
+    }
+    chapters
+}
+
+fn is_section(block: &Block<Pure>) -> bool {
+    match block {
+        Block::Section { .. } => true,
+        Block::Anchor { content, .. } => is_section(content),
+        _ => false,
+    }
+}
+
+/// Lists the sections in the block. Like in the table of contents of HTML pages, sections in
+/// asides and collapsible content are left out.
+fn navigation(block: &Block<Pure>, file_name: &str) -> Vec<NavigationEntry> {
+    match block {
+        Block::Anchor { id, content } => match &**content {
+            Block::Section { title, body } => vec![NavigationEntry {
+                target: format!("{}#{}", file_name, id),
+                title: title.plain_text().replace('\n', " "),
+                children: navigation(body, file_name),
+            }],
+            content => navigation(content, file_name),
+        },
+        Block::Secondary(_) | Block::MoreInfo { .. } => vec![],
+        block => block
+            .children()
+            .into_iter()
+            .flat_map(|child| navigation(child, file_name))
+            .collect(),
+    }
+}
+
+/// Makes links to IDs in other chapters point to the files of those chapters.
+fn link_across_chapters(chapters: &mut [Chapter]) {
+    let mut files_by_id = HashMap::new();
+    for chapter in chapters.iter() {
+        for id in attribute_values(&chapter.html, " id=\"") {
+            files_by_id.insert(id, chapter.file_name.clone());
+        }
+    }
+    for chapter in chapters {
+        let mut html = String::with_capacity(chapter.html.len());
+        let mut rest = chapter.html.as_str();
+        while let Some(start) = rest.find(" href=\"#") {
+            let (before, link) = rest.split_at(start + " href=\"".len());
+            html.push_str(before);
+            let id = &link[1..link.find('"').unwrap_or(link.len())];
+            match files_by_id.get(id) {
+                Some(file_name) if *file_name != chapter.file_name => html.push_str(file_name),
+                _ => {}
+            }
+            rest = link;
+        }
+        html.push_str(rest);
+        chapter.html = html;
+    }
+}
+
+/// Finds the values of attributes in the HTML, given the start of the attribute, like ` id="`.
+fn attribute_values(html: &str, prefix: &str) -> Vec<String> {
+    html.match_indices(prefix)
+        .filter_map(|(start, _)| {
+            let value = &html[start + prefix.len()..];
+            Some(value[..value.find('"')?].to_owned())
+        })
+        .collect()
+}
+
+/// Derives a UUID from the content, so exporting the same document twice results in the same
+/// identifier, but different documents get different ones.
+fn identifier(doc: &SemDoc<Pure>) -> String {
+    use sha2::{Digest, Sha256};
+
+    let hash = Sha256::digest(doc.to_bytes());
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&hash[..16]);
+    // Mark it as a name-based UUID.
+    bytes[6] = (bytes[6] & 0x0f) | 0x50;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!(
+        "urn:uuid:{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use semdoc::{AttachedFile, Block::*, Metadata};
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    fn section(title: &str, body: Block<Pure>) -> Block<Pure> {
+        Section {
+            title: Box::new(text(title)),
+            body: Box::new(body),
+        }
+    }
+
+    /// Reads the files of the book in the order they are saved.
+    fn files(epub: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut archive = ZipArchive::new(Cursor::new(epub)).unwrap();
+        (0..archive.len())
+            .map(|index| {
+                let mut file = archive.by_index(index).unwrap();
+                let mut data = vec![];
+                file.read_to_end(&mut data).unwrap();
+                (file.name().to_owned(), data)
+            })
+            .collect()
+    }
+
+    fn file(epub: &[u8], name: &str) -> String {
+        let (_, data) = files(epub)
+            .into_iter()
+            .find(|(file_name, _)| file_name == name)
+            .unwrap();
+        String::from_utf8(data).unwrap()
+    }
+
+    fn book() -> SemDoc<Pure> {
+        SemDoc::new(Paragraphs(vec![
+            text("Preface."),
+            section(
+                "Setup",
+                Paragraphs(vec![
+                    text("Install it."),
+                    section("On Linux", text("Use a package manager.")),
+                ]),
+            ),
+            section(
+                "Usage",
+                Flow(vec![
+                    text("Do the "),
+                    CrossReference {
+                        target: "on-linux".to_owned(),
+                        content: Box::new(text("setup")),
+                    },
+                    text(" first."),
+                ]),
+            ),
+        ]))
+    }
+
+    #[test]
+    fn mimetype_comes_first_and_uncompressed() {
+        let epub = semdoc_to_epub(&book());
+        let mut archive = ZipArchive::new(Cursor::new(&epub)).unwrap();
+        let mimetype = archive.by_index(0).unwrap();
+        assert_eq!(mimetype.name(), "mimetype");
+        assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+        assert_eq!(file(&epub, "mimetype"), "application/epub+zip");
+        assert!(file(&epub, "META-INF/container.xml").contains("full-path=\"EPUB/package.opf\""));
+    }
+
+    #[test]
+    fn top_level_sections_become_chapters() {
+        let epub = semdoc_to_epub(&book());
+        let names = files(&epub)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "mimetype",
+                "META-INF/container.xml",
+                "EPUB/package.opf",
+                "EPUB/nav.xhtml",
+                "EPUB/chapter-1.xhtml",
+                "EPUB/chapter-2.xhtml",
+                "EPUB/chapter-3.xhtml",
+            ]
+        );
+        assert!(file(&epub, "EPUB/chapter-1.xhtml").contains("<p>Preface.</p>"));
+        let setup = file(&epub, "EPUB/chapter-2.xhtml");
+        assert!(setup.contains("<h1 id=\"setup\">Setup</h1>"));
+        assert!(setup.contains("<h2 id=\"on-linux\">On Linux</h2>"));
+
+        let package = file(&epub, "EPUB/package.opf");
+        assert!(package.contains(
+            "<spine>\n<itemref idref=\"chapter-1\" />\n<itemref idref=\"chapter-2\" />\n\
+             <itemref idref=\"chapter-3\" />\n</spine>"
+        ));
+    }
+
+    #[test]
+    fn navigation_follows_the_sections() {
+        let navigation = file(&semdoc_to_epub(&book()), "EPUB/nav.xhtml");
+        assert!(navigation.contains(
+            "<ol>\n<li><a href=\"chapter-2.xhtml#setup\">Setup</a>\n\
+             <ol>\n<li><a href=\"chapter-2.xhtml#on-linux\">On Linux</a></li>\n</ol>\n</li>\n\
+             <li><a href=\"chapter-3.xhtml#usage\">Usage</a></li>\n</ol>"
+        ));
+    }
+
+    #[test]
+    fn cross_references_point_to_other_chapters() {
+        let usage = file(&semdoc_to_epub(&book()), "EPUB/chapter-3.xhtml");
+        assert!(usage.contains("<a href=\"chapter-2.xhtml#on-linux\">setup</a>"));
+    }
+
+    #[test]
+    fn metadata_and_images_are_included() {
+        let doc = SemDoc::new(section(
+            "Logo",
+            Attachment(AttachedFile::new("The logo", "image/png", b"png".to_vec())),
+        ))
+        .with_metadata(Metadata {
+            title: Some("Brand Guide".to_owned()),
+            authors: vec!["Ada".to_owned()],
+            date: None,
+            tags: vec![],
+            custom: vec![
+                ("lang".to_owned(), "en".to_owned()),
+                ("license".to_owned(), "CC-BY-4.0".to_owned()),
+            ],
+        });
+        let epub = semdoc_to_epub(&doc);
+
+        let package = file(&epub, "EPUB/package.opf");
+        assert!(package.contains("<dc:title>Brand Guide</dc:title>"));
+        assert!(package.contains("<dc:creator>Ada</dc:creator>"));
+        assert!(package.contains("<dc:language>en</dc:language>"));
+        assert!(package.contains("<dc:rights>CC-BY-4.0</dc:rights>"));
+        assert!(package.contains("<meta property=\"dcterms:modified\">"));
+        assert!(package
+            .contains("<item id=\"media-1\" href=\"media/1.png\" media-type=\"image/png\" />"));
+
+        let chapter = file(&epub, "EPUB/chapter-1.xhtml");
+        assert!(chapter.contains("<img src=\"media/1.png\" alt=\"The logo\" />"));
+        assert!(files(&epub).contains(&("EPUB/media/1.png".to_owned(), b"png".to_vec())));
+    }
+
+    #[test]
+    fn identifiers_are_stable() {
+        let package = file(&semdoc_to_epub(&book()), "EPUB/package.opf");
+        let identifier = identifier(&book());
+        assert!(identifier.starts_with("urn:uuid:"));
+        assert_eq!(identifier.len(), "urn:uuid:".len() + 36);
+        assert!(package.contains(&identifier));
+    }
+
+    #[test]
+    fn instants_are_formatted_in_utc() {
+        assert_eq!(package::instant(1615734566), "2021-03-14T15:09:26Z");
+    }
+}
@@ -0,0 +1,254 @@
+//! The files that describe the book, like the package document with the metadata.
+
+use crate::{Chapter, NavigationEntry};
+use semdoc::{days_to_civil, Metadata, TemporalValue};
+use semdoc_to_html::Resource;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) struct Book<'a> {
+    pub metadata: &'a Metadata,
+    pub identifier: String,
+    pub chapters: Vec<Chapter>,
+    pub resources: Vec<Resource>,
+}
+
+/// Tells readers where the package document is.
+pub(crate) const CONTAINER: &str = "<?xml version=\"1.0\" encoding=\"utf-8\"?>
+<container xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\" version=\"1.0\">
+<rootfiles>
+<rootfile full-path=\"EPUB/package.opf\" media-type=\"application/oebps-package+xml\" />
+</rootfiles>
+</container>
+";
+
+impl<'a> Book<'a> {
+    fn title(&self) -> String {
+        match &self.metadata.title {
+            Some(title) => title.clone(),
+            None => self
+                .chapters
+                .iter()
+                .flat_map(|chapter| &chapter.navigation)
+                .map(|entry| entry.title.clone())
+                .next()
+                .unwrap_or_else(|| "Document".to_owned()),
+        }
+    }
+
+    /// The language of the book. EPUBs require one, so it's "und" (undetermined) if the document
+    /// doesn't specify it.
+    fn language(&self) -> String {
+        self.metadata
+            .custom("lang")
+            .or_else(|| self.metadata.custom("language"))
+            .unwrap_or("und")
+            .to_owned()
+    }
+
+    /// Lists the metadata, all files and the order of the chapters.
+    pub fn package(&self) -> String {
+        let metadata = self.metadata;
+        let mut entries = vec![
+            format!(
+                "<dc:identifier id=\"book-id\">{}</dc:identifier>",
+                escape(&self.identifier)
+            ),
+            format!("<dc:title>{}</dc:title>", escape(&self.title())),
+            format!("<dc:language>{}</dc:language>", escape(&self.language())),
+        ];
+        for author in &metadata.authors {
+            entries.push(format!("<dc:creator>{}</dc:creator>", escape(author)));
+        }
+        if let Some(date) = metadata.date.and_then(date) {
+            entries.push(format!("<dc:date>{}</dc:date>", date));
+        }
+        for tag in &metadata.tags {
+            entries.push(format!("<dc:subject>{}</dc:subject>", escape(tag)));
+        }
+        for (key, value) in &metadata.custom {
+            entries.push(match key.to_lowercase().as_str() {
+                "lang" | "language" => continue,
+                "description" | "publisher" | "rights" | "source" => format!(
+                    "<dc:{key}>{}</dc:{key}>",
+                    escape(value),
+                    key = key.to_lowercase()
+                ),
+                "license" => format!("<dc:rights>{}</dc:rights>", escape(value)),
+                _ => format!(
+                    "<meta name=\"{}\" content=\"{}\" />",
+                    escape(key),
+                    escape(value)
+                ),
+            });
+        }
+        entries.push(format!(
+            "<meta property=\"dcterms:modified\">{}</meta>",
+            modified(metadata.date)
+        ));
+
+        let mut manifest = vec![
+            "<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" \
+             properties=\"nav\" />"
+                .to_owned(),
+        ];
+        let mut spine = vec![];
+        for chapter in &self.chapters {
+            let id = chapter.file_name.trim_end_matches(".xhtml");
+            // Media that is streamed from the web has to be declared.
+            let properties = if chapter.html.contains(" src=\"http") {
+                " properties=\"remote-resources\""
+            } else {
+                ""
+            };
+            manifest.push(format!(
+                "<item id=\"{}\" href=\"{}\" media-type=\"application/xhtml+xml\"{} />",
+                id, chapter.file_name, properties
+            ));
+            spine.push(format!("<itemref idref=\"{}\" />", id));
+        }
+        for (index, resource) in self.resources.iter().enumerate() {
+            manifest.push(format!(
+                "<item id=\"media-{}\" href=\"{}\" media-type=\"{}\" />",
+                index + 1,
+                escape(&resource.path),
+                escape(&resource.mime_type)
+            ));
+        }
+
+        format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" \
+             unique-identifier=\"book-id\" xml:lang=\"{}\">\n\
+             <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n{}\n</metadata>\n\
+             <manifest>\n{}\n</manifest>\n<spine>\n{}\n</spine>\n</package>\n",
+            escape(&self.language()),
+            entries.join("\n"),
+            manifest.join("\n"),
+            spine.join("\n"),
+        )
+    }
+
+    /// The table of contents. If the document has no sections, it links to the first chapter.
+    pub fn navigation_document(&self) -> String {
+        let mut entries = self
+            .chapters
+            .iter()
+            .flat_map(|chapter| navigation_to_html(&chapter.navigation))
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            entries.push(format!(
+                "<li><a href=\"{}\">{}</a></li>",
+                self.chapters[0].file_name,
+                escape(&self.title())
+            ));
+        }
+        self.xhtml(
+            "Contents",
+            &format!(
+                "<nav epub:type=\"toc\" id=\"toc\">\n<h1>Contents</h1>\n<ol>\n{}\n</ol>\n</nav>",
+                entries.join("\n")
+            ),
+        )
+    }
+
+    pub fn chapter_document(&self, chapter: &Chapter) -> String {
+        let title = match chapter.navigation.first() {
+            Some(entry) => entry.title.clone(),
+            None => self.title(),
+        };
+        self.xhtml(&title, &chapter.html)
+    }
+
+    fn xhtml(&self, title: &str, body: &str) -> String {
+        let language = escape(&self.language());
+        format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE html>\n\
+             <html xmlns=\"http://www.w3.org/1999/xhtml\" \
+             xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"{}\" xml:lang=\"{}\">\n\
+             <head>\n<meta charset=\"utf-8\" />\n<title>{}</title>\n</head>\n\
+             <body>\n{}\n</body>\n</html>\n",
+            language,
+            language,
+            escape(title),
+            body
+        )
+    }
+}
+
+fn navigation_to_html(entries: &[NavigationEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|entry| {
+            let children = navigation_to_html(&entry.children);
+            format!(
+                "<li><a href=\"{}\">{}</a>{}</li>",
+                escape(&entry.target),
+                escape(&entry.title),
+                if children.is_empty() {
+                    String::new()
+                } else {
+                    format!("\n<ol>\n{}\n</ol>\n", children.join("\n"))
+                }
+            )
+        })
+        .collect()
+}
+
+/// Formats dates and instants like "2021-03-14" and "2021-03-14T15:09:26Z". Durations aren't
+/// dates.
+fn date(value: TemporalValue) -> Option<String> {
+    match value {
+        TemporalValue::Date { year, month, day } => {
+            Some(format!("{:04}-{:02}-{:02}", year, month, day))
+        }
+        TemporalValue::Instant(seconds) => Some(instant(seconds)),
+        TemporalValue::Duration(_) => None,
+    }
+}
+
+/// When the book was last changed. That's the date of the document or, if it doesn't have one,
+/// the time of the export.
+fn modified(date: Option<TemporalValue>) -> String {
+    match date {
+        Some(TemporalValue::Date { year, month, day }) => {
+            format!("{:04}-{:02}-{:02}T00:00:00Z", year, month, day)
+        }
+        Some(TemporalValue::Instant(seconds)) => instant(seconds),
+        _ => instant(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+/// Formats seconds since the Unix epoch in UTC.
+pub(crate) fn instant(seconds: i64) -> String {
+    let days = seconds.div_euclid(86400);
+    let seconds_of_day = seconds.rem_euclid(86400);
+    let (year, month, day) = days_to_civil(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60
+    )
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
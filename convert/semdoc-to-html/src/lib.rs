@@ -0,0 +1,997 @@
+use semdoc::{
+    days_to_civil, normalize_term, AttachedFile, BibliographyEntry, Block, Definition,
+    HighlightReason, ListNumbering, Media, MediaSource, Metadata, Pure, SemDoc, TemporalValue,
+};
+use std::collections::HashSet;
+
+/// The light and dark themes. Browsers choose one based on `prefers-color-scheme`.
+const STYLE: &str = include_str!("style.css");
+
+/// Turns a document into a standalone HTML5 page.
+///
+/// The page has a header with the metadata, a table of contents and the content as the main
+/// landmark. Sections become `<section>`s with headings whose levels follow the nesting of the
+/// sections. The output is also well-formed XML, so it can be used as XHTML.
+pub fn semdoc_to_html(doc: &SemDoc<Pure>) -> String {
+    let metadata = &doc.metadata;
+    // The title of the document is the only first-level heading.
+    let first_level = if metadata.title.is_some() { 2 } else { 1 };
+    let mut renderer = Renderer::new(&doc.block);
+    let content = renderer.block(&doc.block, first_level);
+
+    let title = match &metadata.title {
+        Some(title) => title.clone(),
+        None => renderer
+            .outline
+            .first()
+            .map(|entry| entry.title.clone())
+            .unwrap_or_else(|| "Document".to_owned()),
+    };
+    let language = metadata
+        .custom("lang")
+        .or_else(|| metadata.custom("language"))
+        .map(|language| format!(" lang=\"{}\"", escape(language)))
+        .unwrap_or_default();
+
+    let mut head = vec![
+        "<meta charset=\"utf-8\" />".to_owned(),
+        "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\" />".to_owned(),
+        "<meta name=\"color-scheme\" content=\"light dark\" />".to_owned(),
+        format!("<title>{}</title>", escape(&title)),
+    ];
+    head.extend(meta_tags(metadata));
+    head.push(format!("<style>\n{}</style>", STYLE));
+
+    let mut body = vec![format!(
+        "<a class=\"skip-link\" href=\"#{}\">Skip to content</a>",
+        MAIN_ID
+    )];
+    if let Some(header) = header(metadata) {
+        body.push(header);
+    }
+    if !renderer.outline.is_empty() {
+        body.push(format!(
+            "<nav id=\"{id}\" aria-labelledby=\"{id}-title\">\n\
+             <h2 id=\"{id}-title\">Contents</h2>\n{}\n</nav>",
+            outline_to_html(&renderer.outline),
+            id = TOC_ID,
+        ));
+    }
+    body.push(format!("<main id=\"{}\">\n{}\n</main>", MAIN_ID, content));
+
+    format!(
+        "<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\"{}>\n<head>\n{}\n</head>\n\
+         <body>\n{}\n</body>\n</html>\n",
+        language,
+        head.join("\n"),
+        body.join("\n"),
+    )
+}
+
+/// Turns a block into HTML that can be embedded into other pages. Top-level sections get `<h1>`
+/// headings.
+pub fn block_to_html(block: &Block<Pure>) -> String {
+    Renderer::new(block).block(block, 1)
+}
+
+/// A file that embedded content of a document was saved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+    /// The relative path the HTML refers to the file with, like "media/1.png".
+    pub path: String,
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Turns a part of a document, like a chapter of a book, into HTML that can be embedded into
+/// other pages. Top-level sections get `<h1>` headings.
+///
+/// Cross-references and glossary terms are resolved in the whole document. Instead of embedding
+/// images and media as data URIs, they are referred to by paths and added to the resources.
+pub fn part_to_html(
+    root: &Block<Pure>,
+    part: &Block<Pure>,
+    resources: &mut Vec<Resource>,
+) -> String {
+    let mut renderer = Renderer::new(root);
+    renderer.resources = Some(std::mem::take(resources));
+    let html = renderer.block(part, 1);
+    *resources = renderer.resources.unwrap_or_default();
+    html
+}
+
+const MAIN_ID: &str = "content";
+const TOC_ID: &str = "table-of-contents";
+
+fn meta_tags(metadata: &Metadata) -> Vec<String> {
+    let mut tags = vec![];
+    let mut meta = |name: &str, content: &str| {
+        tags.push(format!(
+            "<meta name=\"{}\" content=\"{}\" />",
+            escape(name),
+            escape(content)
+        ))
+    };
+    for author in &metadata.authors {
+        meta("author", author);
+    }
+    if !metadata.tags.is_empty() {
+        meta("keywords", &metadata.tags.join(", "));
+    }
+    if let Some(date) = metadata.date {
+        meta("date", &datetime(date));
+    }
+    for (key, value) in &metadata.custom {
+        if key != "lang" && key != "language" {
+            meta(key, value);
+        }
+    }
+    tags
+}
+
+fn header(metadata: &Metadata) -> Option<String> {
+    let mut lines = vec![];
+    if let Some(title) = &metadata.title {
+        lines.push(format!("<h1>{}</h1>", escape(title)));
+    }
+    let mut byline = vec![];
+    if !metadata.authors.is_empty() {
+        byline.push(format!("By {}", escape(&metadata.authors.join(", "))));
+    }
+    if let Some(date) = metadata.date {
+        byline.push(format!(
+            "<time datetime=\"{}\">{}</time>",
+            datetime(date),
+            readable_datetime(date)
+        ));
+    }
+    if !byline.is_empty() {
+        lines.push(format!("<p class=\"byline\">{}</p>", byline.join(" · ")));
+    }
+    if !metadata.tags.is_empty() {
+        lines.push(format!(
+            "<p class=\"tags\">Tags: {}</p>",
+            escape(&metadata.tags.join(", "))
+        ));
+    }
+    if lines.is_empty() {
+        None
+    } else {
+        Some(format!("<header>\n{}\n</header>", lines.join("\n")))
+    }
+}
+
+/// A section that appears in the table of contents.
+struct OutlineEntry {
+    level: usize,
+    id: String,
+    title: String,
+}
+
+/// Turns the outline into nested lists. Sections are nested in the closest previous section with
+/// a lower level.
+fn outline_to_html(entries: &[OutlineEntry]) -> String {
+    let base_level = entries.iter().map(|entry| entry.level).min().unwrap_or(1);
+    let mut items = vec![];
+    let mut index = 0;
+    while index < entries.len() {
+        let entry = &entries[index];
+        let num_children = entries[(index + 1)..]
+            .iter()
+            .take_while(|child| child.level > base_level)
+            .count();
+        let children = &entries[(index + 1)..(index + 1 + num_children)];
+        items.push(format!(
+            "<li><a href=\"#{}\">{}</a>{}</li>",
+            escape(&entry.id),
+            escape(&entry.title),
+            if children.is_empty() {
+                String::new()
+            } else {
+                format!("\n{}\n", outline_to_html(children))
+            }
+        ));
+        index += 1 + num_children;
+    }
+    format!("<ol>\n{}\n</ol>", items.join("\n"))
+}
+
+struct Renderer<'a> {
+    root: &'a Block<Pure>,
+    /// IDs that are already used in the page, so generated IDs don't clash with them.
+    ids: HashSet<String>,
+    /// Terms that are defined in a glossary of the document.
+    glossary: HashSet<String>,
+    /// The sections of the document in order.
+    outline: Vec<OutlineEntry>,
+    /// Sections inside asides and collapsible content aren't part of the outline.
+    is_in_outline: bool,
+    /// If set, embedded data is saved as files instead of data URIs.
+    resources: Option<Vec<Resource>>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(root: &'a Block<Pure>) -> Self {
+        let glossary = root.glossary().into_keys().collect::<HashSet<_>>();
+        let mut ids = HashSet::new();
+        ids.insert(MAIN_ID.to_owned());
+        ids.insert(TOC_ID.to_owned());
+        ids.insert(format!("{}-title", TOC_ID));
+        ids.extend(root.anchors().keys().map(|id| id.to_string()));
+        ids.extend(
+            root.bibliography()
+                .keys()
+                .map(|key| format!("cite-{}", key)),
+        );
+        ids.extend(glossary.iter().map(|term| term_id(term)));
+        Self {
+            root,
+            ids,
+            glossary,
+            outline: vec![],
+            is_in_outline: true,
+            resources: None,
+        }
+    }
+
+    /// Returns a new ID based on the text that isn't used in the page yet.
+    fn unique_id(&mut self, text: &str) -> String {
+        let mut slug = String::new();
+        for c in text.to_lowercase().chars() {
+            if c.is_alphanumeric() {
+                slug.push(c);
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        let slug = match slug.trim_end_matches('-') {
+            "" => "section".to_owned(),
+            slug => slug.to_owned(),
+        };
+        let mut id = slug.clone();
+        let mut counter = 1;
+        while self.ids.contains(&id) {
+            counter += 1;
+            id = format!("{}-{}", slug, counter);
+        }
+        self.ids.insert(id.clone());
+        id
+    }
+
+    /// Renders a block that stands on its own, like a paragraph or a list.
+    ///
+    /// The `level` is the heading level of sections in this block.
+    fn block(&mut self, block: &Block<Pure>, level: usize) -> String {
+        use Block::*;
+
+        match block {
+            Error(_) | Empty => String::new(),
+            Paragraphs(children) => children
+                .iter()
+                .map(|child| self.block(child, level))
+                .filter(|html| !html.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Section { title, body } => self.section(None, title, body, level),
+            Anchor { id, content } => match &**content {
+                Section { title, body } => self.section(Some(id), title, body, level),
+                content if is_inline(content) => {
+                    format!("<p id=\"{}\">{}</p>", escape(id), self.inline(content))
+                }
+                content => format!(
+                    "<div id=\"{}\">\n{}\n</div>",
+                    escape(id),
+                    self.block(content, level)
+                ),
+            },
+            TopicChange => "<hr />".to_owned(),
+            BulletList(items) => format!("<ul>\n{}\n</ul>", self.list_items(items, level)),
+            OrderedList {
+                start,
+                numbering,
+                items,
+            } => {
+                let mut attributes = String::new();
+                if *start != 1 {
+                    attributes.push_str(&format!(" start=\"{}\"", start));
+                }
+                if let Some(numbering) = numbering {
+                    attributes.push_str(&format!(" class=\"{}\"", numbering_class(*numbering)));
+                }
+                format!(
+                    "<ol{}>\n{}\n</ol>",
+                    attributes,
+                    self.list_items(items, level)
+                )
+            }
+            Checklist(items) => format!(
+                "<ul class=\"checklist\">\n{}\n</ul>",
+                items
+                    .iter()
+                    .map(|item| format!(
+                        "<li><input type=\"checkbox\" disabled=\"disabled\"{} />{}</li>",
+                        if item.checked {
+                            " checked=\"checked\""
+                        } else {
+                            ""
+                        },
+                        self.list_item(&item.content, level)
+                    ))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            DefinitionList(definitions) => format!(
+                "<dl>\n{}\n</dl>",
+                self.definitions(definitions, level, false)
+            ),
+            Glossary(definitions) => format!(
+                "<dl class=\"glossary\">\n{}\n</dl>",
+                self.definitions(definitions, level, true)
+            ),
+            Table { header, rows } => self.table(header, rows),
+            Code { language, code } => format!(
+                "<pre><code{}>{}</code></pre>",
+                match language {
+                    Some(language) => format!(" class=\"language-{}\"", escape(language)),
+                    None => String::new(),
+                },
+                escape(code)
+            ),
+            Quote(content) => format!(
+                "<blockquote>\n{}\n</blockquote>",
+                self.block(content, level)
+            ),
+            Bibliography(entries) => format!(
+                "<ol class=\"bibliography\">\n{}\n</ol>",
+                entries
+                    .iter()
+                    .map(bibliography_entry)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            Audio(media) => self.media("audio", media, level),
+            Video(media) => self.media("video", media, level),
+            Attachment(attachment) => format!("<p>{}</p>", self.attachment(attachment)),
+            Highlighted { content, reason } if !is_inline(content) => format!(
+                "<div class=\"{}\">\n{}\n</div>",
+                highlight_class(*reason),
+                self.block(content, level)
+            ),
+            Secondary(content) => {
+                let content = self.without_outline(|renderer| renderer.block(content, level));
+                format!("<aside role=\"note\">\n{}\n</aside>", content)
+            }
+            MoreInfo { summary, body } => {
+                let summary = self.inline(summary);
+                let body = self.without_outline(|renderer| renderer.block(body, level));
+                format!(
+                    "<details>\n<summary>{}</summary>\n{}\n</details>",
+                    summary, body
+                )
+            }
+            Signed { content, .. } | Commented { content, .. } => self.block(content, level),
+            Text(_)
+            | Flow(_)
+            | Link { .. }
+            | TermUsage { .. }
+            | CrossReference { .. }
+            | Citation(_)
+            | Temporal { .. }
+            | Highlighted { .. } => {
+                let inline = self.inline(block);
+                if inline.trim().is_empty() {
+                    String::new()
+                } else {
+                    format!("<p>{}</p>", inline.trim())
+                }
+            }
+        }
+    }
+
+    fn section(
+        &mut self,
+        id: Option<&str>,
+        title: &Block<Pure>,
+        body: &Block<Pure>,
+        level: usize,
+    ) -> String {
+        let title_text = title.plain_text().replace('\n', " ");
+        let id = match id {
+            Some(id) => id.to_owned(),
+            None => self.unique_id(&title_text),
+        };
+        if self.is_in_outline {
+            self.outline.push(OutlineEntry {
+                level,
+                id: id.clone(),
+                title: title_text,
+            });
+        }
+        let title = self.inline(title);
+        // HTML only has six heading levels, deeper headings use ARIA.
+        let heading = if level <= 6 {
+            format!(
+                "<h{level} id=\"{}\">{}</h{level}>",
+                escape(&id),
+                title,
+                level = level
+            )
+        } else {
+            format!(
+                "<div role=\"heading\" aria-level=\"{}\" id=\"{}\">{}</div>",
+                level,
+                escape(&id),
+                title
+            )
+        };
+        let body = self.block(body, level + 1);
+        format!(
+            "<section aria-labelledby=\"{}\">\n{}{}\n</section>",
+            escape(&id),
+            heading,
+            if body.is_empty() {
+                String::new()
+            } else {
+                format!("\n{}", body)
+            }
+        )
+    }
+
+    fn without_outline(&mut self, render: impl FnOnce(&mut Self) -> String) -> String {
+        let was_in_outline = self.is_in_outline;
+        self.is_in_outline = false;
+        let html = render(self);
+        self.is_in_outline = was_in_outline;
+        html
+    }
+
+    fn list_items(&mut self, items: &[Block<Pure>], level: usize) -> String {
+        items
+            .iter()
+            .map(|item| format!("<li>{}</li>", self.list_item(item, level)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Short items stay on the line of the list marker.
+    fn list_item(&mut self, item: &Block<Pure>, level: usize) -> String {
+        if is_inline(item) {
+            self.inline(item)
+        } else {
+            format!("\n{}\n", self.block(item, level))
+        }
+    }
+
+    fn definitions(
+        &mut self,
+        definitions: &[Definition<Pure>],
+        level: usize,
+        is_glossary: bool,
+    ) -> String {
+        let mut lines = vec![];
+        for definition in definitions {
+            let term = self.inline(&definition.term);
+            lines.push(if is_glossary {
+                let id = term_id(&normalize_term(&definition.term.plain_text()));
+                format!("<dt><dfn id=\"{}\">{}</dfn></dt>", escape(&id), term)
+            } else {
+                format!("<dt>{}</dt>", term)
+            });
+            for details in &definition.definitions {
+                lines.push(format!("<dd>{}</dd>", self.list_item(details, level)));
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn table(&mut self, header: &[Block<Pure>], rows: &[Vec<Block<Pure>>]) -> String {
+        let mut row = |cells: &[Block<Pure>], tag: &str, attributes: &str| {
+            format!(
+                "<tr>{}</tr>",
+                cells
+                    .iter()
+                    .map(|cell| format!(
+                        "<{tag}{}>{}</{tag}>",
+                        attributes,
+                        self.inline(cell),
+                        tag = tag
+                    ))
+                    .collect::<String>()
+            )
+        };
+        let header = row(header, "th", " scope=\"col\"");
+        let rows = rows
+            .iter()
+            .map(|cells| row(cells, "td", ""))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "<table>\n<thead>\n{}\n</thead>\n<tbody>\n{}\n</tbody>\n</table>",
+            header, rows
+        )
+    }
+
+    fn media(&mut self, kind: &str, media: &Media<Pure>, level: usize) -> String {
+        let source = match &media.source {
+            MediaSource::Embedded(data) => self.embed(&media.mime_type, data),
+            MediaSource::External(uri) => uri.clone(),
+        };
+        let mut lines = vec![
+            format!("<figure class=\"{}\">", kind),
+            format!(
+                "<{kind} controls=\"controls\" preload=\"metadata\">\
+                 <source src=\"{}\" type=\"{}\" /></{kind}>",
+                escape(&source),
+                escape(&media.mime_type),
+                kind = kind,
+            ),
+        ];
+        let transcript = self.block(&media.transcript, level);
+        if !transcript.is_empty() {
+            lines.push(format!(
+                "<figcaption>\n<details>\n<summary>Transcript</summary>\n{}\n</details>\n\
+                 </figcaption>",
+                transcript
+            ));
+        }
+        lines.push("</figure>".to_owned());
+        lines.join("\n")
+    }
+
+    /// Renders a block inside a line of text. Blocks that can't be inside text become their plain
+    /// text.
+    fn inline(&mut self, block: &Block<Pure>) -> String {
+        use Block::*;
+
+        match block {
+            Error(_) | Empty => String::new(),
+            Text(text) => escape(text).replace('\n', "<br />"),
+            Flow(children) => children.iter().map(|child| self.inline(child)).collect(),
+            Code { code, .. } => format!("<code>{}</code>", escape(code)),
+            Link { target, content } => {
+                let content = match self.inline(content) {
+                    content if content.is_empty() => escape(target),
+                    content => content,
+                };
+                link(target, content)
+            }
+            CrossReference { target, content } => {
+                let content = match self.inline(content) {
+                    content if content.is_empty() => escape(&self.describe(target)),
+                    content => content,
+                };
+                format!("<a href=\"#{}\">{}</a>", escape(target), content)
+            }
+            TermUsage { term, content } => {
+                let content = self.inline(content);
+                let term = normalize_term(term);
+                if self.glossary.contains(&term) {
+                    format!(
+                        "<a class=\"term\" href=\"#{}\">{}</a>",
+                        escape(&term_id(&term)),
+                        content
+                    )
+                } else {
+                    content
+                }
+            }
+            Citation(keys) => format!(
+                "<span class=\"citation\">[{}]</span>",
+                keys.iter()
+                    .map(|key| format!("<a href=\"#cite-{}\">{}</a>", escape(key), escape(key)))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            Temporal { value, text } => format!(
+                "<time datetime=\"{}\">{}</time>",
+                datetime(*value),
+                escape(text)
+            ),
+            Highlighted { content, reason } => format!(
+                "<mark class=\"{}\">{}</mark>",
+                highlight_class(*reason),
+                self.inline(content)
+            ),
+            Quote(content) => format!("<q>{}</q>", self.inline(content)),
+            Secondary(content) => format!("<small>{}</small>", self.inline(content)),
+            Anchor { id, content } => {
+                format!(
+                    "<span id=\"{}\">{}</span>",
+                    escape(id),
+                    self.inline(content)
+                )
+            }
+            Signed { content, .. } | Commented { content, .. } => self.inline(content),
+            Attachment(attachment) => self.attachment(attachment),
+            Audio(media) | Video(media) => match &media.source {
+                MediaSource::External(uri) => link(
+                    uri,
+                    match self.inline(&media.transcript) {
+                        transcript if transcript.is_empty() => escape(uri),
+                        transcript => transcript,
+                    },
+                ),
+                MediaSource::Embedded(_) => self.inline(&media.transcript),
+            },
+            _ => escape(&block.plain_text().replace('\n', " ")),
+        }
+    }
+
+    /// Shows images, other files can be downloaded.
+    fn attachment(&mut self, attachment: &AttachedFile) -> String {
+        if attachment.mime_type.starts_with("image/") {
+            format!(
+                "<img src=\"{}\" alt=\"{}\" />",
+                escape(&self.embed(&attachment.mime_type, &attachment.data)),
+                escape(&attachment.name)
+            )
+        } else {
+            format!(
+                "<a download=\"{}\" href=\"{}\">{}</a> ({} bytes)",
+                escape(&attachment.name),
+                data_uri(&attachment.mime_type, &attachment.data),
+                escape(&attachment.name),
+                attachment.size
+            )
+        }
+    }
+
+    /// Returns a URI of the data, which is either a data URI or the path of a resource.
+    fn embed(&mut self, mime_type: &str, data: &[u8]) -> String {
+        let resources = match &mut self.resources {
+            Some(resources) => resources,
+            None => return data_uri(mime_type, data),
+        };
+        if let Some(resource) = resources
+            .iter()
+            .find(|resource| resource.mime_type == mime_type && resource.data == data)
+        {
+            return resource.path.clone();
+        }
+        let extension = mime_type
+            .split('/')
+            .nth(1)
+            .unwrap_or("bin")
+            .split(|c: char| !c.is_alphanumeric())
+            .next()
+            .unwrap_or("bin");
+        let path = format!("media/{}.{}", resources.len() + 1, extension);
+        resources.push(Resource {
+            path: path.clone(),
+            mime_type: mime_type.to_owned(),
+            data: data.to_vec(),
+        });
+        path
+    }
+
+    /// Describes the target of a cross-reference without content, like "Introduction" for a
+    /// section.
+    fn describe(&self, target: &str) -> String {
+        match self.root.resolve(target) {
+            Some(Block::Section { title, .. }) => title.plain_text().replace('\n', " "),
+            _ => target.to_owned(),
+        }
+    }
+}
+
+/// Whether the block can be rendered inside a line of text.
+fn is_inline(block: &Block<Pure>) -> bool {
+    use Block::*;
+
+    match block {
+        Text(_)
+        | Flow(_)
+        | Link { .. }
+        | TermUsage { .. }
+        | CrossReference { .. }
+        | Citation(_)
+        | Temporal { .. } => true,
+        Highlighted { content, .. } | Anchor { content, .. } => is_inline(content),
+        _ => false,
+    }
+}
+
+fn term_id(normalized_term: &str) -> String {
+    format!("term-{}", normalized_term.replace(' ', "-"))
+}
+
+fn numbering_class(numbering: ListNumbering) -> &'static str {
+    match numbering {
+        ListNumbering::Steps => "steps",
+        ListNumbering::Clauses => "clauses",
+        ListNumbering::Ranking => "ranking",
+    }
+}
+
+fn highlight_class(reason: Option<HighlightReason>) -> &'static str {
+    match reason {
+        Some(HighlightReason::SearchHit) => "highlighted search-hit",
+        Some(HighlightReason::KeyPoint) => "highlighted key-point",
+        Some(HighlightReason::Change) => "highlighted change",
+        None => "highlighted",
+    }
+}
+
+fn bibliography_entry(entry: &BibliographyEntry) -> String {
+    let mut parts = vec![];
+    if !entry.authors.is_empty() {
+        parts.push(format!("{}.", escape(&entry.authors.join(", "))));
+    }
+    parts.push(format!("<cite>{}</cite>.", escape(&entry.title)));
+    if let Some(year) = entry.year {
+        parts.push(format!("{}.", year));
+    }
+    for (scheme, value) in &entry.identifiers {
+        let url = match scheme.as_str() {
+            "doi" => Some(format!("https://doi.org/{}", value)),
+            "url" => Some(value.clone()),
+            _ => None,
+        };
+        parts.push(match url {
+            Some(url) => link(&url, escape(value)),
+            None => format!("{}: {}", escape(&scheme.to_uppercase()), escape(value)),
+        });
+    }
+    format!(
+        "<li id=\"cite-{}\">{}</li>",
+        escape(&entry.key),
+        parts.join(" ")
+    )
+}
+
+fn data_uri(mime_type: &str, data: &[u8]) -> String {
+    format!("data:{};base64,{}", escape(mime_type), base64(data))
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Formats the value for the `datetime` attribute of `<time>` elements.
+fn datetime(value: TemporalValue) -> String {
+    match value {
+        TemporalValue::Date { year, month, day } => {
+            format!("{:04}-{:02}-{:02}", year, month, day)
+        }
+        TemporalValue::Instant(seconds) => {
+            let (date, time) = civil_from_seconds(seconds);
+            format!("{}T{}Z", date, time)
+        }
+        TemporalValue::Duration(seconds) => format!("PT{}S", seconds),
+    }
+}
+
+fn readable_datetime(value: TemporalValue) -> String {
+    match value {
+        TemporalValue::Instant(seconds) => {
+            let (date, time) = civil_from_seconds(seconds);
+            format!("{} {} UTC", date, &time[..5])
+        }
+        value => datetime(value),
+    }
+}
+
+/// Splits seconds since the Unix epoch into a date like "2021-03-14" and a time like "15:09:26".
+fn civil_from_seconds(seconds: i64) -> (String, String) {
+    let days = seconds.div_euclid(86400);
+    let seconds = seconds.rem_euclid(86400);
+    let (year, month, day) = days_to_civil(days);
+
+    (
+        format!("{:04}-{:02}-{:02}", year, month, day),
+        format!(
+            "{:02}:{:02}:{:02}",
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        ),
+    )
+}
+
+/// Links the content to the URL. URLs with schemes that can run code when opened, like
+/// `javascript:`, don't get a link, so only the content is shown.
+fn link(url: &str, content: String) -> String {
+    if is_safe_url(url) {
+        format!("<a href=\"{}\">{}</a>", escape(url), content)
+    } else {
+        content
+    }
+}
+
+/// Whether the URL is relative or uses one of the schemes that are safe to link to.
+fn is_safe_url(url: &str) -> bool {
+    // Browsers ignore whitespace and control characters in schemes.
+    let url = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect::<String>();
+    let scheme = match url.find([':', '/', '?', '#']) {
+        Some(end) if url[end..].starts_with(':') => url[..end].to_ascii_lowercase(),
+        _ => return true,
+    };
+    matches!(scheme.as_str(), "http" | "https" | "mailto")
+}
+
+/// Escapes text for use in HTML content and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use semdoc::Block::*;
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    fn section(title: &str, body: Block<Pure>) -> Block<Pure> {
+        Section {
+            title: Box::new(text(title)),
+            body: Box::new(body),
+        }
+    }
+
+    #[test]
+    fn heading_levels_follow_the_nesting_of_sections() {
+        let html = block_to_html(&section("A", section("B", section("C", Empty))));
+        assert!(html.contains("<h1 id=\"a\">A</h1>"));
+        assert!(html.contains("<h2 id=\"b\">B</h2>"));
+        assert!(html.contains("<h3 id=\"c\">C</h3>"));
+    }
+
+    #[test]
+    fn deep_headings_use_aria_levels() {
+        let mut block = section("Deep", Empty);
+        for _ in 0..6 {
+            block = section("Level", block);
+        }
+        assert!(block_to_html(&block)
+            .contains("<div role=\"heading\" aria-level=\"7\" id=\"deep\">Deep</div>"));
+    }
+
+    #[test]
+    fn sections_get_unique_ids() {
+        let html = block_to_html(&Paragraphs(vec![
+            section("Intro", Empty),
+            section("Intro", Empty),
+            Anchor {
+                id: "intro-2".to_owned(),
+                content: Box::new(text("Taken")),
+            },
+        ]));
+        assert!(html.contains("<h1 id=\"intro\">"));
+        assert!(html.contains("<h1 id=\"intro-3\">"));
+        assert!(html.contains("<p id=\"intro-2\">Taken</p>"));
+    }
+
+    #[test]
+    fn pages_have_landmarks_a_table_of_contents_and_themes() {
+        let doc = SemDoc::new(Paragraphs(vec![
+            section("Intro", section("Details", text("Hi"))),
+            section("End", Empty),
+        ]))
+        .with_metadata(Metadata {
+            title: Some("Doc".to_owned()),
+            ..Metadata::default()
+        });
+        let html = semdoc_to_html(&doc);
+        assert!(html.contains("<header>\n<h1>Doc</h1>"));
+        assert!(html.contains("<h2 id=\"intro\">Intro</h2>"));
+        assert!(html.contains("<main id=\"content\">"));
+        assert!(html.contains(
+            "<ol>\n<li><a href=\"#intro\">Intro</a>\n<ol>\n<li><a href=\"#details\">Details</a></li>\
+             \n</ol>\n</li>\n<li><a href=\"#end\">End</a></li>\n</ol>"
+        ));
+        assert!(html.contains("@media (prefers-color-scheme: dark)"));
+    }
+
+    #[test]
+    fn asides_are_not_part_of_the_table_of_contents() {
+        let doc = SemDoc::new(Secondary(Box::new(section("Note", text("Hi")))));
+        let html = semdoc_to_html(&doc);
+        assert!(html.contains("<aside role=\"note\">"));
+        assert!(!html.contains("<nav"));
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(
+            block_to_html(&Link {
+                target: "https://example.com/?a=1&b=\"2\"".to_owned(),
+                content: Box::new(text("<b> & co")),
+            }),
+            "<p><a href=\"https://example.com/?a=1&amp;b=&quot;2&quot;\">&lt;b&gt; &amp; co</a></p>"
+        );
+    }
+
+    #[test]
+    fn unsafe_links_become_text() {
+        let link = |target: &str| Link {
+            target: target.to_owned(),
+            content: Box::new(text("Click")),
+        };
+        assert_eq!(block_to_html(&link("javascript:alert(1)")), "<p>Click</p>");
+        assert_eq!(
+            block_to_html(&link(" Java\tScript:alert(1)")),
+            "<p>Click</p>"
+        );
+        assert_eq!(
+            block_to_html(&link("data:text/html,<script>")),
+            "<p>Click</p>"
+        );
+        assert_eq!(
+            block_to_html(&link("mailto:ada@example.com")),
+            "<p><a href=\"mailto:ada@example.com\">Click</a></p>"
+        );
+        assert_eq!(
+            block_to_html(&link("docs/setup.html#install")),
+            "<p><a href=\"docs/setup.html#install\">Click</a></p>"
+        );
+    }
+
+    #[test]
+    fn image_attachments_are_shown() {
+        let image = Attachment(AttachedFile::new("Logo", "image/png", b"Man".to_vec()));
+        assert_eq!(
+            block_to_html(&image),
+            "<p><img src=\"data:image/png;base64,TWFu\" alt=\"Logo\" /></p>"
+        );
+    }
+
+    #[test]
+    fn parts_save_embedded_data_as_resources() {
+        let image = Attachment(AttachedFile::new("Logo", "image/png", b"Man".to_vec()));
+        let root = Paragraphs(vec![image.clone(), image.clone()]);
+        let mut resources = vec![];
+        let html = part_to_html(&root, &root, &mut resources);
+        assert_eq!(
+            html.matches("<img src=\"media/1.png\" alt=\"Logo\" />")
+                .count(),
+            2
+        );
+        assert_eq!(
+            resources,
+            vec![Resource {
+                path: "media/1.png".to_owned(),
+                mime_type: "image/png".to_owned(),
+                data: b"Man".to_vec(),
+            }]
+        );
+    }
+
+    #[test]
+    fn base64_pads_the_last_chunk() {
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"M"), "TQ==");
+    }
+}
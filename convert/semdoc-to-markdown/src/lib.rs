@@ -0,0 +1,634 @@
+use semdoc::{
+    days_to_civil, BibliographyEntry, Block, Definition, Media, MediaSource, Metadata, Pure,
+    SemDoc, TemporalValue,
+};
+
+/// Turns a document into CommonMark with the GitHub extensions `markdown_to_semdoc` understands.
+///
+/// Metadata becomes YAML front matter.
+pub fn semdoc_to_markdown(doc: &SemDoc<Pure>) -> String {
+    format!(
+        "{}{}",
+        metadata_to_front_matter(&doc.metadata),
+        block_to_markdown(&doc.block)
+    )
+}
+
+/// Turns a block into CommonMark. Nested sections become headings of increasing levels.
+///
+/// Information Markdown can't express, like comments, signatures or embedded files, is lost.
+pub fn block_to_markdown(block: &Block<Pure>) -> String {
+    match render_block(block, 1, ListMarker::Dash) {
+        Some(markdown) => format!("{}\n", markdown),
+        None => String::new(),
+    }
+}
+
+/// Directly following lists need different markers, otherwise Markdown merges them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ListMarker {
+    Dash,
+    Star,
+}
+impl ListMarker {
+    fn bullet(self) -> &'static str {
+        match self {
+            ListMarker::Dash => "-",
+            ListMarker::Star => "*",
+        }
+    }
+
+    fn ordered_delimiter(self) -> &'static str {
+        match self {
+            ListMarker::Dash => ".",
+            ListMarker::Star => ")",
+        }
+    }
+
+    fn other(self) -> ListMarker {
+        match self {
+            ListMarker::Dash => ListMarker::Star,
+            ListMarker::Star => ListMarker::Dash,
+        }
+    }
+}
+
+/// The highest number of an ordered list item, because CommonMark allows at most nine digits.
+const MAX_LIST_NUMBER: u64 = 999_999_999;
+
+/// Renders a block that stands on its own, like a paragraph or a list. Returns `None` if there's
+/// nothing to render.
+///
+/// The `depth` is the heading level of sections in this block.
+fn render_block(block: &Block<Pure>, depth: usize, marker: ListMarker) -> Option<String> {
+    use Block::*;
+
+    Some(match block {
+        Error(_) | Empty => return None,
+        Paragraphs(children) => render_blocks(children, depth, marker)?,
+        Section { title, body } => render_section(title, body, depth),
+        // Headings get their anchors from their title when converting Markdown.
+        Anchor { content, .. } if matches!(**content, Section { .. }) => {
+            render_block(content, depth, marker)?
+        }
+        Anchor { id, content } => match (id.strip_prefix("footnote-"), &**content) {
+            (Some(name), Secondary(body)) => {
+                let body = render_block(body, depth, ListMarker::Dash).unwrap_or_default();
+                prefix_lines(&format!("[^{}]: {}", name, body), "", "    ")
+            }
+            _ => render_block(content, depth, marker)?,
+        },
+        TopicChange => "***".to_owned(),
+        Code { language, code } => {
+            // Info strings of backtick fences can't contain backticks.
+            let fence_char = match language {
+                Some(language) if language.contains('`') => '~',
+                _ => '`',
+            };
+            let fence = fence_char
+                .to_string()
+                .repeat(3.max(longest_run(code, fence_char) + 1));
+            format!(
+                "{}{}\n{}{}{}",
+                fence,
+                language.as_deref().unwrap_or_default(),
+                code,
+                if code.is_empty() { "" } else { "\n" },
+                fence
+            )
+        }
+        // Lists directly inside items use the other marker, because items like `- - -` would be
+        // thematic breaks.
+        BulletList(items) => render_list(items.iter().map(|item| {
+            let content = render_block(item, depth, marker.other());
+            (format!("{} ", marker.bullet()), content)
+        })),
+        Checklist(items) => render_list(items.iter().map(|item| {
+            let check = if item.checked { "x" } else { " " };
+            let content = match &item.content {
+                // Code blocks can't start on the line of the task marker.
+                Code {
+                    language: None,
+                    code,
+                } if !code.contains('\n') => Some(render_inline(&item.content)),
+                content => render_block(content, depth, marker.other()),
+            };
+            (format!("{} [{}] ", marker.bullet(), check), content)
+        })),
+        OrderedList { start, items, .. } => {
+            // Numbers with more digits don't start list items, so lists starting beyond them
+            // start as late as possible instead.
+            let last_start = MAX_LIST_NUMBER.saturating_sub(items.len().saturating_sub(1) as u64);
+            let start = (*start).min(last_start);
+            render_list(items.iter().enumerate().map(|(index, item)| {
+                let number = start + index as u64;
+                let content = render_block(item, depth, marker.other());
+                (
+                    format!("{}{} ", number, marker.ordered_delimiter()),
+                    content,
+                )
+            }))
+        }
+        DefinitionList(definitions) | Glossary(definitions) => {
+            render_definitions(definitions, depth)
+        }
+        Quote(content) => prefix_lines(&render_block(content, depth, marker)?, "> ", "> "),
+        Secondary(content) => match &**content {
+            Section { title, body } => render_callout(false, title, body, depth),
+            content => render_callout(false, &Empty, content, depth),
+        },
+        MoreInfo { summary, body } => render_callout(true, summary, body, depth),
+        Table { header, rows } => render_table(header, rows),
+        Bibliography(entries) => entries
+            .iter()
+            .map(|entry| format!("{} {}", marker.bullet(), bibliography_entry(entry)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Audio(media) | Video(media) => render_media(media, depth)?,
+        Signed { content, .. } | Commented { content, .. } => render_block(content, depth, marker)?,
+        Attachment(attachment) => escape_line_starts(&escape(&attachment.name)),
+        Text(_)
+        | Flow(_)
+        | Link { .. }
+        | TermUsage { .. }
+        | CrossReference { .. }
+        | Citation(_)
+        | Temporal { .. }
+        | Highlighted { .. } => {
+            let inline = render_inline(block);
+            if inline.trim().is_empty() {
+                return None;
+            }
+            escape_line_starts(inline.trim())
+        }
+    })
+}
+
+/// Renders blocks separated by blank lines. The first list uses the given marker, lists right
+/// after another list of the same kind use the other one.
+fn render_blocks(blocks: &[Block<Pure>], depth: usize, first_marker: ListMarker) -> Option<String> {
+    let mut rendered = vec![];
+    let mut previous_list: Option<(bool, ListMarker)> = None;
+    for block in blocks {
+        let is_ordered = match block {
+            Block::BulletList(_) | Block::Checklist(_) | Block::Bibliography(_) => Some(false),
+            Block::OrderedList { .. } => Some(true),
+            _ => None,
+        };
+        let marker = match (is_ordered, previous_list) {
+            (Some(is_ordered), Some((was_ordered, marker))) if is_ordered == was_ordered => {
+                marker.other()
+            }
+            _ => first_marker,
+        };
+        if let Some(markdown) = render_block(block, depth, marker) {
+            rendered.push(markdown);
+            previous_list = is_ordered.map(|is_ordered| (is_ordered, marker));
+        }
+    }
+    if rendered.is_empty() {
+        None
+    } else {
+        Some(rendered.join("\n\n"))
+    }
+}
+
+fn render_section(title: &Block<Pure>, body: &Block<Pure>, depth: usize) -> String {
+    let mut title = render_inline(title).replace('\n', " ").trim().to_owned();
+    // A trailing `#` would be the closing sequence of the heading.
+    if title.ends_with('#') {
+        title.insert(title.len() - 1, '\\');
+    }
+    let heading = format!("{} {}", "#".repeat(depth.min(6)), title.trim());
+    match render_block(body, depth + 1, ListMarker::Dash) {
+        Some(body) => format!("{}\n\n{}", heading.trim_end(), body),
+        None => heading.trim_end().to_owned(),
+    }
+}
+
+/// Renders list items, indenting their continuation lines so they stay inside the item.
+fn render_list(items: impl Iterator<Item = (String, Option<String>)>) -> String {
+    items
+        .map(|(marker, content)| {
+            prefix_lines(
+                &format!("{}{}", marker, content.unwrap_or_default()),
+                "",
+                &" ".repeat(marker.len()),
+            )
+            .trim_end()
+            .to_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_definitions(definitions: &[Definition<Pure>], depth: usize) -> String {
+    definitions
+        .iter()
+        .map(|definition| {
+            let mut parts = vec![escape_line_starts(render_inline(&definition.term).trim())];
+            for details in &definition.definitions {
+                let details = render_block(details, depth, ListMarker::Dash).unwrap_or_default();
+                parts.push(prefix_lines(&format!(": {}", details), "", "  "));
+            }
+            parts.join("\n\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Renders a callout like `> [!note] Title`. Collapsible callouts have a `-` after the marker,
+/// like `> [!note]- Title`.
+fn render_callout(
+    collapsible: bool,
+    title: &Block<Pure>,
+    body: &Block<Pure>,
+    depth: usize,
+) -> String {
+    let title = render_inline(title).replace('\n', " ");
+    let marker = if collapsible { "[!note]-" } else { "[!note]" };
+    let mut callout = format!("{} {}", marker, title.trim()).trim_end().to_owned();
+    if let Some(body) = render_block(body, depth, ListMarker::Dash) {
+        callout = format!("{}\n\n{}", callout, body);
+    }
+    prefix_lines(&callout, "> ", "> ")
+}
+
+fn render_table(header: &[Block<Pure>], rows: &[Vec<Block<Pure>>]) -> String {
+    let num_columns = rows
+        .iter()
+        .map(|row| row.len())
+        .chain(std::iter::once(header.len()))
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    let render_row = |cells: &[Block<Pure>]| {
+        let cells = (0..num_columns)
+            .map(|index| match cells.get(index) {
+                Some(cell) => render_inline(cell).replace('\n', " ").trim().to_owned(),
+                None => String::new(),
+            })
+            .collect::<Vec<_>>();
+        format!("| {} |", cells.join(" | "))
+    };
+    let mut lines = vec![
+        render_row(header),
+        format!("|{}", " --- |".repeat(num_columns)),
+    ];
+    lines.extend(rows.iter().map(|row| render_row(row)));
+    lines.join("\n")
+}
+
+fn render_media(media: &Media<Pure>, depth: usize) -> Option<String> {
+    match &media.source {
+        MediaSource::External(uri) => Some(escape_line_starts(&render_link(
+            &render_inline(&media.transcript),
+            uri,
+        ))),
+        MediaSource::Embedded(_) => render_block(&media.transcript, depth, ListMarker::Dash),
+    }
+}
+
+/// Formats an entry like "[knuth1984] Donald E. Knuth. Literate Programming. 1984."
+fn bibliography_entry(entry: &BibliographyEntry) -> String {
+    let mut parts = vec![format!("[{}]", entry.key)];
+    if !entry.authors.is_empty() {
+        parts.push(format!("{}.", entry.authors.join(", ")));
+    }
+    parts.push(format!("{}.", entry.title));
+    if let Some(year) = entry.year {
+        parts.push(format!("{}.", year));
+    }
+    escape(&parts.join(" "))
+}
+
+/// Renders a block inside a line of text. Blocks that can't be inside text become their plain
+/// text.
+fn render_inline(block: &Block<Pure>) -> String {
+    use Block::*;
+
+    match block {
+        Error(_) | Empty => String::new(),
+        Text(text) => escape(text).replace('\n', "\\\n"),
+        Flow(children) => children.iter().map(render_inline).collect(),
+        Paragraphs(children) => children
+            .iter()
+            .map(render_inline)
+            .collect::<Vec<_>>()
+            .join(" "),
+        Code { code, .. } => {
+            let fence = "`".repeat(longest_run(code, '`') + 1);
+            let needs_padding = code.starts_with('`')
+                || code.ends_with('`')
+                || (code.starts_with(' ') && code.ends_with(' ') && !code.trim().is_empty());
+            let padding = if needs_padding { " " } else { "" };
+            format!("{}{}{}{}{}", fence, padding, code, padding, fence)
+        }
+        Link { target, content } => render_link(&render_link_content(content), target),
+        CrossReference { target, content } => match target.strip_prefix("footnote-") {
+            Some(name) => format!("[^{}]", name),
+            None => render_link(&render_inline(content), &format!("#{}", target)),
+        },
+        Highlighted { content, .. } => format!("=={}==", render_inline(content)),
+        Citation(keys) => format!(
+            "[{}]",
+            keys.iter()
+                .map(|key| format!("@{}", key))
+                .collect::<Vec<_>>()
+                .join("; ")
+        ),
+        Temporal { text, .. } => escape(text),
+        TermUsage { content, .. }
+        | Anchor { content, .. }
+        | Signed { content, .. }
+        | Commented { content, .. } => render_inline(content),
+        Audio(media) | Video(media) => match &media.source {
+            MediaSource::External(uri) => render_link(&render_inline(&media.transcript), uri),
+            MediaSource::Embedded(_) => render_inline(&media.transcript),
+        },
+        Attachment(attachment) => escape(&attachment.name),
+        _ => escape(&block.plain_text().replace('\n', " ")),
+    }
+}
+
+/// Renders the content of a link. Links can't contain other links in Markdown, but they can
+/// contain images, which the converter turns back into links.
+fn render_link_content(block: &Block<Pure>) -> String {
+    match block {
+        Block::Flow(children) => children.iter().map(render_link_content).collect(),
+        Block::Link { target, content } => {
+            format!(
+                "!{}",
+                render_bracketed_link(&render_link_content(content), target)
+            )
+        }
+        other => render_inline(other),
+    }
+}
+
+fn render_link(content: &str, target: &str) -> String {
+    if content == escape(target) && target.contains("://") && !target.contains(['<', '>', ' ']) {
+        return format!("<{}>", target);
+    }
+    render_bracketed_link(content, target)
+}
+
+fn render_bracketed_link(content: &str, target: &str) -> String {
+    if target.contains([' ', '(', ')', '<', '>']) || target.is_empty() {
+        format!(
+            "[{}](<{}>)",
+            content,
+            target.replace('<', "\\<").replace('>', "\\>")
+        )
+    } else {
+        format!("[{}]({})", content, target)
+    }
+}
+
+/// Escapes characters that would otherwise be Markdown syntax.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    let mut previous = None;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '~' => escaped.push('\\'),
+            '&' if matches!(chars.peek(), Some(next) if next.is_alphanumeric() || *next == '#') => {
+                escaped.push('\\')
+            }
+            // Two equal signs would mark highlighted text.
+            '=' if previous == Some('=') => escaped.push('\\'),
+            _ => {}
+        }
+        escaped.push(c);
+        previous = Some(c);
+    }
+    escaped
+}
+
+/// Escapes characters at the start of lines that would otherwise start a heading, list, etc.
+fn escape_line_starts(text: &str) -> String {
+    text.split('\n')
+        .map(|line| {
+            let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+            match line.chars().nth(digits) {
+                Some('.') | Some(')') if digits > 0 => {
+                    format!("{}\\{}", &line[..digits], &line[digits..])
+                }
+                Some('#') | Some('+') | Some('-') | Some(':') if digits == 0 => {
+                    format!("\\{}", line)
+                }
+                // Only lines consisting of equal signs underline setext headings.
+                Some('=') if line.trim_end().chars().all(|c| c == '=') => {
+                    format!("\\{}", line)
+                }
+                _ => line.to_owned(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Prefixes the first line and all other lines of the text. Empty lines don't get trailing
+/// spaces.
+fn prefix_lines(text: &str, first_prefix: &str, prefix: &str) -> String {
+    text.split('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            let prefix = if index == 0 { first_prefix } else { prefix };
+            if line.is_empty() {
+                prefix.trim_end().to_owned()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|it| it != c)
+        .map(|run| run.len())
+        .max()
+        .unwrap_or_default()
+}
+
+fn metadata_to_front_matter(metadata: &Metadata) -> String {
+    if metadata.is_empty() {
+        return String::new();
+    }
+    let mut lines = vec!["---".to_owned()];
+    if let Some(title) = &metadata.title {
+        lines.push(format!("title: {}", yaml_string(title)));
+    }
+    if !metadata.authors.is_empty() {
+        lines.push("authors:".to_owned());
+        lines.extend(
+            metadata
+                .authors
+                .iter()
+                .map(|author| format!("  - {}", yaml_string(author))),
+        );
+    }
+    match metadata.date {
+        Some(TemporalValue::Date { year, month, day }) => {
+            lines.push(format!("date: {:04}-{:02}-{:02}", year, month, day))
+        }
+        Some(TemporalValue::Instant(seconds)) => {
+            lines.push(format!("date: {}", format_instant(seconds)))
+        }
+        // Front matter dates are points in time.
+        Some(TemporalValue::Duration(_)) | None => {}
+    }
+    if !metadata.tags.is_empty() {
+        lines.push("tags:".to_owned());
+        lines.extend(
+            metadata
+                .tags
+                .iter()
+                .map(|tag| format!("  - {}", yaml_string(tag))),
+        );
+    }
+    for (key, value) in &metadata.custom {
+        lines.push(format!("{}: {}", yaml_string(key), yaml_string(value)));
+    }
+    lines.push("---".to_owned());
+    format!("{}\n\n", lines.join("\n"))
+}
+
+/// Quotes the text so YAML doesn't interpret it as a number, list, etc.
+fn yaml_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Formats seconds since the Unix epoch like "2021-03-14T15:09:26Z".
+fn format_instant(seconds: i64) -> String {
+    let days = seconds.div_euclid(86400);
+    let seconds = seconds.rem_euclid(86400);
+    let (year, month, day) = days_to_civil(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use semdoc::Block::*;
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    fn section(title: &str, body: Block<Pure>) -> Block<Pure> {
+        Section {
+            title: Box::new(text(title)),
+            body: Box::new(body),
+        }
+    }
+
+    #[test]
+    fn nested_sections_become_deeper_headings() {
+        let doc = Paragraphs(vec![
+            section("Intro", text("Hello.")),
+            section(
+                "Details",
+                Paragraphs(vec![text("More."), section("Even more", Empty)]),
+            ),
+        ]);
+        assert_eq!(
+            block_to_markdown(&doc),
+            "# Intro\n\nHello.\n\n# Details\n\nMore.\n\n## Even more\n"
+        );
+    }
+
+    #[test]
+    fn adjacent_lists_use_different_markers() {
+        let doc = Paragraphs(vec![
+            BulletList(vec![text("a"), BulletList(vec![text("b")])]),
+            BulletList(vec![text("c")]),
+        ]);
+        assert_eq!(block_to_markdown(&doc), "- a\n- * b\n\n* c\n");
+    }
+
+    #[test]
+    fn list_numbers_have_at_most_nine_digits() {
+        let list = |start| OrderedList {
+            start,
+            numbering: None,
+            items: vec![text("a"), text("b")],
+        };
+        assert_eq!(
+            block_to_markdown(&list(999_999_998)),
+            "999999998. a\n999999999. b\n"
+        );
+        assert_eq!(
+            block_to_markdown(&list(1_000_000_000)),
+            "999999998. a\n999999999. b\n"
+        );
+        assert_eq!(
+            block_to_markdown(&list(u64::MAX)),
+            "999999998. a\n999999999. b\n"
+        );
+    }
+
+    #[test]
+    fn syntax_in_texts_is_escaped() {
+        assert_eq!(
+            block_to_markdown(&text("# Not a *heading* [x] &amp;")),
+            "\\# Not a \\*heading\\* \\[x\\] \\&amp;\n"
+        );
+        assert_eq!(block_to_markdown(&text("1. first")), "1\\. first\n");
+        assert_eq!(block_to_markdown(&text("a==b==c")), "a=\\=b=\\=c\n");
+    }
+
+    #[test]
+    fn only_setext_underlines_are_escaped() {
+        assert_eq!(block_to_markdown(&text("a\n=")), "a\\\n\\=\n");
+        let highlighted = Highlighted {
+            content: Box::new(text("key")),
+            reason: None,
+        };
+        assert_eq!(block_to_markdown(&highlighted), "==key==\n");
+    }
+
+    #[test]
+    fn trailing_hashes_of_headings_are_escaped() {
+        assert_eq!(block_to_markdown(&section("C#", Empty)), "# C\\#\n");
+    }
+
+    #[test]
+    fn metadata_becomes_front_matter() {
+        let doc = SemDoc::new(text("Hi")).with_metadata(Metadata {
+            title: Some("A \"quoted\" title".to_owned()),
+            authors: vec!["Ada".to_owned()],
+            date: Some(TemporalValue::Instant(1615734566)),
+            tags: vec![],
+            custom: vec![("license".to_owned(), "MIT".to_owned())],
+        });
+        assert_eq!(
+            semdoc_to_markdown(&doc),
+            "---\ntitle: \"A \\\"quoted\\\" title\"\nauthors:\n  - \"Ada\"\n\
+             date: 2021-03-14T15:09:26Z\n\"license\": \"MIT\"\n---\n\nHi\n"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Checks that converting Markdown to SemDoc, back to Markdown and to SemDoc again results in the
+//! same document, so writers can round-trip documents through Markdown tooling.
+
+use markdown_to_semdoc::markdown_to_semdoc;
+use quickcheck::{Arbitrary, Gen, QuickCheck, TestResult};
+use semdoc::{Block, Pure, SemDoc};
+use semdoc_to_markdown::semdoc_to_markdown;
+
+/// Markdown made of blocks that use all kinds of syntax, including characters that need escaping.
+#[derive(Clone, Debug)]
+struct Markdown(Vec<String>);
+
+const WORDS: &[&str] = &[
+    "foo", "Bar", "baz", "1.", "2)", "-", "+", "#", "*", "_", "`", "[", "]", "<", ">", "&",
+    "&amp;", "\\", "|", "=", "a\\=\\=b", ":", "~", "!", "(", ")", "{", "}", "'", "\"", "ä", "🦀",
+];
+
+/// Words without Markdown syntax. Formatting constructs only contain these, because constructs
+/// interleaving with each other (like `**[**`) can't be told apart from text anyway.
+const PLAIN_WORDS: &[&str] = &["foo", "Bar", "baz", "ä", "🦀", "x1"];
+
+fn choose<'a>(g: &mut Gen, options: &[&'a str]) -> &'a str {
+    g.choose(options).unwrap()
+}
+
+fn word(g: &mut Gen) -> String {
+    choose(g, WORDS).to_owned()
+}
+
+fn plain_word(g: &mut Gen) -> String {
+    choose(g, PLAIN_WORDS).to_owned()
+}
+
+fn inline(g: &mut Gen) -> String {
+    let num_parts = usize::arbitrary(g) % 5 + 1;
+    (0..num_parts)
+        .map(|_| match u8::arbitrary(g) % 14 {
+            0 => format!("*{}*", plain_word(g)),
+            1 => format!("**{}**", plain_word(g)),
+            2 => format!("`{}`", word(g).replace('`', "'")),
+            3 => format!("[{}](http://example.com/{})", plain_word(g), plain_word(g)),
+            4 => format!("[{}](#foo)", plain_word(g)),
+            5 => format!("=={}==", plain_word(g)),
+            6 => "[@knuth1984]".to_owned(),
+            7 => format!("~~{}~~", plain_word(g)),
+            8 => format!("{}  \n{}", plain_word(g), plain_word(g)),
+            9 => "www.example.com".to_owned(),
+            10 => format!("![{}](image.png)", plain_word(g)),
+            _ => word(g),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn block(g: &mut Gen) -> String {
+    match u8::arbitrary(g) % 15 {
+        0 => format!("{} {}", "#".repeat(usize::arbitrary(g) % 4 + 1), inline(g)),
+        1 => format!("- {}\n- {}\n  - {}", inline(g), inline(g), inline(g)),
+        2 => format!("{}. {}\n{}. {}", 3, inline(g), 4, inline(g)),
+        3 => format!("- [x] {}\n- [ ] {}", inline(g), inline(g)),
+        4 => format!("> {}\n> {}", inline(g), inline(g)),
+        5 => format!("```{}\n{}\n```", choose(g, &["", "rust"]), inline(g)),
+        6 => format!(
+            "| {} | {} |\n| --- | --- |\n| {} | {} |",
+            word(g),
+            inline(g),
+            inline(g),
+            word(g)
+        ),
+        7 => choose(g, &["***", "---", "___"]).to_owned(),
+        8 => format!("> [!note] {}\n> {}", inline(g), inline(g)),
+        9 => format!("> [!tip]- {}\n> {}", inline(g), inline(g)),
+        10 => format!("{}\n\n: {}", word(g), inline(g)),
+        11 => format!("{}[^1]\n\n[^1]: {}", inline(g), inline(g)),
+        _ => format!("{}\n{}", inline(g), inline(g)),
+    }
+}
+
+impl Arbitrary for Markdown {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let mut blocks = vec![];
+        if bool::arbitrary(g) {
+            blocks.push(format!(
+                "---\ntitle: \"{}\"\nauthors: [Ada, Grace]\ndate: 2021-03-14\nlicense: MIT\n---",
+                word(g).replace(['\\', '"'], "")
+            ));
+        }
+        let num_blocks = usize::arbitrary(g) % 8 + 1;
+        blocks.extend((0..num_blocks).map(|_| block(g)));
+        Markdown(blocks)
+    }
+
+    /// Removes single blocks. Shrinking the blocks themselves would result in Markdown this
+    /// generator doesn't produce.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let blocks = self.0.clone();
+        Box::new((0..blocks.len()).map(move |index| {
+            let mut blocks = blocks.clone();
+            blocks.remove(index);
+            Markdown(blocks)
+        }))
+    }
+}
+
+fn convert(markdown: &str) -> SemDoc<Pure> {
+    let doc = markdown_to_semdoc(markdown);
+    SemDoc::new(doc.block.simplify()).with_metadata(doc.metadata)
+}
+
+fn round_trip_is_stable(markdown: Markdown) -> TestResult {
+    let markdown = markdown.0.join("\n\n");
+    let first = markdown_to_semdoc(&markdown);
+    let exported = semdoc_to_markdown(&first);
+    let first = convert(&markdown);
+    let second = convert(&exported);
+    if first == second {
+        TestResult::passed()
+    } else {
+        TestResult::error(format!(
+            "Markdown:\n{}\n\nExported:\n{}\n\nFirst:\n{:?}\n\nSecond:\n{:?}",
+            markdown, exported, first, second
+        ))
+    }
+}
+
+#[test]
+fn equal_signs_in_texts_survive_the_round_trip() {
+    let doc = SemDoc::new(Block::Text("a==b==c".to_owned()));
+    let exported = semdoc_to_markdown(&doc);
+    assert_eq!(convert(&exported), doc);
+    assert!(!round_trip_is_stable(Markdown(vec![exported])).is_failure());
+}
+
+#[test]
+fn lists_starting_beyond_nine_digits_stay_lists() {
+    let list = |start| {
+        SemDoc::new(Block::OrderedList {
+            start,
+            numbering: None,
+            items: vec![Block::Text("a".to_owned()), Block::Text("b".to_owned())],
+        })
+    };
+    let exported = semdoc_to_markdown(&list(u64::MAX));
+    assert_eq!(convert(&exported), list(999_999_998));
+}
+
+#[test]
+fn markdown_round_trip_is_stable() {
+    QuickCheck::new()
+        .tests(500)
+        .quickcheck(round_trip_is_stable as fn(Markdown) -> TestResult);
+}
@@ -0,0 +1,484 @@
+use semdoc::{
+    BibliographyEntry, Block, Definition, Media, MediaSource, Metadata, Pure, SemDoc, TemporalValue,
+};
+use serde_json::{json, Map, Value};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+/// The version of Pandoc's AST the documents use. Pandoc 3.1 and newer read it.
+const API_VERSION: [u64; 3] = [1, 23, 1];
+
+/// The identifiers of bibliography entries that have a CSL variable, which is their uppercase
+/// scheme.
+const IDENTIFIERS: [&str; 6] = ["doi", "isbn", "issn", "url", "pmid", "pmcid"];
+
+/// Turns a document into Pandoc's JSON format, which `pandoc -f json` converts into many other
+/// formats.
+///
+/// Sections become headers. Sections followed by other content are wrapped in a div with the
+/// `section` class like Pandoc's `--section-divs` does, so the content doesn't end up in the
+/// section when reading the document again. The bibliography becomes the `references` field of
+/// the metadata and a div with the ID `refs`, where Pandoc's citeproc puts it. Content Pandoc
+/// has no element for uses divs and spans with these classes: `aside` for secondary content,
+/// `details` for more info, `glossary` for glossaries, `term` for term usages and `mark` for
+/// highlighted content.
+pub fn semdoc_to_pandoc(doc: &SemDoc<Pure>) -> String {
+    let mut footnotes = HashMap::new();
+    let mut references = HashSet::new();
+    let mut entries = vec![];
+    collect(&doc.block, &mut footnotes, &mut references, &mut entries);
+    footnotes.retain(|id, _| references.contains(id));
+
+    let writer = Writer {
+        footnotes,
+        expanding: RefCell::new(vec![]),
+    };
+    json!({
+        "pandoc-api-version": API_VERSION,
+        "meta": metadata(&doc.metadata, &entries),
+        "blocks": writer.blocks(&doc.block, 1),
+    })
+    .to_string()
+}
+
+/// Collects the footnotes, which are anchors with a `footnote-` ID around secondary content, the
+/// targets of cross references and the bibliography entries.
+fn collect<'a>(
+    block: &'a Block<Pure>,
+    footnotes: &mut HashMap<&'a str, &'a Block<Pure>>,
+    references: &mut HashSet<&'a str>,
+    entries: &mut Vec<&'a BibliographyEntry>,
+) {
+    match block {
+        Block::Anchor { id, content } if id.starts_with("footnote-") => {
+            if let Block::Secondary(body) = &**content {
+                footnotes.insert(id, body);
+            }
+        }
+        Block::CrossReference { target, .. } => {
+            references.insert(target);
+        }
+        Block::Bibliography(bibliography) => entries.extend(bibliography),
+        _ => {}
+    }
+    for child in block.children() {
+        collect(child, footnotes, references, entries);
+    }
+}
+
+struct Writer<'a> {
+    /// The bodies of referenced footnotes by their IDs. They become notes where they are
+    /// referenced.
+    footnotes: HashMap<&'a str, &'a Block<Pure>>,
+    /// The IDs of the footnotes whose notes are currently being written. References to them from
+    /// inside their own bodies become links, so they don't expand endlessly.
+    expanding: RefCell<Vec<&'a str>>,
+}
+
+impl<'a> Writer<'a> {
+    /// Converts a block into Pandoc blocks. The `depth` is the level of headers of sections in
+    /// this block.
+    fn blocks(&self, block: &Block<Pure>, depth: u64) -> Vec<Value> {
+        use Block::*;
+
+        match block {
+            Error(_) | Empty => vec![],
+            Paragraphs(children) => {
+                // Content following a section would become part of it when reading the document
+                // again.
+                let mut is_followed_by_content = false;
+                let mut blocks = vec![];
+                for child in children.iter().rev() {
+                    let child_blocks = self.blocks(child, depth);
+                    if is_section(child) && is_followed_by_content {
+                        blocks.push(vec![div(attr("", &["section"]), child_blocks)]);
+                    } else {
+                        is_followed_by_content |= !is_section(child) && !child_blocks.is_empty();
+                        blocks.push(child_blocks);
+                    }
+                }
+                blocks.into_iter().rev().flatten().collect()
+            }
+            Section { title, body } => self.section("", title, body, depth),
+            Anchor { id, .. } if self.footnotes.contains_key(id.as_str()) => vec![],
+            Anchor { id, content } => match &**content {
+                Section { title, body } => self.section(id, title, body, depth),
+                content => vec![div(attr(id, &[]), self.blocks(content, depth))],
+            },
+            TopicChange => vec![json!({ "t": "HorizontalRule" })],
+            BulletList(items) => vec![json!({
+                "t": "BulletList",
+                "c": items.iter().map(|item| self.item(item, depth)).collect::<Vec<_>>(),
+            })],
+            OrderedList { start, items, .. } => vec![json!({
+                "t": "OrderedList",
+                "c": [
+                    [start, { "t": "Decimal" }, { "t": "Period" }],
+                    items.iter().map(|item| self.item(item, depth)).collect::<Vec<_>>(),
+                ],
+            })],
+            Checklist(items) => vec![json!({
+                "t": "BulletList",
+                "c": items
+                    .iter()
+                    .map(|item| with_check_box(item.checked, self.item(&item.content, depth)))
+                    .collect::<Vec<_>>(),
+            })],
+            Table { header, rows } => vec![self.table("", &Empty, header, rows, depth)],
+            Code { language, code } => vec![json!({
+                "t": "CodeBlock",
+                "c": [attr("", &language.iter().map(String::as_str).collect::<Vec<_>>()), code],
+            })],
+            Quote(content) => vec![json!({ "t": "BlockQuote", "c": self.blocks(content, depth) })],
+            DefinitionList(definitions) => vec![self.definition_list(definitions, depth)],
+            Glossary(definitions) => vec![div(
+                attr("", &["glossary"]),
+                vec![self.definition_list(definitions, depth)],
+            )],
+            Bibliography(_) => vec![div(attr("refs", &[]), vec![])],
+            Secondary(content) => match &**content {
+                Section { title, body } => vec![div(
+                    attr("", &["note"]),
+                    self.titled(title, self.blocks(body, depth)),
+                )],
+                content => vec![div(attr("", &["aside"]), self.blocks(content, depth))],
+            },
+            MoreInfo { summary, body } => vec![div(
+                attr("", &["details"]),
+                self.titled(summary, self.blocks(body, depth)),
+            )],
+            Audio(media) | Video(media) => match &media.source {
+                MediaSource::External(_) => paragraph(self.inlines(block)),
+                MediaSource::Embedded(_) => self.blocks(&media.transcript, depth),
+            },
+            Signed { content, .. } | Commented { content, .. } => self.blocks(content, depth),
+            Text(_)
+            | Flow(_)
+            | Link { .. }
+            | TermUsage { .. }
+            | CrossReference { .. }
+            | Citation(_)
+            | Temporal { .. }
+            | Highlighted { .. }
+            | Attachment(_) => paragraph(self.inlines(block)),
+        }
+    }
+
+    /// Converts a list item or a table cell. Items with a single paragraph use `Plain`, so lists
+    /// stay tight.
+    fn item(&self, block: &Block<Pure>, depth: u64) -> Vec<Value> {
+        let mut blocks = self.blocks(block, depth);
+        if let [paragraph] = &mut blocks[..] {
+            if paragraph["t"] == "Para" {
+                paragraph["t"] = json!("Plain");
+            }
+        }
+        blocks
+    }
+
+    fn section(&self, id: &str, title: &Block<Pure>, body: &Block<Pure>, depth: u64) -> Vec<Value> {
+        // Sections with just a table are tables with a caption.
+        if let Block::Table { header, rows } = body {
+            return vec![self.table(id, title, header, rows, depth)];
+        }
+        let mut blocks = vec![json!({
+            "t": "Header",
+            "c": [depth, attr(id, &[]), self.inlines(title)],
+        })];
+        blocks.extend(self.blocks(body, depth + 1));
+        blocks
+    }
+
+    /// Puts a title into a div with the `title` class in front of the blocks, like Pandoc's
+    /// alerts.
+    fn titled(&self, title: &Block<Pure>, mut blocks: Vec<Value>) -> Vec<Value> {
+        let title = self.inlines(title);
+        if !title.is_empty() {
+            blocks.insert(0, div(attr("", &["title"]), paragraph(title)));
+        }
+        blocks
+    }
+
+    fn table(
+        &self,
+        id: &str,
+        caption: &Block<Pure>,
+        header: &[Block<Pure>],
+        rows: &[Vec<Block<Pure>>],
+        depth: u64,
+    ) -> Value {
+        let num_columns = rows
+            .iter()
+            .map(|row| row.len())
+            .chain(std::iter::once(header.len()))
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        let row = |cells: &[Block<Pure>]| {
+            let cells = (0..num_columns)
+                .map(|index| {
+                    let content = match cells.get(index) {
+                        Some(cell) => self.item(cell, depth),
+                        None => vec![],
+                    };
+                    json!([attr("", &[]), { "t": "AlignDefault" }, 1, 1, content])
+                })
+                .collect::<Vec<_>>();
+            json!([attr("", &[]), cells])
+        };
+        let head = if header.is_empty() {
+            vec![]
+        } else {
+            vec![row(header)]
+        };
+        let column = json!([{ "t": "AlignDefault" }, { "t": "ColWidthDefault" }]);
+        json!({
+            "t": "Table",
+            "c": [
+                attr(id, &[]),
+                [null, self.item(caption, depth)],
+                vec![column; num_columns],
+                [attr("", &[]), head],
+                [[attr("", &[]), 0, [], rows.iter().map(|cells| row(cells)).collect::<Vec<_>>()]],
+                [attr("", &[]), []],
+            ],
+        })
+    }
+
+    fn definition_list(&self, definitions: &[Definition<Pure>], depth: u64) -> Value {
+        let definitions = definitions
+            .iter()
+            .map(|definition| {
+                let details = definition
+                    .definitions
+                    .iter()
+                    .map(|details| self.item(details, depth))
+                    .collect::<Vec<_>>();
+                json!([self.inlines(&definition.term), details])
+            })
+            .collect::<Vec<_>>();
+        json!({ "t": "DefinitionList", "c": definitions })
+    }
+
+    /// Converts a block into Pandoc inlines. Blocks that can't be inside text become their
+    /// inlines separated by spaces.
+    fn inlines(&self, block: &Block<Pure>) -> Vec<Value> {
+        use Block::*;
+
+        match block {
+            Error(_) | Empty => vec![],
+            Text(text) | Temporal { text, .. } => text_inlines(text),
+            Flow(children) => children
+                .iter()
+                .flat_map(|child| self.inlines(child))
+                .collect(),
+            Code {
+                language: Some(language),
+                code,
+            } if language == "latex" => {
+                vec![json!({ "t": "Math", "c": [{ "t": "InlineMath" }, code] })]
+            }
+            Code { language, code } => vec![json!({
+                "t": "Code",
+                "c": [attr("", &language.iter().map(String::as_str).collect::<Vec<_>>()), code],
+            })],
+            Link { target, content } => vec![link(target, self.inlines(content))],
+            CrossReference { target, content } => {
+                match self.footnotes.get_key_value(target.as_str()) {
+                    Some((id, body)) if !self.expanding.borrow().contains(id) => {
+                        self.expanding.borrow_mut().push(id);
+                        let note = self.blocks(body, 1);
+                        self.expanding.borrow_mut().pop();
+                        vec![json!({ "t": "Note", "c": note })]
+                    }
+                    _ => vec![link(&format!("#{}", target), self.inlines(content))],
+                }
+            }
+            TermUsage { term, content } => vec![span(
+                json!(["", ["term"], [["term", term]]]),
+                self.inlines(content),
+            )],
+            Highlighted { content, .. } => vec![span(attr("", &["mark"]), self.inlines(content))],
+            Anchor { id, content } => vec![span(attr(id, &[]), self.inlines(content))],
+            Citation(keys) => vec![citation(keys)],
+            Audio(media) | Video(media) => self.media(media),
+            Attachment(attachment) => text_inlines(&attachment.name),
+            other => {
+                let mut inlines = vec![];
+                for child in other.children() {
+                    let child = self.inlines(child);
+                    if child.is_empty() {
+                        continue;
+                    }
+                    if !inlines.is_empty() {
+                        inlines.push(json!({ "t": "Space" }));
+                    }
+                    inlines.extend(child);
+                }
+                inlines
+            }
+        }
+    }
+
+    fn media(&self, media: &Media<Pure>) -> Vec<Value> {
+        match &media.source {
+            MediaSource::External(uri) => vec![link(uri, self.inlines(&media.transcript))],
+            MediaSource::Embedded(_) => self.inlines(&media.transcript),
+        }
+    }
+}
+
+fn metadata(metadata: &Metadata, entries: &[&BibliographyEntry]) -> Value {
+    let mut meta = Map::new();
+    if let Some(title) = &metadata.title {
+        meta.insert("title".to_owned(), meta_inlines(title));
+    }
+    if !metadata.authors.is_empty() {
+        let authors = metadata.authors.iter().map(|author| meta_inlines(author));
+        meta.insert("author".to_owned(), meta_list(authors));
+    }
+    // Pandoc's date is free text, but dates are what most templates and readers expect.
+    if let Some(TemporalValue::Date { year, month, day }) = metadata.date {
+        let date = format!("{:04}-{:02}-{:02}", year, month, day);
+        meta.insert("date".to_owned(), meta_inlines(&date));
+    }
+    if !metadata.tags.is_empty() {
+        let tags = metadata.tags.iter().map(|tag| meta_inlines(tag));
+        meta.insert("keywords".to_owned(), meta_list(tags));
+    }
+    for (key, value) in &metadata.custom {
+        meta.entry(key.clone())
+            .or_insert_with(|| meta_inlines(value));
+    }
+    if !entries.is_empty() {
+        let references = entries.iter().map(|entry| reference(entry));
+        meta.insert("references".to_owned(), meta_list(references));
+    }
+    Value::Object(meta)
+}
+
+/// Turns a bibliography entry into a work in the CSL JSON format, which Pandoc's citeproc reads.
+fn reference(entry: &BibliographyEntry) -> Value {
+    let mut fields = Map::new();
+    fields.insert("id".to_owned(), meta_inlines(&entry.key));
+    fields.insert("title".to_owned(), meta_inlines(&entry.title));
+    if !entry.authors.is_empty() {
+        let authors = entry
+            .authors
+            .iter()
+            .map(|author| json!({ "t": "MetaMap", "c": { "literal": meta_inlines(author) } }));
+        fields.insert("author".to_owned(), meta_list(authors));
+    }
+    if let Some(year) = entry.year {
+        fields.insert("issued".to_owned(), meta_inlines(&year.to_string()));
+    }
+    for (scheme, value) in &entry.identifiers {
+        if IDENTIFIERS.contains(&scheme.as_str()) {
+            fields.insert(scheme.to_uppercase(), meta_inlines(value));
+        }
+    }
+    json!({ "t": "MetaMap", "c": fields })
+}
+
+fn meta_inlines(text: &str) -> Value {
+    json!({ "t": "MetaInlines", "c": text_inlines(text) })
+}
+
+fn meta_list(values: impl Iterator<Item = Value>) -> Value {
+    json!({ "t": "MetaList", "c": values.collect::<Vec<_>>() })
+}
+
+/// Splits text into words, spaces and line breaks, like Pandoc's readers do.
+fn text_inlines(text: &str) -> Vec<Value> {
+    let mut inlines = vec![];
+    let mut word = String::new();
+    for c in text.chars() {
+        let separator = match c {
+            ' ' => "Space",
+            '\n' => "LineBreak",
+            c => {
+                word.push(c);
+                continue;
+            }
+        };
+        if !word.is_empty() {
+            inlines.push(json!({ "t": "Str", "c": std::mem::take(&mut word) }));
+        }
+        inlines.push(json!({ "t": separator }));
+    }
+    if !word.is_empty() {
+        inlines.push(json!({ "t": "Str", "c": word }));
+    }
+    inlines
+}
+
+fn citation(keys: &[String]) -> Value {
+    let citations = keys
+        .iter()
+        .map(|key| {
+            json!({
+                "citationId": key,
+                "citationPrefix": [],
+                "citationSuffix": [],
+                "citationMode": { "t": "NormalCitation" },
+                "citationNoteNum": 0,
+                "citationHash": 0,
+            })
+        })
+        .collect::<Vec<_>>();
+    let keys = keys
+        .iter()
+        .map(|key| format!("@{}", key))
+        .collect::<Vec<_>>();
+    json!({ "t": "Cite", "c": [citations, text_inlines(&format!("[{}]", keys.join("; ")))] })
+}
+
+/// Adds a ballot box in front of a list item, like Pandoc's `task_lists` extension does.
+fn with_check_box(checked: bool, mut blocks: Vec<Value>) -> Vec<Value> {
+    let check_box = json!({ "t": "Str", "c": if checked { "☒" } else { "☐" } });
+    match blocks.first_mut() {
+        Some(first) if first["t"] == "Plain" || first["t"] == "Para" => {
+            if let Some(inlines) = first["c"].as_array_mut() {
+                inlines.insert(0, json!({ "t": "Space" }));
+                inlines.insert(0, check_box);
+            }
+        }
+        _ => blocks.insert(0, json!({ "t": "Plain", "c": [check_box] })),
+    }
+    blocks
+}
+
+/// Whether the block becomes a header followed by the body of the section.
+fn is_section(block: &Block<Pure>) -> bool {
+    match block {
+        Block::Section { body, .. } => !matches!(**body, Block::Table { .. }),
+        Block::Anchor { content, .. } => is_section(content),
+        _ => false,
+    }
+}
+
+fn attr(id: &str, classes: &[&str]) -> Value {
+    json!([id, classes, []])
+}
+
+fn paragraph(inlines: Vec<Value>) -> Vec<Value> {
+    if inlines.is_empty() {
+        vec![]
+    } else {
+        vec![json!({ "t": "Para", "c": inlines })]
+    }
+}
+
+fn div(attr: Value, blocks: Vec<Value>) -> Value {
+    json!({ "t": "Div", "c": [attr, blocks] })
+}
+
+fn span(attr: Value, inlines: Vec<Value>) -> Value {
+    json!({ "t": "Span", "c": [attr, inlines] })
+}
+
+fn link(target: &str, inlines: Vec<Value>) -> Value {
+    json!({ "t": "Link", "c": [attr("", &[]), inlines, [target, ""]] })
+}
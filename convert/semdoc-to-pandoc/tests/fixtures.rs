@@ -0,0 +1,67 @@
+//! Documents along with the Pandoc JSON they should turn into. The fixtures are checked in, so
+//! the tests don't need Pandoc.
+
+use semdoc::{Block, Block::*, Definition, Metadata, Pure, SemDoc, TemporalValue};
+use semdoc_to_pandoc::semdoc_to_pandoc;
+use serde_json::Value;
+
+fn text(text: &str) -> Block<Pure> {
+    Text(text.to_owned())
+}
+
+fn assert_exports(doc: &SemDoc<Pure>, fixture: &str) {
+    let exported: Value = serde_json::from_str(&semdoc_to_pandoc(doc)).unwrap();
+    let expected: Value = serde_json::from_str(fixture).unwrap();
+    assert_eq!(exported, expected, "Exported JSON: {}", exported);
+}
+
+#[test]
+fn handbook() {
+    let glossary = Glossary(vec![Definition {
+        term: text("Shift"),
+        definitions: vec![text("Eight hours of work.")],
+    }]);
+    let doc = SemDoc::new(Paragraphs(vec![
+        Anchor {
+            id: "shifts".to_owned(),
+            content: Box::new(Section {
+                title: Box::new(text("Shifts")),
+                body: Box::new(Flow(vec![
+                    text("A "),
+                    TermUsage {
+                        term: "shift".to_owned(),
+                        content: Box::new(text("shift")),
+                    },
+                    text(" starts on "),
+                    Temporal {
+                        value: TemporalValue::Date {
+                            year: 2021,
+                            month: 3,
+                            day: 15,
+                        },
+                        text: "Monday".to_owned(),
+                    },
+                    text("."),
+                ])),
+            }),
+        },
+        Secondary(Box::new(text("Breaks are paid."))),
+        MoreInfo {
+            summary: Box::new(text("Night shifts")),
+            body: Box::new(Highlighted {
+                content: Box::new(text("Twice the pay.")),
+                reason: None,
+            }),
+        },
+        Section {
+            title: Box::new(text("Glossary")),
+            body: Box::new(glossary),
+        },
+    ]))
+    .with_metadata(Metadata {
+        title: Some("Handbook".to_owned()),
+        custom: vec![("lang".to_owned(), "en".to_owned())],
+        ..Default::default()
+    });
+    assert_exports(&doc, include_str!("fixtures/handbook.json"));
+}
@@ -0,0 +1,14 @@
+//! Checks that footnotes become notes where they are referenced.
+
+use markdown_to_semdoc::markdown_to_semdoc;
+use semdoc::SemDoc;
+use semdoc_to_pandoc::semdoc_to_pandoc;
+
+#[test]
+fn footnotes_referencing_themselves_become_links_inside_their_notes() {
+    let doc = markdown_to_semdoc("a[^1]\n\n[^1]: b [^1]");
+    let doc = SemDoc::new(doc.block.simplify()).with_metadata(doc.metadata);
+    let exported = semdoc_to_pandoc(&doc);
+    assert_eq!(exported.matches(r#""t":"Note""#).count(), 1, "{}", exported);
+    assert!(exported.contains("#footnote-"), "{}", exported);
+}
@@ -0,0 +1,31 @@
+//! Checks that converting Pandoc documents to SemDoc, back to Pandoc and to SemDoc again results
+//! in the same document, so documents can round-trip through Pandoc.
+
+use pandoc_to_semdoc::pandoc_to_semdoc;
+use semdoc::{Pure, SemDoc};
+use semdoc_to_pandoc::semdoc_to_pandoc;
+
+fn import(json: &str) -> SemDoc<Pure> {
+    let doc = pandoc_to_semdoc(json).unwrap();
+    SemDoc::new(doc.block.simplify()).with_metadata(doc.metadata)
+}
+
+fn assert_round_trips(json: &str) {
+    let doc = import(json);
+    let exported = semdoc_to_pandoc(&doc);
+    assert_eq!(import(&exported), doc, "Exported JSON: {}", exported);
+}
+
+#[test]
+fn release_notes() {
+    assert_round_trips(include_str!(
+        "../../pandoc-to-semdoc/tests/fixtures/release-notes.json"
+    ));
+}
+
+#[test]
+fn inlines() {
+    assert_round_trips(include_str!(
+        "../../pandoc-to-semdoc/tests/fixtures/inlines.json"
+    ));
+}
@@ -0,0 +1,539 @@
+use semdoc::{
+    BibliographyEntry, Block, Definition, MediaSource, Metadata, Pure, SemDoc, TemporalValue,
+};
+use textwrap::core::display_width;
+
+/// How the plain text is laid out.
+pub struct TextOptions {
+    /// The maximum number of columns of a line. Code and tables keep their lines as they are, so
+    /// they may be wider.
+    pub width: usize,
+    /// The markers of bullet list items. Nested lists use the next marker and start over after
+    /// the last one.
+    pub bullets: Vec<String>,
+}
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            bullets: vec!["*".to_owned(), "-".to_owned(), "+".to_owned()],
+        }
+    }
+}
+
+/// The characters that underline section titles, from top-level sections to nested ones. Deeper
+/// sections use the last one.
+const UNDERLINES: [char; 4] = ['=', '-', '~', '.'];
+
+/// Deeply nested content still gets a few words per line, even if that exceeds the width.
+const MIN_WIDTH: usize = 20;
+
+/// Turns a document into plain text, like for the body of an email or a terminal without colors.
+///
+/// The title, authors and date from the metadata come first.
+pub fn semdoc_to_text(doc: &SemDoc<Pure>, options: &TextOptions) -> String {
+    let header = metadata_to_text(&doc.metadata, options.width);
+    let body = block_to_text(&doc.block, options);
+    if header.is_empty() || body.is_empty() {
+        format!("{}{}", header, body)
+    } else {
+        format!("{}\n{}", header, body)
+    }
+}
+
+/// Turns a block into plain text. Texts are reflowed to the width, list items get markers and
+/// indentation and section titles are underlined according to their depth.
+///
+/// Information that plain text can't express, like highlights, comments or embedded files, is
+/// lost.
+pub fn block_to_text(block: &Block<Pure>, options: &TextOptions) -> String {
+    let layout = Layout {
+        width: options.width,
+        depth: 0,
+        bullet_level: 0,
+    };
+    match (Renderer { options }).block(block, layout) {
+        Some(text) => format!("{}\n", text),
+        None => String::new(),
+    }
+}
+
+/// Where a block is rendered.
+#[derive(Clone, Copy)]
+struct Layout {
+    /// The number of columns available.
+    width: usize,
+    /// The number of sections around the block.
+    depth: usize,
+    /// The number of bullet lists around the block.
+    bullet_level: usize,
+}
+impl Layout {
+    fn indented(self, columns: usize) -> Layout {
+        Layout {
+            width: self.width.saturating_sub(columns).max(MIN_WIDTH),
+            ..self
+        }
+    }
+}
+
+struct Renderer<'a> {
+    options: &'a TextOptions,
+}
+
+impl<'a> Renderer<'a> {
+    /// Renders a block that stands on its own, like a paragraph or a list. Returns `None` if
+    /// there's nothing to render.
+    fn block(&self, block: &Block<Pure>, layout: Layout) -> Option<String> {
+        use Block::*;
+
+        Some(match block {
+            Error(_) | Empty => return None,
+            Paragraphs(children) => self.blocks(children.iter(), layout)?,
+            Section { title, body } => self.section(title, body, layout)?,
+            TopicChange => format!("{}* * *", " ".repeat(layout.width.saturating_sub(5) / 2)),
+            BulletList(items) => {
+                let bullets = &self.options.bullets;
+                let bullet = match bullets.len() {
+                    0 => "*",
+                    len => &bullets[layout.bullet_level % len],
+                };
+                let layout = Layout {
+                    bullet_level: layout.bullet_level + 1,
+                    ..layout
+                };
+                self.list(
+                    items.iter().map(|item| (format!("{} ", bullet), item)),
+                    layout,
+                )
+            }
+            // Numbers are right-aligned, so the content of all items starts in the same column.
+            // They're counted in u128, because lists may start right below u64::MAX.
+            OrderedList { start, items, .. } => {
+                let numbers = (0..items.len())
+                    .map(|index| format!("{}.", u128::from(*start) + index as u128))
+                    .collect::<Vec<_>>();
+                let number_width = numbers.iter().map(|number| number.len()).max();
+                let number_width = number_width.unwrap_or_default();
+                let markers = numbers
+                    .iter()
+                    .map(|number| format!("{:>width$} ", number, width = number_width));
+                self.list(markers.zip(items), layout)
+            }
+            Checklist(items) => self.list(
+                items.iter().map(|item| {
+                    let marker = if item.checked { "[x] " } else { "[ ] " };
+                    (marker.to_owned(), &item.content)
+                }),
+                layout,
+            ),
+            DefinitionList(definitions) | Glossary(definitions) => {
+                self.definitions(definitions, layout)
+            }
+            Code { code, .. } => prefix_lines(code.trim_end_matches('\n'), "    ", "    "),
+            Quote(content) => {
+                let content = self.block(content, layout.indented(2))?;
+                prefix_lines(&content, "> ", "> ")
+            }
+            Table { header, rows } => table(header, rows),
+            Bibliography(entries) => entries
+                .iter()
+                .map(|entry| wrap(&bibliography_entry(entry), layout.width, "    "))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Audio(media) | Video(media) if matches!(media.source, MediaSource::Embedded(_)) => {
+                self.block(&media.transcript, layout)?
+            }
+            MoreInfo { summary, body } => {
+                self.blocks(vec![&**summary, &**body].into_iter(), layout)?
+            }
+            Anchor { content, .. }
+            | Secondary(content)
+            | Signed { content, .. }
+            | Commented { content, .. } => self.block(content, layout)?,
+            Text(_)
+            | Flow(_)
+            | Link { .. }
+            | TermUsage { .. }
+            | CrossReference { .. }
+            | Citation(_)
+            | Temporal { .. }
+            | Highlighted { .. }
+            | Audio(_)
+            | Video(_)
+            | Attachment(_) => {
+                let text = inline(block);
+                if text.trim().is_empty() {
+                    return None;
+                }
+                wrap(text.trim(), layout.width, "")
+            }
+        })
+    }
+
+    /// Renders blocks separated by blank lines.
+    fn blocks<'b>(
+        &self,
+        blocks: impl Iterator<Item = &'b Block<Pure>>,
+        layout: Layout,
+    ) -> Option<String> {
+        let rendered = blocks
+            .filter_map(|block| self.block(block, layout))
+            .collect::<Vec<_>>();
+        if rendered.is_empty() {
+            None
+        } else {
+            Some(rendered.join("\n\n"))
+        }
+    }
+
+    fn section(&self, title: &Block<Pure>, body: &Block<Pure>, layout: Layout) -> Option<String> {
+        let body = self.block(
+            body,
+            Layout {
+                depth: layout.depth + 1,
+                ..layout
+            },
+        );
+        let title = inline(title).replace('\n', " ");
+        if title.trim().is_empty() {
+            return body;
+        }
+        let title = wrap(title.trim(), layout.width, "");
+        let underline = UNDERLINES[layout.depth.min(UNDERLINES.len() - 1)]
+            .to_string()
+            .repeat(title.lines().map(display_width).max().unwrap_or_default());
+        let heading = format!("{}\n{}", title, underline);
+        Some(match body {
+            Some(body) => format!("{}\n\n{}", heading, body),
+            None => heading,
+        })
+    }
+
+    /// Renders list items with the marker in front of the first line. The other lines are
+    /// indented, so they line up with the content.
+    fn list<'b>(
+        &self,
+        items: impl Iterator<Item = (String, &'b Block<Pure>)>,
+        layout: Layout,
+    ) -> String {
+        items
+            .map(|(marker, item)| {
+                let indentation = " ".repeat(display_width(&marker));
+                let content = self.block(item, layout.indented(indentation.len()));
+                prefix_lines(&content.unwrap_or_default(), &marker, &indentation)
+                    .trim_end()
+                    .to_owned()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Renders each term on its own line with the definitions indented below it.
+    fn definitions(&self, definitions: &[Definition<Pure>], layout: Layout) -> String {
+        definitions
+            .iter()
+            .map(|definition| {
+                let term = inline(&definition.term).replace('\n', " ");
+                let mut lines = vec![wrap(term.trim(), layout.width, "")];
+                for details in &definition.definitions {
+                    if let Some(details) = self.block(details, layout.indented(4)) {
+                        lines.push(prefix_lines(&details, "    ", "    "));
+                    }
+                }
+                lines.join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Renders the rows with aligned columns. The header is separated from the rows by a line.
+fn table(header: &[Block<Pure>], rows: &[Vec<Block<Pure>>]) -> String {
+    let cells = |row: &[Block<Pure>]| {
+        row.iter()
+            .map(|cell| inline(cell).replace('\n', " ").trim().to_owned())
+            .collect::<Vec<_>>()
+    };
+    let header = cells(header);
+    let rows = rows.iter().map(|row| cells(row)).collect::<Vec<_>>();
+    let num_columns = rows
+        .iter()
+        .map(|row| row.len())
+        .chain(std::iter::once(header.len()))
+        .max()
+        .unwrap_or_default();
+    let widths = (0..num_columns)
+        .map(|column| {
+            rows.iter()
+                .chain(std::iter::once(&header))
+                .filter_map(|row| row.get(column))
+                .map(|cell| display_width(cell))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let format_row = |row: &[String]| {
+        widths
+            .iter()
+            .enumerate()
+            .map(|(column, width)| {
+                let cell = row.get(column).map(String::as_str).unwrap_or_default();
+                format!("{}{}", cell, " ".repeat(width - display_width(cell)))
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_owned()
+    };
+
+    let mut lines = vec![];
+    if header.iter().any(|cell| !cell.is_empty()) {
+        lines.push(format_row(&header));
+        lines.push(
+            widths
+                .iter()
+                .map(|width| "-".repeat(*width))
+                .collect::<Vec<_>>()
+                .join("  "),
+        );
+    }
+    lines.extend(rows.iter().map(|row| format_row(row)));
+    lines.join("\n")
+}
+
+/// Formats an entry like "[knuth1984] Donald E. Knuth. Literate Programming. 1984."
+fn bibliography_entry(entry: &BibliographyEntry) -> String {
+    let mut parts = vec![format!("[{}]", entry.key)];
+    if !entry.authors.is_empty() {
+        parts.push(format!("{}.", entry.authors.join(", ")));
+    }
+    parts.push(format!("{}.", entry.title));
+    if let Some(year) = entry.year {
+        parts.push(format!("{}.", year));
+    }
+    parts.join(" ")
+}
+
+/// Renders a block inside a line of text. Blocks that can't be inside text become their plain
+/// text.
+fn inline(block: &Block<Pure>) -> String {
+    use Block::*;
+
+    match block {
+        Error(_) | Empty => String::new(),
+        Text(text) => text.clone(),
+        Flow(children) => children.iter().map(inline).collect(),
+        Paragraphs(children) => children.iter().map(inline).collect::<Vec<_>>().join(" "),
+        Code { code, .. } => code.clone(),
+        Link { target, content } => link(&inline(content), target),
+        Citation(keys) => format!("[{}]", keys.join("; ")),
+        Temporal { text, .. } => text.clone(),
+        Audio(media) | Video(media) => match &media.source {
+            MediaSource::External(uri) => link(&inline(&media.transcript), uri),
+            MediaSource::Embedded(_) => inline(&media.transcript),
+        },
+        Attachment(attachment) => attachment.name.clone(),
+        CrossReference { content, .. }
+        | Highlighted { content, .. }
+        | TermUsage { content, .. }
+        | Anchor { content, .. }
+        | Secondary(content)
+        | Signed { content, .. }
+        | Commented { content, .. } => inline(content),
+        _ => block.plain_text().replace('\n', " "),
+    }
+}
+
+/// Readers of plain text can't follow links, so the target comes after the content, like
+/// "the website (https://example.com)".
+fn link(content: &str, target: &str) -> String {
+    let content = content.trim();
+    if content.is_empty() || content == target || Some(content) == target.strip_prefix("mailto:") {
+        target.to_owned()
+    } else {
+        format!("{} ({})", content, target)
+    }
+}
+
+/// Reflows the text to the width. Line breaks in the text are kept and long words, like links,
+/// are not broken. Lines after the first one are indented by the given indentation.
+fn wrap(text: &str, width: usize, indentation: &str) -> String {
+    let options = textwrap::Options::new(width)
+        .subsequent_indent(indentation)
+        .break_words(false);
+    text.split('\n')
+        .map(|line| textwrap::fill(line, &options))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Prefixes the first line and all other lines of the text. Empty lines don't get trailing
+/// spaces.
+fn prefix_lines(text: &str, first_prefix: &str, prefix: &str) -> String {
+    text.split('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            let prefix = if index == 0 { first_prefix } else { prefix };
+            if line.is_empty() {
+                prefix.trim_end().to_owned()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders the title between two lines, followed by the authors and the date. Only dates are
+/// shown, because instants depend on the time zone of the reader.
+fn metadata_to_text(metadata: &Metadata, width: usize) -> String {
+    let mut lines = vec![];
+    if let Some(title) = &metadata.title {
+        let title = wrap(title.trim(), width, "");
+        let rule = "=".repeat(title.lines().map(display_width).max().unwrap_or_default());
+        lines.extend(vec![rule.clone(), title, rule]);
+    }
+    if !metadata.authors.is_empty() {
+        lines.push(wrap(&metadata.authors.join(", "), width, ""));
+    }
+    if let Some(TemporalValue::Date { year, month, day }) = metadata.date {
+        lines.push(format!("{:04}-{:02}-{:02}", year, month, day));
+    }
+    if lines.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use semdoc::Block::*;
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    fn section(title: &str, body: Block<Pure>) -> Block<Pure> {
+        Section {
+            title: Box::new(text(title)),
+            body: Box::new(body),
+        }
+    }
+
+    fn with_width(width: usize) -> TextOptions {
+        TextOptions {
+            width,
+            ..TextOptions::default()
+        }
+    }
+
+    #[test]
+    fn texts_are_reflowed() {
+        let doc = Flow(vec![
+            text("The quick brown fox "),
+            Link {
+                target: "https://example.com/fox".to_owned(),
+                content: Box::new(text("jumps")),
+            },
+            text(" over the lazy dog."),
+        ]);
+        assert_eq!(
+            block_to_text(&doc, &with_width(24)),
+            "The quick\nbrown fox jumps\n(https://example.com/fox)\nover the lazy dog.\n"
+        );
+    }
+
+    #[test]
+    fn section_titles_are_underlined_by_depth() {
+        let doc = Paragraphs(vec![
+            section("Intro", text("Hello.")),
+            section(
+                "Details",
+                Paragraphs(vec![text("More."), section("Even more", Empty)]),
+            ),
+        ]);
+        assert_eq!(
+            block_to_text(&doc, &TextOptions::default()),
+            "Intro\n=====\n\nHello.\n\nDetails\n=======\n\nMore.\n\nEven more\n---------\n"
+        );
+    }
+
+    #[test]
+    fn list_items_get_markers_and_indentation() {
+        let doc = Paragraphs(vec![
+            BulletList(vec![
+                text("A long item that wraps."),
+                Paragraphs(vec![text("Nested:"), BulletList(vec![text("Inner")])]),
+            ]),
+            OrderedList {
+                start: 9,
+                numbering: None,
+                items: vec![text("Nine"), text("Ten")],
+            },
+        ]);
+        assert_eq!(
+            block_to_text(&doc, &with_width(22)),
+            "* A long item that\n  wraps.\n* Nested:\n\n  - Inner\n\n 9. Nine\n10. Ten\n"
+        );
+
+        let options = TextOptions {
+            bullets: vec!["o".to_owned()],
+            ..TextOptions::default()
+        };
+        let doc = BulletList(vec![BulletList(vec![text("Inner")])]);
+        assert_eq!(block_to_text(&doc, &options), "o o Inner\n");
+    }
+
+    #[test]
+    fn list_numbers_can_exceed_u64() {
+        let doc = OrderedList {
+            start: u64::MAX,
+            numbering: None,
+            items: vec![text("Last"), text("Beyond")],
+        };
+        assert_eq!(
+            block_to_text(&doc, &TextOptions::default()),
+            "18446744073709551615. Last\n18446744073709551616. Beyond\n"
+        );
+    }
+
+    #[test]
+    fn code_and_tables_keep_their_lines() {
+        let doc = Paragraphs(vec![
+            Code {
+                language: Some("rust".to_owned()),
+                code: "fn main() {\n    println!(\"Hi\");\n}".to_owned(),
+            },
+            Table {
+                header: vec![text("Name"), text("Age")],
+                rows: vec![vec![text("Ada"), text("36")], vec![text("Grace")]],
+            },
+        ]);
+        assert_eq!(
+            block_to_text(&doc, &with_width(10)),
+            "    fn main() {\n        println!(\"Hi\");\n    }\n\n\
+             Name   Age\n-----  ---\nAda    36\nGrace\n"
+        );
+    }
+
+    #[test]
+    fn metadata_comes_first() {
+        let doc = SemDoc::new(text("Hello.")).with_metadata(Metadata {
+            title: Some("Greetings".to_owned()),
+            authors: vec!["Ada".to_owned(), "Grace".to_owned()],
+            date: Some(TemporalValue::Date {
+                year: 2021,
+                month: 3,
+                day: 14,
+            }),
+            ..Metadata::default()
+        });
+        assert_eq!(
+            semdoc_to_text(&doc, &TextOptions::default()),
+            "=========\nGreetings\n=========\nAda, Grace\n2021-03-14\n\nHello.\n"
+        );
+    }
+}
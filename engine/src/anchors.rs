@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use crate::blocks::*;
+use crate::source::*;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ReferenceError {
+    /// Multiple anchors have the same ID, so references to it are ambiguous.
+    DuplicateAnchor(String),
+    /// A cross-reference points to an ID that no anchor has.
+    UnresolvedReference(String),
+}
+
+impl<S: Source> Block<S> {
+    /// Collects all anchors in this block, keyed by their ID.
+    ///
+    /// If multiple anchors have the same ID, the first one wins.
+    pub fn anchors(&self) -> HashMap<&str, &Block<S>> {
+        let mut anchors = HashMap::new();
+        self.visit_anchors(&mut |id, content| {
+            anchors.entry(id).or_insert(content);
+        });
+        anchors
+    }
+
+    fn visit_anchors<'a>(&'a self, visit: &mut impl FnMut(&'a str, &'a Block<S>)) {
+        if let Block::Anchor { id, content } = self {
+            visit(id, content);
+        }
+        for child in self.children() {
+            child.visit_anchors(visit);
+        }
+    }
+
+    /// Returns the content of the anchor with the given ID.
+    pub fn resolve(&self, id: &str) -> Option<&Block<S>> {
+        self.anchors().get(id).cloned()
+    }
+
+    /// Checks that anchor IDs are unique and that every cross-reference points to an anchor.
+    pub fn validate_references(&self) -> Result<(), ReferenceError> {
+        let mut ids = vec![];
+        self.visit_anchors(&mut |id, _| ids.push(id));
+        let mut seen = HashMap::new();
+        for id in ids {
+            if seen.insert(id, ()).is_some() {
+                return Err(ReferenceError::DuplicateAnchor(id.to_owned()));
+            }
+        }
+        self.validate_targets(&seen)
+    }
+
+    fn validate_targets(&self, anchors: &HashMap<&str, ()>) -> Result<(), ReferenceError> {
+        if let Block::CrossReference { target, .. } = self {
+            if !anchors.contains_key(target.as_str()) {
+                return Err(ReferenceError::UnresolvedReference(target.clone()));
+            }
+        }
+        for child in self.children() {
+            child.validate_targets(anchors)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Block::*;
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    fn section(id: &str, title: &str) -> Block<Pure> {
+        Anchor {
+            id: id.to_owned(),
+            content: Box::new(Section {
+                title: Box::new(text(title)),
+                body: Box::new(Empty),
+            }),
+        }
+    }
+
+    fn reference(target: &str) -> Block<Pure> {
+        CrossReference {
+            target: target.to_owned(),
+            content: Box::new(text("see there")),
+        }
+    }
+
+    #[test]
+    fn references_resolve_to_anchors() {
+        let document = Paragraphs(vec![section("intro", "Introduction"), reference("intro")]);
+        assert_eq!(document.validate_references(), Ok(()));
+        assert_eq!(
+            document.resolve("intro"),
+            Some(&Section {
+                title: Box::new(text("Introduction")),
+                body: Box::new(Empty),
+            })
+        );
+        assert_eq!(document.resolve("outro"), None);
+    }
+
+    #[test]
+    fn unresolved_references_are_invalid() {
+        let document = Paragraphs(vec![section("intro", "Introduction"), reference("outro")]);
+        assert_eq!(
+            document.validate_references(),
+            Err(ReferenceError::UnresolvedReference("outro".to_owned()))
+        );
+    }
+
+    #[test]
+    fn duplicate_anchors_are_invalid() {
+        let document = Paragraphs(vec![section("intro", "One"), section("intro", "Two")]);
+        assert_eq!(
+            document.validate_references(),
+            Err(ReferenceError::DuplicateAnchor("intro".to_owned()))
+        );
+    }
+}
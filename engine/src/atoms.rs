@@ -73,7 +73,7 @@ impl Atom {
                 assert!(payload_bytes.len() <= MAX_VALUE_USING_7_BYTES as usize, "The bytes saved in an Atom::Bytes are too long. The maximum supported length is {}.", MAX_VALUE_USING_7_BYTES);
                 let mut bytes = vec![kind::BYTES];
                 bytes.extend_from_slice(&(payload_bytes.len() as u64).to_be_bytes()[1..]);
-                bytes.extend_from_slice(&payload_bytes);
+                bytes.extend_from_slice(payload_bytes);
                 bytes.align();
                 bytes
             }
@@ -81,7 +81,7 @@ impl Atom {
                 assert!(payload_bytes.len() <= u8::MAX as usize, "The bytes saved in an Atom::FewBytes are too long. The maximum supported length is {}.", u8::MAX);
                 let mut bytes = vec![kind::FEW_BYTES];
                 bytes.push(payload_bytes.len() as u8);
-                bytes.extend_from_slice(&payload_bytes);
+                bytes.extend_from_slice(payload_bytes);
                 bytes.align();
                 bytes
             }
@@ -100,17 +100,25 @@ impl Atom {
             return Err(AtomError::UnexpectedEnd);
         }
         Ok(match *bytes.first().ok_or(AtomError::UnexpectedEnd)? {
-            kind::BLOCK => Block {
-                kind: u64::clone_from_slice(&bytes[2..8]),
-                num_children: u64::clone_from_slice(&bytes[9..16]),
-            },
+            kind::BLOCK => {
+                if bytes.len() < 16 {
+                    return Err(AtomError::UnexpectedEnd);
+                }
+                if bytes[1] != 0 {
+                    return Err(AtomError::AlignmentNotZero);
+                }
+                Block {
+                    kind: u64::clone_from_slice(&bytes[2..8]),
+                    num_children: u64::clone_from_slice(&bytes[8..16]),
+                }
+            }
             kind::SMALL_BLOCK => SmallBlock {
                 kind: u64::clone_from_slice(&bytes[2..8]),
                 num_children: *bytes.get(1).ok_or(AtomError::UnexpectedEnd)?,
             },
             kind::BYTES => {
                 let length = (u64::clone_from_slice(&bytes[1..8])) as usize;
-                if bytes.len() < 8 + length {
+                if bytes.len() < (8 + length).round_up_to_multiple_of(8) {
                     return Err(AtomError::UnexpectedEnd);
                 }
                 let payload_bytes = &bytes[8..(8 + length)];
@@ -128,7 +136,13 @@ impl Atom {
                     return Err(AtomError::UnexpectedEnd);
                 }
                 let payload_bytes = &bytes[2..(2 + length)];
-                // TODO(marcelgarus): Check alignment bytes.
+                let end = (2 + length).round_up_to_multiple_of(8);
+                if bytes.len() < end {
+                    return Err(AtomError::UnexpectedEnd);
+                }
+                if bytes[(2 + length)..end].iter().any(|byte| *byte != 0) {
+                    return Err(AtomError::AlignmentNotZero);
+                }
                 FewBytes(payload_bytes.to_vec())
             }
             kind::REFERENCE => Reference(u64::clone_from_slice(&bytes[1..8])),
@@ -139,3 +153,41 @@ impl Atom {
 
 const MAX_VALUE_USING_6_BYTES: u64 = 281474976710656 - 1;
 const MAX_VALUE_USING_7_BYTES: u64 = 72057594037927936 - 1;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn block_atoms_round_trip() {
+        let atom = Atom::Block {
+            kind: 3,
+            num_children: 300,
+        };
+        let bytes = atom.to_bytes();
+        assert!(matches!(
+            Atom::try_from(&bytes),
+            Ok(Atom::Block {
+                kind: 3,
+                num_children: 300
+            })
+        ));
+        assert!(matches!(
+            Atom::try_from(&bytes[..8]),
+            Err(AtomError::UnexpectedEnd)
+        ));
+    }
+
+    #[test]
+    fn alignment_of_few_bytes_is_checked() {
+        let mut bytes = Atom::FewBytes(vec![1, 2, 3]).to_bytes();
+        assert!(
+            matches!(Atom::try_from(&bytes), Ok(Atom::FewBytes(payload)) if payload == [1, 2, 3])
+        );
+        bytes[7] = 1;
+        assert!(matches!(
+            Atom::try_from(&bytes),
+            Err(AtomError::AlignmentNotZero)
+        ));
+    }
+}
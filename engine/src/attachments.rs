@@ -0,0 +1,162 @@
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
+
+use crate::blocks::*;
+use crate::molecule::*;
+use crate::source::*;
+
+/// A file embedded in a document using `Block::Attachment`, like a spreadsheet supporting a
+/// contract.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AttachedFile {
+    /// The file name, like "prices.csv".
+    pub name: String,
+    /// The MIME type of the file, like "text/csv".
+    pub mime_type: String,
+    /// The size of the data in bytes.
+    pub size: u64,
+    /// The SHA-256 hash of the data.
+    pub checksum: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AttachmentError {
+    /// The data doesn't have the size stored in the attachment.
+    SizeMismatch { name: String },
+    /// The data doesn't match the checksum stored in the attachment.
+    ChecksumMismatch { name: String },
+}
+
+impl AttachedFile {
+    /// Creates an attachment for the given data, calculating its size and checksum.
+    pub fn new(name: &str, mime_type: &str, data: Vec<u8>) -> AttachedFile {
+        AttachedFile {
+            name: name.to_owned(),
+            mime_type: mime_type.to_owned(),
+            size: data.len() as u64,
+            checksum: Sha256::digest(&data).to_vec(),
+            data,
+        }
+    }
+
+    /// Returns the data after making sure it's what was originally attached.
+    pub fn extract(&self) -> Result<&[u8], AttachmentError> {
+        if self.data.len() as u64 != self.size {
+            return Err(AttachmentError::SizeMismatch {
+                name: self.name.clone(),
+            });
+        }
+        if Sha256::digest(&self.data)[..] != self.checksum[..] {
+            return Err(AttachmentError::ChecksumMismatch {
+                name: self.name.clone(),
+            });
+        }
+        Ok(&self.data)
+    }
+
+    pub fn to_molecule<S: Source>(&self) -> Molecule<S> {
+        Molecule::block(
+            kinds::ATTACHMENT,
+            vec![
+                Molecule::Bytes(self.name.as_bytes().to_vec()),
+                Molecule::Bytes(self.mime_type.as_bytes().to_vec()),
+                Molecule::Bytes(self.size.to_be_bytes().to_vec()),
+                Molecule::Bytes(self.checksum.clone()),
+                // Files can be big, so they are saved after the content of the document.
+                Molecule::OutOfLine(Box::new(Molecule::Bytes(self.data.clone()))),
+            ],
+        )
+    }
+
+    pub fn try_from<S: Source>(children: Vec<Molecule<S>>) -> Result<AttachedFile, BlockError> {
+        Ok(AttachedFile {
+            name: String::from_utf8(children.need_at(0)?.need_bytes()?)
+                .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+            mime_type: String::from_utf8(children.need_at(1)?.need_bytes()?)
+                .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+            size: u64::from_be_bytes(
+                children.need_at(2)?.need_bytes()?[..]
+                    .try_into()
+                    .map_err(|_| BlockError::InvalidAttachmentSize)?,
+            ),
+            checksum: children.need_at(3)?.need_bytes()?,
+            data: children.need_at(4)?.need_bytes()?,
+        })
+    }
+}
+
+impl<S: Source> Block<S> {
+    /// Returns all attachments in this block and the blocks inside of it.
+    pub fn attachments(&self) -> Vec<&AttachedFile> {
+        let mut attachments = vec![];
+        self.collect_attachments_into(&mut attachments);
+        attachments
+    }
+
+    fn collect_attachments_into<'a>(&'a self, attachments: &mut Vec<&'a AttachedFile>) {
+        if let Block::Attachment(attachment) = self {
+            attachments.push(attachment);
+        }
+        for child in self.children() {
+            child.collect_attachments_into(attachments);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doc::*;
+    use crate::Block::*;
+    use quickcheck::*;
+
+    impl Arbitrary for AttachedFile {
+        fn arbitrary(g: &mut Gen) -> Self {
+            AttachedFile::new(
+                &String::arbitrary(g),
+                &String::arbitrary(g),
+                Vec::arbitrary(g),
+            )
+        }
+    }
+
+    fn contract() -> SemDoc<Pure> {
+        SemDoc::new(Paragraphs(vec![
+            Text("Alice pays Bob the prices listed in the attachment.".to_string()),
+            Attachment(AttachedFile::new(
+                "prices.csv",
+                "text/csv",
+                b"item,price\ngoods,100\n".to_vec(),
+            )),
+        ]))
+    }
+
+    #[test]
+    fn attachments_survive_encoding() {
+        let doc = contract();
+        let decoded = SemDoc::from_bytes(&doc.to_bytes()).unwrap();
+        let decoded = decoded.into_pure().unwrap();
+        assert_eq!(decoded, doc);
+        let attachments = decoded.block.attachments();
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(
+            attachments[0].extract(),
+            Ok(&b"item,price\ngoods,100\n"[..])
+        );
+    }
+
+    #[test]
+    fn damaged_attachments_fail_extraction() {
+        let mut bytes = contract().to_bytes();
+        let position = bytes.windows(5).position(|it| it == b"goods").unwrap();
+        bytes[position] = b'f';
+        let doc = SemDoc::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            doc.block.attachments()[0].extract(),
+            Err(AttachmentError::ChecksumMismatch {
+                name: "prices.csv".to_string()
+            })
+        );
+    }
+}
@@ -1,5 +1,14 @@
+use std::convert::TryInto;
+
+use crate::attachments::*;
+use crate::citations::*;
+use crate::comments::*;
+use crate::glossary::*;
+use crate::media::*;
 use crate::molecule::*;
+use crate::signatures::*;
 use crate::source::*;
+use crate::temporal::*;
 
 /// Every SemDoc is a composition of blocks.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -47,18 +56,380 @@ pub enum Block<S: Source> {
     /// Displays multiple other blocks with a small content break.
     Paragraphs(Vec<Block<S>>),
 
+    /// A bigger content break inside `Paragraphs`, indicating that the following paragraphs are
+    /// about a different topic.
+    ///
+    /// For example, stories use it for scene breaks. Readers may display it as a horizontal line
+    /// or as some extra space.
+    TopicChange,
+
     /// Displays multiple blocks in a bullet list.
     BulletList(Vec<Block<S>>),
 
     /// Displays multiple blocks in a numbered list.
-    OrderedList(Vec<Block<S>>),
+    ///
+    /// The first item has the number `start`, so lists can continue where a previous list stopped
+    /// and items keep the numbers that other content refers to. The optional numbering hint tells
+    /// readers what the numbers mean, so they can choose a fitting numbering style.
+    OrderedList {
+        start: u64,
+        numbering: Option<ListNumbering>,
+        items: Vec<Block<S>>,
+    },
+
+    /// Displays multiple blocks in a list where each item is either checked or not.
+    ///
+    /// For example, a checklist for a release process could show which steps are already done.
+    Checklist(Vec<ChecklistItem<S>>),
+
+    /// Displays cells in rows and columns.
+    ///
+    /// The header contains a cell for each column that describes the content of the column.
+    Table {
+        header: Vec<Block<S>>,
+        rows: Vec<Vec<Block<S>>>,
+    },
+
+    /// Source code or other text that is meant for computers, like a command or a configuration
+    /// file.
+    ///
+    /// Readers keep the text as is, including its whitespace. If the language is known, readers
+    /// may highlight the syntax.
+    Code {
+        language: Option<String>,
+        code: String,
+    },
+
+    /// Content that someone else said or wrote.
+    Quote(Box<Block<S>>),
+
+    /// A reference to something outside of the document, like a website.
+    Link {
+        target: String,
+        content: Box<Block<S>>,
+    },
+
+    /// Displays terms along with their definitions.
+    DefinitionList(Vec<Definition<S>>),
+
+    /// Definitions of terms that are used throughout the document.
+    ///
+    /// Usages of the terms can be marked using `TermUsage` blocks, so readers can show the
+    /// definition right where the term is used, for example, when hovering over it.
+    Glossary(Vec<Definition<S>>),
+
+    /// A usage of a term defined in a glossary.
+    ///
+    /// The term is the normalized text of the glossary term, the content is what the document
+    /// displays.
+    TermUsage {
+        term: String,
+        content: Box<Block<S>>,
+    },
+
+    /// Content that other blocks can refer to using the given ID.
+    ///
+    /// IDs should be unique in a document and stay the same across versions of it, so references
+    /// from outside the document remain valid.
+    Anchor {
+        id: String,
+        content: Box<Block<S>>,
+    },
+
+    /// A reference to the content of the `Anchor` with the given ID, like "see section 3".
+    ///
+    /// Readers may let users jump to the target or preview it. If the content is empty, readers
+    /// generate a fitting description of the target.
+    CrossReference {
+        target: String,
+        content: Box<Block<S>>,
+    },
+
+    /// Works that the document cites.
+    Bibliography(Vec<BibliographyEntry>),
+
+    /// A citation of one or more works in a bibliography, referred to by their keys.
+    ///
+    /// Readers format citations in the style the user prefers, like "[1]" or "(Knuth, 1984)".
+    Citation(Vec<String>),
+
+    /// A date, an instant or a duration, like "next Tuesday" or "two hours".
+    ///
+    /// Readers can show the value in the user's locale and timezone. The text is how the author
+    /// originally wrote it, which readers can show if they don't support the value.
+    Temporal { value: TemporalValue, text: String },
+
+    /// An audio clip, like a recorded interview.
+    Audio(Media<S>),
+
+    /// A video, like a screen recording of a tutorial.
+    Video(Media<S>),
+
+    /// A file embedded in the document.
+    Attachment(AttachedFile),
+
+    /// Content that stands out from the surrounding content.
+    ///
+    /// Instead of a color, the block contains an optional reason why the content is highlighted.
+    /// Readers decide how to present it, for example, using a marker-like background.
+    Highlighted {
+        content: Box<Block<S>>,
+        reason: Option<HighlightReason>,
+    },
+
+    /// Content that is not essential, like an aside or a side note.
+    ///
+    /// Readers may de-emphasize it, for example, by displaying it smaller or in a margin.
+    Secondary(Box<Block<S>>),
+
+    /// Content that is only relevant for some readers and can be revealed on demand.
+    ///
+    /// The summary is always visible and tells readers what's hidden in the body. Readers may
+    /// collapse the body by default.
+    MoreInfo {
+        summary: Box<Block<S>>,
+        body: Box<Block<S>>,
+    },
+
+    // Trust.
+
+    /// Content that one or more signers vouch for.
+    ///
+    /// Each signature covers the canonical byte encoding of the content as well as the signer's
+    /// name, so changing either of them invalidates the signature. Signing the root block signs
+    /// the whole document.
+    Signed {
+        content: Box<Block<S>>,
+        signatures: Vec<Signature>,
+    },
+
+    // Collaboration.
+
+    /// Content with comments of reviewers attached to it.
+    ///
+    /// Readers may show or hide the comments. Before distributing a final version of a document,
+    /// all comments can be removed using `without_comments`.
+    Commented {
+        content: Box<Block<S>>,
+        comments: Vec<Comment>,
+    },
 }
 use Block::*;
 
+/// An item of a `Block::Checklist`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ChecklistItem<S: Source> {
+    pub checked: bool,
+    pub content: Block<S>,
+}
+
+/// What the numbers of a `Block::OrderedList` mean.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ListNumbering {
+    /// Steps that should be followed in order, like in instructions.
+    Steps,
+    /// Clauses that are referred to by their number, like in contracts or specifications. Nested
+    /// clauses are usually numbered hierarchically, like 2.1.3.
+    Clauses,
+    /// Items ranked by some criterion, like in a top ten list.
+    Ranking,
+}
+impl ListNumbering {
+    fn to_byte(self) -> u8 {
+        match self {
+            ListNumbering::Steps => 0,
+            ListNumbering::Clauses => 1,
+            ListNumbering::Ranking => 2,
+        }
+    }
+
+    /// Returns `None` for unknown numberings, so that newer numberings still get displayed.
+    fn from_byte(byte: u8) -> Option<ListNumbering> {
+        match byte {
+            0 => Some(ListNumbering::Steps),
+            1 => Some(ListNumbering::Clauses),
+            2 => Some(ListNumbering::Ranking),
+            _ => None,
+        }
+    }
+}
+
+/// Why some content is highlighted.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum HighlightReason {
+    /// The content matches something the reader searched for.
+    SearchHit,
+    /// The content is one of the most important points of the document.
+    KeyPoint,
+    /// The content changed compared to a previous version of the document.
+    Change,
+}
+impl HighlightReason {
+    fn to_byte(self) -> u8 {
+        match self {
+            HighlightReason::SearchHit => 0,
+            HighlightReason::KeyPoint => 1,
+            HighlightReason::Change => 2,
+        }
+    }
+
+    /// Returns `None` for unknown reasons, so that newer reasons still get highlighted.
+    fn from_byte(byte: u8) -> Option<HighlightReason> {
+        match byte {
+            0 => Some(HighlightReason::SearchHit),
+            1 => Some(HighlightReason::KeyPoint),
+            2 => Some(HighlightReason::Change),
+            _ => None,
+        }
+    }
+}
+
 impl<S: Source> Block<S> {
     pub fn is_empty(&self) -> bool {
         matches!(self, Empty)
     }
+    pub fn children(&self) -> Vec<&Block<S>> {
+        match self {
+            Error(_)
+            | Empty
+            | Text(_)
+            | TopicChange
+            | Bibliography(_)
+            | Citation(_)
+            | Temporal { .. }
+            | Attachment(_)
+            | Code { .. } => vec![],
+            Section { title, body } => vec![title, body],
+            Checklist(items) => items.iter().map(|item| &item.content).collect(),
+            Table { header, rows } => header.iter().chain(rows.iter().flatten()).collect(),
+            Quote(content) | Link { content, .. } => vec![content],
+            DefinitionList(definitions) | Glossary(definitions) => definitions
+                .iter()
+                .flat_map(|definition| {
+                    std::iter::once(&definition.term).chain(definition.definitions.iter())
+                })
+                .collect(),
+            TermUsage { content, .. } | Anchor { content, .. } | CrossReference { content, .. } => {
+                vec![content]
+            }
+            Highlighted { content, .. } | Secondary(content) => vec![content],
+            Audio(media) | Video(media) => vec![&media.transcript],
+            MoreInfo { summary, body } => vec![summary, body],
+            Flow(children)
+            | Paragraphs(children)
+            | BulletList(children)
+            | OrderedList {
+                items: children, ..
+            } => {
+                children.iter().collect()
+            }
+            Signed { content, .. } | Commented { content, .. } => vec![content],
+        }
+    }
+    /// Returns the text of this block and all blocks inside of it.
+    pub fn plain_text(&self) -> String {
+        match self {
+            Text(text) | Temporal { text, .. } | Code { code: text, .. } => text.clone(),
+            Flow(children) => children.iter().map(|child| child.plain_text()).collect(),
+            other => other
+                .children()
+                .iter()
+                .map(|child| child.plain_text())
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+    pub fn map_children(self, mut f: impl FnMut(Block<S>) -> Block<S>) -> Block<S> {
+        match self {
+            block @ (Error(_)
+            | Empty
+            | Text(_)
+            | TopicChange
+            | Bibliography(_)
+            | Citation(_)
+            | Temporal { .. }
+            | Attachment(_)
+            | Code { .. }) => block,
+            Section { title, body } => Section {
+                title: Box::new(f(*title)),
+                body: Box::new(f(*body)),
+            },
+            Flow(children) => Flow(children.into_iter().map(f).collect()),
+            Paragraphs(children) => Paragraphs(children.into_iter().map(f).collect()),
+            BulletList(items) => BulletList(items.into_iter().map(f).collect()),
+            OrderedList {
+                start,
+                numbering,
+                items,
+            } => OrderedList {
+                start,
+                numbering,
+                items: items.into_iter().map(f).collect(),
+            },
+            Checklist(items) => Checklist(
+                items
+                    .into_iter()
+                    .map(|item| ChecklistItem {
+                        checked: item.checked,
+                        content: f(item.content),
+                    })
+                    .collect(),
+            ),
+            Table { header, rows } => Table {
+                header: header.into_iter().map(&mut f).collect(),
+                rows: rows
+                    .into_iter()
+                    .map(|row| row.into_iter().map(&mut f).collect())
+                    .collect(),
+            },
+            Quote(content) => Quote(Box::new(f(*content))),
+            Link { target, content } => Link {
+                target,
+                content: Box::new(f(*content)),
+            },
+            DefinitionList(definitions) => {
+                DefinitionList(definitions.into_iter().map(|it| it.map(&mut f)).collect())
+            }
+            Glossary(definitions) => {
+                Glossary(definitions.into_iter().map(|it| it.map(&mut f)).collect())
+            }
+            TermUsage { term, content } => TermUsage {
+                term,
+                content: Box::new(f(*content)),
+            },
+            Anchor { id, content } => Anchor {
+                id,
+                content: Box::new(f(*content)),
+            },
+            CrossReference { target, content } => CrossReference {
+                target,
+                content: Box::new(f(*content)),
+            },
+            Highlighted { content, reason } => Highlighted {
+                content: Box::new(f(*content)),
+                reason,
+            },
+            Secondary(content) => Secondary(Box::new(f(*content))),
+            Audio(media) => Audio(media.map(f)),
+            Video(media) => Video(media.map(f)),
+            MoreInfo { summary, body } => MoreInfo {
+                summary: Box::new(f(*summary)),
+                body: Box::new(f(*body)),
+            },
+            Signed {
+                content,
+                signatures,
+            } => Signed {
+                content: Box::new(f(*content)),
+                signatures,
+            },
+            Commented { content, comments } => Commented {
+                content: Box::new(f(*content)),
+                comments,
+            },
+        }
+    }
     pub fn simplify(self) -> Block<S> {
         use Block::*;
         match self {
@@ -81,7 +452,12 @@ impl<S: Source> Block<S> {
                 }
             }
             Flow(children) => {
-                let original_children = children.simplify();
+                // Nested flows are just a part of the outer flow.
+                let original_children =
+                    children.simplify().into_iter().flat_map(|child| match child {
+                        Flow(grandchildren) => grandchildren,
+                        other => vec![other],
+                    });
                 
                 // Merge adjacent texts.
                 let mut children = vec![];
@@ -109,7 +485,15 @@ impl<S: Source> Block<S> {
                 }
             },
             Paragraphs(children) => {
-                let children = children.simplify();
+                // Topic changes only make sense between paragraphs.
+                let mut children = children.simplify();
+                children.dedup_by(|a, b| matches!((a, b), (TopicChange, TopicChange)));
+                if matches!(children.first(), Some(TopicChange)) {
+                    children.remove(0);
+                }
+                if matches!(children.last(), Some(TopicChange)) {
+                    children.pop();
+                }
                 match children.len() {
                     0 => Empty,
                     1 => children.first().unwrap().clone(),
@@ -117,7 +501,89 @@ impl<S: Source> Block<S> {
                 }
             }
             BulletList(items) => BulletList(items.simplify()),
-            OrderedList(items) => OrderedList(items.simplify()),
+            OrderedList {
+                start,
+                numbering,
+                items,
+            } => OrderedList {
+                start,
+                numbering,
+                items: items.simplify(),
+            },
+            Checklist(items) => Checklist(
+                items
+                    .into_iter()
+                    .map(|item| ChecklistItem {
+                        checked: item.checked,
+                        content: item.content.simplify(),
+                    })
+                    .collect(),
+            ),
+            // Empty cells stay, so that the other cells stay in their columns.
+            Table { header, rows } => Table {
+                header: header.into_iter().map(|cell| cell.simplify()).collect(),
+                rows: rows
+                    .into_iter()
+                    .map(|row| row.into_iter().map(|cell| cell.simplify()).collect())
+                    .collect(),
+            },
+            Code { language, code } => Code { language, code },
+            Quote(content) => match content.simplify() {
+                Empty => Empty,
+                content => Quote(Box::new(content)),
+            },
+            // Links without content stay, because readers can display the target instead.
+            Link { target, content } => Link { target, content: Box::new(content.simplify()) },
+            DefinitionList(definitions) => DefinitionList(
+                definitions.into_iter().map(|it| it.map(|block| block.simplify())).collect(),
+            ),
+            Glossary(definitions) => Glossary(
+                definitions.into_iter().map(|it| it.map(|block| block.simplify())).collect(),
+            ),
+            TermUsage { term, content } => match content.simplify() {
+                Empty => Empty,
+                content => TermUsage { term, content: Box::new(content) },
+            },
+            // Anchors and references stay, because other blocks may refer to them and readers can
+            // describe targets themselves.
+            Anchor { id, content } => Anchor { id, content: Box::new(content.simplify()) },
+            CrossReference { target, content } => {
+                CrossReference { target, content: Box::new(content.simplify()) }
+            }
+            Bibliography(entries) => Bibliography(entries),
+            TopicChange => TopicChange,
+            Citation(keys) => Citation(keys),
+            Temporal { value, text } => Temporal { value, text },
+            Attachment(attachment) => Attachment(attachment),
+            Highlighted { content, reason } => match content.simplify() {
+                Empty => Empty,
+                content => Highlighted { content: Box::new(content), reason },
+            },
+            Audio(media) => Audio(media.map(|transcript| transcript.simplify())),
+            Video(media) => Video(media.map(|transcript| transcript.simplify())),
+            Secondary(content) => match content.simplify() {
+                Empty => Empty,
+                content => Secondary(Box::new(content)),
+            },
+            MoreInfo { summary, body } => {
+                let summary = summary.simplify();
+                let body = body.simplify();
+                if body.is_empty() {
+                    summary
+                } else {
+                    MoreInfo { summary: Box::new(summary), body: Box::new(body) }
+                }
+            }
+            // Simplifying the content would change its bytes and break the signatures.
+            Signed { content, signatures } => Signed { content, signatures },
+            Commented { content, comments } => {
+                let content = content.simplify();
+                if comments.is_empty() {
+                    content
+                } else {
+                    Commented { content: Box::new(content), comments }
+                }
+            }
         }
     }
 }
@@ -144,6 +610,41 @@ pub mod kinds {
     pub const PARAGRAPHS: u64 = 4;
     pub const BULLET_LIST: u64 = 5;
     pub const ORDERED_LIST: u64 = 6;
+    pub const SIGNED: u64 = 7;
+    pub const COMMENTED: u64 = 8;
+    /// Not a block on its own, but a comment inside a `Commented` block.
+    pub const COMMENT: u64 = 9;
+    pub const SECONDARY: u64 = 10;
+    pub const MORE_INFO: u64 = 11;
+    pub const HIGHLIGHTED: u64 = 12;
+    pub const CHECKLIST: u64 = 13;
+    pub const DEFINITION_LIST: u64 = 14;
+    /// Not a block on its own, but a term with its definitions inside a `DefinitionList` or
+    /// `Glossary` block.
+    pub const DEFINITION: u64 = 15;
+    pub const GLOSSARY: u64 = 16;
+    pub const TERM_USAGE: u64 = 17;
+    pub const ANCHOR: u64 = 18;
+    pub const CROSS_REFERENCE: u64 = 19;
+    pub const BIBLIOGRAPHY: u64 = 20;
+    /// Not a block on its own, but an entry of a bibliography.
+    pub const BIBLIOGRAPHY_ENTRY: u64 = 21;
+    pub const CITATION: u64 = 22;
+    pub const TEMPORAL: u64 = 23;
+    pub const TOPIC_CHANGE: u64 = 24;
+    pub const AUDIO: u64 = 25;
+    pub const VIDEO: u64 = 26;
+    pub const ATTACHMENT: u64 = 27;
+    pub const TABLE: u64 = 28;
+    /// Not a block on its own, but a row of a table.
+    pub const TABLE_ROW: u64 = 29;
+    pub const CODE: u64 = 30;
+    pub const QUOTE: u64 = 31;
+    pub const LINK: u64 = 32;
+    /// Not a block on its own, but a document with metadata.
+    pub const DOCUMENT: u64 = 33;
+    /// Not a block on its own, but the metadata of a document.
+    pub const METADATA: u64 = 34;
 }
 
 impl<S: Source> Block<S> {
@@ -163,7 +664,114 @@ impl<S: Source> Block<S> {
                 Molecule::block(kinds::PARAGRAPHS, children.clone().into_molecules())
             }
             BulletList(items) => Molecule::block(kinds::BULLET_LIST, items.clone().into_molecules()),
-            OrderedList(items) => Molecule::block(kinds::ORDERED_LIST, items.clone().into_molecules()),
+            OrderedList {
+                start,
+                numbering,
+                items,
+            } => {
+                let mut children = vec![
+                    Molecule::Bytes(start.to_be_bytes().to_vec()),
+                    Molecule::Bytes(numbering.iter().map(|it| it.to_byte()).collect()),
+                ];
+                children.extend(items.clone().into_molecules());
+                Molecule::block(kinds::ORDERED_LIST, children)
+            }
+            Checklist(items) => Molecule::block(
+                kinds::CHECKLIST,
+                items
+                    .iter()
+                    .flat_map(|item| {
+                        vec![
+                            Molecule::Bytes(vec![item.checked as u8]),
+                            item.content.to_molecule(),
+                        ]
+                    })
+                    .collect(),
+            ),
+            Table { header, rows } => Molecule::block(
+                kinds::TABLE,
+                std::iter::once(header)
+                    .chain(rows.iter())
+                    .map(|row| Molecule::block(kinds::TABLE_ROW, row.clone().into_molecules()))
+                    .collect(),
+            ),
+            Code { language, code } => Molecule::block(
+                kinds::CODE,
+                vec![
+                    Molecule::Bytes(language.iter().flat_map(|it| it.bytes()).collect()),
+                    Molecule::Bytes(code.as_bytes().to_vec()),
+                ],
+            ),
+            Quote(content) => Molecule::block(kinds::QUOTE, vec![content.to_molecule()]),
+            Link { target, content } => Molecule::block(
+                kinds::LINK,
+                vec![Molecule::Bytes(target.as_bytes().to_vec()), content.to_molecule()],
+            ),
+            DefinitionList(definitions) => Molecule::block(
+                kinds::DEFINITION_LIST,
+                definitions.iter().map(|it| it.to_molecule()).collect(),
+            ),
+            Glossary(definitions) => Molecule::block(
+                kinds::GLOSSARY,
+                definitions.iter().map(|it| it.to_molecule()).collect(),
+            ),
+            TermUsage { term, content } => Molecule::block(
+                kinds::TERM_USAGE,
+                vec![Molecule::Bytes(term.as_bytes().to_vec()), content.to_molecule()],
+            ),
+            Anchor { id, content } => Molecule::block(
+                kinds::ANCHOR,
+                vec![Molecule::Bytes(id.as_bytes().to_vec()), content.to_molecule()],
+            ),
+            CrossReference { target, content } => Molecule::block(
+                kinds::CROSS_REFERENCE,
+                vec![Molecule::Bytes(target.as_bytes().to_vec()), content.to_molecule()],
+            ),
+            Bibliography(entries) => Molecule::block(
+                kinds::BIBLIOGRAPHY,
+                entries.iter().map(|entry| entry.to_molecule()).collect(),
+            ),
+            Citation(keys) => Molecule::block(
+                kinds::CITATION,
+                keys.iter().map(|key| Molecule::Bytes(key.as_bytes().to_vec())).collect(),
+            ),
+            TopicChange => Molecule::block(kinds::TOPIC_CHANGE, vec![]),
+            Temporal { value, text } => Molecule::block(
+                kinds::TEMPORAL,
+                vec![
+                    Molecule::Bytes(value.to_bytes()),
+                    Molecule::Bytes(text.as_bytes().to_vec()),
+                ],
+            ),
+            Highlighted { content, reason } => {
+                let mut children = vec![content.to_molecule()];
+                if let Some(reason) = reason {
+                    children.push(Molecule::Bytes(vec![reason.to_byte()]));
+                }
+                Molecule::block(kinds::HIGHLIGHTED, children)
+            }
+            Secondary(content) => Molecule::block(kinds::SECONDARY, vec![content.to_molecule()]),
+            Attachment(attachment) => attachment.to_molecule(),
+            Audio(media) => media.to_molecule(kinds::AUDIO),
+            Video(media) => media.to_molecule(kinds::VIDEO),
+            MoreInfo { summary, body } => Molecule::block(
+                kinds::MORE_INFO,
+                vec![summary.to_molecule(), body.to_molecule()],
+            ),
+            Signed { content, signatures } => {
+                let mut children = vec![content.to_molecule()];
+                for signature in signatures {
+                    children.push(Molecule::Bytes(signature.signer.as_bytes().to_vec()));
+                    children.push(Molecule::Bytes(signature.public_key.clone()));
+                    children.push(Molecule::Bytes(signature.signature.clone()));
+                }
+                Molecule::block(kinds::SIGNED, children)
+            }
+            Commented { content, comments } => {
+                let mut children = vec![content.to_molecule()];
+                children.extend(comments.iter().map(|comment| comment.to_molecule()));
+                Molecule::block(kinds::COMMENTED, children)
+            }
         }
     }
 
@@ -185,7 +793,163 @@ impl<S: Source> Block<S> {
                 children.into_blocks(),
             )),
             kinds::BULLET_LIST => Ok(BulletList(children.into_blocks())),
-            kinds::ORDERED_LIST => Ok(OrderedList(children.into_blocks())),
+            kinds::ORDERED_LIST => {
+                // Lists without the leading bytes were saved before lists had a start and
+                // numbering.
+                let num_bytes = children
+                    .iter()
+                    .take_while(|child| matches!(child, Molecule::Bytes(_)))
+                    .count();
+                let start = match children.first() {
+                    Some(Molecule::Bytes(bytes)) => u64::from_be_bytes(
+                        bytes[..].try_into().map_err(|_| BlockError::InvalidListStart)?,
+                    ),
+                    _ => 1,
+                };
+                let numbering = match children.get(1) {
+                    Some(Molecule::Bytes(bytes)) => match bytes[..] {
+                        [byte] => ListNumbering::from_byte(byte),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                Ok(OrderedList {
+                    start,
+                    numbering,
+                    items: children[num_bytes..].to_vec().into_blocks(),
+                })
+            }
+            kinds::CHECKLIST => Ok(Checklist(
+                children
+                    .chunks(2)
+                    .map(|fields| {
+                        let fields = fields.to_vec();
+                        Ok(ChecklistItem {
+                            checked: match fields.need_at(0)?.need_bytes()?[..] {
+                                [0] => false,
+                                [1] => true,
+                                _ => return Err(BlockError::InvalidCheckedState),
+                            },
+                            content: Block::from(&fields.need_at(1)?),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            kinds::TABLE => {
+                let mut rows = children
+                    .iter()
+                    .map(|row| match row {
+                        Molecule::Block {
+                            kind: kinds::TABLE_ROW,
+                            children,
+                        } => Ok(children.clone().into_blocks()),
+                        Molecule::Block { .. } => Err(BlockError::UnknownKind),
+                        _ => Err(BlockError::ExpectedBlock),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if rows.is_empty() {
+                    return Err(BlockError::TooFewMolecules);
+                }
+                let header = rows.remove(0);
+                Ok(Table { header, rows })
+            }
+            kinds::CODE => Ok(Code {
+                language: match children.need_at(0)?.need_bytes()? {
+                    bytes if bytes.is_empty() => None,
+                    bytes => Some(
+                        String::from_utf8(bytes).map_err(|_| BlockError::InvalidUtf8Encoding)?,
+                    ),
+                },
+                code: String::from_utf8(children.need_at(1)?.need_bytes()?)
+                    .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+            }),
+            kinds::QUOTE => Ok(Quote(Box::new(Block::from(&children.need_at(0)?)))),
+            kinds::LINK => Ok(Link {
+                target: String::from_utf8(children.need_at(0)?.need_bytes()?)
+                    .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+                content: Box::new(Block::from(&children.need_at(1)?)),
+            }),
+            kinds::DEFINITION_LIST => Ok(DefinitionList(
+                children.iter().map(Definition::try_from).collect::<Result<_, _>>()?,
+            )),
+            kinds::GLOSSARY => Ok(Glossary(
+                children.iter().map(Definition::try_from).collect::<Result<_, _>>()?,
+            )),
+            kinds::TERM_USAGE => Ok(TermUsage {
+                term: String::from_utf8(children.need_at(0)?.need_bytes()?)
+                    .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+                content: Box::new(Block::from(&children.need_at(1)?)),
+            }),
+            kinds::ANCHOR => Ok(Anchor {
+                id: String::from_utf8(children.need_at(0)?.need_bytes()?)
+                    .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+                content: Box::new(Block::from(&children.need_at(1)?)),
+            }),
+            kinds::CROSS_REFERENCE => Ok(CrossReference {
+                target: String::from_utf8(children.need_at(0)?.need_bytes()?)
+                    .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+                content: Box::new(Block::from(&children.need_at(1)?)),
+            }),
+            kinds::BIBLIOGRAPHY => Ok(Bibliography(
+                children.iter().map(BibliographyEntry::try_from).collect::<Result<_, _>>()?,
+            )),
+            kinds::CITATION => Ok(Citation(
+                children
+                    .iter()
+                    .map(|key| {
+                        String::from_utf8(key.need_bytes()?)
+                            .map_err(|_| BlockError::InvalidUtf8Encoding)
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            kinds::TOPIC_CHANGE => Ok(TopicChange),
+            kinds::TEMPORAL => Ok(Temporal {
+                value: TemporalValue::from_bytes(&children.need_at(0)?.need_bytes()?)?,
+                text: String::from_utf8(children.need_at(1)?.need_bytes()?)
+                    .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+            }),
+            kinds::HIGHLIGHTED => Ok(Highlighted {
+                content: Box::new(Block::from(&children.need_at(0)?)),
+                reason: match children.get(1) {
+                    Some(reason) => match reason.need_bytes()?[..] {
+                        [byte] => HighlightReason::from_byte(byte),
+                        _ => None,
+                    },
+                    None => None,
+                },
+            }),
+            kinds::SECONDARY => Ok(Secondary(Box::new(Block::from(&children.need_at(0)?)))),
+            kinds::ATTACHMENT => Ok(Attachment(AttachedFile::try_from(children)?)),
+            kinds::AUDIO => Ok(Audio(Media::try_from(children)?)),
+            kinds::VIDEO => Ok(Video(Media::try_from(children)?)),
+            kinds::MORE_INFO => Ok(MoreInfo {
+                summary: Box::new(Block::from(&children.need_at(0)?)),
+                body: Box::new(Block::from(&children.need_at(1)?)),
+            }),
+            kinds::SIGNED => {
+                let content = Box::new(Block::from(&children.need_at(0)?));
+                let mut signatures = vec![];
+                for fields in children[1..].chunks(3) {
+                    let signature = Signature {
+                        signer: String::from_utf8(fields.to_vec().need_at(0)?.need_bytes()?)
+                            .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+                        public_key: fields.to_vec().need_at(1)?.need_bytes()?,
+                        signature: fields.to_vec().need_at(2)?.need_bytes()?,
+                    };
+                    if !signature.is_well_formed() {
+                        return Err(BlockError::MalformedSignature);
+                    }
+                    signatures.push(signature);
+                }
+                Ok(Signed { content, signatures })
+            }
+            kinds::COMMENTED => Ok(Commented {
+                content: Box::new(Block::from(&children.need_at(0)?)),
+                comments: children[1..]
+                    .iter()
+                    .map(Comment::try_from)
+                    .collect::<Result<_, _>>()?,
+            }),
             _kind => Err(BlockError::UnknownKind),
         }
     }
@@ -194,6 +958,7 @@ impl<S: Source> Block<S> {
         match molecule {
             Molecule::Bytes(_) => Error(Error::BlockLayer(BlockError::ExpectedBlock)),
             Molecule::Error(error) => Error(Error::LowerLayer(error.clone())),
+            Molecule::OutOfLine(molecule) => Block::from(molecule),
             Molecule::Block { kind, children } => match Self::try_from(*kind, children.clone()) {
                 Ok(block) => block,
                 Err(error) => Block::Error(Error::BlockLayer(error)),
@@ -216,7 +981,87 @@ impl<S: Source> Block<S> {
             Flow(children) => Flow(children.into_pure()?),
             Paragraphs(children) => Paragraphs(children.into_pure()?),
             BulletList(items) => BulletList(items.into_pure()?),
-            OrderedList(items) => OrderedList(items.into_pure()?),
+            OrderedList {
+                start,
+                numbering,
+                items,
+            } => OrderedList {
+                start,
+                numbering,
+                items: items.into_pure()?,
+            },
+            Checklist(items) => Checklist(
+                items
+                    .into_iter()
+                    .map(|item| {
+                        Ok(ChecklistItem {
+                            checked: item.checked,
+                            content: item.content.into_pure()?,
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Table { header, rows } => Table {
+                header: header.into_pure()?,
+                rows: rows
+                    .into_iter()
+                    .map(|row| row.into_pure())
+                    .collect::<Result<_, _>>()?,
+            },
+            Code { language, code } => Code { language, code },
+            Quote(content) => Quote(Box::new(content.into_pure()?)),
+            Link { target, content } => Link {
+                target,
+                content: Box::new(content.into_pure()?),
+            },
+            DefinitionList(definitions) => DefinitionList(
+                definitions
+                    .into_iter()
+                    .map(|it| it.into_pure())
+                    .collect::<Result<_, _>>()?,
+            ),
+            Glossary(definitions) => Glossary(
+                definitions
+                    .into_iter()
+                    .map(|it| it.into_pure())
+                    .collect::<Result<_, _>>()?,
+            ),
+            TermUsage { term, content } => TermUsage {
+                term,
+                content: Box::new(content.into_pure()?),
+            },
+            Anchor { id, content } => Anchor {
+                id,
+                content: Box::new(content.into_pure()?),
+            },
+            CrossReference { target, content } => CrossReference {
+                target,
+                content: Box::new(content.into_pure()?),
+            },
+            Bibliography(entries) => Bibliography(entries),
+            TopicChange => TopicChange,
+            Citation(keys) => Citation(keys),
+            Temporal { value, text } => Temporal { value, text },
+            Attachment(attachment) => Attachment(attachment),
+            Highlighted { content, reason } => Highlighted {
+                content: Box::new(content.into_pure()?),
+                reason,
+            },
+            Secondary(content) => Secondary(Box::new(content.into_pure()?)),
+            Audio(media) => Audio(media.into_pure()?),
+            Video(media) => Video(media.into_pure()?),
+            MoreInfo { summary, body } => MoreInfo {
+                summary: Box::new(summary.into_pure()?),
+                body: Box::new(body.into_pure()?),
+            },
+            Signed { content, signatures } => Signed {
+                content: Box::new(content.into_pure()?),
+                signatures,
+            },
+            Commented { content, comments } => Commented {
+                content: Box::new(content.into_pure()?),
+                comments,
+            },
         })
     }
 }
@@ -228,6 +1073,17 @@ pub enum BlockError {
     UnknownKind,
     InvalidUtf8Encoding,
     TooFewMolecules,
+    MalformedSignature,
+    InvalidTimestamp,
+    InvalidCheckedState,
+    InvalidListStart,
+    InvalidYear,
+    InvalidAuthorCount,
+    InvalidTemporalValue,
+    InvalidMediaSource,
+    InvalidMediaDuration,
+    InvalidAttachmentSize,
+    InvalidTagCount,
 }
 
 trait IntoMolecules<S: Source> {
@@ -238,7 +1094,7 @@ impl<S: Source> IntoMolecules<S> for Vec<Block<S>> {
         self.iter().map(|child| child.to_molecule()).collect()
     }
 }
-trait IntoBlocks<S: Source> {
+pub(crate) trait IntoBlocks<S: Source> {
     fn into_blocks(self) -> Vec<Block<S>>;
 }
 impl<S: Source> IntoBlocks<S> for Vec<Molecule<S>> {
@@ -246,7 +1102,7 @@ impl<S: Source> IntoBlocks<S> for Vec<Molecule<S>> {
         self.into_iter().map(|data| Block::from(&data)).collect()
     }
 }
-trait NeedAt<S: Source> {
+pub(crate) trait NeedAt<S: Source> {
     fn need_at(&self, index: usize) -> Result<Molecule<S>, BlockError>;
 }
 impl<S: Source> NeedAt<S> for Vec<Molecule<S>> {
@@ -254,13 +1110,14 @@ impl<S: Source> NeedAt<S> for Vec<Molecule<S>> {
         Ok(self.get(index).ok_or(BlockError::TooFewMolecules)?.clone())
     }
 }
-trait NeedBytes<S: Source> {
+pub(crate) trait NeedBytes<S: Source> {
     fn need_bytes(&self) -> Result<Vec<u8>, BlockError>;
 }
 impl<S: Source> NeedBytes<S> for Molecule<S> {
     fn need_bytes(&self) -> Result<Vec<u8>, BlockError> {
         match self {
             Molecule::Bytes(bytes) => Ok(bytes.clone()),
+            Molecule::OutOfLine(molecule) => molecule.need_bytes(),
             _ => Err(BlockError::ExpectedBytes),
         }
     }
@@ -279,29 +1136,127 @@ impl<S: Source> VecIntoPure<S> for Vec<Block<S>> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use ed25519_dalek::SigningKey;
     use quickcheck::*;
 
+    impl Arbitrary for ChecklistItem<Pure> {
+        fn arbitrary(g: &mut Gen) -> Self {
+            ChecklistItem {
+                checked: bool::arbitrary(g),
+                content: Block::arbitrary(g),
+            }
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            let checked = self.checked;
+            Box::new(
+                self.content
+                    .shrink()
+                    .map(move |content| ChecklistItem { checked, content }),
+            )
+        }
+    }
+
     impl Arbitrary for Block<Pure> {
         fn arbitrary(g: &mut Gen) -> Self {
-            match u64::arbitrary(g) % 9 {
+            // Small generators only create blocks without children so that the tree stays finite.
+            let num_choices = if g.size() < 2 { 12 } else { 32 };
+            let children_g = &mut Gen::new(g.size() / 2);
+            match u64::arbitrary(g) % num_choices {
                 // Blocks without children.
-                0 | 1 | 2 => Empty,
-                3 | 4 | 5 => Text(String::arbitrary(g)),
+                0..=2 => Empty,
+                3..=5 => Text(String::arbitrary(g)),
+                6 => Citation(Vec::arbitrary(g)),
+                7 => Bibliography(Vec::arbitrary(g)),
+                8 => TopicChange,
+                9 => Temporal {
+                    value: TemporalValue::arbitrary(g),
+                    text: String::arbitrary(g),
+                },
+                10 => Attachment(AttachedFile::arbitrary(g)),
+                11 => Code {
+                    language: Option::<String>::arbitrary(g).filter(|it| !it.is_empty()),
+                    code: String::arbitrary(g),
+                },
                 // Blocks with two children.
-                6 => Section {
-                    title: Box::new(Block::arbitrary(g)),
-                    body: Box::new(Block::arbitrary(g)),
+                12 => Section {
+                    title: Box::new(Block::arbitrary(children_g)),
+                    body: Box::new(Block::arbitrary(children_g)),
                 },
                 // Blocks with a variable number of children.
-                7 => Flow(Vec::arbitrary(g)),
-                8 => Paragraphs(Vec::arbitrary(g)),
+                13 => Flow(Vec::arbitrary(children_g)),
+                14 => Paragraphs(Vec::arbitrary(children_g)),
+                // Blocks with metadata.
+                15 => Block::arbitrary(children_g)
+                    .sign(&String::arbitrary(g), &SigningKey::from_bytes(&[42; 32]))
+                    .unwrap(),
+                16 => Commented {
+                    content: Box::new(Block::arbitrary(children_g)),
+                    comments: Vec::arbitrary(children_g),
+                },
+                17 => Secondary(Box::new(Block::arbitrary(children_g))),
+                18 => MoreInfo {
+                    summary: Box::new(Block::arbitrary(children_g)),
+                    body: Box::new(Block::arbitrary(children_g)),
+                },
+                19 => Highlighted {
+                    content: Box::new(Block::arbitrary(children_g)),
+                    reason: g
+                        .choose(&[
+                            None,
+                            Some(HighlightReason::SearchHit),
+                            Some(HighlightReason::KeyPoint),
+                            Some(HighlightReason::Change),
+                        ])
+                        .cloned()
+                        .unwrap(),
+                },
+                20 => Checklist(Vec::arbitrary(children_g)),
+                21 => DefinitionList(Vec::arbitrary(children_g)),
+                22 => Glossary(Vec::arbitrary(children_g)),
+                23 => TermUsage {
+                    term: String::arbitrary(g),
+                    content: Box::new(Block::arbitrary(children_g)),
+                },
+                24 => OrderedList {
+                    start: u64::arbitrary(g),
+                    numbering: g
+                        .choose(&[
+                            None,
+                            Some(ListNumbering::Steps),
+                            Some(ListNumbering::Clauses),
+                            Some(ListNumbering::Ranking),
+                        ])
+                        .cloned()
+                        .unwrap(),
+                    items: Vec::arbitrary(children_g),
+                },
+                25 => Anchor {
+                    id: String::arbitrary(g),
+                    content: Box::new(Block::arbitrary(children_g)),
+                },
+                26 => CrossReference {
+                    target: String::arbitrary(g),
+                    content: Box::new(Block::arbitrary(children_g)),
+                },
+                27 => Audio(Media::arbitrary(children_g)),
+                28 => Video(Media::arbitrary(children_g)),
+                29 => Table {
+                    header: Vec::arbitrary(children_g),
+                    rows: Vec::arbitrary(children_g),
+                },
+                30 => Quote(Box::new(Block::arbitrary(children_g))),
+                31 => Link {
+                    target: String::arbitrary(g),
+                    content: Box::new(Block::arbitrary(children_g)),
+                },
                 _ => panic!("Modulo didn't work."),
             }
         }
 
         fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
             match self.clone() {
-                Empty => empty_shrinker(),
+                Empty | TopicChange => empty_shrinker(),
                 Text(text) => Box::new(text.shrink().map(Text)),
                 Section { title, body } => {
                     let body_for_closure = body.clone();
@@ -325,8 +1280,170 @@ mod test {
                     error
                 ),
                 BulletList(items) => Box::new(items.shrink().map(BulletList)),
-                OrderedList(items) => Box::new(items.shrink().map(OrderedList)),
+                OrderedList {
+                    start,
+                    numbering,
+                    items,
+                } => Box::new(items.shrink().map(move |items| OrderedList {
+                    start,
+                    numbering,
+                    items,
+                })),
+                Checklist(items) => Box::new(items.shrink().map(Checklist)),
+                Bibliography(entries) => Box::new(entries.shrink().map(Bibliography)),
+                Citation(keys) => Box::new(keys.shrink().map(Citation)),
+                Attachment(_) => empty_shrinker(),
+                Code { language, code } => {
+                    Box::new(code.shrink().map(move |code| Code { language: language.clone(), code }))
+                }
+                Table { header, rows } => {
+                    let rows_for_closure = rows.clone();
+                    Box::new(
+                        header
+                            .shrink()
+                            .map(move |header| Table { header, rows: rows_for_closure.clone() })
+                            .chain(rows.shrink().map(move |rows| Table { header: header.clone(), rows })),
+                    )
+                }
+                Quote(content) => {
+                    Box::new(single_shrinker(*content.clone()).chain(content.shrink().map(Quote)))
+                }
+                Link { target, content } => Box::new(
+                    single_shrinker(*content.clone()).chain(content.shrink().map(move |content| {
+                        Link {
+                            target: target.clone(),
+                            content,
+                        }
+                    })),
+                ),
+                Temporal { value, text } => {
+                    Box::new(text.shrink().map(move |text| Temporal { value, text }))
+                }
+                DefinitionList(definitions) => Box::new(definitions.shrink().map(DefinitionList)),
+                Glossary(definitions) => Box::new(definitions.shrink().map(Glossary)),
+                TermUsage { term, content } => Box::new(
+                    single_shrinker(*content.clone()).chain(content.shrink().map(move |content| {
+                        TermUsage {
+                            term: term.clone(),
+                            content,
+                        }
+                    })),
+                ),
+                Anchor { id, content } => Box::new(
+                    single_shrinker(*content.clone()).chain(content.shrink().map(move |content| {
+                        Anchor {
+                            id: id.clone(),
+                            content,
+                        }
+                    })),
+                ),
+                CrossReference { target, content } => Box::new(
+                    single_shrinker(*content.clone()).chain(content.shrink().map(move |content| {
+                        CrossReference {
+                            target: target.clone(),
+                            content,
+                        }
+                    })),
+                ),
+                Highlighted { content, reason } => Box::new(
+                    single_shrinker(*content.clone()).chain(
+                        content
+                            .shrink()
+                            .map(move |content| Highlighted { content, reason }),
+                    ),
+                ),
+                Audio(media) => Box::new(
+                    single_shrinker(*media.transcript.clone()).chain(media.shrink().map(Audio)),
+                ),
+                Video(media) => Box::new(
+                    single_shrinker(*media.transcript.clone()).chain(media.shrink().map(Video)),
+                ),
+                Secondary(content) => Box::new(
+                    single_shrinker(*content.clone())
+                        .chain(content.shrink().map(Secondary)),
+                ),
+                MoreInfo { summary, body } => {
+                    let body_for_closure = body.clone();
+                    Box::new(
+                        single_shrinker(*summary.clone())
+                            .chain(single_shrinker(*body.clone()))
+                            .chain(summary.shrink().map(move |summary| MoreInfo {
+                                summary,
+                                body: body_for_closure.clone(),
+                            }))
+                            .chain(body.shrink().map(move |body| MoreInfo {
+                                summary: summary.clone(),
+                                body,
+                            })),
+                    )
+                }
+                Signed { content, .. } => single_shrinker(*content),
+                Commented { content, comments } => {
+                    let content_for_closure = content.clone();
+                    Box::new(
+                        single_shrinker(*content.clone())
+                            .chain(comments.shrink().map(move |comments| Commented {
+                                content: content_for_closure.clone(),
+                                comments,
+                            })),
+                    )
+                }
             }
         }
     }
+
+    #[test]
+    fn ordered_lists_without_start_begin_at_one() {
+        let molecule = Molecule::<Pure>::block(
+            kinds::ORDERED_LIST,
+            vec![Text("First".to_string()).to_molecule()],
+        );
+        assert_eq!(
+            Block::from(&molecule),
+            OrderedList {
+                start: 1,
+                numbering: None,
+                items: vec![Text("First".to_string())],
+            }
+        );
+    }
+
+    #[test]
+    fn topic_changes_only_stay_between_paragraphs() {
+        let paragraph = |text: &str| Text(text.to_string());
+        let story: Block<Pure> = Paragraphs(vec![
+            TopicChange,
+            paragraph("The night fell."),
+            TopicChange,
+            Empty,
+            TopicChange,
+            paragraph("The sun rose."),
+            TopicChange,
+        ]);
+        assert_eq!(
+            story.simplify(),
+            Paragraphs(vec![
+                paragraph("The night fell."),
+                TopicChange,
+                paragraph("The sun rose."),
+            ])
+        );
+    }
+
+    #[test]
+    fn nested_flows_are_flattened() {
+        let flow: Block<Pure> = Flow(vec![
+            Text("See ".to_string()),
+            Flow(vec![Text("the ".to_string()), Citation(vec!["knuth".to_string()])]),
+            Text(".".to_string()),
+        ]);
+        assert_eq!(
+            flow.simplify(),
+            Flow(vec![
+                Text("See the ".to_string()),
+                Citation(vec!["knuth".to_string()]),
+                Text(".".to_string()),
+            ])
+        );
+    }
 }
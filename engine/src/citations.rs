@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::convert::TryInto;
+
+use crate::blocks::*;
+use crate::molecule::*;
+use crate::source::*;
+
+/// A work that can be cited, used in `Block::Bibliography`.
+///
+/// Entries only contain the facts about the work, so readers can format them in the citation
+/// style the user prefers.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BibliographyEntry {
+    /// The key `Block::Citation`s use to refer to this entry, like "knuth1984".
+    pub key: String,
+    /// The names of the authors in the order they appear on the work.
+    pub authors: Vec<String>,
+    pub title: String,
+    pub year: Option<i32>,
+    /// Identifiers of the work as pairs of a scheme and a value, like ("doi", "10.1093/comjnl/27.2.97").
+    pub identifiers: Vec<(String, String)>,
+}
+
+impl BibliographyEntry {
+    pub fn to_molecule<S: Source>(&self) -> Molecule<S> {
+        let mut children = vec![
+            Molecule::Bytes(self.key.as_bytes().to_vec()),
+            Molecule::Bytes(self.title.as_bytes().to_vec()),
+            Molecule::Bytes(
+                self.year
+                    .iter()
+                    .flat_map(|year| year.to_be_bytes())
+                    .collect(),
+            ),
+            Molecule::Bytes((self.authors.len() as u64).to_be_bytes().to_vec()),
+        ];
+        for author in &self.authors {
+            children.push(Molecule::Bytes(author.as_bytes().to_vec()));
+        }
+        for (scheme, value) in &self.identifiers {
+            children.push(Molecule::Bytes(scheme.as_bytes().to_vec()));
+            children.push(Molecule::Bytes(value.as_bytes().to_vec()));
+        }
+        Molecule::block(kinds::BIBLIOGRAPHY_ENTRY, children)
+    }
+
+    pub fn try_from<S: Source>(molecule: &Molecule<S>) -> Result<BibliographyEntry, BlockError> {
+        let children = match molecule {
+            Molecule::Block {
+                kind: kinds::BIBLIOGRAPHY_ENTRY,
+                children,
+            } => children,
+            Molecule::Block { .. } => return Err(BlockError::UnknownKind),
+            _ => return Err(BlockError::ExpectedBlock),
+        };
+        let string_at = |index: usize| {
+            String::from_utf8(children.need_at(index)?.need_bytes()?)
+                .map_err(|_| BlockError::InvalidUtf8Encoding)
+        };
+        let year = match &children.need_at(2)?.need_bytes()?[..] {
+            [] => None,
+            bytes => Some(i32::from_be_bytes(
+                bytes.try_into().map_err(|_| BlockError::InvalidYear)?,
+            )),
+        };
+        let num_authors = u64::from_be_bytes(
+            children.need_at(3)?.need_bytes()?[..]
+                .try_into()
+                .map_err(|_| BlockError::InvalidAuthorCount)?,
+        );
+        let num_authors = (num_authors as usize).min(children.len());
+        let authors = (4..4 + num_authors)
+            .map(string_at)
+            .collect::<Result<_, _>>()?;
+        let num_identifier_fields = children.len().saturating_sub(4 + num_authors);
+        if num_identifier_fields % 2 != 0 {
+            return Err(BlockError::TooFewMolecules);
+        }
+        let identifiers = (0..num_identifier_fields / 2)
+            .map(|index| {
+                let index = 4 + num_authors + 2 * index;
+                Ok((string_at(index)?, string_at(index + 1)?))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(BibliographyEntry {
+            key: string_at(0)?,
+            authors,
+            title: string_at(1)?,
+            year,
+            identifiers,
+        })
+    }
+
+    /// Returns the value of the identifier with the given scheme, like "doi".
+    pub fn identifier(&self, scheme: &str) -> Option<&str> {
+        self.identifiers
+            .iter()
+            .find(|(it, _)| it.eq_ignore_ascii_case(scheme))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl<S: Source> Block<S> {
+    /// Collects the entries of all bibliographies in this block, keyed by their key.
+    pub fn bibliography(&self) -> HashMap<&str, &BibliographyEntry> {
+        let mut bibliography = HashMap::new();
+        self.collect_bibliography_into(&mut bibliography);
+        bibliography
+    }
+
+    fn collect_bibliography_into<'a>(
+        &'a self,
+        bibliography: &mut HashMap<&'a str, &'a BibliographyEntry>,
+    ) {
+        if let Block::Bibliography(entries) = self {
+            for entry in entries {
+                bibliography.insert(&entry.key, entry);
+            }
+        }
+        for child in self.children() {
+            child.collect_bibliography_into(bibliography);
+        }
+    }
+
+    /// Returns the keys of all citations that have no entry in a bibliography.
+    pub fn unresolved_citations(&self) -> Vec<String> {
+        let bibliography = self.bibliography();
+        let mut keys = vec![];
+        self.collect_citations_into(&mut keys);
+        keys.retain(|key| !bibliography.contains_key(key.as_str()));
+        keys
+    }
+
+    fn collect_citations_into(&self, keys: &mut Vec<String>) {
+        if let Block::Citation(cited) = self {
+            keys.extend(cited.iter().cloned());
+        }
+        for child in self.children() {
+            child.collect_citations_into(keys);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doc::*;
+    use crate::Block::*;
+    use quickcheck::*;
+
+    impl Arbitrary for BibliographyEntry {
+        fn arbitrary(g: &mut Gen) -> Self {
+            BibliographyEntry {
+                key: String::arbitrary(g),
+                authors: Vec::arbitrary(g),
+                title: String::arbitrary(g),
+                year: Option::arbitrary(g),
+                identifiers: Vec::arbitrary(g),
+            }
+        }
+    }
+
+    fn knuth() -> BibliographyEntry {
+        BibliographyEntry {
+            key: "knuth1984".to_string(),
+            authors: vec!["Donald E. Knuth".to_string()],
+            title: "Literate Programming".to_string(),
+            year: Some(1984),
+            identifiers: vec![("doi".to_string(), "10.1093/comjnl/27.2.97".to_string())],
+        }
+    }
+
+    #[test]
+    fn bibliographies_survive_encoding() {
+        let doc = SemDoc::new(Paragraphs(vec![
+            Flow(vec![
+                Text("Programs are literature ".to_string()),
+                Citation(vec!["knuth1984".to_string()]),
+                Text(".".to_string()),
+            ]),
+            Bibliography(vec![knuth()]),
+        ]));
+        let decoded = SemDoc::from_bytes(&doc.to_bytes()).unwrap();
+        assert_eq!(decoded.into_pure().unwrap(), doc);
+    }
+
+    #[test]
+    fn citations_resolve_to_entries() {
+        let doc: Block<Pure> = Paragraphs(vec![
+            Citation(vec!["knuth1984".to_string(), "dijkstra1968".to_string()]),
+            Bibliography(vec![knuth()]),
+        ]);
+        assert_eq!(doc.bibliography().get("knuth1984"), Some(&&knuth()));
+        assert_eq!(doc.unresolved_citations(), vec!["dijkstra1968"]);
+        assert_eq!(knuth().identifier("DOI"), Some("10.1093/comjnl/27.2.97"));
+    }
+}
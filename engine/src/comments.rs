@@ -0,0 +1,146 @@
+use std::convert::TryInto;
+
+use crate::blocks::*;
+use crate::molecule::*;
+use crate::source::*;
+
+/// A comment of a reviewer, attached to the content of a `Block::Commented`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Comment {
+    pub author: String,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub text: String,
+    /// Comments answering this one, forming a thread.
+    pub replies: Vec<Comment>,
+}
+
+impl Comment {
+    pub fn to_molecule<S: Source>(&self) -> Molecule<S> {
+        let mut children = vec![
+            Molecule::Bytes(self.author.as_bytes().to_vec()),
+            Molecule::Bytes(self.timestamp.to_be_bytes().to_vec()),
+            Molecule::Bytes(self.text.as_bytes().to_vec()),
+        ];
+        children.extend(self.replies.iter().map(|reply| reply.to_molecule()));
+        Molecule::block(kinds::COMMENT, children)
+    }
+
+    pub fn try_from<S: Source>(molecule: &Molecule<S>) -> Result<Comment, BlockError> {
+        let children = match molecule {
+            Molecule::Block {
+                kind: kinds::COMMENT,
+                children,
+            } => children,
+            Molecule::Block { .. } => return Err(BlockError::UnknownKind),
+            _ => return Err(BlockError::ExpectedBlock),
+        };
+        Ok(Comment {
+            author: String::from_utf8(children.need_at(0)?.need_bytes()?)
+                .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+            timestamp: u64::from_be_bytes(
+                children.need_at(1)?.need_bytes()?[..]
+                    .try_into()
+                    .map_err(|_| BlockError::InvalidTimestamp)?,
+            ),
+            text: String::from_utf8(children.need_at(2)?.need_bytes()?)
+                .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+            replies: children[3..]
+                .iter()
+                .map(Comment::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl<S: Source> Block<S> {
+    /// Removes all comments from this block and all blocks inside of it.
+    ///
+    /// Comments inside signed content are removed as well, which invalidates the signatures. So,
+    /// remove comments before signing a document.
+    pub fn without_comments(self) -> Block<S> {
+        match self {
+            Block::Commented { content, .. } => content.without_comments(),
+            other => other.map_children(|child| child.without_comments()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doc::*;
+    use crate::Block::*;
+    use quickcheck::*;
+
+    impl Arbitrary for Comment {
+        fn arbitrary(g: &mut Gen) -> Self {
+            // Replies get a smaller generator so that threads stay finite.
+            let replies_g = &mut Gen::new(g.size() / 2);
+            Comment {
+                author: String::arbitrary(g),
+                timestamp: u64::arbitrary(g),
+                text: String::arbitrary(g),
+                replies: if g.size() < 2 {
+                    vec![]
+                } else {
+                    Vec::arbitrary(replies_g)
+                },
+            }
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            Box::new(self.replies.clone().into_iter())
+        }
+    }
+
+    fn comment(author: &str, text: &str, replies: Vec<Comment>) -> Comment {
+        Comment {
+            author: author.to_string(),
+            timestamp: 1_600_000_000,
+            text: text.to_string(),
+            replies,
+        }
+    }
+
+    fn reviewed_doc() -> SemDoc<Pure> {
+        SemDoc::new(Paragraphs(vec![
+            Commented {
+                content: Box::new(Text("The sky is green.".to_string())),
+                comments: vec![comment(
+                    "Alice",
+                    "Isn't it blue?",
+                    vec![comment("Bob", "Depends on the planet.", vec![])],
+                )],
+            },
+            Section {
+                title: Box::new(Text("Details".to_string())),
+                body: Box::new(Commented {
+                    content: Box::new(Text("Trust me.".to_string())),
+                    comments: vec![comment("Alice", "Needs a source.", vec![])],
+                }),
+            },
+        ]))
+    }
+
+    #[test]
+    fn threads_survive_encoding() {
+        let doc = reviewed_doc();
+        let decoded = SemDoc::from_bytes(&doc.to_bytes()).unwrap();
+        assert_eq!(decoded.into_pure().unwrap(), doc);
+    }
+
+    #[test]
+    fn stripping_removes_all_comments() {
+        assert_eq!(
+            reviewed_doc().without_comments(),
+            SemDoc::new(Paragraphs(vec![
+                Text("The sky is green.".to_string()),
+                Section {
+                    title: Box::new(Text("Details".to_string())),
+                    body: Box::new(Text("Trust me.".to_string())),
+                },
+            ]))
+        );
+    }
+}
@@ -1,7 +1,13 @@
 use std::convert::TryInto;
 
+use ed25519_dalek::SigningKey;
+
+use crate::anchors::*;
 use crate::blocks::*;
 use crate::memory::*;
+use crate::metadata::*;
+use crate::molecule::*;
+use crate::signatures::*;
 use crate::source::*;
 
 const MAGIC_BYTES: &[u8] = b"SemDoc";
@@ -9,11 +15,23 @@ const VERSION: u16 = 0;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SemDoc<S: Source> {
+    pub metadata: Metadata,
     pub block: Block<S>,
 }
 impl<S: Source> SemDoc<S> {
     pub fn new(block: Block<S>) -> Self {
-        Self { block }
+        Self {
+            metadata: Metadata::default(),
+            block,
+        }
+    }
+
+    pub fn with_metadata(self, metadata: Metadata) -> Self {
+        Self { metadata, ..self }
+    }
+
+    fn to_molecule(&self) -> Molecule<S> {
+        document_molecule(&self.metadata, &self.block)
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
@@ -22,12 +40,10 @@ impl<S: Source> SemDoc<S> {
         bytes.extend_from_slice(&VERSION.to_be_bytes());
         bytes.extend_from_slice(
             &self
-                .block
                 .to_molecule()
                 .to_atoms()
                 .iter()
-                .map(|atom| atom.to_bytes())
-                .flatten()
+                .flat_map(|atom| atom.to_bytes())
                 .collect::<Vec<_>>(),
         );
         bytes
@@ -35,9 +51,88 @@ impl<S: Source> SemDoc<S> {
 
     pub fn into_pure(self) -> Result<SemDoc<Pure>, S::Error> {
         Ok(SemDoc::<Pure> {
+            metadata: self.metadata,
             block: self.block.into_pure()?,
         })
     }
+
+    /// Returns the content of the anchor with the given ID.
+    pub fn resolve(&self, id: &str) -> Option<&Block<S>> {
+        self.block.resolve(id)
+    }
+
+    /// Checks that anchor IDs are unique and that every cross-reference points to an anchor.
+    pub fn validate_references(&self) -> Result<(), ReferenceError> {
+        self.block.validate_references()
+    }
+
+    /// Removes all comments, for example, before distributing the final version of a document.
+    pub fn without_comments(self) -> Self {
+        Self {
+            block: self.block.without_comments(),
+            ..self
+        }
+    }
+
+    /// Signs the whole document, including its metadata. Fails if the document contains errors.
+    pub fn sign(self, signer: &str, key: &SigningKey) -> Result<Self, SignatureError> {
+        let metadata = &self.metadata;
+        let block = self
+            .block
+            .sign_bytes(signer, key, |content| document_bytes(metadata, content))?;
+        Ok(Self { block, ..self })
+    }
+
+    /// Verifies all signatures in the document.
+    ///
+    /// Fails if any signature is invalid or if the document isn't signed at all. Roots signed with
+    /// `Block::sign` only cover the block, so they fail with `SignatureError::UnsignedMetadata`
+    /// if the document has metadata.
+    pub fn verify(&self) -> Result<Vec<Signature>, SignatureError> {
+        let signatures = match self
+            .block
+            .verify_signatures_of_bytes(|content| document_bytes(&self.metadata, content))
+        {
+            Err(SignatureError::InvalidSignature { signer })
+                if self.block.verify_signatures().is_ok() =>
+            {
+                return Err(SignatureError::UnsignedMetadata { signer })
+            }
+            result => result?,
+        };
+        if signatures.is_empty() {
+            return Err(SignatureError::Unsigned);
+        }
+        Ok(signatures)
+    }
+}
+
+/// Documents without metadata only consist of their block. Otherwise, a document molecule
+/// contains both the metadata and the block.
+fn document_molecule<S: Source>(metadata: &Metadata, block: &Block<S>) -> Molecule<S> {
+    if metadata.is_empty() {
+        block.to_molecule()
+    } else {
+        Molecule::block(
+            kinds::DOCUMENT,
+            vec![metadata.to_molecule(), block.to_molecule()],
+        )
+    }
+}
+
+/// The bytes that signatures of a whole document sign: The document with the signed content as
+/// its block, so that the metadata can't be changed either.
+fn document_bytes<S: Source>(metadata: &Metadata, content: &Block<S>) -> Option<Vec<u8>> {
+    if content.contains_error() {
+        return None;
+    }
+    Some(
+        document_molecule(metadata, content)
+            .to_atoms()
+            .iter()
+            .flat_map(|atom| atom.to_bytes())
+            .collect(),
+    )
 }
 impl SemDoc<Memory> {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, SemDocError> {
@@ -52,8 +147,22 @@ impl SemDoc<Memory> {
         {
             return Err(SemDocError::UnknownVersion);
         }
-        let block = Block::from(&MemoryMolecule::from(&bytes[8..]));
-        Ok(SemDoc { block })
+        match MemoryMolecule::from(&bytes[8..]) {
+            Molecule::Block {
+                kind: kinds::DOCUMENT,
+                children,
+            } => Ok(SemDoc {
+                metadata: children
+                    .need_at(0)
+                    .and_then(|metadata| Metadata::try_from(&metadata))
+                    .map_err(SemDocError::InvalidMetadata)?,
+                block: match children.need_at(1) {
+                    Ok(block) => Block::from(&block),
+                    Err(error) => Block::Error(Error::BlockLayer(error)),
+                },
+            }),
+            molecule => Ok(SemDoc::new(Block::from(&molecule))),
+        }
     }
 }
 
@@ -62,6 +171,7 @@ pub enum SemDocError {
     UnexpectedEnd,
     MagicBytesInvalid,
     UnknownVersion,
+    InvalidMetadata(BlockError),
 }
 
 #[cfg(test)]
@@ -71,12 +181,17 @@ mod test {
     impl quickcheck::Arbitrary for SemDoc<Pure> {
         fn arbitrary(g: &mut quickcheck::Gen) -> Self {
             Self {
+                metadata: Metadata::arbitrary(g),
                 block: Block::arbitrary(g),
             }
         }
 
         fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
-            Box::new(self.block.shrink().map(|block| Self { block }))
+            let metadata = self.metadata.clone();
+            Box::new(self.block.shrink().map(move |block| Self {
+                metadata: metadata.clone(),
+                block,
+            }))
         }
     }
 
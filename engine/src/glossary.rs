@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+
+use crate::blocks::*;
+use crate::molecule::*;
+use crate::source::*;
+
+/// A term along with its definitions, used in `Block::DefinitionList` and `Block::Glossary`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Definition<S: Source> {
+    pub term: Block<S>,
+    pub definitions: Vec<Block<S>>,
+}
+
+impl<S: Source> Definition<S> {
+    pub fn map(self, mut f: impl FnMut(Block<S>) -> Block<S>) -> Definition<S> {
+        Definition {
+            term: f(self.term),
+            definitions: self.definitions.into_iter().map(f).collect(),
+        }
+    }
+
+    pub fn to_molecule(&self) -> Molecule<S> {
+        let mut children = vec![self.term.to_molecule()];
+        children.extend(self.definitions.iter().map(|it| it.to_molecule()));
+        Molecule::block(kinds::DEFINITION, children)
+    }
+
+    pub fn try_from(molecule: &Molecule<S>) -> Result<Definition<S>, BlockError> {
+        match molecule {
+            Molecule::Block {
+                kind: kinds::DEFINITION,
+                children,
+            } => Ok(Definition {
+                term: Block::from(&children.need_at(0)?),
+                definitions: children[1..].to_vec().into_blocks(),
+            }),
+            Molecule::Block { .. } => Err(BlockError::UnknownKind),
+            _ => Err(BlockError::ExpectedBlock),
+        }
+    }
+
+    pub fn into_pure(self) -> Result<Definition<Pure>, S::Error> {
+        Ok(Definition {
+            term: self.term.into_pure()?,
+            definitions: self
+                .definitions
+                .into_iter()
+                .map(|it| it.into_pure())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Turns a term into the form used for looking it up, so that "Semantic  Document" and
+/// "semantic document" refer to the same term.
+pub fn normalize_term(term: &str) -> String {
+    term.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl<S: Source> Block<S> {
+    /// Collects the definitions of all glossaries in this block, keyed by their normalized term.
+    pub fn glossary(&self) -> HashMap<String, &Definition<S>> {
+        let mut glossary = HashMap::new();
+        self.collect_glossary_into(&mut glossary);
+        glossary
+    }
+
+    fn collect_glossary_into<'a>(&'a self, glossary: &mut HashMap<String, &'a Definition<S>>) {
+        if let Block::Glossary(definitions) = self {
+            for definition in definitions {
+                glossary.insert(normalize_term(&definition.term.plain_text()), definition);
+            }
+        }
+        for child in self.children() {
+            child.collect_glossary_into(glossary);
+        }
+    }
+
+    /// Returns the glossary definition of a term, if there is one.
+    pub fn definition_of(&self, term: &str) -> Option<&Definition<S>> {
+        self.glossary().get(&normalize_term(term)).cloned()
+    }
+
+    /// Returns the terms of all `Block::TermUsage`s that have no definition in a glossary.
+    pub fn undefined_terms(&self) -> Vec<String> {
+        let glossary = self.glossary();
+        let mut undefined = vec![];
+        self.collect_term_usages_into(&mut undefined);
+        undefined.retain(|term| !glossary.contains_key(&normalize_term(term)));
+        undefined
+    }
+
+    fn collect_term_usages_into(&self, usages: &mut Vec<String>) {
+        if let Block::TermUsage { term, .. } = self {
+            usages.push(term.clone());
+        }
+        for child in self.children() {
+            child.collect_term_usages_into(usages);
+        }
+    }
+
+    /// Marks all usages of glossary terms in texts as `Block::TermUsage`s.
+    ///
+    /// Texts inside glossaries and signed blocks are left untouched.
+    pub fn link_glossary_terms(self) -> Block<S> {
+        let mut terms = self.glossary().keys().cloned().collect::<Vec<_>>();
+        // Prefer longer terms like "semantic document" over shorter ones like "document".
+        terms.sort_by_key(|term| std::cmp::Reverse(term.len()));
+        self.link_terms(&terms)
+    }
+
+    fn link_terms(self, terms: &[String]) -> Block<S> {
+        match self {
+            Block::Text(text) => link_terms_in_text(text, terms),
+            block @ (Block::Error(_)
+            | Block::Glossary(_)
+            | Block::TermUsage { .. }
+            | Block::Signed { .. }) => block,
+            other => other.map_children(|child| child.link_terms(terms)),
+        }
+    }
+}
+
+fn link_terms_in_text<S: Source>(text: String, terms: &[String]) -> Block<S> {
+    let mut parts = vec![];
+    let mut rest = &text[..];
+    while let Some((start, end, term)) = find_first_term(rest, terms) {
+        if start > 0 {
+            parts.push(Block::Text(rest[..start].to_owned()));
+        }
+        parts.push(Block::TermUsage {
+            term: term.clone(),
+            content: Box::new(Block::Text(rest[start..end].to_owned())),
+        });
+        rest = &rest[end..];
+    }
+    if parts.is_empty() {
+        return Block::Text(text);
+    }
+    if !rest.is_empty() {
+        parts.push(Block::Text(rest.to_owned()));
+    }
+    Block::Flow(parts)
+}
+
+/// Finds the first term in the text that is surrounded by word boundaries and returns where it
+/// starts and ends.
+///
+/// Spans of the text are compared in their normalized form, so that terms at the beginning of a
+/// sentence or spanning a line break match too. Spans longer than the longest term are skipped,
+/// so that long texts without spaces don't take quadratic time.
+fn find_first_term<'a>(text: &str, terms: &'a [String]) -> Option<(usize, usize, &'a String)> {
+    let is_boundary = |c: Option<char>| !matches!(c, Some(c) if c.is_alphanumeric());
+    let max_len = terms.iter().map(|term| term.chars().count()).max()?;
+    for (start, first) in text.char_indices() {
+        if first.is_whitespace() || !is_boundary(text[..start].chars().next_back()) {
+            continue;
+        }
+        // All spans starting here that end at a word boundary, along with their normalized form.
+        // Normalizing never makes a span shorter than its characters outside of whitespace plus
+        // one space between its words, so longer spans can't match any term.
+        let mut spans = vec![];
+        let mut len = 0;
+        let mut after_whitespace = false;
+        let mut chars = text[start..].char_indices().peekable();
+        while let Some((offset, c)) = chars.next() {
+            if c.is_whitespace() {
+                after_whitespace = true;
+                continue;
+            }
+            len += if after_whitespace { 2 } else { 1 };
+            after_whitespace = false;
+            if len > max_len {
+                break;
+            }
+            if is_boundary(chars.peek().map(|(_, next)| *next)) {
+                let end = start + offset + c.len_utf8();
+                spans.push((end, normalize_term(&text[start..end])));
+            }
+        }
+        for term in terms {
+            if let Some((end, _)) = spans.iter().find(|(_, span)| span == term) {
+                return Some((start, *end, term));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Block::*;
+    use quickcheck::*;
+
+    impl Arbitrary for Definition<Pure> {
+        fn arbitrary(g: &mut Gen) -> Self {
+            // Definitions get a smaller generator so that the tree doesn't grow too large.
+            let definitions_g = &mut Gen::new(g.size() / 2);
+            Definition {
+                term: Block::arbitrary(g),
+                definitions: if g.size() < 2 {
+                    vec![]
+                } else {
+                    Vec::arbitrary(definitions_g)
+                },
+            }
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            let term = self.term.clone();
+            Box::new(
+                self.definitions
+                    .shrink()
+                    .map(move |definitions| Definition {
+                        term: term.clone(),
+                        definitions,
+                    }),
+            )
+        }
+    }
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    fn document() -> Block<Pure> {
+        Paragraphs(vec![
+            text("A SemDoc reader displays a semantic document."),
+            Glossary(vec![
+                Definition {
+                    term: text("Semantic  Document"),
+                    definitions: vec![text("A document without layout information.")],
+                },
+                Definition {
+                    term: text("reader"),
+                    definitions: vec![text("A program displaying documents.")],
+                },
+            ]),
+        ])
+    }
+
+    #[test]
+    fn terms_are_looked_up_normalized() {
+        let document = document();
+        let definition = document.definition_of("semantic document").unwrap();
+        assert_eq!(definition.term, text("Semantic  Document"));
+        assert!(document.definition_of("writer").is_none());
+    }
+
+    #[test]
+    fn usages_are_linked() {
+        let linked = document().link_glossary_terms();
+        assert_eq!(
+            linked.children()[0],
+            &Flow(vec![
+                text("A SemDoc "),
+                TermUsage {
+                    term: "reader".to_owned(),
+                    content: Box::new(text("reader")),
+                },
+                text(" displays a "),
+                TermUsage {
+                    term: "semantic document".to_owned(),
+                    content: Box::new(text("semantic document")),
+                },
+                text("."),
+            ])
+        );
+        assert!(linked.undefined_terms().is_empty());
+    }
+
+    #[test]
+    fn undefined_usages_are_reported() {
+        let document = Paragraphs(vec![TermUsage {
+            term: "writer".to_owned(),
+            content: Box::new(text("writers")),
+        }]);
+        assert_eq!(document.undefined_terms(), vec!["writer".to_owned()]);
+    }
+
+    #[test]
+    fn long_texts_without_spaces_are_linked() {
+        let words = "a-".repeat(100_000);
+        let document = Paragraphs(vec![
+            text(&format!("{}reader", words)),
+            Glossary(vec![Definition {
+                term: text("Reader"),
+                definitions: vec![text("A program displaying documents.")],
+            }]),
+        ]);
+        assert_eq!(
+            document.link_glossary_terms().children()[0],
+            &Flow(vec![
+                text(&words),
+                TermUsage {
+                    term: "reader".to_owned(),
+                    content: Box::new(text("reader")),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn usages_are_linked_regardless_of_case_and_whitespace() {
+        let document = Paragraphs(vec![
+            text("Äpfel are sweet. A semantic\ndocument is readable."),
+            Glossary(vec![
+                Definition {
+                    term: text("äpfel"),
+                    definitions: vec![text("The German word for apples.")],
+                },
+                Definition {
+                    term: text("Semantic Document"),
+                    definitions: vec![text("A document without layout information.")],
+                },
+            ]),
+        ]);
+        assert_eq!(
+            document.link_glossary_terms().children()[0],
+            &Flow(vec![
+                TermUsage {
+                    term: "äpfel".to_owned(),
+                    content: Box::new(text("Äpfel")),
+                },
+                text(" are sweet. A "),
+                TermUsage {
+                    term: "semantic document".to_owned(),
+                    content: Box::new(text("semantic\ndocument")),
+                },
+                text(" is readable."),
+            ])
+        );
+    }
+}
@@ -1,14 +1,36 @@
+mod anchors;
 mod atoms;
+mod attachments;
 mod blocks;
+mod citations;
+mod comments;
 mod doc;
+mod glossary;
+mod media;
 mod memory;
+mod metadata;
 mod molecule;
+mod sections;
+mod signatures;
 mod source;
+mod temporal;
 mod utils;
 
+pub use anchors::ReferenceError;
 pub use atoms::{Atom, AtomError};
-pub use blocks::{Block, BlockError};
+pub use attachments::{AttachedFile, AttachmentError};
+pub use blocks::{Block, BlockError, ChecklistItem, HighlightReason, ListNumbering};
+pub use citations::BibliographyEntry;
+pub use comments::Comment;
 pub use doc::{SemDoc, SemDocError};
+pub use glossary::{normalize_term, Definition};
+pub use media::{Media, MediaSource};
 pub use memory::{Memory, MemoryError};
+pub use metadata::Metadata;
 pub use molecule::Molecule;
+pub use sections::{nest_sections, Heading, SectionItem};
+pub use signatures::{Signature, SignatureError};
 pub use source::{Pure, Source};
+pub use temporal::{civil_to_days, days_to_civil, TemporalValue};
+
+pub use ed25519_dalek::SigningKey;
@@ -1,19 +1,11 @@
 use std::fs::File;
 use std::io::prelude::*;
 
-mod atoms;
-mod blocks;
-mod doc;
-mod memory;
-mod molecule;
-mod source;
-mod utils;
-
-use blocks::Block::*;
-use doc::*;
+use semdoc::Block::*;
+use semdoc::*;
 
 pub fn main() {
-    let doc = SemDoc::<source::Pure>::new(Section {
+    let doc = SemDoc::<Pure>::new(Section {
         title: Box::new(Text("SemDoc".to_string())),
         body: Box::new(Paragraphs(vec![
             Text("Hello, world!".to_string()),
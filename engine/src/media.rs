@@ -0,0 +1,180 @@
+use std::convert::TryInto;
+use std::time::Duration;
+
+use crate::blocks::*;
+use crate::molecule::*;
+use crate::source::*;
+
+/// An audio clip or a video, used in `Block::Audio` and `Block::Video`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Media<S: Source> {
+    pub source: MediaSource,
+    /// The MIME type of the media, like "audio/ogg" or "video/mp4".
+    pub mime_type: String,
+    pub duration: Option<Duration>,
+    /// A text version of the media, like a transcript of an audio clip or the captions of a video.
+    ///
+    /// Readers show it if they can't play the media or the user prefers text.
+    pub transcript: Box<Block<S>>,
+}
+
+/// Where the content of a `Media` comes from.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MediaSource {
+    /// The content is embedded in the document. It's saved after the rest of the document, so
+    /// readers can show the text around it first.
+    Embedded(Vec<u8>),
+    /// The content is available at the given URI.
+    External(String),
+}
+
+impl<S: Source> Media<S> {
+    pub fn map(self, f: impl FnOnce(Block<S>) -> Block<S>) -> Media<S> {
+        Media {
+            transcript: Box::new(f(*self.transcript)),
+            ..self
+        }
+    }
+
+    pub fn to_molecule(&self, kind: u64) -> Molecule<S> {
+        let (source_kind, source) = match &self.source {
+            MediaSource::Embedded(payload) => (
+                0,
+                Molecule::OutOfLine(Box::new(Molecule::Bytes(payload.clone()))),
+            ),
+            MediaSource::External(uri) => (1, Molecule::Bytes(uri.as_bytes().to_vec())),
+        };
+        Molecule::block(
+            kind,
+            vec![
+                Molecule::Bytes(self.mime_type.as_bytes().to_vec()),
+                Molecule::Bytes(
+                    self.duration
+                        .iter()
+                        .flat_map(|duration| (duration.as_millis() as u64).to_be_bytes())
+                        .collect(),
+                ),
+                Molecule::Bytes(vec![source_kind]),
+                source,
+                self.transcript.to_molecule(),
+            ],
+        )
+    }
+
+    pub fn try_from(children: Vec<Molecule<S>>) -> Result<Media<S>, BlockError> {
+        Ok(Media {
+            mime_type: String::from_utf8(children.need_at(0)?.need_bytes()?)
+                .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+            duration: match &children.need_at(1)?.need_bytes()?[..] {
+                [] => None,
+                bytes => Some(Duration::from_millis(u64::from_be_bytes(
+                    bytes
+                        .try_into()
+                        .map_err(|_| BlockError::InvalidMediaDuration)?,
+                ))),
+            },
+            source: match children.need_at(2)?.need_bytes()?[..] {
+                [0] => MediaSource::Embedded(children.need_at(3)?.need_bytes()?),
+                [1] => MediaSource::External(
+                    String::from_utf8(children.need_at(3)?.need_bytes()?)
+                        .map_err(|_| BlockError::InvalidUtf8Encoding)?,
+                ),
+                _ => return Err(BlockError::InvalidMediaSource),
+            },
+            transcript: Box::new(Block::from(&children.need_at(4)?)),
+        })
+    }
+
+    pub fn into_pure(self) -> Result<Media<Pure>, S::Error> {
+        Ok(Media {
+            source: self.source,
+            mime_type: self.mime_type,
+            duration: self.duration,
+            transcript: Box::new(self.transcript.into_pure()?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doc::*;
+    use crate::Block::*;
+    use quickcheck::*;
+
+    impl Arbitrary for Media<Pure> {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Media {
+                source: if bool::arbitrary(g) {
+                    MediaSource::Embedded(Vec::arbitrary(g))
+                } else {
+                    MediaSource::External(String::arbitrary(g))
+                },
+                mime_type: String::arbitrary(g),
+                duration: Option::<u64>::arbitrary(g).map(Duration::from_millis),
+                transcript: Box::new(Block::arbitrary(g)),
+            }
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+            let media = self.clone();
+            Box::new(self.transcript.shrink().map(move |transcript| Media {
+                transcript,
+                ..media.clone()
+            }))
+        }
+    }
+
+    fn interview() -> SemDoc<Pure> {
+        SemDoc::new(Paragraphs(vec![
+            Audio(Media {
+                source: MediaSource::Embedded(vec![42; 10_000]),
+                mime_type: "audio/ogg".to_string(),
+                duration: Some(Duration::from_millis(1_500)),
+                transcript: Box::new(Text("Hello and welcome!".to_string())),
+            }),
+            Text("The interview continues in the video.".to_string()),
+            Video(Media {
+                source: MediaSource::External("https://example.com/interview.mp4".to_string()),
+                mime_type: "video/mp4".to_string(),
+                duration: None,
+                transcript: Box::new(Text("[Applause]".to_string())),
+            }),
+        ]))
+    }
+
+    #[test]
+    fn media_survives_encoding() {
+        let doc = interview();
+        let decoded = SemDoc::from_bytes(&doc.to_bytes()).unwrap();
+        assert_eq!(decoded.into_pure().unwrap(), doc);
+    }
+
+    #[test]
+    fn payloads_come_after_the_text() {
+        let bytes = interview().to_bytes();
+        let position_of = |needle: &[u8]| {
+            bytes
+                .windows(needle.len())
+                .position(|window| window == needle)
+                .unwrap()
+        };
+        assert!(position_of(b"The interview continues") < position_of(&[42; 100]));
+        assert!(position_of(b"[Applause]") < position_of(&[42; 100]));
+    }
+
+    #[test]
+    fn durations_must_have_eight_bytes() {
+        let children = vec![
+            Molecule::<Pure>::Bytes(b"video/mp4".to_vec()),
+            Molecule::Bytes(vec![0, 0, 1]),
+            Molecule::Bytes(vec![1]),
+            Molecule::Bytes(b"https://example.com/interview.mp4".to_vec()),
+            Text("[Applause]".to_string()).to_molecule(),
+        ];
+        assert_eq!(
+            Media::try_from(children),
+            Err(BlockError::InvalidMediaDuration)
+        );
+    }
+}
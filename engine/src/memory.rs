@@ -11,6 +11,8 @@ impl Source for Memory {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MemoryError {
     UnexpectedEnd,
+    /// A reference doesn't point to an aligned position after itself.
+    InvalidReference,
 }
 
 pub type MemoryMolecule = Molecule<Memory>;
@@ -34,14 +36,17 @@ impl MemoryMolecule {
             Ok(atom) => Ok(match atom {
                 Atom::Block { kind, num_children } => {
                     let mut children = vec![];
-                    let mut cursor = 8;
+                    let mut cursor = 16;
                     for _ in 0..num_children {
                         match MemoryMolecule::try_from(&bytes[cursor..]) {
                             Ok((data, consumed_bytes)) => {
                                 children.push(data);
                                 cursor += consumed_bytes;
                             }
-                            Err(_) => break,
+                            Err(error) => {
+                                children.push(Molecule::Error(error));
+                                break;
+                            }
                         }
                     }
                     let data = Molecule::block(kind, children);
@@ -56,14 +61,23 @@ impl MemoryMolecule {
                                 children.push(data);
                                 cursor += consumed_bytes;
                             }
-                            Err(_) => break,
+                            Err(error) => {
+                                children.push(Molecule::Error(error));
+                                break;
+                            }
                         }
                     }
                     let data = Molecule::block(kind, children);
                     (data, cursor)
                 }
-                Atom::Reference(_offset) => {
-                    todo!("Implement getting MemoryMolecule from Atom::Reference.")
+                Atom::Reference(offset) => {
+                    // References only point forward, so they can't form cycles.
+                    let offset = offset as usize;
+                    if offset < 8 || offset % 8 != 0 || offset >= bytes.len() {
+                        return Err(MemoryError::InvalidReference);
+                    }
+                    let (molecule, _) = MemoryMolecule::try_from(&bytes[offset..])?;
+                    (molecule, 8)
                 }
                 Atom::Bytes(bytes) => {
                     let len = Atom::Bytes(bytes.clone()).length_in_bytes();
@@ -77,3 +91,44 @@ impl MemoryMolecule {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn children_of_big_blocks_are_read() {
+        let children = (0..300u16)
+            .map(|i| Molecule::<Memory>::Bytes(i.to_be_bytes().to_vec()))
+            .collect();
+        let bytes = Molecule::<Memory>::block(6, children)
+            .to_atoms()
+            .iter()
+            .flat_map(|atom| atom.to_bytes())
+            .collect::<Vec<_>>();
+        match MemoryMolecule::from(&bytes) {
+            Molecule::Block { kind, children } => {
+                assert_eq!(kind, 6);
+                assert_eq!(children.len(), 300);
+                assert!(matches!(&children[299], Molecule::Bytes(bytes) if bytes == &[1, 43]));
+            }
+            other => panic!("Expected a block, got {:?}.", other),
+        }
+    }
+
+    #[test]
+    fn missing_children_become_errors() {
+        let bytes = Atom::SmallBlock {
+            kind: 6,
+            num_children: 1,
+        }
+        .to_bytes();
+        match MemoryMolecule::from(&bytes) {
+            Molecule::Block { children, .. } => assert!(matches!(
+                &children[..],
+                [Molecule::Error(MemoryError::UnexpectedEnd)]
+            )),
+            other => panic!("Expected a block, got {:?}.", other),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::convert::TryInto;
+
+use crate::blocks::*;
+use crate::molecule::*;
+use crate::source::*;
+use crate::temporal::*;
+
+/// Information about a document as a whole rather than about a part of its content.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Metadata {
+    pub title: Option<String>,
+    /// The names of the authors in the order they should be credited.
+    pub authors: Vec<String>,
+    /// When the document was written or published.
+    pub date: Option<TemporalValue>,
+    /// Keywords describing what the document is about.
+    pub tags: Vec<String>,
+    /// Other information as pairs of a key and a value, like ("license", "CC-BY-4.0").
+    pub custom: Vec<(String, String)>,
+}
+
+impl Metadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Metadata::default()
+    }
+
+    /// Returns the value of the custom entry with the given key.
+    pub fn custom(&self, key: &str) -> Option<&str> {
+        self.custom
+            .iter()
+            .find(|(it, _)| it == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn to_molecule<S: Source>(&self) -> Molecule<S> {
+        let mut children = vec![
+            Molecule::Bytes(self.title.iter().flat_map(|it| it.bytes()).collect()),
+            Molecule::Bytes(self.date.map(|it| it.to_bytes()).unwrap_or_default()),
+            Molecule::Bytes((self.authors.len() as u64).to_be_bytes().to_vec()),
+        ];
+        for author in &self.authors {
+            children.push(Molecule::Bytes(author.as_bytes().to_vec()));
+        }
+        children.push(Molecule::Bytes(
+            (self.tags.len() as u64).to_be_bytes().to_vec(),
+        ));
+        for tag in &self.tags {
+            children.push(Molecule::Bytes(tag.as_bytes().to_vec()));
+        }
+        for (key, value) in &self.custom {
+            children.push(Molecule::Bytes(key.as_bytes().to_vec()));
+            children.push(Molecule::Bytes(value.as_bytes().to_vec()));
+        }
+        Molecule::block(kinds::METADATA, children)
+    }
+
+    pub(crate) fn try_from<S: Source>(molecule: &Molecule<S>) -> Result<Metadata, BlockError> {
+        let children = match molecule {
+            Molecule::Block {
+                kind: kinds::METADATA,
+                children,
+            } => children,
+            Molecule::Block { .. } => return Err(BlockError::UnknownKind),
+            _ => return Err(BlockError::ExpectedBlock),
+        };
+        let string_at = |index: usize| {
+            String::from_utf8(children.need_at(index)?.need_bytes()?)
+                .map_err(|_| BlockError::InvalidUtf8Encoding)
+        };
+        let count_at = |index: usize, error: BlockError| {
+            let count = u64::from_be_bytes(
+                children.need_at(index)?.need_bytes()?[..]
+                    .try_into()
+                    .map_err(|_| error)?,
+            );
+            Ok((count as usize).min(children.len()))
+        };
+
+        let title = Some(string_at(0)?).filter(|it| !it.is_empty());
+        let date = match &children.need_at(1)?.need_bytes()?[..] {
+            [] => None,
+            bytes => Some(TemporalValue::from_bytes(bytes)?),
+        };
+        let num_authors = count_at(2, BlockError::InvalidAuthorCount)?;
+        let authors = (3..3 + num_authors)
+            .map(string_at)
+            .collect::<Result<_, _>>()?;
+        let tags_start = 3 + num_authors;
+        let num_tags = count_at(tags_start, BlockError::InvalidTagCount)?;
+        let tags = (tags_start + 1..tags_start + 1 + num_tags)
+            .map(string_at)
+            .collect::<Result<_, _>>()?;
+        let custom_start = tags_start + 1 + num_tags;
+        let num_custom_fields = children.len().saturating_sub(custom_start);
+        if num_custom_fields % 2 != 0 {
+            return Err(BlockError::TooFewMolecules);
+        }
+        let custom = (0..num_custom_fields / 2)
+            .map(|index| {
+                let index = custom_start + 2 * index;
+                Ok((string_at(index)?, string_at(index + 1)?))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Metadata {
+            title,
+            authors,
+            date,
+            tags,
+            custom,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doc::*;
+    use crate::Block::*;
+    use quickcheck::*;
+
+    impl Arbitrary for Metadata {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Metadata {
+                title: Option::<String>::arbitrary(g).filter(|it| !it.is_empty()),
+                authors: Vec::arbitrary(g),
+                date: Option::arbitrary(g),
+                tags: Vec::arbitrary(g),
+                custom: Vec::arbitrary(g),
+            }
+        }
+    }
+
+    #[test]
+    fn documents_without_metadata_only_contain_the_block() {
+        let doc: SemDoc<Pure> = SemDoc::new(Text("Hello, world!".to_owned()));
+        assert_eq!(
+            doc.to_bytes()[8..],
+            Block::<Pure>::Text("Hello, world!".to_owned())
+                .to_molecule()
+                .to_atoms()
+                .iter()
+                .flat_map(|atom| atom.to_bytes())
+                .collect::<Vec<_>>()[..]
+        );
+    }
+
+    #[test]
+    fn metadata_survives_encoding() {
+        let doc: SemDoc<Pure> =
+            SemDoc::new(Text("Hello, world!".to_owned())).with_metadata(Metadata {
+                title: Some("Greetings".to_owned()),
+                authors: vec!["Ada".to_owned(), "Grace".to_owned()],
+                date: Some(TemporalValue::Date {
+                    year: 2021,
+                    month: 3,
+                    day: 14,
+                }),
+                tags: vec!["demo".to_owned()],
+                custom: vec![("license".to_owned(), "CC-BY-4.0".to_owned())],
+            });
+        let decoded = SemDoc::from_bytes(&doc.to_bytes())
+            .unwrap()
+            .into_pure()
+            .unwrap();
+        assert_eq!(decoded.metadata.custom("license"), Some("CC-BY-4.0"));
+        assert_eq!(decoded, doc);
+    }
+}
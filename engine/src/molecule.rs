@@ -9,6 +9,11 @@ pub enum Molecule<S: Source> {
         children: Vec<Molecule<S>>,
     },
     Error(S::Error),
+    /// A molecule that is saved after the rest of the document and referenced using an
+    /// `Atom::Reference`, so that large payloads don't delay loading the content around them.
+    ///
+    /// Only used while encoding. When decoding, references are resolved transparently.
+    OutOfLine(Box<Molecule<S>>),
 }
 impl<S: Source> Molecule<S> {
     pub fn block(kind: u64, children: Vec<Molecule<S>>) -> Self {
@@ -19,12 +24,28 @@ impl<S: Source> Molecule<S> {
 impl<S: Source> Molecule<S> {
     pub fn to_atoms(&self) -> Vec<Atom> {
         let mut atoms = vec![];
-        self.to_atoms_into(&mut atoms);
+        let mut out_of_line = vec![];
+        self.to_atoms_into(&mut atoms, &mut out_of_line);
+
+        // Out-of-line molecules are appended in the order they are referenced. They may contain
+        // out-of-line molecules themselves, which are then appended after them.
+        let mut index = 0;
+        while index < out_of_line.len() {
+            let (reference_index, molecule) = out_of_line[index];
+            let reference_position = length_in_bytes(&atoms[..reference_index]);
+            let position = length_in_bytes(&atoms);
+            atoms[reference_index] = Atom::Reference((position - reference_position) as u64);
+            molecule.to_atoms_into(&mut atoms, &mut out_of_line);
+            index += 1;
+        }
         atoms
     }
 
-    // TODO: Use Atom::Reference when appropriate.
-    fn to_atoms_into(&self, output: &mut Vec<Atom>) {
+    fn to_atoms_into<'a>(
+        &'a self,
+        output: &mut Vec<Atom>,
+        out_of_line: &mut Vec<(usize, &'a Molecule<S>)>,
+    ) {
         match self {
             Molecule::Block { kind, children } => {
                 output.push(if children.len() < 256 {
@@ -39,7 +60,7 @@ impl<S: Source> Molecule<S> {
                     }
                 });
                 for child in children {
-                    child.to_atoms_into(output);
+                    child.to_atoms_into(output, out_of_line);
                 }
             }
             Molecule::Bytes(bytes) => output.push(if bytes.len() < 256 {
@@ -48,6 +69,15 @@ impl<S: Source> Molecule<S> {
                 Atom::Bytes(bytes.clone())
             }),
             Molecule::Error(_) => todo!("Handle error while serializing molecule into atoms."),
+            Molecule::OutOfLine(molecule) => {
+                // The offset is filled in once the position of the molecule is known.
+                out_of_line.push((output.len(), molecule));
+                output.push(Atom::Reference(0));
+            }
         }
     }
 }
+
+fn length_in_bytes(atoms: &[Atom]) -> usize {
+    atoms.iter().map(|atom| atom.length_in_bytes()).sum()
+}
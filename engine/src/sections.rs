@@ -0,0 +1,214 @@
+use std::iter::Peekable;
+
+use crate::blocks::*;
+use crate::glossary::*;
+use crate::source::*;
+
+/// A piece of a document whose sections are only marked by headings, like a Markdown or HTML
+/// document.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SectionItem<S: Source> {
+    Heading(Heading<S>),
+    Block(Block<S>),
+}
+
+/// The start of a section, which lasts until a heading of the same or a higher level. Higher
+/// levels have lower numbers, like in HTML.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Heading<S: Source> {
+    pub level: usize,
+    pub title: Block<S>,
+    /// The IDs of the anchors around the section, starting with the outermost one.
+    pub ids: Vec<String>,
+    /// Whether the definition lists in the section define terms for the whole document.
+    pub is_glossary: bool,
+}
+
+impl<S: Source> Heading<S> {
+    /// Creates a heading without IDs. Sections titled "Glossary" are glossaries.
+    pub fn new(level: usize, title: Block<S>) -> Self {
+        let is_glossary = normalize_term(&title.plain_text()) == "glossary";
+        Heading {
+            level,
+            title,
+            ids: vec![],
+            is_glossary,
+        }
+    }
+
+    pub fn with_ids(mut self, ids: impl IntoIterator<Item = String>) -> Self {
+        self.ids.extend(ids);
+        self
+    }
+
+    /// Makes the section a glossary regardless of its title, like AsciiDoc sections with the
+    /// `glossary` style.
+    pub fn glossary(mut self) -> Self {
+        self.is_glossary = true;
+        self
+    }
+}
+
+/// Puts the blocks following a heading into the body of its section, until a heading of the same
+/// or a higher level starts a new section. Sections become anchors around a
+/// `Block::Section` with the body as `Block::Paragraphs`, and definition lists in glossary
+/// sections become `Block::Glossary`s.
+pub fn nest_sections<S: Source>(items: impl IntoIterator<Item = SectionItem<S>>) -> Vec<Block<S>> {
+    nest(&mut items.into_iter().peekable(), None)
+}
+
+fn nest<S: Source>(
+    items: &mut Peekable<impl Iterator<Item = SectionItem<S>>>,
+    parent: Option<usize>,
+) -> Vec<Block<S>> {
+    let mut blocks = vec![];
+    while let Some(item) = items.peek() {
+        if let (SectionItem::Heading(heading), Some(parent)) = (item, parent) {
+            if heading.level <= parent {
+                break;
+            }
+        }
+        blocks.push(match items.next().unwrap() {
+            SectionItem::Heading(heading) => {
+                let body = nest(items, Some(heading.level));
+                section(heading, body)
+            }
+            SectionItem::Block(block) => block,
+        });
+    }
+    blocks
+}
+
+fn section<S: Source>(heading: Heading<S>, body: Vec<Block<S>>) -> Block<S> {
+    let body = if heading.is_glossary {
+        body.into_iter().map(into_glossary).collect()
+    } else {
+        body
+    };
+    let section = Block::Section {
+        title: Box::new(heading.title),
+        body: Box::new(Block::Paragraphs(body)),
+    };
+    heading
+        .ids
+        .into_iter()
+        .rev()
+        .fold(section, |content, id| Block::Anchor {
+            id,
+            content: Box::new(content),
+        })
+}
+
+/// Turns definition lists into glossaries, also the ones in groups of paragraphs.
+fn into_glossary<S: Source>(block: Block<S>) -> Block<S> {
+    match block {
+        Block::DefinitionList(definitions) => Block::Glossary(definitions),
+        Block::Paragraphs(blocks) => {
+            Block::Paragraphs(blocks.into_iter().map(into_glossary).collect())
+        }
+        block => block,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Block::*;
+
+    fn text(text: &str) -> Block<Pure> {
+        Text(text.to_owned())
+    }
+
+    fn heading(level: usize, title: &str) -> SectionItem<Pure> {
+        SectionItem::Heading(Heading::new(level, text(title)).with_ids(vec![title.to_lowercase()]))
+    }
+
+    fn definitions() -> Vec<Definition<Pure>> {
+        vec![Definition {
+            term: text("SemDoc"),
+            definitions: vec![text("A semantic document.")],
+        }]
+    }
+
+    fn section(id: &str, title: &str, body: Vec<Block<Pure>>) -> Block<Pure> {
+        Anchor {
+            id: id.to_owned(),
+            content: Box::new(Section {
+                title: Box::new(text(title)),
+                body: Box::new(Paragraphs(body)),
+            }),
+        }
+    }
+
+    #[test]
+    fn headings_of_lower_levels_are_nested() {
+        let blocks = nest_sections(vec![
+            SectionItem::Block(text("Intro")),
+            heading(1, "Setup"),
+            SectionItem::Block(text("Install it.")),
+            heading(2, "Linux"),
+            SectionItem::Block(text("Use apt.")),
+            heading(1, "Usage"),
+        ]);
+        assert_eq!(
+            blocks,
+            vec![
+                text("Intro"),
+                section(
+                    "setup",
+                    "Setup",
+                    vec![
+                        text("Install it."),
+                        section("linux", "Linux", vec![text("Use apt.")]),
+                    ],
+                ),
+                section("usage", "Usage", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn definition_lists_in_glossary_sections_become_glossaries() {
+        let blocks = nest_sections(vec![
+            heading(1, "  GLOSSARY "),
+            SectionItem::Block(DefinitionList(definitions())),
+            SectionItem::Block(Paragraphs(vec![DefinitionList(definitions())])),
+            heading(1, "Terms"),
+            SectionItem::Block(DefinitionList(definitions())),
+            SectionItem::Heading(Heading::new(1, text("Words")).glossary()),
+            SectionItem::Block(DefinitionList(definitions())),
+        ]);
+        assert_eq!(
+            blocks,
+            vec![
+                section(
+                    "  glossary ",
+                    "  GLOSSARY ",
+                    vec![
+                        Glossary(definitions()),
+                        Paragraphs(vec![Glossary(definitions())]),
+                    ],
+                ),
+                section("terms", "Terms", vec![DefinitionList(definitions())]),
+                Section {
+                    title: Box::new(text("Words")),
+                    body: Box::new(Paragraphs(vec![Glossary(definitions())])),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sections_are_wrapped_in_all_anchors() {
+        let blocks = nest_sections(vec![SectionItem::Heading(
+            Heading::new(1, text("Setup")).with_ids(vec!["install".to_owned(), "setup".to_owned()]),
+        )]);
+        assert_eq!(
+            blocks,
+            vec![Anchor {
+                id: "install".to_owned(),
+                content: Box::new(section("setup", "Setup", vec![])),
+            }]
+        );
+    }
+}
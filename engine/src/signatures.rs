@@ -0,0 +1,314 @@
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
+use std::convert::TryInto;
+
+use crate::blocks::*;
+use crate::source::*;
+
+/// A signature of the content of a `Block::Signed`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Signature {
+    /// A human-readable identity of the signer, like a name or an email address.
+    pub signer: String,
+    /// The Ed25519 public key of the signer.
+    pub public_key: Vec<u8>,
+    /// The Ed25519 signature of the content and the signer.
+    pub signature: Vec<u8>,
+}
+impl Signature {
+    /// Whether the key and signature have the lengths that Ed25519 requires.
+    pub fn is_well_formed(&self) -> bool {
+        self.public_key.len() == ed25519_dalek::PUBLIC_KEY_LENGTH
+            && self.signature.len() == ed25519_dalek::SIGNATURE_LENGTH
+    }
+
+    fn verify(&self, content: &[u8]) -> bool {
+        let public_key = match self.public_key[..].try_into() {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+        let public_key = match VerifyingKey::from_bytes(public_key) {
+            Ok(key) => key,
+            Err(_) => return false,
+        };
+        let signature = match ed25519_dalek::Signature::from_slice(&self.signature) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+        public_key
+            .verify(&message(&self.signer, content), &signature)
+            .is_ok()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SignatureError {
+    /// There are no signatures to verify.
+    Unsigned,
+    /// Parts of the content couldn't be parsed, so they can't be signed or be what was signed.
+    DamagedContent,
+    /// The signature doesn't match the content and the signer.
+    InvalidSignature { signer: String },
+    /// The signature is valid for the block of the document, but doesn't cover its metadata,
+    /// because the block was signed on its own instead of as part of the document.
+    UnsignedMetadata { signer: String },
+}
+
+/// The bytes that actually get signed: The signer followed by the content.
+fn message(signer: &str, content: &[u8]) -> Vec<u8> {
+    let mut message = vec![];
+    message.extend_from_slice(&(signer.len() as u64).to_be_bytes());
+    message.extend_from_slice(signer.as_bytes());
+    message.extend_from_slice(content);
+    message
+}
+
+impl<S: Source> Block<S> {
+    /// Returns the bytes this block is encoded as in a SemDoc file.
+    ///
+    /// Returns `None` if the block contains errors, because those can't be encoded.
+    pub fn canonical_bytes(&self) -> Option<Vec<u8>> {
+        if self.contains_error() {
+            return None;
+        }
+        Some(
+            self.to_molecule()
+                .to_atoms()
+                .iter()
+                .flat_map(|atom| atom.to_bytes())
+                .collect(),
+        )
+    }
+
+    pub(crate) fn contains_error(&self) -> bool {
+        matches!(self, Block::Error(_))
+            || self.children().iter().any(|child| child.contains_error())
+    }
+
+    /// Signs this block.
+    ///
+    /// If the block is already signed, the signature is added to the existing ones. Otherwise, the
+    /// block is wrapped in a `Block::Signed`. Fails if the block contains errors, because those
+    /// can't be encoded.
+    ///
+    /// The signature only covers the block. To also sign the metadata of a document, use
+    /// `SemDoc::sign` instead, because `SemDoc::verify` rejects roots signed with this method if
+    /// the document has metadata.
+    pub fn sign(self, signer: &str, key: &SigningKey) -> Result<Block<S>, SignatureError> {
+        self.sign_bytes(signer, key, Block::canonical_bytes)
+    }
+
+    /// Signs this block like `sign`, but signs the bytes that `canonical_bytes` returns for the
+    /// content instead of the content's own encoding.
+    pub(crate) fn sign_bytes(
+        self,
+        signer: &str,
+        key: &SigningKey,
+        canonical_bytes: impl FnOnce(&Block<S>) -> Option<Vec<u8>>,
+    ) -> Result<Block<S>, SignatureError> {
+        let (content, mut signatures) = match self {
+            Block::Signed {
+                content,
+                signatures,
+            } => (content, signatures),
+            other => (Box::new(other), vec![]),
+        };
+        let bytes = canonical_bytes(&content).ok_or(SignatureError::DamagedContent)?;
+        signatures.push(Signature {
+            signer: signer.to_owned(),
+            public_key: key.verifying_key().to_bytes().to_vec(),
+            signature: key.sign(&message(signer, &bytes)).to_bytes().to_vec(),
+        });
+        Ok(Block::Signed {
+            content,
+            signatures,
+        })
+    }
+
+    /// Verifies the signatures of this block and all blocks inside of it.
+    ///
+    /// If all of them are valid, returns them in depth-first order.
+    pub fn verify_signatures(&self) -> Result<Vec<Signature>, SignatureError> {
+        self.verify_signatures_of_bytes(Block::canonical_bytes)
+    }
+
+    /// Verifies signatures like `verify_signatures`, but expects the signatures of this block to
+    /// sign the bytes that `canonical_bytes` returns for the content.
+    pub(crate) fn verify_signatures_of_bytes(
+        &self,
+        canonical_bytes: impl FnOnce(&Block<S>) -> Option<Vec<u8>>,
+    ) -> Result<Vec<Signature>, SignatureError> {
+        let mut verified = vec![];
+        if let Block::Signed {
+            content,
+            signatures,
+        } = self
+        {
+            if signatures.is_empty() {
+                return Err(SignatureError::Unsigned);
+            }
+            let bytes = canonical_bytes(content).ok_or(SignatureError::DamagedContent)?;
+            for signature in signatures {
+                if !signature.verify(&bytes) {
+                    return Err(SignatureError::InvalidSignature {
+                        signer: signature.signer.clone(),
+                    });
+                }
+                verified.push(signature.clone());
+            }
+        }
+        for child in self.children() {
+            verified.extend(child.verify_signatures()?);
+        }
+        Ok(verified)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doc::*;
+    use crate::memory::*;
+    use crate::metadata::*;
+    use crate::Block::*;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn contract() -> SemDoc<Pure> {
+        SemDoc::new(Section {
+            title: Box::new(Text("Contract".to_string())),
+            body: Box::new(Paragraphs(vec![
+                Text("Alice pays Bob 100 €.".to_string()),
+                Text("Bob delivers the goods.".to_string()),
+            ])),
+        })
+    }
+
+    #[test]
+    fn unsigned_documents_fail_verification() {
+        assert_eq!(contract().verify(), Err(SignatureError::Unsigned));
+    }
+
+    #[test]
+    fn signed_documents_survive_encoding() {
+        let doc = contract()
+            .sign("Alice", &key(1))
+            .unwrap()
+            .sign("Bob", &key(2))
+            .unwrap();
+        let decoded = SemDoc::from_bytes(&doc.to_bytes()).unwrap();
+        let signers = decoded
+            .verify()
+            .unwrap()
+            .into_iter()
+            .map(|signature| signature.signer)
+            .collect::<Vec<_>>();
+        assert_eq!(signers, vec!["Alice", "Bob"]);
+    }
+
+    #[test]
+    fn signed_subtrees_are_verified() {
+        let doc = SemDoc::new(Paragraphs(vec![
+            Text("Unsigned introduction.".to_string()),
+            contract().block.sign("Alice", &key(1)).unwrap(),
+        ]));
+        assert_eq!(doc.verify().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn signatures_are_bound_to_the_signer() {
+        let mut doc = contract().sign("Alice", &key(1)).unwrap();
+        if let Signed { signatures, .. } = &mut doc.block {
+            signatures[0].signer = "Mallory".to_string();
+        }
+        assert_eq!(
+            doc.verify(),
+            Err(SignatureError::InvalidSignature {
+                signer: "Mallory".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn changing_any_byte_fails_verification() {
+        let bytes = contract().sign("Alice", &key(1)).unwrap().to_bytes();
+        for index in 8..bytes.len() {
+            for flip in &[0x01, 0x80] {
+                let mut tampered = bytes.clone();
+                tampered[index] ^= flip;
+                if let Ok(doc) = SemDoc::from_bytes(&tampered) {
+                    assert!(
+                        doc.verify().is_err(),
+                        "Flipping byte {} with {:#04x} went unnoticed.",
+                        index,
+                        flip
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blocks_containing_errors_cant_be_signed() {
+        let damaged = Paragraphs(vec![
+            Text("Alice pays Bob 100 €.".to_string()),
+            Block::<Memory>::Error(crate::blocks::Error::BlockLayer(
+                BlockError::InvalidUtf8Encoding,
+            )),
+        ]);
+        assert!(matches!(
+            damaged.sign("Alice", &key(1)),
+            Err(SignatureError::DamagedContent)
+        ));
+    }
+
+    #[test]
+    fn changing_the_metadata_fails_verification() {
+        let metadata = Metadata {
+            title: Some("Purchase Contract".to_string()),
+            authors: vec!["Alice".to_string()],
+            ..Metadata::default()
+        };
+        let bytes = contract()
+            .with_metadata(metadata)
+            .sign("Alice", &key(1))
+            .unwrap()
+            .to_bytes();
+        let doc = SemDoc::from_bytes(&bytes).unwrap();
+        assert_eq!(doc.verify().unwrap().len(), 1);
+
+        let title = bytes
+            .windows(8)
+            .position(|window| window == b"Purchase")
+            .unwrap();
+        let mut tampered = bytes.clone();
+        tampered[title] ^= 0x01;
+        let doc = SemDoc::from_bytes(&tampered).unwrap();
+        assert_eq!(doc.metadata.title.as_deref(), Some("Qurchase Contract"));
+        assert_eq!(
+            doc.verify(),
+            Err(SignatureError::InvalidSignature {
+                signer: "Alice".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn signing_only_the_block_leaves_the_metadata_unsigned() {
+        let metadata = Metadata {
+            title: Some("Purchase Contract".to_string()),
+            ..Metadata::default()
+        };
+        let doc = SemDoc::new(contract().block.sign("Alice", &key(1)).unwrap());
+        assert_eq!(doc.verify().unwrap().len(), 1);
+        let doc = doc.with_metadata(metadata);
+        assert_eq!(doc.block.verify_signatures().unwrap().len(), 1);
+        assert_eq!(
+            doc.verify(),
+            Err(SignatureError::UnsignedMetadata {
+                signer: "Alice".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use std::convert::TryInto;
+
+use crate::blocks::*;
+
+/// A machine-readable point or span in time, used in `Block::Temporal`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TemporalValue {
+    /// A calendar date without a time of day, like a birthday. Months and days start at 1.
+    Date { year: i32, month: u8, day: u8 },
+    /// A precise moment, in seconds since the Unix epoch in UTC. Readers show it in the user's
+    /// timezone.
+    Instant(i64),
+    /// A length of time in seconds, like the runtime of a video.
+    Duration(u64),
+}
+
+impl TemporalValue {
+    /// Whether the value describes something that exists, like a date that is in the calendar.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            TemporalValue::Date { year, month, day } => {
+                (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month)
+            }
+            TemporalValue::Instant(_) | TemporalValue::Duration(_) => true,
+        }
+    }
+
+    pub(crate) fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = vec![];
+        match self {
+            TemporalValue::Date { year, month, day } => {
+                bytes.push(0);
+                bytes.extend_from_slice(&year.to_be_bytes());
+                bytes.push(month);
+                bytes.push(day);
+            }
+            TemporalValue::Instant(seconds) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&seconds.to_be_bytes());
+            }
+            TemporalValue::Duration(seconds) => {
+                bytes.push(2);
+                bytes.extend_from_slice(&seconds.to_be_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<TemporalValue, BlockError> {
+        let value = match bytes {
+            [0, year @ .., month, day] => TemporalValue::Date {
+                year: i32::from_be_bytes(
+                    year.try_into()
+                        .map_err(|_| BlockError::InvalidTemporalValue)?,
+                ),
+                month: *month,
+                day: *day,
+            },
+            [1, seconds @ ..] => TemporalValue::Instant(i64::from_be_bytes(
+                seconds
+                    .try_into()
+                    .map_err(|_| BlockError::InvalidTemporalValue)?,
+            )),
+            [2, seconds @ ..] => TemporalValue::Duration(u64::from_be_bytes(
+                seconds
+                    .try_into()
+                    .map_err(|_| BlockError::InvalidTemporalValue)?,
+            )),
+            _ => return Err(BlockError::InvalidTemporalValue),
+        };
+        if value.is_valid() {
+            Ok(value)
+        } else {
+            Err(BlockError::InvalidTemporalValue)
+        }
+    }
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    match month {
+        2 if is_leap_year => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Converts a number of days since the Unix epoch into a year, month and day, see
+/// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn days_to_civil(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Converts a year, month and day into the number of days since the Unix epoch, see
+/// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+pub fn civil_to_days(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = (month + 9) % 12;
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doc::*;
+    use crate::molecule::*;
+    use crate::source::*;
+    use crate::Block::*;
+    use quickcheck::*;
+
+    impl Arbitrary for TemporalValue {
+        fn arbitrary(g: &mut Gen) -> Self {
+            match u8::arbitrary(g) % 3 {
+                0 => {
+                    let year = i32::arbitrary(g);
+                    let month = u8::arbitrary(g) % 12 + 1;
+                    TemporalValue::Date {
+                        year,
+                        month,
+                        day: u8::arbitrary(g) % days_in_month(year, month) + 1,
+                    }
+                }
+                1 => TemporalValue::Instant(i64::arbitrary(g)),
+                _ => TemporalValue::Duration(u64::arbitrary(g)),
+            }
+        }
+    }
+
+    fn date(year: i32, month: u8, day: u8) -> TemporalValue {
+        TemporalValue::Date { year, month, day }
+    }
+
+    #[test]
+    fn temporal_values_survive_encoding() {
+        let doc = SemDoc::new(Flow(vec![
+            Text("The launch on ".to_string()),
+            Temporal {
+                value: TemporalValue::Instant(1_600_000_000),
+                text: "Sep 13, 2020, 12:26 UTC".to_string(),
+            },
+            Text(" took ".to_string()),
+            Temporal {
+                value: TemporalValue::Duration(90),
+                text: "a minute and a half".to_string(),
+            },
+            Text(".".to_string()),
+        ]));
+        let decoded = SemDoc::from_bytes(&doc.to_bytes()).unwrap();
+        assert_eq!(decoded.into_pure().unwrap(), doc);
+    }
+
+    #[test]
+    fn dates_must_be_in_the_calendar() {
+        assert!(date(2020, 2, 29).is_valid());
+        assert!(date(2000, 2, 29).is_valid());
+        assert!(!date(1900, 2, 29).is_valid());
+        assert!(!date(2021, 4, 31).is_valid());
+        assert!(!date(2021, 13, 1).is_valid());
+        assert!(!date(2021, 1, 0).is_valid());
+    }
+
+    #[test]
+    fn days_convert_to_civil_dates() {
+        assert_eq!(days_to_civil(0), (1970, 1, 1));
+        assert_eq!(days_to_civil(18_321), (2020, 2, 29));
+        assert_eq!(days_to_civil(-1), (1969, 12, 31));
+        assert_eq!(civil_to_days(2020, 2, 29), 18_321);
+        assert_eq!(civil_to_days(1969, 12, 31), -1);
+    }
+
+    quickcheck::quickcheck! {
+        fn civil_dates_round_trip(days: i32) -> bool {
+            let (year, month, day) = days_to_civil(days.into());
+            civil_to_days(year, month, day) == i64::from(days)
+        }
+    }
+
+    #[test]
+    fn invalid_values_are_rejected_on_decode() {
+        let molecule = Molecule::<Pure>::block(
+            kinds::TEMPORAL,
+            vec![
+                Molecule::Bytes(date(2021, 2, 30).to_bytes()),
+                Molecule::Bytes(b"February 30th".to_vec()),
+            ],
+        );
+        assert_eq!(
+            Block::from(&molecule),
+            Error(crate::blocks::Error::BlockLayer(
+                BlockError::InvalidTemporalValue
+            ))
+        );
+        assert_eq!(
+            TemporalValue::from_bytes(&[1, 0, 0]),
+            Err(BlockError::InvalidTemporalValue)
+        );
+    }
+}